@@ -1,7 +1,8 @@
 pub use error::*;
 use aetherium_core::{
-    GasPaymentKey, AetheriumDomain, AetheriumMessage, InterchainGasPayment,
-    InterchainGasPaymentMeta, MerkleTreeInsertion, PendingOperationStatus, H256,
+    DeadLetter, GasPaymentKey, AetheriumDomain, AetheriumMessage, InterchainGasPayment,
+    InterchainGasPaymentMeta, MerkleTreeInsertion, OperationAttempt, PendingOperationStatus,
+    H256, U256,
 };
 pub use rocks::*;
 
@@ -123,6 +124,66 @@ pub trait AetheriumDb: Send + Sync {
         message_id: &H256,
     ) -> DbResult<Option<u32>>;
 
+    /// Store the last error hit while processing a pending message by its message id
+    fn store_pending_message_last_error_by_message_id(
+        &self,
+        message_id: &H256,
+        error: &str,
+    ) -> DbResult<()>;
+
+    /// Retrieve the last error hit while processing a pending message by its message id
+    fn retrieve_pending_message_last_error_by_message_id(
+        &self,
+        message_id: &H256,
+    ) -> DbResult<Option<String>>;
+
+    /// Delete the last error hit while processing a pending message, once it is delivered
+    fn delete_pending_message_last_error_by_message_id(&self, message_id: &H256) -> DbResult<()>;
+
+    /// Store the most recent failed attempts at processing a pending message by its message id
+    fn store_pending_message_attempts_by_message_id(
+        &self,
+        message_id: &H256,
+        attempts: &[OperationAttempt],
+    ) -> DbResult<()>;
+
+    /// Retrieve the most recent failed attempts at processing a pending message by its message id
+    fn retrieve_pending_message_attempts_by_message_id(
+        &self,
+        message_id: &H256,
+    ) -> DbResult<Option<Vec<OperationAttempt>>>;
+
+    /// Store the gas limit to submit a pending message with instead of the estimate, by its message id
+    fn store_pending_message_gas_limit_override_by_message_id(
+        &self,
+        message_id: &H256,
+        gas_limit: &U256,
+    ) -> DbResult<()>;
+
+    /// Retrieve the gas limit to submit a pending message with instead of the estimate, by its message id
+    fn retrieve_pending_message_gas_limit_override_by_message_id(
+        &self,
+        message_id: &H256,
+    ) -> DbResult<Option<U256>>;
+
+    /// Delete the gas limit to submit a pending message with, once it is delivered or re-driven
+    /// without one
+    fn delete_pending_message_gas_limit_override_by_message_id(
+        &self,
+        message_id: &H256,
+    ) -> DbResult<()>;
+
+    /// Store the dead letter of a message that exhausted its retries by its message id
+    fn store_dead_letter_by_message_id(
+        &self,
+        message_id: &H256,
+        dead_letter: &DeadLetter,
+    ) -> DbResult<()>;
+
+    /// Retrieve the dead letter of a message that exhausted its retries by its message id
+    fn retrieve_dead_letter_by_message_id(&self, message_id: &H256)
+        -> DbResult<Option<DeadLetter>>;
+
     fn store_merkle_tree_insertion_by_leaf_index(
         &self,
         leaf_index: &u32,
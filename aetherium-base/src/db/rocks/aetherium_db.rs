@@ -6,7 +6,7 @@ use aetherium_core::{
     Decode, Encode, GasPaymentKey, AetheriumDomain, AetheriumLogStore, AetheriumMessage,
     AetheriumSequenceAwareIndexerStoreReader, AetheriumWatermarkedLogStore, Indexed,
     InterchainGasExpenditure, InterchainGasPayment, InterchainGasPaymentMeta, LogMeta,
-    MerkleTreeInsertion, PendingOperationStatus, H256,
+    MerkleTreeInsertion, DeadLetter, OperationAttempt, PendingOperationStatus, H256, U256,
 };
 
 use super::{DbError, TypedDB, DB};
@@ -31,6 +31,12 @@ const GAS_EXPENDITURE_FOR_MESSAGE_ID: &str = "gas_expenditure_for_message_id_v2_
 const STATUS_BY_MESSAGE_ID: &str = "status_by_message_id_";
 const PENDING_MESSAGE_RETRY_COUNT_FOR_MESSAGE_ID: &str =
     "pending_message_retry_count_for_message_id_";
+const PENDING_MESSAGE_LAST_ERROR_FOR_MESSAGE_ID: &str =
+    "pending_message_last_error_for_message_id_";
+const PENDING_MESSAGE_ATTEMPTS_FOR_MESSAGE_ID: &str = "pending_message_attempts_for_message_id_";
+const PENDING_MESSAGE_GAS_LIMIT_OVERRIDE_FOR_MESSAGE_ID: &str =
+    "pending_message_gas_limit_override_for_message_id_";
+const DEAD_LETTER_FOR_MESSAGE_ID: &str = "dead_letter_for_message_id_";
 const MERKLE_TREE_INSERTION: &str = "merkle_tree_insertion_";
 const MERKLE_LEAF_INDEX_BY_MESSAGE_ID: &str = "merkle_leaf_index_by_message_id_";
 const MERKLE_TREE_INSERTION_BLOCK_NUMBER_BY_LEAF_INDEX: &str =
@@ -105,6 +111,16 @@ impl AetheriumRocksDB {
         Ok(true)
     }
 
+    /// Retrieve the dead letters of all the messages from this domain that exhausted their retries
+    pub fn retrieve_dead_letters(&self) -> DbResult<Vec<DeadLetter>> {
+        self.retrieve_decodables_by_key_prefix(DEAD_LETTER_FOR_MESSAGE_ID, [])
+    }
+
+    /// Delete the dead letter of a message, once it is re-driven or discarded
+    pub fn delete_dead_letter_by_message_id(&self, message_id: &H256) -> DbResult<()> {
+        self.delete_value_by_key(DEAD_LETTER_FOR_MESSAGE_ID, message_id)
+    }
+
     /// Retrieve a message by its nonce
     pub fn retrieve_message_by_nonce(&self, nonce: u32) -> DbResult<Option<AetheriumMessage>> {
         let id = self.retrieve_message_id_by_nonce(&nonce)?;
@@ -598,6 +614,106 @@ impl AetheriumDb for AetheriumRocksDB {
         self.retrieve_value_by_key(PENDING_MESSAGE_RETRY_COUNT_FOR_MESSAGE_ID, message_id)
     }
 
+    /// Store the last error hit while processing a pending message by its message id
+    fn store_pending_message_last_error_by_message_id(
+        &self,
+        message_id: &H256,
+        error: &str,
+    ) -> DbResult<()> {
+        self.store_value_by_key(
+            PENDING_MESSAGE_LAST_ERROR_FOR_MESSAGE_ID,
+            message_id,
+            &error.to_owned(),
+        )
+    }
+
+    /// Retrieve the last error hit while processing a pending message by its message id
+    fn retrieve_pending_message_last_error_by_message_id(
+        &self,
+        message_id: &H256,
+    ) -> DbResult<Option<String>> {
+        self.retrieve_value_by_key(PENDING_MESSAGE_LAST_ERROR_FOR_MESSAGE_ID, message_id)
+    }
+
+    /// Delete the last error hit while processing a pending message, once it is delivered
+    fn delete_pending_message_last_error_by_message_id(&self, message_id: &H256) -> DbResult<()> {
+        self.delete_value_by_key(PENDING_MESSAGE_LAST_ERROR_FOR_MESSAGE_ID, message_id)
+    }
+
+    /// Store the most recent failed attempts at processing a pending message by its message id
+    fn store_pending_message_attempts_by_message_id(
+        &self,
+        message_id: &H256,
+        attempts: &[OperationAttempt],
+    ) -> DbResult<()> {
+        self.store_value_by_key(
+            PENDING_MESSAGE_ATTEMPTS_FOR_MESSAGE_ID,
+            message_id,
+            &attempts.to_vec(),
+        )
+    }
+
+    /// Retrieve the most recent failed attempts at processing a pending message by its message id
+    fn retrieve_pending_message_attempts_by_message_id(
+        &self,
+        message_id: &H256,
+    ) -> DbResult<Option<Vec<OperationAttempt>>> {
+        self.retrieve_value_by_key(PENDING_MESSAGE_ATTEMPTS_FOR_MESSAGE_ID, message_id)
+    }
+
+    /// Store the gas limit to submit a pending message with instead of the estimate, by its message id
+    fn store_pending_message_gas_limit_override_by_message_id(
+        &self,
+        message_id: &H256,
+        gas_limit: &U256,
+    ) -> DbResult<()> {
+        self.store_value_by_key(
+            PENDING_MESSAGE_GAS_LIMIT_OVERRIDE_FOR_MESSAGE_ID,
+            message_id,
+            gas_limit,
+        )
+    }
+
+    /// Retrieve the gas limit to submit a pending message with instead of the estimate, by its message id
+    fn retrieve_pending_message_gas_limit_override_by_message_id(
+        &self,
+        message_id: &H256,
+    ) -> DbResult<Option<U256>> {
+        self.retrieve_value_by_key(
+            PENDING_MESSAGE_GAS_LIMIT_OVERRIDE_FOR_MESSAGE_ID,
+            message_id,
+        )
+    }
+
+    /// Delete the gas limit to submit a pending message with, once it is delivered or re-driven
+    /// without one
+    fn delete_pending_message_gas_limit_override_by_message_id(
+        &self,
+        message_id: &H256,
+    ) -> DbResult<()> {
+        self.delete_value_by_key(
+            PENDING_MESSAGE_GAS_LIMIT_OVERRIDE_FOR_MESSAGE_ID,
+            message_id,
+        )
+    }
+
+    /// Store the dead letter of a message that exhausted its retries by its message id
+    fn store_dead_letter_by_message_id(
+        &self,
+        message_id: &H256,
+        dead_letter: &DeadLetter,
+    ) -> DbResult<()> {
+        self.store_value_by_key(DEAD_LETTER_FOR_MESSAGE_ID, message_id, dead_letter)
+    }
+
+    /// Retrieve the dead letter of a message that exhausted its retries by its message id
+    fn retrieve_dead_letter_by_message_id(
+        &self,
+        message_id: &H256,
+    ) -> DbResult<Option<DeadLetter>> {
+        self.retrieve_value_by_key(DEAD_LETTER_FOR_MESSAGE_ID, message_id)
+    }
+
     fn store_merkle_tree_insertion_by_leaf_index(
         &self,
         leaf_index: &u32,
@@ -677,4 +793,13 @@ impl AetheriumRocksDB {
     ) -> DbResult<Option<V>> {
         self.retrieve_decodable(prefix, key.to_vec())
     }
+
+    /// Delete a value by key
+    pub fn delete_value_by_key<K: Encode>(
+        &self,
+        prefix: impl AsRef<[u8]>,
+        key: &K,
+    ) -> DbResult<()> {
+        self.delete_value(prefix, key.to_vec())
+    }
 }
@@ -1,7 +1,7 @@
 use std::{path::Path, sync::Arc};
 
 use super::error::DbError;
-use rocksdb::{Options, DB as Rocks};
+use rocksdb::{Options, WriteBatch, DB as Rocks};
 use tracing::info;
 
 pub use aetherium_db::*;
@@ -74,4 +74,27 @@ impl DB {
     pub fn retrieve(&self, key: &[u8]) -> Result<Option<Vec<u8>>> {
         Ok(self.0.get(key)?)
     }
+
+    /// Delete a value from the DB
+    pub fn delete(&self, key: &[u8]) -> Result<()> {
+        Ok(self.0.delete(key)?)
+    }
+
+    /// Apply all the writes of `batch` atomically
+    pub fn write(&self, batch: WriteBatch) -> Result<()> {
+        Ok(self.0.write(batch)?)
+    }
+
+    /// Retrieve all values whose keys start with `prefix`, in key order
+    pub fn retrieve_by_prefix(&self, prefix: &[u8]) -> Result<Vec<Vec<u8>>> {
+        let mut values = vec![];
+        for entry in self.0.prefix_iterator(prefix) {
+            let (key, value) = entry?;
+            if !key.starts_with(prefix) {
+                break;
+            }
+            values.push(value.into_vec());
+        }
+        Ok(values)
+    }
 }
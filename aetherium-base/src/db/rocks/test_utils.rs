@@ -35,7 +35,7 @@ mod test {
         RawAetheriumMessage, H256, H512, U256,
     };
 
-    use crate::db::AetheriumRocksDB;
+    use crate::db::{AetheriumRocksDB, TypedDB};
 
     use super::*;
 
@@ -77,4 +77,74 @@ mod test {
         })
         .await;
     }
+
+    #[tokio::test]
+    async fn db_retrieves_values_by_key_prefix_and_deletes() {
+        run_test_db(|db| async move {
+            let domain = AetheriumDomain::new_test_domain("db_retrieves_values_by_key_prefix");
+            let db = TypedDB::new(&domain, db);
+
+            db.store_encodable("prefix_", [1u8, 1], &11u32).unwrap();
+            db.store_encodable("prefix_", [1u8, 2], &12u32).unwrap();
+            db.store_encodable("prefix_", [2u8, 1], &21u32).unwrap();
+            db.store_encodable("other_prefix_", [1u8, 3], &13u32)
+                .unwrap();
+
+            let values: Vec<u32> = db
+                .retrieve_decodables_by_key_prefix("prefix_", [1u8])
+                .unwrap();
+            assert_eq!(values, vec![11, 12]);
+
+            db.delete_value("prefix_", [1u8, 1]).unwrap();
+            let values: Vec<u32> = db
+                .retrieve_decodables_by_key_prefix("prefix_", [1u8])
+                .unwrap();
+            assert_eq!(values, vec![12]);
+            assert_eq!(
+                db.retrieve_decodable::<u32>("prefix_", [1u8, 1]).unwrap(),
+                None
+            );
+        })
+        .await;
+    }
+
+    #[tokio::test]
+    async fn db_applies_batched_writes_together() {
+        run_test_db(|db| async move {
+            let domain = AetheriumDomain::new_test_domain("db_applies_batched_writes_together");
+            let db = TypedDB::new(&domain, db);
+            db.store_encodable("prefix_", [1u8], &1u32).unwrap();
+
+            let mut batch = db.batch();
+            batch.delete_value("prefix_", [1u8]);
+            batch.store_encodable("prefix_", [2u8], &2u32);
+            batch.store_keyed_encodable("prefix_", &3u32, &3u32);
+
+            // nothing is written until the batch is
+            assert_eq!(
+                db.retrieve_decodable::<u32>("prefix_", [1u8]).unwrap(),
+                Some(1)
+            );
+            assert_eq!(
+                db.retrieve_decodable::<u32>("prefix_", [2u8]).unwrap(),
+                None
+            );
+
+            batch.write().unwrap();
+            assert_eq!(
+                db.retrieve_decodable::<u32>("prefix_", [1u8]).unwrap(),
+                None
+            );
+            assert_eq!(
+                db.retrieve_decodable::<u32>("prefix_", [2u8]).unwrap(),
+                Some(2)
+            );
+            assert_eq!(
+                db.retrieve_keyed_decodable::<_, u32>("prefix_", &3u32)
+                    .unwrap(),
+                Some(3)
+            );
+        })
+        .await;
+    }
 }
@@ -1,4 +1,5 @@
 use aetherium_core::{Decode, Encode, AetheriumDomain};
+use rocksdb::WriteBatch;
 
 use crate::db::{error::DbError, DB};
 
@@ -67,6 +68,25 @@ impl TypedDB {
             .map_err(Into::into)
     }
 
+    /// Delete value
+    pub fn delete_value(&self, prefix: impl AsRef<[u8]>, key: impl AsRef<[u8]>) -> Result<()> {
+        self.db
+            .delete(&self.prefixed_key(prefix.as_ref(), key.as_ref()))
+    }
+
+    /// Retrieve all decodable values whose keys start with `key_prefix`
+    pub fn retrieve_decodables_by_key_prefix<V: Decode>(
+        &self,
+        prefix: impl AsRef<[u8]>,
+        key_prefix: impl AsRef<[u8]>,
+    ) -> Result<Vec<V>> {
+        self.db
+            .retrieve_by_prefix(&self.prefixed_key(prefix.as_ref(), key_prefix.as_ref()))?
+            .into_iter()
+            .map(|v| V::read_from(&mut v.as_slice()).map_err(Into::into))
+            .collect()
+    }
+
     /// Store encodable kv pair
     pub fn store_keyed_encodable<K: Encode, V: Encode>(
         &self,
@@ -85,4 +105,54 @@ impl TypedDB {
     ) -> Result<Option<V>> {
         self.retrieve_decodable(prefix, key.to_vec())
     }
+
+    /// Start a batch of writes, which are only applied, all at once, by `TypedBatch::write`
+    pub fn batch(&self) -> TypedBatch<'_> {
+        TypedBatch {
+            db: self,
+            batch: WriteBatch::default(),
+        }
+    }
+}
+
+/// Writes to a `TypedDB` that are applied atomically: either all of them or none are persisted.
+pub struct TypedBatch<'a> {
+    db: &'a TypedDB,
+    batch: WriteBatch,
+}
+
+impl TypedBatch<'_> {
+    /// Store encodable value
+    pub fn store_encodable<V: Encode>(
+        &mut self,
+        prefix: impl AsRef<[u8]>,
+        key: impl AsRef<[u8]>,
+        value: &V,
+    ) {
+        self.batch.put(
+            self.db.prefixed_key(prefix.as_ref(), key.as_ref()),
+            value.to_vec(),
+        );
+    }
+
+    /// Store encodable kv pair
+    pub fn store_keyed_encodable<K: Encode, V: Encode>(
+        &mut self,
+        prefix: impl AsRef<[u8]>,
+        key: &K,
+        value: &V,
+    ) {
+        self.store_encodable(prefix, key.to_vec(), value)
+    }
+
+    /// Delete value
+    pub fn delete_value(&mut self, prefix: impl AsRef<[u8]>, key: impl AsRef<[u8]>) {
+        self.batch
+            .delete(self.db.prefixed_key(prefix.as_ref(), key.as_ref()));
+    }
+
+    /// Apply all the writes of the batch atomically
+    pub fn write(self) -> Result<()> {
+        self.db.db.write(self.batch)
+    }
 }
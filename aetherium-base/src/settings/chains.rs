@@ -61,6 +61,13 @@ impl From<Value> for RawChainConf {
     }
 }
 
+impl RawChainConf {
+    /// Looks up a value by JSON pointer, e.g. `/blocks/estimateBlockTime`
+    pub fn pointer(&self, pointer: &str) -> Option<&Value> {
+        self.0.pointer(pointer)
+    }
+}
+
 /// A chain setup is a domain ID, an address on that chain (where the mailbox is
 /// deployed) and details for connecting to the chain API.
 #[derive(Clone, Debug)]
@@ -278,6 +285,73 @@ impl ChainConf {
         .context(ctx)
     }
 
+    /// Try to convert the chain settings into the provider used by the
+    /// submitter's EVM transaction adapter.
+    pub async fn build_evm_provider_for_submitter(
+        &self,
+        metrics: &CoreMetrics,
+    ) -> Result<Box<dyn h_eth::EvmProviderForSubmitter>> {
+        let ctx = "Building EVM provider for submitter";
+        let locator = self.locator(H256::zero());
+        match &self.connection {
+            ChainConnectionConf::Ethereum(conf) => {
+                self.build_ethereum(conf, &locator, metrics, h_eth::SubmitterProviderBuilder {})
+                    .await
+            }
+            _ => Err(eyre!("Only Ethereum chains have an EVM provider")),
+        }
+        .context(ctx)
+    }
+
+    /// Try to convert the chain settings into the provider, transaction submitter and payer
+    /// used by the submitter's Sealevel transaction adapter.
+    pub async fn build_sealevel_provider_for_submitter(
+        &self,
+        metrics: &CoreMetrics,
+    ) -> Result<(
+        Box<dyn h_sealevel::SealevelProviderForSubmitter>,
+        Box<dyn TransactionSubmitter>,
+        Option<h_sealevel::SealevelKeypair>,
+    )> {
+        let ctx = "Building Sealevel provider for submitter";
+        let locator = self.locator(H256::zero());
+        match &self.connection {
+            ChainConnectionConf::Sealevel(conf) => {
+                let payer = self.sealevel_signer().await.context(ctx)?;
+                let rpc_client = Arc::new(build_sealevel_rpc_client(self, conf, metrics));
+                let provider =
+                    build_sealevel_provider(rpc_client, locator.domain.clone(), &[], conf);
+                let tx_submitter = build_sealevel_tx_submitter(self, conf, metrics);
+                Ok((
+                    Box::new(provider) as Box<dyn h_sealevel::SealevelProviderForSubmitter>,
+                    tx_submitter,
+                    payer.map(h_sealevel::SealevelKeypair::new),
+                ))
+            }
+            _ => Err(eyre!("Only Sealevel chains have a Sealevel provider")),
+        }
+        .context(ctx)
+    }
+
+    /// Try to convert the chain settings into the provider used by the
+    /// submitter's CosmWasm transaction adapter.
+    pub async fn build_cosmos_provider_for_submitter(
+        &self,
+        metrics: &CoreMetrics,
+    ) -> Result<Box<dyn h_cosmos::CosmosProviderForSubmitter>> {
+        let ctx = "Building Cosmos provider for submitter";
+        let locator = self.locator(H256::zero());
+        match &self.connection {
+            ChainConnectionConf::Cosmos(conf) => {
+                let signer = self.cosmos_signer().await.context(ctx)?;
+                let provider = build_cosmos_provider(self, conf, metrics, &locator, signer)?;
+                Ok(Box::new(provider) as Box<dyn h_cosmos::CosmosProviderForSubmitter>)
+            }
+            _ => Err(eyre!("Only Cosmos chains have a Cosmos provider")),
+        }
+        .context(ctx)
+    }
+
     /// Try to convert the chain setting into a Mailbox contract
     pub async fn build_mailbox(&self, metrics: &CoreMetrics) -> Result<Box<dyn Mailbox>> {
         let ctx = "Building mailbox";
@@ -1,6 +1,6 @@
 use crate::{
-    CheckpointSyncer, GcsStorageClientBuilder, LocalStorage, S3Storage, GCS_SERVICE_ACCOUNT_KEY,
-    GCS_USER_SECRET,
+    CheckpointSyncer, CompositeCheckpointSyncer, GcsStorageClientBuilder, LocalStorage,
+    ReplicaMetrics, S3Storage, GCS_SERVICE_ACCOUNT_KEY, GCS_USER_SECRET,
 };
 use core::str::FromStr;
 use eyre::{eyre, Context, Report, Result};
@@ -40,6 +40,13 @@ pub enum CheckpointSyncerConf {
         /// `gcloud auth application-default login`
         user_secrets: Option<String>,
     },
+    /// A checkpoint syncer that replicates what it writes to several checkpoint syncers
+    Composite {
+        /// The replicas. Reads go to the first healthy one, and the first one is announced.
+        syncers: Vec<CheckpointSyncerConf>,
+        /// How many replicas a write must succeed on
+        write_quorum: usize,
+    },
 }
 
 /// Checkpoint Syncer errors
@@ -125,8 +132,10 @@ impl CheckpointSyncerConf {
     pub async fn build_and_validate(
         &self,
         latest_index_gauge: Option<IntGauge>,
+        replica_metrics: Option<ReplicaMetrics>,
     ) -> Result<Box<dyn CheckpointSyncer>, CheckpointSyncerBuildError> {
-        let syncer: Box<dyn CheckpointSyncer> = self.build(latest_index_gauge).await?;
+        let syncer: Box<dyn CheckpointSyncer> =
+            self.build(latest_index_gauge, replica_metrics).await?;
 
         match syncer.reorg_status().await {
             Ok(Some(reorg_event)) => {
@@ -143,10 +152,24 @@ impl CheckpointSyncerConf {
         Ok(syncer)
     }
 
-    // keep this private to force all initializations to perform the reorg check via `build_and_validate`
+    /// Turn conf info a Checkpoint Syncer, even if a reorg event has been posted to it.
+    ///
+    /// Only meant for inspecting the checkpoint store, e.g. to audit it. Anything that signs
+    /// checkpoints with the result must check its reorg status first.
+    pub async fn build_unvalidated(
+        &self,
+        latest_index_gauge: Option<IntGauge>,
+        replica_metrics: Option<ReplicaMetrics>,
+    ) -> Result<Box<dyn CheckpointSyncer>, CheckpointSyncerBuildError> {
+        Ok(self.build(latest_index_gauge, replica_metrics).await?)
+    }
+
+    // keep this private to force all initializations to make the reorg check explicit, via
+    // `build_and_validate` or `build_unvalidated`
     async fn build(
         &self,
         latest_index_gauge: Option<IntGauge>,
+        replica_metrics: Option<ReplicaMetrics>,
     ) -> Result<Box<dyn CheckpointSyncer>, Report> {
         Ok(match self {
             CheckpointSyncerConf::LocalStorage { path } => {
@@ -183,6 +206,23 @@ impl CheckpointSyncerConf {
                         .await?,
                 )
             }
+            CheckpointSyncerConf::Composite {
+                syncers,
+                write_quorum,
+            } => {
+                let mut replicas = Vec::with_capacity(syncers.len());
+                for syncer in syncers {
+                    replicas.push(
+                        Box::pin(syncer.build(latest_index_gauge.clone(), replica_metrics.clone()))
+                            .await?,
+                    );
+                }
+                Box::new(CompositeCheckpointSyncer::new(
+                    replicas,
+                    *write_quorum,
+                    replica_metrics,
+                )?)
+            }
         })
     }
 }
@@ -221,7 +261,7 @@ mod test {
         // then `drop` it, to simulate a restart
         {
             let checkpoint_syncer = checkpoint_syncer_conf
-                .build_and_validate(None)
+                .build_and_validate(None, None)
                 .await
                 .unwrap();
 
@@ -232,7 +272,7 @@ mod test {
         }
 
         // Initialize a new checkpoint syncer and expect it to panic due to the reorg event.
-        let result = checkpoint_syncer_conf.build_and_validate(None).await;
+        let result = checkpoint_syncer_conf.build_and_validate(None, None).await;
         match result {
             Err(CheckpointSyncerBuildError::ReorgEvent(e)) => {
                 assert_eq!(e, dummy_reorg_event, "Reported reorg event doesn't match");
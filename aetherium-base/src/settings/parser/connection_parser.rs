@@ -68,6 +68,21 @@ pub fn build_ethereum_connection_conf(
                 .get_opt_key("maxPriorityFeePerGas")
                 .parse_u256()
                 .end(),
+            gas_price_escalation_percent: value_parser
+                .chain(err)
+                .get_opt_key("gasPriceEscalationPercent")
+                .parse_u32()
+                .end(),
+            gas_price_escalation_interval_blocks: value_parser
+                .chain(err)
+                .get_opt_key("gasPriceEscalationIntervalBlocks")
+                .parse_u32()
+                .end(),
+            max_gas_price: value_parser
+                .chain(err)
+                .get_opt_key("maxGasPrice")
+                .parse_u256()
+                .end(),
         })
         .unwrap_or_default();
 
@@ -87,7 +87,7 @@ impl FromRawConf<RawAgentConf, Option<&HashSet<&str>>> for Settings {
         let default_signer = p
             .chain(&mut err)
             .get_opt_key("defaultSigner")
-            .and_then(parse_signer)
+            .and_then(parse_chain_signer)
             .end();
 
         let default_rpc_consensus_type = p
@@ -131,7 +131,7 @@ fn parse_chain(
     let signer = chain
         .chain(&mut err)
         .get_opt_key("signer")
-        .and_then(parse_signer)
+        .and_then(parse_chain_signer)
         .end();
 
     let reorg_period = chain
@@ -346,12 +346,53 @@ fn parse_signer(signer: ValueParser) -> ConfigResult<SignerConf> {
                 account_address_type,
             })
         }};
+        (remote) => {{
+            let url = signer
+                .chain(&mut err)
+                .get_key("url")
+                .parse_from_str("Expected remote signer url")
+                .end();
+            let address = signer
+                .chain(&mut err)
+                .get_key("address")
+                .parse_from_str("Expected remote signer address")
+                .end();
+            cfg_unwrap_all!(&signer.cwp, err: [url, address]);
+            err.into_result(SignerConf::Remote { url, address })
+        }};
+        (pkcs11) => {{
+            let module = signer
+                .chain(&mut err)
+                .get_key("module")
+                .parse_from_str("Expected PKCS#11 module path")
+                .end();
+            let mut string = |key: &str| {
+                signer
+                    .chain(&mut err)
+                    .get_key(key)
+                    .parse_string()
+                    .end()
+                    .map(str::to_owned)
+            };
+            let token_label = string("tokenLabel");
+            let key_label = string("keyLabel");
+            let pin = string("pin");
+            cfg_unwrap_all!(&signer.cwp, err: [module, token_label, key_label, pin]);
+            err.into_result(SignerConf::Pkcs11 {
+                module,
+                token_label,
+                key_label,
+                pin,
+            })
+        }};
     }
 
     match signer_type {
         Some("hexKey") => parse_signer!(hexKey),
         Some("aws") => parse_signer!(aws),
         Some("cosmosKey") => parse_signer!(cosmosKey),
+        Some("remote") => parse_signer!(remote),
+        Some("pkcs11") => parse_signer!(pkcs11),
         Some(t) => {
             Err(eyre!("Unknown signer type `{t}`")).into_config_result(|| &signer.cwp + "type")
         }
@@ -361,6 +402,18 @@ fn parse_signer(signer: ValueParser) -> ConfigResult<SignerConf> {
     }
 }
 
+/// Parses the signer of a chain, which signs transactions. Remote signers only sign messages, so
+/// they can only be used as the validator signer.
+fn parse_chain_signer(signer: ValueParser) -> ConfigResult<SignerConf> {
+    match parse_signer(signer.clone())? {
+        SignerConf::Remote { .. } => Err(eyre!(
+            "Remote signers can't sign transactions, they can only be used as the validator signer"
+        ))
+        .into_config_result(|| &signer.cwp + "type"),
+        conf => Ok(conf),
+    }
+}
+
 /// Parser for agent signers.
 #[derive(Debug, Deserialize)]
 #[serde(transparent)]
@@ -1,13 +1,16 @@
+use std::path::PathBuf;
+
 use async_trait::async_trait;
 use ed25519_dalek::SecretKey;
 use ethers::prelude::{AwsSigner, LocalWallet};
 use ethers::utils::hex::ToHex;
 use eyre::{bail, Context, Report};
-use aetherium_core::{AccountAddressType, H256};
+use aetherium_core::{AccountAddressType, H160, H256};
 use aetherium_sealevel::Keypair;
 use rusoto_core::Region;
 use rusoto_kms::KmsClient;
 use tracing::instrument;
+use url::Url;
 
 use super::aws_credentials::AwsChainCredentialsProvider;
 use crate::types::utils;
@@ -37,6 +40,25 @@ pub enum SignerConf {
         /// Account address type for cosmos address
         account_address_type: AccountAddressType,
     },
+    /// A remote signer serving the `eth_sign` JSON-RPC method, e.g. Web3Signer. It only signs
+    /// messages, so it can sign checkpoints but not transactions.
+    Remote {
+        /// The URL of the remote signer
+        url: Url,
+        /// The address of the key to sign with
+        address: H160,
+    },
+    /// A key held by an HSM, used through its PKCS#11 module. Requires the `pkcs11` feature.
+    Pkcs11 {
+        /// Path of the PKCS#11 module of the HSM
+        module: PathBuf,
+        /// Label of the token holding the key
+        token_label: String,
+        /// Label of the key
+        key_label: String,
+        /// PIN of the token user
+        pin: String,
+    },
     /// Assume node will sign on RPC calls
     #[default]
     Node,
@@ -88,6 +110,25 @@ impl BuildableWithSignerConf for aetherium_ethereum::Signers {
             SignerConf::CosmosKey { .. } => {
                 bail!("cosmosKey signer is not supported by Ethereum")
             }
+            SignerConf::Remote { url, address } => aetherium_ethereum::Signers::Remote(
+                aetherium_ethereum::RemoteSigner::new(url.clone(), *address)?,
+            ),
+            #[cfg(feature = "pkcs11")]
+            SignerConf::Pkcs11 {
+                module,
+                token_label,
+                key_label,
+                pin,
+            } => aetherium_ethereum::Signers::Pkcs11(aetherium_ethereum::Pkcs11Signer::connect(
+                module,
+                token_label,
+                key_label,
+                pin,
+            )?),
+            #[cfg(not(feature = "pkcs11"))]
+            SignerConf::Pkcs11 { .. } => {
+                bail!("pkcs11 signer requires the agent to be built with the pkcs11 feature")
+            }
             SignerConf::Node => bail!("Node signer"),
         })
     }
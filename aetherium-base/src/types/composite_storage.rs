@@ -0,0 +1,465 @@
+use std::{
+    fmt,
+    sync::atomic::{AtomicBool, Ordering},
+};
+
+use aetherium_core::{ReorgEvent, SignedAnnouncement, SignedCheckpointWithMessageId};
+use async_trait::async_trait;
+use eyre::{bail, ensure, Result};
+use futures::future::{join_all, BoxFuture};
+use prometheus::{IntCounterVec, IntGaugeVec};
+use tracing::warn;
+
+use crate::{AgentMetadata, CheckpointSyncer, CoreMetrics};
+
+/// Health metrics of the replicas of composite checkpoint syncers, labeled by the storage
+/// location of each replica
+#[derive(Debug, Clone)]
+pub struct ReplicaMetrics {
+    healthy: IntGaugeVec,
+    failures: IntCounterVec,
+}
+
+impl ReplicaMetrics {
+    /// Registers the replica metrics
+    pub fn new(metrics: &CoreMetrics) -> Result<Self> {
+        Ok(Self {
+            healthy: metrics.new_int_gauge(
+                "checkpoint_syncer_replica_healthy",
+                "Whether the last operation on a checkpoint syncer replica succeeded",
+                &["location"],
+            )?,
+            failures: metrics.new_int_counter(
+                "checkpoint_syncer_replica_failures",
+                "Failed operations on a checkpoint syncer replica",
+                &["location", "operation"],
+            )?,
+        })
+    }
+}
+
+struct Replica {
+    location: String,
+    syncer: Box<dyn CheckpointSyncer>,
+    healthy: AtomicBool,
+}
+
+/// A checkpoint syncer that replicates everything it writes to several checkpoint syncers, so
+/// that the outage of one storage backend doesn't stop the validator.
+///
+/// Writes go to every replica, and succeed once `write_quorum` of them succeed. Reads go to the
+/// first replica that's healthy, i.e. whose last operation succeeded and that didn't fall behind
+/// the others, and fall back to the next ones on errors. The latest index and the reorg status are
+/// the exceptions: the latest index is the highest one of the replicas, and the reorg status is
+/// read from every replica, since a reorg flagged on any of them must stop the validator.
+///
+/// The location of every replica is announced, so that relayers can fall back to the other
+/// replicas too.
+pub struct CompositeCheckpointSyncer {
+    replicas: Vec<Replica>,
+    write_quorum: usize,
+    metrics: Option<ReplicaMetrics>,
+}
+
+impl fmt::Debug for CompositeCheckpointSyncer {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("CompositeCheckpointSyncer")
+            .field(
+                "replicas",
+                &self
+                    .replicas
+                    .iter()
+                    .map(|replica| &replica.location)
+                    .collect::<Vec<_>>(),
+            )
+            .field("write_quorum", &self.write_quorum)
+            .finish()
+    }
+}
+
+impl CompositeCheckpointSyncer {
+    /// Create a composite checkpoint syncer over `syncers`. The first one is the primary.
+    pub fn new(
+        syncers: Vec<Box<dyn CheckpointSyncer>>,
+        write_quorum: usize,
+        metrics: Option<ReplicaMetrics>,
+    ) -> Result<Self> {
+        ensure!(
+            !syncers.is_empty(),
+            "A composite checkpoint syncer needs at least one replica"
+        );
+        ensure!(
+            (1..=syncers.len()).contains(&write_quorum),
+            "The write quorum must be between 1 and the number of replicas ({}), got {write_quorum}",
+            syncers.len()
+        );
+        let replicas = syncers
+            .into_iter()
+            .map(|syncer| Replica {
+                location: syncer.announcement_location(),
+                syncer,
+                healthy: AtomicBool::new(true),
+            })
+            .collect::<Vec<_>>();
+        if let Some(metrics) = &metrics {
+            for replica in &replicas {
+                metrics
+                    .healthy
+                    .with_label_values(&[&replica.location])
+                    .set(1);
+            }
+        }
+        Ok(Self {
+            replicas,
+            write_quorum,
+            metrics,
+        })
+    }
+
+    fn report<T>(&self, replica: &Replica, operation: &str, result: &Result<T>) {
+        let healthy = result.is_ok();
+        if let Err(err) = result {
+            warn!(
+                location = replica.location,
+                operation,
+                ?err,
+                "Checkpoint syncer replica failed"
+            );
+        }
+        replica.healthy.store(healthy, Ordering::Relaxed);
+        if let Some(metrics) = &self.metrics {
+            metrics
+                .healthy
+                .with_label_values(&[&replica.location])
+                .set(healthy as i64);
+            if !healthy {
+                metrics
+                    .failures
+                    .with_label_values(&[&replica.location, operation])
+                    .inc();
+            }
+        }
+    }
+
+    /// Takes a replica out of the read order until its next successful operation, because it
+    /// misses checkpoints the other replicas have
+    fn report_behind(&self, replica: &Replica, index: Option<u32>, latest_index: u32) {
+        warn!(
+            location = replica.location,
+            ?index,
+            latest_index,
+            "Checkpoint syncer replica fell behind the others"
+        );
+        replica.healthy.store(false, Ordering::Relaxed);
+        if let Some(metrics) = &self.metrics {
+            metrics
+                .healthy
+                .with_label_values(&[&replica.location])
+                .set(0);
+            metrics
+                .failures
+                .with_label_values(&[&replica.location, "behind"])
+                .inc();
+        }
+    }
+
+    /// The replicas in the order reads try them: the healthy ones first, in configuration order
+    fn read_order(&self) -> impl Iterator<Item = &Replica> {
+        let healthy = |replica: &&Replica| replica.healthy.load(Ordering::Relaxed);
+        self.replicas.iter().filter(healthy).chain(
+            self.replicas
+                .iter()
+                .filter(move |replica| !healthy(replica)),
+        )
+    }
+
+    /// Reads from the replicas in read order, until one of them has the value
+    async fn read<'a, T>(
+        &'a self,
+        operation: &str,
+        read: impl Fn(&'a dyn CheckpointSyncer) -> BoxFuture<'a, Result<Option<T>>>,
+    ) -> Result<Option<T>> {
+        let mut errors = vec![];
+        for replica in self.read_order() {
+            let result = read(replica.syncer.as_ref()).await;
+            self.report(replica, operation, &result);
+            match result {
+                Ok(Some(value)) => return Ok(Some(value)),
+                // another replica may have it, if this one fell behind
+                Ok(None) => {}
+                Err(err) => errors.push(format!("{}: {err}", replica.location)),
+            }
+        }
+        if errors.len() == self.replicas.len() {
+            bail!(
+                "Failed to read {operation} from every replica: {}",
+                errors.join("; ")
+            );
+        }
+        Ok(None)
+    }
+
+    async fn write<'a>(
+        &'a self,
+        operation: &str,
+        write: impl Fn(&'a dyn CheckpointSyncer) -> BoxFuture<'a, Result<()>>,
+    ) -> Result<()> {
+        let results = join_all(
+            self.replicas
+                .iter()
+                .map(|replica| write(replica.syncer.as_ref())),
+        )
+        .await;
+        let mut errors = vec![];
+        for (replica, result) in self.replicas.iter().zip(results) {
+            self.report(replica, operation, &result);
+            if let Err(err) = result {
+                errors.push(format!("{}: {err}", replica.location));
+            }
+        }
+        let written = self.replicas.len() - errors.len();
+        if written < self.write_quorum {
+            bail!(
+                "Wrote {operation} to {written} of {} replicas, short of the write quorum of {}: {}",
+                self.replicas.len(),
+                self.write_quorum,
+                errors.join("; ")
+            );
+        }
+        Ok(())
+    }
+}
+
+#[async_trait]
+impl CheckpointSyncer for CompositeCheckpointSyncer {
+    async fn latest_index(&self) -> Result<Option<u32>> {
+        let results = join_all(
+            self.replicas
+                .iter()
+                .map(|replica| replica.syncer.latest_index()),
+        )
+        .await;
+        let mut indices = vec![];
+        let mut errors = vec![];
+        for (replica, result) in self.replicas.iter().zip(results) {
+            self.report(replica, "latest_index", &result);
+            match result {
+                Ok(index) => indices.push((replica, index)),
+                Err(err) => errors.push(format!("{}: {err}", replica.location)),
+            }
+        }
+        if indices.is_empty() {
+            bail!(
+                "Failed to read the latest index from every replica: {}",
+                errors.join("; ")
+            );
+        }
+        let latest_index = indices.iter().filter_map(|(_, index)| *index).max();
+        if let Some(latest_index) = latest_index {
+            for (replica, index) in indices {
+                if index < Some(latest_index) {
+                    self.report_behind(replica, index, latest_index);
+                }
+            }
+        }
+        Ok(latest_index)
+    }
+
+    async fn write_latest_index(&self, index: u32) -> Result<()> {
+        self.write("latest_index", |syncer| syncer.write_latest_index(index))
+            .await
+    }
+
+    async fn fetch_checkpoint(&self, index: u32) -> Result<Option<SignedCheckpointWithMessageId>> {
+        self.read("checkpoint", |syncer| syncer.fetch_checkpoint(index))
+            .await
+    }
+
+    async fn write_checkpoint(
+        &self,
+        signed_checkpoint: &SignedCheckpointWithMessageId,
+    ) -> Result<()> {
+        self.write("checkpoint", |syncer| {
+            syncer.write_checkpoint(signed_checkpoint)
+        })
+        .await
+    }
+
+    async fn write_metadata(&self, metadata: &AgentMetadata) -> Result<()> {
+        self.write("metadata", |syncer| syncer.write_metadata(metadata))
+            .await
+    }
+
+    /// Each replica gets the announcement of its own location, if it's one of them
+    async fn write_announcement(&self, signed_announcement: &SignedAnnouncement) -> Result<()> {
+        let location = &signed_announcement.value.storage_location;
+        let Some(replica) = self
+            .replicas
+            .iter()
+            .find(|replica| &replica.location == location)
+        else {
+            return self
+                .write("announcement", |syncer| {
+                    syncer.write_announcement(signed_announcement)
+                })
+                .await;
+        };
+        let result = replica.syncer.write_announcement(signed_announcement).await;
+        self.report(replica, "announcement", &result);
+        result
+    }
+
+    fn announcement_location(&self) -> String {
+        self.replicas[0].location.clone()
+    }
+
+    fn announcement_locations(&self) -> Vec<String> {
+        self.replicas
+            .iter()
+            .map(|replica| replica.location.clone())
+            .collect()
+    }
+
+    async fn write_reorg_status(&self, reorg_event: &ReorgEvent) -> Result<()> {
+        self.write("reorg_status", |syncer| {
+            syncer.write_reorg_status(reorg_event)
+        })
+        .await
+    }
+
+    async fn reorg_status(&self) -> Result<Option<ReorgEvent>> {
+        let results = join_all(
+            self.replicas
+                .iter()
+                .map(|replica| replica.syncer.reorg_status()),
+        )
+        .await;
+        let mut errors = vec![];
+        for (replica, result) in self.replicas.iter().zip(results) {
+            self.report(replica, "reorg_status", &result);
+            match result {
+                Ok(Some(reorg_event)) => return Ok(Some(reorg_event)),
+                Ok(None) => {}
+                Err(err) => errors.push(format!("{}: {err}", replica.location)),
+            }
+        }
+        // a reorg flag written to a quorum of replicas must be among the statuses that were read,
+        // and no status counts as read unless a write quorum of the replicas was reached
+        let read = self.replicas.len() - errors.len();
+        let read_quorum = self
+            .write_quorum
+            .max(self.replicas.len() - self.write_quorum + 1);
+        if read < read_quorum {
+            bail!(
+                "Read the reorg status from {read} of {} replicas, short of the read quorum of \
+                 {read_quorum}: {}",
+                self.replicas.len(),
+                errors.join("; ")
+            );
+        }
+        Ok(None)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use aetherium_core::{Checkpoint, CheckpointWithMessageId, Signature, H256, U256};
+
+    use super::*;
+    use crate::LocalStorage;
+
+    fn signed_checkpoint(index: u32) -> SignedCheckpointWithMessageId {
+        SignedCheckpointWithMessageId {
+            value: CheckpointWithMessageId {
+                checkpoint: Checkpoint {
+                    merkle_tree_hook_address: H256::zero(),
+                    mailbox_domain: 1,
+                    root: H256::zero(),
+                    index,
+                },
+                message_id: H256::repeat_byte(1),
+            },
+            signature: Signature {
+                r: U256::one(),
+                s: U256::one(),
+                v: 27,
+            },
+        }
+    }
+
+    #[tokio::test]
+    async fn test_writes_reach_the_quorum() {
+        let dir = tempfile::tempdir().unwrap();
+        // a file where a directory is expected, so that writes to this replica fail
+        let broken = dir.path().join("broken");
+        std::fs::write(&broken, "").unwrap();
+        let replicas = || -> Vec<Box<dyn CheckpointSyncer>> {
+            vec![
+                Box::new(LocalStorage::new(broken.clone(), None).unwrap()),
+                Box::new(LocalStorage::new(dir.path().join("first"), None).unwrap()),
+                Box::new(LocalStorage::new(dir.path().join("second"), None).unwrap()),
+            ]
+        };
+
+        let syncer = CompositeCheckpointSyncer::new(replicas(), 2, None).unwrap();
+        assert!(syncer.announcement_location().ends_with("broken"));
+        syncer
+            .write_checkpoint(&signed_checkpoint(3))
+            .await
+            .unwrap();
+        syncer.write_latest_index(3).await.unwrap();
+        // reads skip the broken replica once a write to it failed
+        assert_eq!(syncer.latest_index().await.unwrap(), Some(3));
+        assert!(syncer.fetch_checkpoint(3).await.unwrap().is_some());
+
+        let syncer = CompositeCheckpointSyncer::new(replicas(), 3, None).unwrap();
+        assert!(syncer.write_latest_index(4).await.is_err());
+        assert!(CompositeCheckpointSyncer::new(replicas(), 4, None).is_err());
+    }
+
+    #[tokio::test]
+    async fn test_reads_fall_back_to_replicas_ahead() {
+        let dir = tempfile::tempdir().unwrap();
+        let (behind, ahead) = (dir.path().join("behind"), dir.path().join("ahead"));
+        let replicas: Vec<Box<dyn CheckpointSyncer>> = vec![
+            Box::new(LocalStorage::new(behind, None).unwrap()),
+            Box::new(LocalStorage::new(ahead.clone(), None).unwrap()),
+        ];
+        let syncer = CompositeCheckpointSyncer::new(replicas, 1, None).unwrap();
+        let locations = syncer.announcement_locations();
+        assert_eq!(locations.len(), 2);
+        assert!(locations[0].ends_with("behind") && locations[1].ends_with("ahead"));
+
+        // only one replica got the latest checkpoint
+        let ahead = LocalStorage::new(ahead, None).unwrap();
+        ahead.write_checkpoint(&signed_checkpoint(5)).await.unwrap();
+        ahead.write_latest_index(5).await.unwrap();
+        assert_eq!(syncer.latest_index().await.unwrap(), Some(5));
+        assert!(syncer.fetch_checkpoint(5).await.unwrap().is_some());
+    }
+
+    #[tokio::test]
+    async fn test_reorg_status_needs_a_read_quorum() {
+        let dir = tempfile::tempdir().unwrap();
+        let paths = ["first", "second", "third"].map(|name| dir.path().join(name));
+        let syncer = CompositeCheckpointSyncer::new(
+            paths
+                .iter()
+                .map(|path| {
+                    Box::new(LocalStorage::new(path.clone(), None).unwrap())
+                        as Box<dyn CheckpointSyncer>
+                })
+                .collect(),
+            2,
+            None,
+        )
+        .unwrap();
+        assert_eq!(syncer.reorg_status().await.unwrap(), None);
+
+        // an unreadable reorg status may be a flagged reorg
+        std::fs::write(paths[0].join("reorg_flag.json"), "garbage").unwrap();
+        assert_eq!(syncer.reorg_status().await.unwrap(), None);
+        std::fs::write(paths[1].join("reorg_flag.json"), "garbage").unwrap();
+        assert!(syncer.reorg_status().await.is_err());
+    }
+}
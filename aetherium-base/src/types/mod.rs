@@ -1,3 +1,4 @@
+mod composite_storage;
 mod gcs_storage;
 mod local_storage;
 mod multisig;
@@ -6,6 +7,7 @@ mod s3_storage;
 /// Reusable logic for working with storage backends.
 pub mod utils;
 
+pub use composite_storage::*;
 pub use gcs_storage::*;
 pub use local_storage::*;
 pub use multisig::*;
@@ -185,6 +185,34 @@ impl Decode for bool {
     }
 }
 
+/// Strings are prefixed with their length in bytes, so they can be followed by other fields
+impl Encode for String {
+    fn write_to<W>(&self, writer: &mut W) -> std::io::Result<usize>
+    where
+        W: std::io::Write,
+    {
+        let len = u32::try_from(self.len())
+            .map_err(|_| Error::new(ErrorKind::InvalidInput, "string too long to encode"))?;
+        let written = len.write_to(writer)?;
+        writer.write_all(self.as_bytes())?;
+        Ok(written + self.len())
+    }
+}
+
+impl Decode for String {
+    fn read_from<R>(reader: &mut R) -> Result<Self, AetheriumProtocolError>
+    where
+        R: std::io::Read,
+        Self: Sized,
+    {
+        let len = u32::read_from(reader)?;
+        let mut buf = vec![0; len as usize];
+        reader.read_exact(&mut buf)?;
+        String::from_utf8(buf)
+            .map_err(|err| AetheriumProtocolError::IoError(Error::new(ErrorKind::InvalidData, err)))
+    }
+}
+
 impl Encode for UniqueIdentifier {
     fn write_to<W>(&self, writer: &mut W) -> std::io::Result<usize>
     where
@@ -327,4 +355,15 @@ mod test {
         let decoded = super::InterchainGasPayment::read_from(&mut &encoded[..]).unwrap();
         assert_eq!(payment, decoded);
     }
+
+    #[test]
+    fn test_encoding_string_is_length_prefixed() {
+        let strings = ("first".to_owned(), "second".to_owned());
+        let mut encoded = strings.0.to_vec();
+        encoded.extend(strings.1.to_vec());
+        let mut reader = &encoded[..];
+        assert_eq!(String::read_from(&mut reader).unwrap(), strings.0);
+        assert_eq!(String::read_from(&mut reader).unwrap(), strings.1);
+        assert!(reader.is_empty());
+    }
 }
@@ -17,6 +17,7 @@ use crate::{ChainResult, AetheriumContract, AetheriumMessage, U256};
     Copy,
     PartialEq,
     Eq,
+    Hash,
     BorshDeserialize,
     BorshSerialize,
     Serialize,
@@ -262,6 +262,15 @@ pub enum ReprepareReason {
     #[strum(to_string = "ApplicationReport({0})")]
     /// Application report
     ApplicationReport(ApplicationReport),
+    #[strum(to_string = "Relay budget exhausted")]
+    /// A relay budget the message is subject to is exhausted for the current window
+    RelayBudgetExhausted,
+    #[strum(to_string = "Relaying paused")]
+    /// Relaying is paused for the operation through the admin API
+    Paused,
+    #[strum(to_string = "Waiting for an earlier message of the lane to be delivered")]
+    /// The operation is on an ordered lane, and a message with a lower nonce isn't delivered yet
+    WaitingForEarlierMessage,
 }
 
 #[derive(Display, Debug, Clone, Serialize, Deserialize, PartialEq)]
@@ -279,8 +288,82 @@ pub enum ConfirmReason {
     ErrorConfirmingDelivery,
     /// Error storing delivery outcome
     ErrorRecordingProcessSuccess,
+    #[strum(to_string = "Not submitted in dry-run mode, awaiting delivery by another relayer")]
+    /// Operation would have been submitted, but the relayer runs in dry-run mode
+    DryRun,
 }
 
+/// A failed attempt at preparing or submitting an operation
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct OperationAttempt {
+    /// Unix timestamp of the attempt, in seconds
+    pub attempted_at: u64,
+    /// Why the attempt failed
+    pub reason: ReprepareReason,
+    /// The error the attempt failed with, if any
+    pub error: Option<String>,
+}
+
+/// A message that exhausted its retries and was taken out of processing. It stays in the
+/// database until it is re-driven; discarding it only marks it, so it isn't dead-lettered again.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct DeadLetter {
+    /// Id of the message
+    pub message_id: H256,
+    /// Domain the message is sent to
+    pub destination: u32,
+    /// Status of the message when it was dead-lettered
+    pub status: PendingOperationStatus,
+    /// Number of failed attempts
+    pub retry_count: u32,
+    /// The error of the last failed attempt
+    pub last_error: Option<String>,
+    /// The most recent failed attempts, oldest first
+    pub attempts: Vec<OperationAttempt>,
+    /// Unix timestamp of when the message was dead-lettered, in seconds
+    pub dead_lettered_at: u64,
+    /// Whether the message was discarded from the queue, which keeps it out of processing
+    #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+    pub discarded: bool,
+}
+
+/// Encodes a type as JSON, so that it can be extended without a database migration
+macro_rules! impl_json_encoding {
+    ($t:ty) => {
+        impl Encode for $t {
+            fn write_to<W>(&self, writer: &mut W) -> std::io::Result<usize>
+            where
+                W: Write,
+            {
+                let serialized = serde_json::to_vec(self).map_err(|_| {
+                    std::io::Error::new(std::io::ErrorKind::Other, "Failed to serialize")
+                })?;
+                writer.write(&serialized)
+            }
+        }
+
+        impl Decode for $t {
+            fn read_from<R>(reader: &mut R) -> Result<Self, AetheriumProtocolError>
+            where
+                R: std::io::Read,
+                Self: Sized,
+            {
+                serde_json::from_reader(reader).map_err(|err| {
+                    AetheriumProtocolError::IoError(std::io::Error::new(
+                        std::io::ErrorKind::Other,
+                        format!("Failed to deserialize. Error: {}", err),
+                    ))
+                })
+            }
+        }
+    };
+}
+
+impl_json_encoding!(Vec<OperationAttempt>);
+impl_json_encoding!(DeadLetter);
+
 /// Utility fn to calculate the total estimated cost of an operation batch
 pub fn total_estimated_cost(ops: &[Box<dyn PendingOperation>]) -> U256 {
     ops.iter()
@@ -385,4 +468,25 @@ mod test {
         let decoded = PendingOperationStatus::read_from(&mut &encoded[..]).unwrap();
         assert_eq!(status, decoded);
     }
+
+    #[test]
+    fn test_encoding_dead_letter() {
+        let dead_letter = DeadLetter {
+            message_id: H256::repeat_byte(1),
+            destination: 2,
+            status: PendingOperationStatus::Retry(ReprepareReason::ErrorEstimatingGas),
+            retry_count: 3,
+            last_error: Some("execution reverted".to_owned()),
+            attempts: vec![OperationAttempt {
+                attempted_at: 100,
+                reason: ReprepareReason::ErrorEstimatingGas,
+                error: Some("execution reverted".to_owned()),
+            }],
+            dead_lettered_at: 200,
+            discarded: false,
+        };
+        let encoded = dead_letter.to_vec();
+        let decoded = DeadLetter::read_from(&mut &encoded[..]).unwrap();
+        assert_eq!(dead_letter, decoded);
+    }
 }
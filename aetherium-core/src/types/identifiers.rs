@@ -81,7 +81,7 @@ impl Decode for AetheriumIdentifier {
     }
 }
 
-#[derive(Debug, Clone, serde::Deserialize, serde::Serialize, PartialEq, Eq)]
+#[derive(Debug, Clone, Copy, serde::Deserialize, serde::Serialize, PartialEq, Eq, Hash)]
 /// Unique identifier type
 pub struct UniqueIdentifier(Uuid);
 
@@ -90,6 +90,17 @@ impl UniqueIdentifier {
     pub fn new(uuid: Uuid) -> Self {
         UniqueIdentifier(uuid)
     }
+
+    /// Create a new random unique identifier
+    pub fn random() -> Self {
+        UniqueIdentifier(Uuid::new_v4())
+    }
+}
+
+impl std::fmt::Display for UniqueIdentifier {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}", self.0)
+    }
 }
 
 impl Deref for UniqueIdentifier {
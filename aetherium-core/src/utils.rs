@@ -1,9 +1,7 @@
 use eyre::Result;
 use sha3::{digest::Update, Digest, Keccak256};
 use std::str::FromStr;
-
-#[cfg(feature = "float")]
-use std::time::Duration;
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
 
 use crate::{KnownAetheriumDomain, H160, H256, U256};
 
@@ -73,6 +71,18 @@ pub fn to_atto(value: U256, decimals: u32) -> Option<U256> {
     value.checked_mul(coefficient)
 }
 
+/// Time elapsed since the unix epoch, or zero if the system clock is set before it
+pub fn since_unix_epoch() -> Duration {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .unwrap_or_default()
+}
+
+/// Current unix timestamp, in seconds
+pub fn unix_timestamp() -> u64 {
+    since_unix_epoch().as_secs()
+}
+
 /// Format a domain id as a name if it is known or just the number if not.
 pub fn fmt_domain(domain: u32) -> String {
     #[cfg(feature = "strum")]
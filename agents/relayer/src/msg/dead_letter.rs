@@ -0,0 +1,258 @@
+use std::collections::HashMap;
+
+use aetherium_base::db::{AetheriumDb, AetheriumRocksDB};
+use aetherium_core::{AetheriumMessage, DeadLetter, PendingOperationStatus, H256, U256};
+use eyre::{eyre, Result};
+use serde::Serialize;
+use tokio::sync::mpsc::{self, UnboundedReceiver, UnboundedSender};
+use tracing::info;
+
+/// A dead-lettered message, as returned by the admin API
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct DeadLetteredMessage {
+    pub origin: u32,
+    pub message: AetheriumMessage,
+    #[serde(flatten)]
+    pub dead_letter: DeadLetter,
+}
+
+/// The messages of every origin that exhausted their `max_retries`. Pending messages add
+/// themselves to the origin database when they give up; from there they can be re-driven, which
+/// hands them back to the processor of their origin with a fresh retry count, or discarded.
+#[derive(Debug)]
+pub struct DeadLetterQueue {
+    dbs: HashMap<u32, AetheriumRocksDB>,
+    redrive_senders: HashMap<u32, UnboundedSender<AetheriumMessage>>,
+}
+
+impl DeadLetterQueue {
+    pub fn new(dbs: HashMap<u32, AetheriumRocksDB>) -> Self {
+        Self {
+            dbs,
+            redrive_senders: HashMap::new(),
+        }
+    }
+
+    /// Creates the channel that re-driven messages from `origin` are sent to
+    pub fn redrive_receiver(&mut self, origin: u32) -> UnboundedReceiver<AetheriumMessage> {
+        let (sender, receiver) = mpsc::unbounded_channel();
+        self.redrive_senders.insert(origin, sender);
+        receiver
+    }
+
+    /// All the dead-lettered messages, oldest first
+    pub fn list(&self) -> Result<Vec<DeadLetteredMessage>> {
+        let mut dead_letters = vec![];
+        for (origin, db) in &self.dbs {
+            for dead_letter in db.retrieve_dead_letters()? {
+                if dead_letter.discarded {
+                    continue;
+                }
+                dead_letters.push(with_message(*origin, db, dead_letter)?);
+            }
+        }
+        dead_letters.sort_by_key(|dead_lettered| dead_lettered.dead_letter.dead_lettered_at);
+        Ok(dead_letters)
+    }
+
+    pub fn get(&self, id: &H256) -> Result<Option<DeadLetteredMessage>> {
+        Ok(self.find(id)?.map(|(_, dead_lettered)| dead_lettered))
+    }
+
+    /// Puts the message back in the queue of its destination with a fresh retry count. If
+    /// `gas_limit` is set, the message is submitted with it instead of the estimated one.
+    pub fn redrive(
+        &self,
+        id: &H256,
+        gas_limit: Option<U256>,
+    ) -> Result<Option<DeadLetteredMessage>> {
+        let Some((db, dead_lettered)) = self.find(id)? else {
+            return Ok(None);
+        };
+        let sender = self
+            .redrive_senders
+            .get(&dead_lettered.origin)
+            .ok_or_else(|| {
+                eyre!(
+                    "Messages from domain {} can't be re-driven",
+                    dead_lettered.origin
+                )
+            })?;
+        match gas_limit {
+            Some(gas_limit) => {
+                db.store_pending_message_gas_limit_override_by_message_id(id, &gas_limit)?
+            }
+            None => db.delete_pending_message_gas_limit_override_by_message_id(id)?,
+        }
+        db.store_pending_message_retry_count_by_message_id(id, &0)?;
+        db.store_status_by_message_id(id, &PendingOperationStatus::FirstPrepareAttempt)?;
+        if let Err(err) = sender.send(dead_lettered.message.clone()) {
+            // keep the message out of processing, as it still is in the queue
+            let dead_letter = &dead_lettered.dead_letter;
+            db.store_pending_message_retry_count_by_message_id(id, &dead_letter.retry_count)?;
+            db.store_status_by_message_id(id, &dead_letter.status)?;
+            return Err(err.into());
+        }
+        // only once the message is back in processing, so it can't get lost in between
+        db.delete_dead_letter_by_message_id(id)?;
+        info!(message_id = ?id, ?gas_limit, "Re-driving dead-lettered message");
+        Ok(Some(dead_lettered))
+    }
+
+    /// Removes the message from the dead-letter queue. It stays out of processing for good.
+    pub fn discard(&self, id: &H256) -> Result<Option<DeadLetteredMessage>> {
+        let Some((db, dead_lettered)) = self.find(id)? else {
+            return Ok(None);
+        };
+        // the dead letter is kept, marked as discarded, so that the message isn't dead-lettered
+        // again when it is skipped for having exhausted its retries
+        db.store_dead_letter_by_message_id(
+            id,
+            &DeadLetter {
+                discarded: true,
+                ..dead_lettered.dead_letter.clone()
+            },
+        )?;
+        info!(message_id = ?id, "Discarded dead-lettered message");
+        Ok(Some(dead_lettered))
+    }
+
+    fn find(&self, id: &H256) -> Result<Option<(&AetheriumRocksDB, DeadLetteredMessage)>> {
+        for (origin, db) in &self.dbs {
+            if let Some(dead_letter) = db.retrieve_dead_letter_by_message_id(id)? {
+                if dead_letter.discarded {
+                    return Ok(None);
+                }
+                return Ok(Some((db, with_message(*origin, db, dead_letter)?)));
+            }
+        }
+        Ok(None)
+    }
+}
+
+fn with_message(
+    origin: u32,
+    db: &AetheriumRocksDB,
+    dead_letter: DeadLetter,
+) -> Result<DeadLetteredMessage> {
+    let message = db
+        .retrieve_message_by_id(&dead_letter.message_id)?
+        .ok_or_else(|| {
+            eyre!(
+                "Dead-lettered message {:?} not found",
+                dead_letter.message_id
+            )
+        })?;
+    Ok(DeadLetteredMessage {
+        origin,
+        message,
+        dead_letter,
+    })
+}
+
+#[cfg(test)]
+mod test {
+    use aetherium_base::db::test_utils;
+    use aetherium_core::{AetheriumDomain, ReprepareReason};
+
+    use super::*;
+
+    fn dead_letter(db: &AetheriumRocksDB, nonce: u32, dead_lettered_at: u64) -> AetheriumMessage {
+        let message = AetheriumMessage {
+            nonce,
+            origin: db.domain().id(),
+            ..AetheriumMessage::default()
+        };
+        db.store_message(&message, 1).unwrap();
+        db.store_pending_message_retry_count_by_message_id(&message.id(), &66)
+            .unwrap();
+        db.store_dead_letter_by_message_id(
+            &message.id(),
+            &DeadLetter {
+                message_id: message.id(),
+                destination: message.destination,
+                status: PendingOperationStatus::Retry(ReprepareReason::ErrorEstimatingGas),
+                retry_count: 66,
+                last_error: None,
+                attempts: vec![],
+                dead_lettered_at,
+                discarded: false,
+            },
+        )
+        .unwrap();
+        message
+    }
+
+    #[tokio::test]
+    async fn test_redrive_and_discard() {
+        test_utils::run_test_db(|db| async move {
+            let domain = AetheriumDomain::new_test_domain("test_dead_letters");
+            let db = AetheriumRocksDB::new(&domain, db);
+            let redriven = dead_letter(&db, 1, 200);
+            let discarded = dead_letter(&db, 2, 100);
+            let mut dead_letters = DeadLetterQueue::new(HashMap::from([(domain.id(), db.clone())]));
+            let mut receiver = dead_letters.redrive_receiver(domain.id());
+
+            let listed: Vec<_> = dead_letters
+                .list()
+                .unwrap()
+                .into_iter()
+                .map(|dead_lettered| dead_lettered.message)
+                .collect();
+            assert_eq!(listed, vec![discarded.clone(), redriven.clone()]);
+
+            dead_letters
+                .redrive(&redriven.id(), Some(U256::from(500_000)))
+                .unwrap()
+                .unwrap();
+            assert_eq!(receiver.try_recv().unwrap(), redriven);
+            assert_eq!(
+                db.retrieve_pending_message_retry_count_by_message_id(&redriven.id())
+                    .unwrap(),
+                Some(0)
+            );
+            assert_eq!(
+                db.retrieve_pending_message_gas_limit_override_by_message_id(&redriven.id())
+                    .unwrap(),
+                Some(U256::from(500_000))
+            );
+
+            dead_letters.discard(&discarded.id()).unwrap().unwrap();
+            assert!(receiver.try_recv().is_err());
+            assert!(dead_letters.list().unwrap().is_empty());
+            assert!(dead_letters.get(&discarded.id()).unwrap().is_none());
+            assert!(
+                db.retrieve_dead_letter_by_message_id(&discarded.id())
+                    .unwrap()
+                    .unwrap()
+                    .discarded
+            );
+            assert!(dead_letters
+                .redrive(&discarded.id(), None)
+                .unwrap()
+                .is_none());
+        })
+        .await;
+    }
+
+    #[tokio::test]
+    async fn test_failed_redrive_keeps_the_dead_letter() {
+        test_utils::run_test_db(|db| async move {
+            let domain = AetheriumDomain::new_test_domain("test_failed_redrive");
+            let db = AetheriumRocksDB::new(&domain, db);
+            let message = dead_letter(&db, 1, 100);
+            let mut dead_letters = DeadLetterQueue::new(HashMap::from([(domain.id(), db.clone())]));
+            drop(dead_letters.redrive_receiver(domain.id()));
+
+            assert!(dead_letters.redrive(&message.id(), None).is_err());
+            assert!(dead_letters.get(&message.id()).unwrap().is_some());
+            assert_eq!(
+                db.retrieve_pending_message_retry_count_by_message_id(&message.id())
+                    .unwrap(),
+                Some(66)
+            );
+        })
+        .await;
+    }
+}
@@ -0,0 +1,269 @@
+use std::{
+    collections::{BTreeMap, HashMap, VecDeque},
+    sync::Mutex,
+};
+
+use aetherium_base::CoreMetrics;
+use aetherium_core::{utils::unix_timestamp, AetheriumMessage, H256, U256};
+use eyre::Result;
+use prometheus::IntCounterVec;
+use serde::{Deserialize, Serialize};
+use strum::Display;
+use tracing::{info, warn};
+
+/// How many messages the recorder keeps; the oldest ones are forgotten first
+const MAX_DRY_RUN_RECORDS: usize = 10_000;
+
+/// What a dry-run relayer would have done with a message
+#[derive(Debug, Clone, PartialEq, Serialize)]
+#[serde(rename_all = "camelCase", tag = "action")]
+pub enum DryRunDecision {
+    /// The message passed every check, and would have been submitted with `gas_limit`
+    #[serde(rename_all = "camelCase")]
+    Submit {
+        gas_limit: U256,
+        /// Gas estimated for the `verify` call of the recipient ISM
+        ism_verify_gas: Option<U256>,
+    },
+    /// The message wasn't ready to be submitted, for `reason`
+    #[serde(rename_all = "camelCase")]
+    Hold { reason: String },
+}
+
+/// How the decision of the dry-run relayer compares to what happened on-chain
+#[derive(
+    Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, Display, Serialize, Deserialize,
+)]
+#[serde(rename_all = "camelCase")]
+#[strum(serialize_all = "snake_case")]
+pub enum DryRunOutcome {
+    /// Would have been submitted, and isn't delivered yet
+    WouldSubmit,
+    /// Held, and isn't delivered either
+    Held,
+    /// Would have been submitted, and was delivered
+    Matched,
+    /// Was delivered while the dry-run relayer held it
+    DeliveredWhileHeld,
+    /// Was delivered before the dry-run relayer decided anything about it
+    DeliveredUndecided,
+}
+
+/// The last decision about a message, and whether it was delivered
+#[derive(Debug, Clone, PartialEq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct DryRunRecord {
+    pub message_id: H256,
+    pub origin: u32,
+    pub destination: u32,
+    pub app_context: Option<String>,
+    pub decision: Option<DryRunDecision>,
+    /// Unix timestamp of the last decision, in seconds
+    pub decided_at: Option<u64>,
+    /// Unix timestamp of when the delivery was observed, in seconds
+    pub delivered_at: Option<u64>,
+    pub outcome: DryRunOutcome,
+}
+
+impl DryRunRecord {
+    fn new(message: &AetheriumMessage, app_context: Option<&str>) -> Self {
+        Self {
+            message_id: message.id(),
+            origin: message.origin,
+            destination: message.destination,
+            app_context: app_context.map(str::to_owned),
+            decision: None,
+            decided_at: None,
+            delivered_at: None,
+            outcome: DryRunOutcome::Held,
+        }
+    }
+
+    fn update_outcome(&mut self) {
+        use DryRunDecision::*;
+        use DryRunOutcome::*;
+
+        self.outcome = match (&self.decision, self.delivered_at.is_some()) {
+            (Some(Submit { .. }), false) => WouldSubmit,
+            (Some(Submit { .. }), true) => Matched,
+            (Some(Hold { .. }) | None, false) => Held,
+            (Some(Hold { .. }), true) => DeliveredWhileHeld,
+            (None, true) => DeliveredUndecided,
+        };
+    }
+}
+
+#[derive(Debug, Default)]
+struct DryRunRecords {
+    by_id: HashMap<H256, DryRunRecord>,
+    /// Ids in the order they were first recorded, to forget the oldest ones
+    order: VecDeque<H256>,
+}
+
+impl DryRunRecords {
+    fn entry(
+        &mut self,
+        message: &AetheriumMessage,
+        app_context: Option<&str>,
+    ) -> &mut DryRunRecord {
+        let id = message.id();
+        if !self.by_id.contains_key(&id) {
+            if self.order.len() >= MAX_DRY_RUN_RECORDS {
+                if let Some(oldest) = self.order.pop_front() {
+                    self.by_id.remove(&oldest);
+                }
+            }
+            self.order.push_back(id);
+        }
+        self.by_id
+            .entry(id)
+            .or_insert_with(|| DryRunRecord::new(message, app_context))
+    }
+}
+
+/// Records what the relayer would have done with each message when it runs in dry-run mode, and
+/// compares it with the deliveries made by other relayers. Records are kept in memory.
+#[derive(Debug, Default)]
+pub struct DryRunRecorder {
+    records: Mutex<DryRunRecords>,
+    deliveries: Option<IntCounterVec>,
+}
+
+impl DryRunRecorder {
+    /// Counts the observed deliveries by outcome as a Prometheus counter
+    pub fn with_metrics(mut self, metrics: &CoreMetrics) -> Result<Self> {
+        self.deliveries = Some(metrics.new_int_counter(
+            "dry_run_deliveries",
+            "Deliveries observed by the dry-run relayer, by how they compare to its decision",
+            &["origin", "destination", "outcome"],
+        )?);
+        Ok(self)
+    }
+
+    pub fn record_decision(
+        &self,
+        message: &AetheriumMessage,
+        app_context: Option<&str>,
+        decision: DryRunDecision,
+    ) {
+        let mut records = self.records.lock().unwrap_or_else(|e| e.into_inner());
+        let record = records.entry(message, app_context);
+        if record.delivered_at.is_some() {
+            return;
+        }
+        if let DryRunDecision::Submit { .. } = &decision {
+            info!(id = ?record.message_id, ?decision, "Dry run: would submit message");
+        }
+        record.decision = Some(decision);
+        record.decided_at = Some(unix_timestamp());
+        record.update_outcome();
+    }
+
+    /// Records that the message was delivered on-chain. Only the first delivery of a message is
+    /// counted.
+    pub fn record_delivery(
+        &self,
+        message: &AetheriumMessage,
+        app_context: Option<&str>,
+        (origin_name, destination_name): (&str, &str),
+    ) {
+        let mut records = self.records.lock().unwrap_or_else(|e| e.into_inner());
+        let record = records.entry(message, app_context);
+        if record.delivered_at.is_some() {
+            return;
+        }
+        record.delivered_at = Some(unix_timestamp());
+        record.update_outcome();
+        match record.outcome {
+            DryRunOutcome::Matched => {
+                info!(id = ?record.message_id, "Dry run: message delivered as expected")
+            }
+            outcome => {
+                warn!(id = ?record.message_id, %outcome, decision = ?record.decision, "Dry run: message delivered differently than expected")
+            }
+        }
+        if let Some(deliveries) = &self.deliveries {
+            deliveries
+                .with_label_values(&[origin_name, destination_name, &record.outcome.to_string()])
+                .inc();
+        }
+    }
+
+    /// The recorded messages, optionally only those with `outcome`
+    pub fn records(&self, outcome: Option<DryRunOutcome>) -> Vec<DryRunRecord> {
+        let records = self.records.lock().unwrap_or_else(|e| e.into_inner());
+        records
+            .order
+            .iter()
+            .filter_map(|id| records.by_id.get(id))
+            .filter(|record| outcome.map_or(true, |outcome| record.outcome == outcome))
+            .cloned()
+            .collect()
+    }
+
+    /// Number of recorded messages by outcome
+    pub fn summary(&self) -> BTreeMap<DryRunOutcome, u64> {
+        let records = self.records.lock().unwrap_or_else(|e| e.into_inner());
+        let mut summary = BTreeMap::new();
+        for record in records.by_id.values() {
+            *summary.entry(record.outcome).or_default() += 1;
+        }
+        summary
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    fn message(nonce: u32) -> AetheriumMessage {
+        AetheriumMessage {
+            nonce,
+            ..AetheriumMessage::default()
+        }
+    }
+
+    #[test]
+    fn test_outcomes() {
+        let recorder = DryRunRecorder::default();
+        let submit = DryRunDecision::Submit {
+            gas_limit: U256::from(100_000),
+            ism_verify_gas: None,
+        };
+        let hold = DryRunDecision::Hold {
+            reason: "Gas payment requirement not met".to_owned(),
+        };
+
+        recorder.record_decision(&message(1), None, hold.clone());
+        recorder.record_decision(&message(1), None, submit.clone());
+        recorder.record_delivery(&message(1), None, ("origin", "destination"));
+        recorder.record_decision(&message(2), None, hold.clone());
+        recorder.record_delivery(&message(2), None, ("origin", "destination"));
+        // decisions made after the delivery are ignored
+        recorder.record_decision(&message(2), None, submit.clone());
+        recorder.record_decision(&message(3), None, submit);
+        recorder.record_decision(&message(4), None, hold);
+        recorder.record_delivery(&message(5), None, ("origin", "destination"));
+
+        let outcomes: Vec<_> = recorder
+            .records(None)
+            .into_iter()
+            .map(|record| record.outcome)
+            .collect();
+        assert_eq!(
+            outcomes,
+            vec![
+                DryRunOutcome::Matched,
+                DryRunOutcome::DeliveredWhileHeld,
+                DryRunOutcome::WouldSubmit,
+                DryRunOutcome::Held,
+                DryRunOutcome::DeliveredUndecided,
+            ]
+        );
+        assert_eq!(
+            recorder.records(Some(DryRunOutcome::DeliveredWhileHeld))[0].message_id,
+            message(2).id()
+        );
+        assert_eq!(recorder.summary()[&DryRunOutcome::Held], 1);
+    }
+}
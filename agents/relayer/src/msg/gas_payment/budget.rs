@@ -0,0 +1,465 @@
+use std::{
+    collections::HashMap,
+    sync::{Mutex, MutexGuard},
+    time::{Duration, Instant},
+};
+
+use aetherium_base::db::{AetheriumRocksDB, DB};
+use aetherium_core::{utils::since_unix_epoch, AetheriumMessage, H256, U256};
+use eyre::Result;
+use serde::{Deserialize, Serialize};
+use tracing::warn;
+
+use crate::settings::RelayBudgetConf;
+
+/// Key prefix of the messages charged to the budgets. Budgets apply across origins, so the
+/// charges aren't scoped to a domain.
+const RELAY_BUDGET_CHARGE_PREFIX: &[u8] = b"relay_budget_charge_";
+
+/// Tracks the relay budgets of the config against the gas spent on messages, as recorded by
+/// `GasPaymentEnforcer::record_tx_outcome`. The messages charged in the current windows are
+/// persisted, and what they spent is read back from their gas expenditures when the relayer
+/// restarts.
+#[derive(Debug, Default)]
+pub struct RelayBudgets {
+    budgets: Vec<RelayBudget>,
+    db: Option<BudgetDb>,
+}
+
+#[derive(Debug)]
+struct BudgetDb {
+    db: DB,
+    /// Databases of the origins, which hold the gas expenditures of their messages
+    origin_dbs: HashMap<u32, AetheriumRocksDB>,
+}
+
+#[derive(Debug)]
+struct RelayBudget {
+    conf: RelayBudgetConf,
+    usage: Mutex<BudgetUsage>,
+}
+
+#[derive(Debug, Default)]
+struct BudgetUsage {
+    /// Index of the window the usage is for, counted in windows since the unix epoch
+    window: u64,
+    /// Gas spent by the messages charged in the window
+    charged: HashMap<H256, U256>,
+    /// Estimated cost of the messages that passed the check in the window but whose outcome
+    /// isn't recorded yet
+    reserved: HashMap<H256, U256>,
+}
+
+/// A message charged to a budget, as persisted
+#[derive(Debug, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct BudgetCharge {
+    window: u64,
+    origin: u32,
+    message_id: H256,
+}
+
+impl BudgetUsage {
+    fn spent(&self) -> U256 {
+        self.charged
+            .values()
+            .chain(self.reserved.values())
+            .fold(U256::zero(), |total, spent| total.saturating_add(*spent))
+    }
+
+    fn messages(&self) -> u64 {
+        let reserved_only = self
+            .reserved
+            .keys()
+            .filter(|id| !self.charged.contains_key(id))
+            .count();
+        (self.charged.len() + reserved_only) as u64
+    }
+
+    /// Whether charging `message_id` an extra `tx_cost` would exceed the budget
+    fn exceeds(&self, conf: &RelayBudgetConf, message_id: &H256, tx_cost: U256) -> bool {
+        // a message that was already charged in the window doesn't count against the message
+        // limit again, and its previous reservation is replaced
+        let own_reservation = self.reserved.get(message_id).copied().unwrap_or_default();
+        let spend_exceeded = conf.max_spend.is_some_and(|max| {
+            self.spent()
+                .saturating_sub(own_reservation)
+                .saturating_add(tx_cost)
+                > max
+        });
+        let counted =
+            self.charged.contains_key(message_id) || self.reserved.contains_key(message_id);
+        let messages_exceeded = conf
+            .max_messages
+            .is_some_and(|max| !counted && self.messages() >= max);
+        spend_exceeded || messages_exceeded
+    }
+}
+
+impl RelayBudget {
+    fn window_at(&self, since_epoch: Duration) -> u64 {
+        since_epoch.as_secs() / self.conf.window.as_secs().max(1)
+    }
+
+    /// Locks the usage for the window `since_epoch` falls in, resetting it if the window
+    /// rolled over. Returns the ids of the messages charged in the previous window if it did.
+    fn lock_usage(&self, since_epoch: Duration) -> (MutexGuard<'_, BudgetUsage>, Vec<H256>) {
+        let window = self.window_at(since_epoch);
+        let mut usage = self.usage.lock().unwrap_or_else(|e| e.into_inner());
+        let mut expired = vec![];
+        if usage.window != window {
+            expired = usage.charged.keys().copied().collect();
+            *usage = BudgetUsage {
+                window,
+                ..Default::default()
+            };
+        }
+        (usage, expired)
+    }
+
+    /// Time left until the window `since_epoch` falls in rolls over
+    fn until_rollover(&self, since_epoch: Duration) -> Duration {
+        let window_secs = self.conf.window.as_secs().max(1);
+        let next_window_start =
+            Duration::from_secs((self.window_at(since_epoch) + 1) * window_secs);
+        next_window_start.saturating_sub(since_epoch)
+    }
+}
+
+impl RelayBudgets {
+    pub fn new(confs: impl IntoIterator<Item = RelayBudgetConf>) -> Self {
+        let budgets = confs
+            .into_iter()
+            .map(|conf| RelayBudget {
+                conf,
+                usage: Mutex::new(BudgetUsage::default()),
+            })
+            .collect();
+        Self { budgets, db: None }
+    }
+
+    /// Creates the budgets of the config and restores the usage of their current windows.
+    /// Charges are then persisted to `db`, and `origin_dbs` are where the gas spent by the
+    /// charged messages is read back from.
+    pub fn load(
+        confs: impl IntoIterator<Item = RelayBudgetConf>,
+        db: DB,
+        origin_dbs: impl IntoIterator<Item = AetheriumRocksDB>,
+    ) -> Result<Self> {
+        let mut budgets = Self::new(confs);
+        let budget_db = BudgetDb {
+            db,
+            origin_dbs: origin_dbs
+                .into_iter()
+                .map(|origin_db| (origin_db.domain().id(), origin_db))
+                .collect(),
+        };
+        let since_epoch = since_unix_epoch();
+        for (index, budget) in budgets.budgets.iter().enumerate() {
+            let (mut usage, _) = budget.lock_usage(since_epoch);
+            for raw in budget_db.db.retrieve_by_prefix(&budget_prefix(index))? {
+                let charge: BudgetCharge = serde_json::from_slice(&raw)?;
+                if charge.window != usage.window {
+                    budget_db
+                        .db
+                        .delete(&charge_key(index, &charge.message_id))?;
+                    continue;
+                }
+                let Some(origin_db) = budget_db.origin_dbs.get(&charge.origin) else {
+                    continue;
+                };
+                // the expenditure covers every submission of the message, including any from
+                // before the window started, so this can only overestimate the usage
+                let expenditure =
+                    origin_db.retrieve_gas_expenditure_by_message_id(charge.message_id)?;
+                usage
+                    .charged
+                    .insert(charge.message_id, expenditure.tokens_used);
+            }
+        }
+        budgets.db = Some(budget_db);
+        Ok(budgets)
+    }
+
+    /// If submitting `message` at an estimated cost of `tx_cost` would exceed one of the budgets
+    /// it is subject to, returns when the budgets roll over. Otherwise the estimate is reserved
+    /// until the outcome of the submission is recorded, so messages checked in the meantime
+    /// can't overrun the budgets together.
+    pub fn reserve(&self, message: &AetheriumMessage, tx_cost: U256) -> Option<Instant> {
+        self.reserve_at(message, tx_cost, since_unix_epoch())
+            .map(|remaining| Instant::now() + remaining)
+    }
+
+    /// Frees the reservation of `message`, once it's known that its submission won't be recorded,
+    /// e.g. because the message left the queue or goes back to being prepared
+    pub fn release(&self, message: &AetheriumMessage) {
+        let id = message.id();
+        for (index, budget) in self.matching(message) {
+            let (mut usage, expired) = budget.lock_usage(since_unix_epoch());
+            self.delete_charges(index, &expired);
+            usage.reserved.remove(&id);
+        }
+    }
+
+    /// Charges the budgets `message` is subject to for a submission that spent `tokens_used`,
+    /// in place of its reservation
+    pub fn record_spend(&self, message: &AetheriumMessage, tokens_used: U256) -> Result<()> {
+        self.record_spend_at(message, tokens_used, since_unix_epoch())
+    }
+
+    fn reserve_at(
+        &self,
+        message: &AetheriumMessage,
+        tx_cost: U256,
+        since_epoch: Duration,
+    ) -> Option<Duration> {
+        let id = message.id();
+        // lock every budget the message is subject to before checking any of them, so the check
+        // and the reservation are atomic across budgets
+        let mut usages = self
+            .matching(message)
+            .map(|(index, budget)| {
+                let (usage, expired) = budget.lock_usage(since_epoch);
+                self.delete_charges(index, &expired);
+                (budget, usage)
+            })
+            .collect::<Vec<_>>();
+        let exhausted = usages
+            .iter()
+            .filter(|(budget, usage)| usage.exceeds(&budget.conf, &id, tx_cost))
+            .map(|(budget, _)| budget.until_rollover(since_epoch))
+            .max();
+        if exhausted.is_none() {
+            for (_, usage) in usages.iter_mut() {
+                usage.reserved.insert(id, tx_cost);
+            }
+        }
+        exhausted
+    }
+
+    fn record_spend_at(
+        &self,
+        message: &AetheriumMessage,
+        tokens_used: U256,
+        since_epoch: Duration,
+    ) -> Result<()> {
+        let id = message.id();
+        for (index, budget) in self.matching(message) {
+            let (mut usage, expired) = budget.lock_usage(since_epoch);
+            self.delete_charges(index, &expired);
+            usage.reserved.remove(&id);
+            let spent = usage.charged.entry(id).or_default();
+            *spent = spent.saturating_add(tokens_used);
+            if let Some(budget_db) = &self.db {
+                let charge = BudgetCharge {
+                    window: usage.window,
+                    origin: message.origin,
+                    message_id: id,
+                };
+                budget_db
+                    .db
+                    .store(&charge_key(index, &id), &serde_json::to_vec(&charge)?)?;
+            }
+        }
+        Ok(())
+    }
+
+    /// Deletes the persisted charges of a window that rolled over. Charges that fail to be
+    /// deleted are cleaned up the next time the budgets are loaded.
+    fn delete_charges(&self, index: usize, message_ids: &[H256]) {
+        let Some(budget_db) = &self.db else {
+            return;
+        };
+        for id in message_ids {
+            if let Err(err) = budget_db.db.delete(&charge_key(index, id)) {
+                warn!(?err, budget = index, message_id = ?id, "Failed to delete relay budget charge");
+            }
+        }
+    }
+
+    fn matching<'a>(
+        &'a self,
+        message: &'a AetheriumMessage,
+    ) -> impl Iterator<Item = (usize, &'a RelayBudget)> + 'a {
+        self.budgets
+            .iter()
+            .enumerate()
+            .filter(move |(_, budget)| budget.conf.matching_list.msg_matches(message, true))
+    }
+}
+
+/// Budgets are persisted by their position in the config
+fn budget_prefix(index: usize) -> Vec<u8> {
+    [RELAY_BUDGET_CHARGE_PREFIX, &(index as u32).to_be_bytes()].concat()
+}
+
+fn charge_key(index: usize, message_id: &H256) -> Vec<u8> {
+    [budget_prefix(index).as_slice(), message_id.as_bytes()].concat()
+}
+
+#[cfg(test)]
+mod test {
+    use aetherium_base::db::test_utils;
+    use aetherium_core::{AetheriumDomain, InterchainGasExpenditure};
+
+    use crate::settings::matching_list::MatchingList;
+
+    use super::*;
+
+    const HOUR: Duration = Duration::from_secs(3600);
+
+    fn budget(max_spend: Option<u64>, max_messages: Option<u64>) -> RelayBudgetConf {
+        RelayBudgetConf {
+            matching_list: MatchingList::default(),
+            window: HOUR,
+            max_spend: max_spend.map(U256::from),
+            max_messages,
+        }
+    }
+
+    fn message(nonce: u32) -> AetheriumMessage {
+        AetheriumMessage {
+            nonce,
+            ..AetheriumMessage::default()
+        }
+    }
+
+    #[test]
+    fn test_spend_budget_rolls_over() {
+        let budgets = RelayBudgets::new([budget(Some(1000), None)]);
+        let message = message(0);
+        let start = HOUR * 10 + Duration::from_secs(600);
+
+        assert_eq!(budgets.reserve_at(&message, U256::from(600), start), None);
+        budgets
+            .record_spend_at(&message, U256::from(600), start)
+            .unwrap();
+        // the next submission would go over the budget, until the window rolls over in 50 minutes
+        assert_eq!(
+            budgets.reserve_at(&message, U256::from(600), start),
+            Some(Duration::from_secs(3000))
+        );
+        assert_eq!(budgets.reserve_at(&message, U256::from(400), start), None);
+        assert_eq!(
+            budgets.reserve_at(&message, U256::from(600), HOUR * 11),
+            None
+        );
+    }
+
+    #[test]
+    fn test_reservations_count_against_the_budget() {
+        let budgets = RelayBudgets::new([budget(Some(1000), None)]);
+        let start = HOUR * 10;
+
+        assert_eq!(
+            budgets.reserve_at(&message(0), U256::from(600), start),
+            None
+        );
+        // the first message hasn't been submitted yet, but its estimate is reserved
+        assert_eq!(
+            budgets.reserve_at(&message(1), U256::from(600), start),
+            Some(HOUR)
+        );
+        // checking the same message again replaces its reservation
+        assert_eq!(
+            budgets.reserve_at(&message(0), U256::from(700), start),
+            None
+        );
+        // and recording its outcome replaces the reservation with what it actually spent
+        budgets
+            .record_spend_at(&message(0), U256::from(300), start)
+            .unwrap();
+        assert_eq!(
+            budgets.reserve_at(&message(1), U256::from(600), start),
+            None
+        );
+    }
+
+    #[test]
+    fn test_released_reservations_free_the_budget() {
+        let budgets = RelayBudgets::new([budget(Some(1000), Some(1))]);
+
+        assert!(budgets.reserve(&message(0), U256::from(600)).is_none());
+        assert!(budgets.reserve(&message(1), U256::from(600)).is_some());
+        budgets.release(&message(0));
+        assert!(budgets.reserve(&message(1), U256::from(600)).is_none());
+    }
+
+    #[test]
+    fn test_message_rate_limit() {
+        let budgets = RelayBudgets::new([budget(None, Some(2))]);
+        let start = HOUR * 10;
+
+        assert_eq!(budgets.reserve_at(&message(0), U256::zero(), start), None);
+        assert_eq!(budgets.reserve_at(&message(1), U256::zero(), start), None);
+        assert_eq!(
+            budgets.reserve_at(&message(2), U256::zero(), start),
+            Some(HOUR)
+        );
+        // resubmitting a message that was already counted is fine
+        budgets
+            .record_spend_at(&message(0), U256::one(), start)
+            .unwrap();
+        assert_eq!(budgets.reserve_at(&message(0), U256::zero(), start), None);
+    }
+
+    #[test]
+    fn test_budgets_only_apply_to_matching_messages() {
+        let matching_list: MatchingList =
+            serde_json::from_str(r#"[{"destinationdomain": 2}]"#).unwrap();
+        let budgets = RelayBudgets::new([RelayBudgetConf {
+            matching_list,
+            ..budget(None, Some(0))
+        }]);
+        let start = HOUR * 10;
+
+        let limited = AetheriumMessage {
+            destination: 2,
+            ..AetheriumMessage::default()
+        };
+        let unlimited = AetheriumMessage {
+            destination: 3,
+            ..AetheriumMessage::default()
+        };
+        assert!(budgets.reserve_at(&limited, U256::zero(), start).is_some());
+        assert_eq!(budgets.reserve_at(&unlimited, U256::zero(), start), None);
+    }
+
+    #[tokio::test]
+    async fn test_usage_is_restored_from_gas_expenditures() {
+        test_utils::run_test_db(|db| async move {
+            let origin_db = AetheriumRocksDB::new(
+                &AetheriumDomain::new_test_domain("test_usage_is_restored"),
+                db.clone(),
+            );
+            let message = AetheriumMessage {
+                origin: origin_db.domain().id(),
+                ..AetheriumMessage::default()
+            };
+            let load = || {
+                RelayBudgets::load([budget(Some(1000), None)], db.clone(), [origin_db.clone()])
+                    .unwrap()
+            };
+
+            let budgets = load();
+            assert!(budgets.reserve(&message, U256::from(600)).is_none());
+            origin_db
+                .process_gas_expenditure(InterchainGasExpenditure {
+                    message_id: message.id(),
+                    gas_used: U256::from(6),
+                    tokens_used: U256::from(600),
+                })
+                .unwrap();
+            budgets.record_spend(&message, U256::from(600)).unwrap();
+
+            // a restarted relayer still knows 600 of the budget were spent
+            let other = AetheriumMessage {
+                nonce: 1,
+                ..message.clone()
+            };
+            assert!(load().reserve(&other, U256::from(600)).is_some());
+            assert!(load().reserve(&other, U256::from(400)).is_none());
+        })
+        .await;
+    }
+}
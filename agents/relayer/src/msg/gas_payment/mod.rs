@@ -1,15 +1,16 @@
-use std::fmt::Debug;
+use std::{fmt::Debug, sync::Arc, time::Instant};
 
 use async_trait::async_trait;
 use eyre::Result;
 use aetherium_base::db::AetheriumRocksDB;
 use aetherium_core::{
-    FixedPointNumber, GasPaymentKey, AetheriumMessage, InterchainGasExpenditure,
+    AetheriumDomain, FixedPointNumber, GasPaymentKey, AetheriumMessage, InterchainGasExpenditure,
     InterchainGasPayment, TxCostEstimate, TxOutcome, U256,
 };
 use tracing::{debug, error, trace};
 
-use self::policies::{GasPaymentPolicyMinimum, GasPaymentPolicyNone};
+pub use self::{budget::RelayBudgets, profitability::ProfitabilityTracker};
+use self::policies::{GasPaymentPolicyMinimum, GasPaymentPolicyNone, GasPaymentPolicyUsdValue};
 use crate::{
     msg::gas_payment::policies::GasPaymentPolicyOnChainFeeQuoting,
     settings::{
@@ -17,7 +18,10 @@ use crate::{
     },
 };
 
+mod budget;
 mod policies;
+mod profitability;
+mod token_price;
 
 pub const GAS_EXPENDITURE_LOG_MESSAGE: &str = "Recording gas expenditure for message";
 
@@ -53,6 +57,10 @@ pub struct GasPaymentEnforcer {
     /// policy or another. If a message matches multiple policies'
     /// whitelists, then whichever is first in the list will be used.
     policies: Vec<(Box<dyn GasPaymentPolicy>, MatchingList)>,
+    /// Budgets are shared by the enforcers of all origins
+    budgets: Arc<RelayBudgets>,
+    /// Profitability is accounted across all origins as well
+    profitability: Arc<ProfitabilityTracker>,
     db: AetheriumRocksDB,
 }
 
@@ -75,12 +83,31 @@ impl GasPaymentEnforcer {
                         gas_fraction_numerator: n,
                         gas_fraction_denominator: d,
                     } => Box::new(GasPaymentPolicyOnChainFeeQuoting::new(n, d)),
+                    GasPaymentEnforcementPolicy::UsdValue {
+                        price_source,
+                        margin_percent,
+                    } => Box::new(GasPaymentPolicyUsdValue::new(&price_source, margin_percent)),
                 };
                 (p, cfg.matching_list)
             })
             .collect();
 
-        Self { policies, db }
+        Self {
+            policies,
+            budgets: Default::default(),
+            profitability: Default::default(),
+            db,
+        }
+    }
+
+    pub fn with_budgets(mut self, budgets: Arc<RelayBudgets>) -> Self {
+        self.budgets = budgets;
+        self
+    }
+
+    pub fn with_profitability(mut self, profitability: Arc<ProfitabilityTracker>) -> Self {
+        self.profitability = profitability;
+        self
     }
 }
 
@@ -168,6 +195,65 @@ impl GasPaymentEnforcer {
         Ok(GasPolicyStatus::PolicyNotMet)
     }
 
+    /// Returns when the budgets `message` is subject to roll over, if relaying it now would exceed
+    /// one of them. Otherwise the estimated cost is reserved against the budgets until
+    /// `record_tx_outcome` is called for the message.
+    pub fn reserve_budget(
+        &self,
+        message: &AetheriumMessage,
+        tx_cost_estimate: &TxCostEstimate,
+    ) -> Result<Option<Instant>> {
+        let tx_cost: U256 = (FixedPointNumber::try_from(tx_cost_estimate.gas_limit)?
+            * tx_cost_estimate.gas_price.clone())
+        .ceil_to_integer()
+        .try_into()?;
+        Ok(self.budgets.reserve(message, tx_cost))
+    }
+
+    /// Frees the budget reservation of `message`, when its submission won't be recorded by
+    /// `record_tx_outcome`
+    pub fn release_budget(&self, message: &AetheriumMessage) {
+        self.budgets.release(message);
+    }
+
+    /// Joins the gas payment and the gas expenditure of a delivered message, and adds them to
+    /// the profitability of its lane. Valuing them in USD can take a price fetch, so the totals
+    /// are updated in a separate task rather than holding up the confirmation of the message.
+    pub fn record_delivery(
+        &self,
+        message: &AetheriumMessage,
+        app_context: Option<&str>,
+        destination: &AetheriumDomain,
+    ) -> Result<()> {
+        let payment = self
+            .db
+            .retrieve_gas_payment_by_gas_payment_key(GasPaymentKey {
+                message_id: message.id(),
+                destination: message.destination,
+            })?
+            .map(|payment| payment.payment)
+            .unwrap_or_default();
+        let expenditure = self
+            .db
+            .retrieve_gas_expenditure_by_message_id(message.id())?;
+        let origin = self.db.domain().clone();
+        let destination = destination.clone();
+        let app_context = app_context.map(ToOwned::to_owned);
+        let profitability = self.profitability.clone();
+        tokio::spawn(async move {
+            profitability
+                .record_delivery(
+                    app_context.as_deref(),
+                    (origin.name(), origin.id()),
+                    (destination.name(), destination.id()),
+                    payment,
+                    expenditure.tokens_used,
+                )
+                .await;
+        });
+        Ok(())
+    }
+
     pub fn record_tx_outcome(&self, message: &AetheriumMessage, outcome: TxOutcome) -> Result<()> {
         // This log is required in E2E, hence the use of a `const`
         debug!(
@@ -176,12 +262,14 @@ impl GasPaymentEnforcer {
             "{}",
             GAS_EXPENDITURE_LOG_MESSAGE,
         );
+        let tokens_used: U256 =
+            (FixedPointNumber::try_from(outcome.gas_used)? * outcome.gas_price).try_into()?;
         self.db.process_gas_expenditure(InterchainGasExpenditure {
             message_id: message.id(),
             gas_used: outcome.gas_used,
-            tokens_used: (FixedPointNumber::try_from(outcome.gas_used)? * outcome.gas_price)
-                .try_into()?,
+            tokens_used,
         })?;
+        self.budgets.record_spend(message, tokens_used)?;
         Ok(())
     }
 }
@@ -0,0 +1,235 @@
+use async_trait::async_trait;
+use eyre::{eyre, Result};
+use num_traits::CheckedDiv;
+
+use aetherium_core::{
+    AetheriumMessage, FixedPointNumber, InterchainGasExpenditure, InterchainGasPayment,
+    TxCostEstimate, U256,
+};
+
+use crate::{
+    msg::gas_payment::{
+        token_price::{build_token_price_source, TokenPriceSource},
+        GasPaymentPolicy,
+    },
+    settings::{TokenPrice, TokenPriceSourceConf},
+};
+
+#[derive(Debug)]
+pub struct GasPaymentPolicyUsdValue {
+    price_source: Box<dyn TokenPriceSource>,
+    /// Percentage the payment has to exceed the estimated cost by
+    margin_percent: u64,
+}
+
+impl GasPaymentPolicyUsdValue {
+    pub fn new(price_source: &TokenPriceSourceConf, margin_percent: u64) -> Self {
+        Self {
+            price_source: build_token_price_source(price_source),
+            margin_percent,
+        }
+    }
+
+    async fn price(&self, domain: u32) -> Result<TokenPrice> {
+        self.price_source
+            .price(domain)
+            .await?
+            .ok_or_else(|| eyre!("No USD price for the native token of domain {domain}"))
+    }
+
+    /// Converts an amount of destination tokens into the amount of origin tokens of the same USD
+    /// value, plus the margin
+    fn required_payment(
+        &self,
+        destination_amount: U256,
+        origin: &TokenPrice,
+        destination: &TokenPrice,
+    ) -> Result<U256> {
+        let numerator = FixedPointNumber::try_from(destination_amount)?
+            * destination.usd_price.clone()
+            * FixedPointNumber::try_from(U256::exp10(origin.decimals.into()))?
+            * (100 + self.margin_percent);
+        let denominator = origin.usd_price.clone()
+            * FixedPointNumber::try_from(U256::exp10(destination.decimals.into()))?
+            * 100u64;
+        let required = numerator
+            .checked_div(&denominator)
+            .ok_or_else(|| eyre!("The USD price of the origin native token is zero"))?;
+        Ok(required.ceil_to_integer().try_into()?)
+    }
+}
+
+#[async_trait]
+impl GasPaymentPolicy for GasPaymentPolicyUsdValue {
+    /// UsdValue values the payment and the estimated cost of the transaction in USD, so that
+    /// messages between chains with very different native token prices can be enforced with one
+    /// policy. Like the Minimum policy, UsdValue requires a payment to exist on the IGP specified
+    /// in the config.
+    async fn message_meets_gas_payment_requirement(
+        &self,
+        message: &AetheriumMessage,
+        current_payment: &InterchainGasPayment,
+        current_expenditure: &InterchainGasExpenditure,
+        tx_cost_estimate: &TxCostEstimate,
+    ) -> Result<Option<U256>> {
+        let origin = self.price(message.origin).await?;
+        let destination = self.price(message.destination).await?;
+
+        let tx_cost: U256 = (FixedPointNumber::try_from(tx_cost_estimate.gas_limit)?
+            * tx_cost_estimate.gas_price.clone())
+        .ceil_to_integer()
+        .try_into()?;
+        // tokens already spent on previous attempts have to be covered by the payment as well
+        let destination_amount = tx_cost.saturating_add(current_expenditure.tokens_used);
+
+        let required_payment = self.required_payment(destination_amount, &origin, &destination)?;
+        if current_payment.payment >= required_payment {
+            Ok(Some(tx_cost_estimate.gas_limit))
+        } else {
+            Ok(None)
+        }
+    }
+
+    fn requires_payment_found(&self) -> bool {
+        true
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use std::{collections::HashMap, str::FromStr};
+
+    use aetherium_core::H256;
+
+    use super::*;
+
+    const ORIGIN: u32 = 1;
+    const DESTINATION: u32 = 2;
+
+    fn policy(margin_percent: u64) -> GasPaymentPolicyUsdValue {
+        let price = |usd_price, decimals| TokenPrice {
+            usd_price: FixedPointNumber::from_str(usd_price).unwrap(),
+            decimals,
+        };
+        let prices = HashMap::from([
+            // an expensive origin token with 18 decimals
+            (ORIGIN, price("2000", 18)),
+            // a cheap destination token with 6 decimals
+            (DESTINATION, price("0.5", 6)),
+        ]);
+        GasPaymentPolicyUsdValue::new(&TokenPriceSourceConf::Static { prices }, margin_percent)
+    }
+
+    fn message() -> AetheriumMessage {
+        AetheriumMessage {
+            origin: ORIGIN,
+            destination: DESTINATION,
+            ..AetheriumMessage::default()
+        }
+    }
+
+    fn current_payment(payment: U256) -> InterchainGasPayment {
+        InterchainGasPayment {
+            message_id: H256::zero(),
+            destination: DESTINATION,
+            payment,
+            gas_amount: U256::zero(),
+        }
+    }
+
+    fn current_expenditure(tokens_used: U256) -> InterchainGasExpenditure {
+        InterchainGasExpenditure {
+            message_id: H256::zero(),
+            gas_used: U256::zero(),
+            tokens_used,
+        }
+    }
+
+    // costs 200_000 * 20 = 4_000_000 destination units, i.e. 4 tokens or $2, i.e. 0.001 origin
+    // tokens or 10^15 origin wei
+    fn cost_estimate() -> TxCostEstimate {
+        TxCostEstimate {
+            gas_limit: U256::from(200_000u32),
+            gas_price: FixedPointNumber::from(20u32),
+            l2_gas_limit: None,
+        }
+    }
+
+    async fn meets_requirement(
+        policy: &GasPaymentPolicyUsdValue,
+        payment: U256,
+        tokens_used: U256,
+    ) -> Option<U256> {
+        policy
+            .message_meets_gas_payment_requirement(
+                &message(),
+                &current_payment(payment),
+                &current_expenditure(tokens_used),
+                &cost_estimate(),
+            )
+            .await
+            .unwrap()
+    }
+
+    #[tokio::test]
+    async fn test_payment_must_cover_usd_cost() {
+        let policy = policy(0);
+        let cost = U256::exp10(15);
+
+        assert_eq!(
+            meets_requirement(&policy, cost, U256::zero()).await,
+            Some(U256::from(200_000u32))
+        );
+        assert_eq!(
+            meets_requirement(&policy, cost - 1, U256::zero()).await,
+            None
+        );
+    }
+
+    #[tokio::test]
+    async fn test_margin_and_expenditure_raise_the_requirement() {
+        let cost = U256::exp10(15);
+
+        // 10% on top of the cost
+        let policy_with_margin = policy(10);
+        assert_eq!(
+            meets_requirement(&policy_with_margin, cost, U256::zero()).await,
+            None
+        );
+        assert!(
+            meets_requirement(&policy_with_margin, cost * 11 / 10, U256::zero())
+                .await
+                .is_some()
+        );
+
+        // a previous attempt already spent as much as the transaction costs
+        let policy = policy(0);
+        assert_eq!(
+            meets_requirement(&policy, cost, U256::from(4_000_000u32)).await,
+            None
+        );
+        assert!(
+            meets_requirement(&policy, cost * 2, U256::from(4_000_000u32))
+                .await
+                .is_some()
+        );
+    }
+
+    #[tokio::test]
+    async fn test_unknown_price_is_an_error() {
+        let policy = policy(0);
+        let message = AetheriumMessage {
+            destination: 3,
+            ..message()
+        };
+        assert!(policy
+            .message_meets_gas_payment_requirement(
+                &message,
+                &current_payment(U256::exp10(18)),
+                &current_expenditure(U256::zero()),
+                &cost_estimate(),
+            )
+            .await
+            .is_err());
+    }
+}
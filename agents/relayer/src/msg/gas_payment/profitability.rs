@@ -0,0 +1,406 @@
+use std::{collections::BTreeMap, fmt::Write, sync::Mutex};
+
+use aetherium_base::{db::DB, CoreMetrics};
+use aetherium_core::{FixedPointNumber, U256};
+use eyre::Result;
+use prometheus::GaugeVec;
+use serde::{Deserialize, Serialize};
+use tracing::warn;
+
+use crate::{
+    msg::gas_payment::token_price::{build_token_price_source, TokenPriceSource},
+    settings::{TokenPrice, TokenPriceSourceConf},
+};
+
+/// Label used for messages that don't match any of the `metricAppContexts`
+const UNKNOWN_APP_CONTEXT: &str = "unknown";
+
+/// Key prefix of the lane totals in the database. Lanes span origins, so unlike message data
+/// they aren't scoped to a domain.
+const RELAY_PROFITABILITY_PREFIX: &[u8] = b"relay_profitability_";
+
+/// USD amounts are computed in millionths of a dollar before being converted to floats
+const MICRO_USD_PER_USD: u64 = 1_000_000;
+
+/// Revenue and cost of the messages delivered between two chains for an app context
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct LaneProfitability {
+    pub app_context: String,
+    pub origin: String,
+    pub destination: String,
+    /// Number of delivered messages
+    pub messages: u64,
+    /// Gas payments made for the messages, in the smallest unit of the origin native token
+    pub revenue: U256,
+    /// Gas spent delivering the messages, in the smallest unit of the destination native token
+    pub cost: U256,
+    pub revenue_usd: f64,
+    pub cost_usd: f64,
+    pub margin_usd: f64,
+    /// Messages whose revenue or cost couldn't be valued in USD, and that are left out of the
+    /// USD amounts
+    pub unpriced_messages: u64,
+}
+
+impl LaneProfitability {
+    fn key(&self) -> LaneKey {
+        (
+            self.app_context.clone(),
+            self.origin.clone(),
+            self.destination.clone(),
+        )
+    }
+
+    const CSV_HEADER: &'static str = "app_context,origin,destination,messages,revenue,cost,revenue_usd,cost_usd,margin_usd,unpriced_messages";
+
+    fn write_csv_row(&self, csv: &mut String) -> std::fmt::Result {
+        writeln!(
+            csv,
+            "{},{},{},{},{},{},{:.6},{:.6},{:.6},{}",
+            csv_field(&self.app_context),
+            csv_field(&self.origin),
+            csv_field(&self.destination),
+            self.messages,
+            self.revenue,
+            self.cost,
+            self.revenue_usd,
+            self.cost_usd,
+            self.margin_usd,
+            self.unpriced_messages,
+        )
+    }
+}
+
+/// Quotes a field if it contains characters that are special in CSV
+fn csv_field(field: &str) -> String {
+    if field.contains([',', '"', '\n']) {
+        format!("\"{}\"", field.replace('"', "\"\""))
+    } else {
+        field.to_owned()
+    }
+}
+
+#[derive(Debug)]
+struct ProfitabilityMetrics {
+    revenue: GaugeVec,
+    cost: GaugeVec,
+    revenue_usd: GaugeVec,
+    cost_usd: GaugeVec,
+    margin_usd: GaugeVec,
+}
+
+impl ProfitabilityMetrics {
+    const LABELS: [&'static str; 3] = ["app_context", "origin", "destination"];
+
+    fn new(metrics: &CoreMetrics) -> Result<Self> {
+        Ok(Self {
+            revenue: metrics.new_gauge(
+                "profitability_revenue",
+                "Gas payments for delivered messages, in the smallest unit of the origin native token",
+                &Self::LABELS,
+            )?,
+            cost: metrics.new_gauge(
+                "profitability_cost",
+                "Gas spent delivering messages, in the smallest unit of the destination native token",
+                &Self::LABELS,
+            )?,
+            revenue_usd: metrics.new_gauge(
+                "profitability_revenue_usd",
+                "Gas payments for delivered messages, in USD",
+                &Self::LABELS,
+            )?,
+            cost_usd: metrics.new_gauge(
+                "profitability_cost_usd",
+                "Gas spent delivering messages, in USD",
+                &Self::LABELS,
+            )?,
+            margin_usd: metrics.new_gauge(
+                "profitability_margin_usd",
+                "Gas payments minus gas spent for delivered messages, in USD",
+                &Self::LABELS,
+            )?,
+        })
+    }
+
+    fn update(&self, lane: &LaneProfitability) {
+        let labels = [
+            lane.app_context.as_str(),
+            lane.origin.as_str(),
+            lane.destination.as_str(),
+        ];
+        self.revenue
+            .with_label_values(&labels)
+            .set(lane.revenue.to_f64_lossy());
+        self.cost
+            .with_label_values(&labels)
+            .set(lane.cost.to_f64_lossy());
+        self.revenue_usd
+            .with_label_values(&labels)
+            .set(lane.revenue_usd);
+        self.cost_usd.with_label_values(&labels).set(lane.cost_usd);
+        self.margin_usd
+            .with_label_values(&labels)
+            .set(lane.margin_usd);
+    }
+}
+
+/// Aggregates the gas payment and the gas expenditure of delivered messages by app context,
+/// origin and destination. Totals are persisted when a database is given, so they survive
+/// restarts.
+#[derive(Debug, Default)]
+pub struct ProfitabilityTracker {
+    lanes: Mutex<BTreeMap<LaneKey, LaneProfitability>>,
+    price_source: Option<Box<dyn TokenPriceSource>>,
+    db: Option<DB>,
+    metrics: Option<ProfitabilityMetrics>,
+}
+
+/// App context, origin and destination of a lane
+type LaneKey = (String, String, String);
+
+impl ProfitabilityTracker {
+    pub fn new(price_source: Option<&TokenPriceSourceConf>) -> Self {
+        Self {
+            price_source: price_source.map(build_token_price_source),
+            ..Default::default()
+        }
+    }
+
+    /// Loads the totals stored in `db`, which the totals are then written to as messages are
+    /// delivered
+    pub fn load(price_source: Option<&TokenPriceSourceConf>, db: DB) -> Result<Self> {
+        let lanes = db
+            .retrieve_by_prefix(RELAY_PROFITABILITY_PREFIX)?
+            .iter()
+            .map(|raw| -> Result<_> {
+                let lane: LaneProfitability = serde_json::from_slice(raw)?;
+                Ok((lane.key(), lane))
+            })
+            .collect::<Result<_>>()?;
+        Ok(Self {
+            lanes: Mutex::new(lanes),
+            db: Some(db),
+            ..Self::new(price_source)
+        })
+    }
+
+    /// Exports the totals as Prometheus gauges
+    pub fn with_metrics(mut self, metrics: &CoreMetrics) -> Result<Self> {
+        let profitability_metrics = ProfitabilityMetrics::new(metrics)?;
+        for lane in self.lanes() {
+            profitability_metrics.update(&lane);
+        }
+        self.metrics = Some(profitability_metrics);
+        Ok(self)
+    }
+
+    /// Adds a delivered message to the totals of its lane
+    pub async fn record_delivery(
+        &self,
+        app_context: Option<&str>,
+        (origin_name, origin): (&str, u32),
+        (destination_name, destination): (&str, u32),
+        payment: U256,
+        tokens_used: U256,
+    ) {
+        // prices are fetched before taking the lock, which is never held across an await
+        let revenue_usd = self.usd_value(origin, payment).await;
+        let cost_usd = self.usd_value(destination, tokens_used).await;
+
+        let app_context = app_context.unwrap_or(UNKNOWN_APP_CONTEXT).to_owned();
+        let key = (
+            app_context.clone(),
+            origin_name.to_owned(),
+            destination_name.to_owned(),
+        );
+        let mut lanes = self.lanes.lock().unwrap_or_else(|e| e.into_inner());
+        let lane = lanes.entry(key).or_insert_with(|| LaneProfitability {
+            app_context,
+            origin: origin_name.to_owned(),
+            destination: destination_name.to_owned(),
+            ..Default::default()
+        });
+        lane.messages += 1;
+        lane.revenue = lane.revenue.saturating_add(payment);
+        lane.cost = lane.cost.saturating_add(tokens_used);
+        match (revenue_usd, cost_usd) {
+            (Some(revenue_usd), Some(cost_usd)) => {
+                lane.revenue_usd += revenue_usd;
+                lane.cost_usd += cost_usd;
+                lane.margin_usd = lane.revenue_usd - lane.cost_usd;
+            }
+            _ => lane.unpriced_messages += 1,
+        }
+
+        if let Some(metrics) = &self.metrics {
+            metrics.update(lane);
+        }
+        if let Some(db) = &self.db {
+            let stored = serde_json::to_vec(&lane)
+                .map_err(Into::into)
+                .and_then(|raw| db.store(&lane_db_key(&lane.key()), &raw));
+            if let Err(err) = stored {
+                warn!(?err, ?lane, "Failed to store lane profitability");
+            }
+        }
+    }
+
+    /// The totals of every lane, ordered by app context, origin and destination
+    pub fn lanes(&self) -> Vec<LaneProfitability> {
+        self.lanes
+            .lock()
+            .unwrap_or_else(|e| e.into_inner())
+            .values()
+            .cloned()
+            .collect()
+    }
+
+    pub fn to_csv(&self) -> String {
+        let mut csv = String::new();
+        // writing to a `String` can't fail
+        let _ = writeln!(csv, "{}", LaneProfitability::CSV_HEADER);
+        for lane in self.lanes() {
+            let _ = lane.write_csv_row(&mut csv);
+        }
+        csv
+    }
+
+    async fn usd_value(&self, domain: u32, amount: U256) -> Option<f64> {
+        let price_source = self.price_source.as_ref()?;
+        let price = match price_source.price(domain).await {
+            Ok(Some(price)) => price,
+            Ok(None) => return None,
+            Err(err) => {
+                warn!(
+                    ?err,
+                    domain, "Failed to fetch token price for profitability"
+                );
+                return None;
+            }
+        };
+        let micro_usd = micro_usd_value(amount, price).ok()?;
+        Some(micro_usd.to_f64_lossy() / MICRO_USD_PER_USD as f64)
+    }
+}
+
+fn lane_db_key(key: &LaneKey) -> Vec<u8> {
+    // names are JSON-encoded so that no separator can be confused with a name
+    let encoded = serde_json::to_vec(key).expect("strings always serialize");
+    [RELAY_PROFITABILITY_PREFIX, &encoded].concat()
+}
+
+fn micro_usd_value(amount: U256, price: TokenPrice) -> Result<U256> {
+    let value = FixedPointNumber::try_from(amount)? * price.usd_price * MICRO_USD_PER_USD
+        / FixedPointNumber::try_from(U256::exp10(price.decimals.into()))?;
+    Ok(value.try_into()?)
+}
+
+#[cfg(test)]
+mod test {
+    use std::{collections::HashMap, str::FromStr};
+
+    use aetherium_base::db::test_utils;
+
+    use super::*;
+
+    fn tracker_with_prices() -> ProfitabilityTracker {
+        let price = |usd_price, decimals| TokenPrice {
+            usd_price: FixedPointNumber::from_str(usd_price).unwrap(),
+            decimals,
+        };
+        let prices = HashMap::from([(1, price("2000", 18)), (2, price("0.5", 6))]);
+        ProfitabilityTracker::new(Some(&TokenPriceSourceConf::Static { prices }))
+    }
+
+    #[tokio::test]
+    async fn test_aggregates_by_lane() {
+        let tracker = tracker_with_prices();
+        // $2 of revenue and $1 of cost each
+        for _ in 0..2 {
+            tracker
+                .record_delivery(
+                    Some("app"),
+                    ("origin", 1),
+                    ("destination", 2),
+                    U256::exp10(15),
+                    U256::from(2_000_000u32),
+                )
+                .await;
+        }
+        // a message to a destination without a price
+        tracker
+            .record_delivery(
+                None,
+                ("origin", 1),
+                ("other", 3),
+                U256::exp10(15),
+                U256::from(1u32),
+            )
+            .await;
+
+        let lanes = tracker.lanes();
+        assert_eq!(lanes.len(), 2);
+        assert_eq!(
+            lanes[0],
+            LaneProfitability {
+                app_context: "app".to_owned(),
+                origin: "origin".to_owned(),
+                destination: "destination".to_owned(),
+                messages: 2,
+                revenue: U256::exp10(15) * 2,
+                cost: U256::from(4_000_000u32),
+                revenue_usd: 4.0,
+                cost_usd: 2.0,
+                margin_usd: 2.0,
+                unpriced_messages: 0,
+            }
+        );
+        assert_eq!(lanes[1].app_context, UNKNOWN_APP_CONTEXT);
+        assert_eq!(lanes[1].unpriced_messages, 1);
+        assert_eq!(lanes[1].revenue_usd, 0.0);
+    }
+
+    #[tokio::test]
+    async fn test_csv_export() {
+        let tracker = tracker_with_prices();
+        tracker
+            .record_delivery(
+                Some("app, with comma"),
+                ("origin", 1),
+                ("destination", 2),
+                U256::exp10(15),
+                U256::from(2_000_000u32),
+            )
+            .await;
+
+        assert_eq!(
+            tracker.to_csv(),
+            format!(
+                "{}\n\"app, with comma\",origin,destination,1,1000000000000000,2000000,2.000000,1.000000,1.000000,0\n",
+                LaneProfitability::CSV_HEADER
+            )
+        );
+    }
+
+    #[tokio::test]
+    async fn test_totals_are_persisted() {
+        test_utils::run_test_db(|db| async move {
+            let tracker = ProfitabilityTracker::load(None, db.clone()).unwrap();
+            tracker
+                .record_delivery(
+                    Some("app"),
+                    ("origin", 1),
+                    ("destination", 2),
+                    U256::from(10u32),
+                    U256::from(4u32),
+                )
+                .await;
+
+            let reloaded = ProfitabilityTracker::load(None, db).unwrap();
+            assert_eq!(reloaded.lanes(), tracker.lanes());
+            assert_eq!(reloaded.lanes()[0].messages, 1);
+        })
+        .await;
+    }
+}
@@ -0,0 +1,305 @@
+use std::{
+    collections::HashMap,
+    fmt::Debug,
+    path::PathBuf,
+    time::{Duration, Instant},
+};
+
+use async_trait::async_trait;
+use eyre::{eyre, Context, Result};
+use aetherium_core::FixedPointNumber;
+use reqwest::Url;
+use serde::Deserialize;
+use tokio::sync::{Mutex, RwLock};
+use tracing::warn;
+
+use crate::settings::{TokenPrice, TokenPriceSourceConf};
+
+/// Provides the USD prices of the native tokens of chains
+#[async_trait]
+pub trait TokenPriceSource: Debug + Send + Sync {
+    /// Returns the price of the native token of `domain`, or None if it's unknown
+    async fn price(&self, domain: u32) -> Result<Option<TokenPrice>>;
+}
+
+pub fn build_token_price_source(conf: &TokenPriceSourceConf) -> Box<dyn TokenPriceSource> {
+    match conf {
+        TokenPriceSourceConf::Static { prices } => Box::new(StaticTokenPriceSource {
+            prices: prices.clone(),
+        }),
+        TokenPriceSourceConf::File {
+            path,
+            max_staleness,
+        } => Box::new(FetchedTokenPriceSource::new(
+            TokenPriceLocation::File(path.clone()),
+            *max_staleness,
+        )),
+        TokenPriceSourceConf::Http { url, max_staleness } => Box::new(
+            FetchedTokenPriceSource::new(TokenPriceLocation::Http(url.clone()), *max_staleness),
+        ),
+    }
+}
+
+#[derive(Debug)]
+pub struct StaticTokenPriceSource {
+    prices: HashMap<u32, TokenPrice>,
+}
+
+#[async_trait]
+impl TokenPriceSource for StaticTokenPriceSource {
+    async fn price(&self, domain: u32) -> Result<Option<TokenPrice>> {
+        Ok(self.prices.get(&domain).cloned())
+    }
+}
+
+#[derive(Debug)]
+enum TokenPriceLocation {
+    File(PathBuf),
+    Http(Url),
+}
+
+/// An entry of the list of prices read from a file or an HTTP endpoint, e.g.
+/// `{ "domain": 1, "usdPrice": "3150.25", "decimals": 18 }`
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct RawTokenPrice {
+    domain: u32,
+    usd_price: String,
+    #[serde(default = "default_decimals")]
+    decimals: u8,
+}
+
+fn default_decimals() -> u8 {
+    18
+}
+
+#[derive(Debug)]
+struct CachedTokenPrices {
+    prices: HashMap<u32, TokenPrice>,
+    fetched_at: Instant,
+}
+
+/// Prices fetched from a file or an HTTP endpoint, cached for a period of time to avoid
+/// fetching them for every message
+#[derive(Debug)]
+pub struct FetchedTokenPriceSource {
+    location: TokenPriceLocation,
+    /// Cached prices older than this aren't used while fresh ones can't be fetched
+    max_staleness: Duration,
+    client: reqwest::Client,
+    cache: RwLock<Option<CachedTokenPrices>>,
+    /// Held while fetching, so that only one fetch is in flight, and holds the time of the last
+    /// failed one. The cache isn't locked meanwhile, so that stale prices can still be read.
+    fetch_lock: Mutex<Option<Instant>>,
+}
+
+impl FetchedTokenPriceSource {
+    /// Time to live for the cached prices. 1 min.
+    const TTL: Duration = Duration::from_secs(60);
+    /// Time to wait after a failed fetch before fetching again. 10 sec.
+    const RETRY_BACKOFF: Duration = Duration::from_secs(10);
+    /// Timeout of the requests to an HTTP endpoint. 10 sec.
+    const REQUEST_TIMEOUT: Duration = Duration::from_secs(10);
+
+    fn new(location: TokenPriceLocation, max_staleness: Duration) -> Self {
+        Self {
+            location,
+            max_staleness,
+            client: reqwest::Client::new(),
+            cache: RwLock::new(None),
+            fetch_lock: Mutex::new(None),
+        }
+    }
+
+    /// The cached price of `domain` and the age of the cached prices, or None if no prices were
+    /// fetched yet
+    async fn cached_price(&self, domain: u32) -> Option<(Option<TokenPrice>, Duration)> {
+        self.cache.read().await.as_ref().map(|cached| {
+            (
+                cached.prices.get(&domain).cloned(),
+                cached.fetched_at.elapsed(),
+            )
+        })
+    }
+
+    async fn fetch(&self) -> Result<HashMap<u32, TokenPrice>> {
+        let raw_prices: Vec<RawTokenPrice> = match &self.location {
+            TokenPriceLocation::File(path) => {
+                let contents = tokio::fs::read_to_string(path)
+                    .await
+                    .with_context(|| format!("Reading token prices from {}", path.display()))?;
+                serde_json::from_str(&contents)?
+            }
+            TokenPriceLocation::Http(url) => {
+                self.client
+                    .get(url.clone())
+                    .timeout(Self::REQUEST_TIMEOUT)
+                    .send()
+                    .await?
+                    .error_for_status()?
+                    .json()
+                    .await?
+            }
+        };
+        raw_prices
+            .into_iter()
+            .map(|raw| {
+                let usd_price = raw.usd_price.parse::<FixedPointNumber>().with_context(|| {
+                    format!(
+                        "Invalid USD price `{}` for domain {}",
+                        raw.usd_price, raw.domain
+                    )
+                })?;
+                Ok((
+                    raw.domain,
+                    TokenPrice {
+                        usd_price,
+                        decimals: raw.decimals,
+                    },
+                ))
+            })
+            .collect()
+    }
+}
+
+#[async_trait]
+impl TokenPriceSource for FetchedTokenPriceSource {
+    async fn price(&self, domain: u32) -> Result<Option<TokenPrice>> {
+        let stale = match self.cached_price(domain).await {
+            Some((price, age)) if age < Self::TTL => return Ok(price),
+            Some((price, age)) if age < self.max_staleness => Some(price),
+            _ => None,
+        };
+
+        // stale prices are better than none while the source is unavailable, or while another
+        // caller is fetching fresh ones
+        let mut last_failure = match (self.fetch_lock.try_lock(), stale.clone()) {
+            (Ok(last_failure), _) => last_failure,
+            (Err(_), Some(price)) => return Ok(price),
+            (Err(_), None) => self.fetch_lock.lock().await,
+        };
+        if let Some((price, age)) = self.cached_price(domain).await {
+            if age < Self::TTL {
+                return Ok(price);
+            }
+        }
+        if let Some(failed_at) = *last_failure {
+            if failed_at.elapsed() < Self::RETRY_BACKOFF {
+                return stale.ok_or_else(|| {
+                    eyre!(
+                        "Token prices are unavailable, the last fetch failed {:?} ago",
+                        failed_at.elapsed()
+                    )
+                });
+            }
+        }
+
+        match self.fetch().await {
+            Ok(prices) => {
+                let price = prices.get(&domain).cloned();
+                *self.cache.write().await = Some(CachedTokenPrices {
+                    prices,
+                    fetched_at: Instant::now(),
+                });
+                *last_failure = None;
+                Ok(price)
+            }
+            Err(err) => {
+                *last_failure = Some(Instant::now());
+                match stale {
+                    Some(price) => {
+                        warn!(?err, location=?self.location, "Failed to refresh token prices, using stale ones");
+                        Ok(price)
+                    }
+                    None => Err(err),
+                }
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use std::str::FromStr;
+
+    use super::*;
+
+    #[tokio::test]
+    async fn test_file_token_price_source() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("token-prices.json");
+        std::fs::write(
+            &path,
+            r#"[{ "domain": 1, "usdPrice": "3150.25" }, { "domain": 2, "usdPrice": "0.5", "decimals": 6 }]"#,
+        )
+        .unwrap();
+        let source = build_token_price_source(&TokenPriceSourceConf::File {
+            path: path.clone(),
+            max_staleness: Duration::from_secs(600),
+        });
+
+        assert_eq!(
+            source.price(1).await.unwrap(),
+            Some(TokenPrice {
+                usd_price: FixedPointNumber::from_str("3150.25").unwrap(),
+                decimals: 18,
+            })
+        );
+        assert_eq!(source.price(2).await.unwrap().unwrap().decimals, 6);
+        assert_eq!(source.price(3).await.unwrap(), None);
+
+        // cached prices are used until they expire
+        std::fs::remove_file(&path).unwrap();
+        assert!(source.price(1).await.unwrap().is_some());
+    }
+
+    #[tokio::test]
+    async fn test_file_token_price_source_errors_without_prices() {
+        let source = build_token_price_source(&TokenPriceSourceConf::File {
+            path: PathBuf::from("/nonexistent/prices.json"),
+            max_staleness: Duration::from_secs(600),
+        });
+        assert!(source.price(1).await.is_err());
+    }
+
+    #[tokio::test]
+    async fn test_failed_fetch_backs_off() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("token-prices.json");
+        let source = build_token_price_source(&TokenPriceSourceConf::File {
+            path: path.clone(),
+            max_staleness: Duration::from_secs(600),
+        });
+        assert!(source.price(1).await.is_err());
+
+        // the source isn't read again until the backoff passed
+        std::fs::write(&path, r#"[{ "domain": 1, "usdPrice": "3150.25" }]"#).unwrap();
+        assert!(source.price(1).await.is_err());
+    }
+
+    #[tokio::test]
+    async fn test_prices_past_max_staleness_are_not_used() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("token-prices.json");
+        std::fs::write(&path, r#"[{ "domain": 1, "usdPrice": "3150.25" }]"#).unwrap();
+        let source = FetchedTokenPriceSource::new(
+            TokenPriceLocation::File(path.clone()),
+            Duration::from_secs(600),
+        );
+        assert!(source.price(1).await.unwrap().is_some());
+        std::fs::remove_file(&path).unwrap();
+
+        // expired prices are still used while they can't be refreshed
+        age_cache(&source, Duration::from_secs(120)).await;
+        assert!(source.price(1).await.unwrap().is_some());
+
+        // but not once they are older than the max staleness
+        age_cache(&source, Duration::from_secs(900)).await;
+        assert!(source.price(1).await.is_err());
+    }
+
+    async fn age_cache(source: &FetchedTokenPriceSource, age: Duration) {
+        let mut cache = source.cache.write().await;
+        cache.as_mut().unwrap().fetched_at = Instant::now() - age;
+    }
+}
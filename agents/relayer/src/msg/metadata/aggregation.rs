@@ -126,15 +126,26 @@ impl MetadataBuilder for AggregationIsmMetadataBuilder {
         message: &AetheriumMessage,
         params: MessageMetadataBuildParams,
     ) -> Result<Metadata, MetadataBuildError> {
-        let ism = self
-            .base_builder()
-            .build_aggregation_ism(ism_address)
-            .await
-            .map_err(|err| MetadataBuildError::FailedToBuild(err.to_string()))?;
-        let (ism_addresses, threshold) = ism
-            .modules_and_threshold(message)
-            .await
-            .map_err(|err| MetadataBuildError::FailedToBuild(err.to_string()))?;
+        let cache = self.base_builder().metadata_cache();
+        let (ism_addresses, threshold) =
+            match cache.and_then(|cache| cache.modules(ism_address, message.id())) {
+                Some(modules) => modules,
+                None => {
+                    let ism = self
+                        .base_builder()
+                        .build_aggregation_ism(ism_address)
+                        .await
+                        .map_err(|err| MetadataBuildError::FailedToBuild(err.to_string()))?;
+                    let modules = ism
+                        .modules_and_threshold(message)
+                        .await
+                        .map_err(|err| MetadataBuildError::FailedToBuild(err.to_string()))?;
+                    if let Some(cache) = cache {
+                        cache.set_modules(ism_address, message.id(), modules.clone());
+                    }
+                    modules
+                }
+            };
 
         let threshold = threshold as usize;
 
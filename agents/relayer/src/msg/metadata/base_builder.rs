@@ -23,7 +23,7 @@ use aetherium_core::{
 use tokio::sync::RwLock;
 use tracing::{debug, info, warn};
 
-use super::IsmAwareAppContextClassifier;
+use super::{IsmAwareAppContextClassifier, MetadataCache};
 
 /// Base metadata builder with types used by higher level metadata builders.
 #[allow(clippy::too_many_arguments)]
@@ -37,6 +37,8 @@ pub struct BaseMetadataBuilder {
     metrics: Arc<CoreMetrics>,
     db: AetheriumRocksDB,
     app_context_classifier: IsmAwareAppContextClassifier,
+    /// Shared by the builders of every origin with the same destination
+    metadata_cache: Option<Arc<MetadataCache>>,
 }
 
 impl Debug for BaseMetadataBuilder {
@@ -54,6 +56,9 @@ pub trait BuildsBaseMetadata: Send + Sync + Debug {
     fn origin_domain(&self) -> &AetheriumDomain;
     fn destination_domain(&self) -> &AetheriumDomain;
     fn app_context_classifier(&self) -> &IsmAwareAppContextClassifier;
+    /// Where the results of the calls made to build metadata are cached across retries, if
+    /// caching is enabled
+    fn metadata_cache(&self) -> Option<&MetadataCache>;
 
     async fn get_proof(&self, leaf_index: u32, checkpoint: Checkpoint) -> eyre::Result<Proof>;
     async fn highest_known_leaf_index(&self) -> Option<u32>;
@@ -85,6 +90,10 @@ impl BuildsBaseMetadata for BaseMetadataBuilder {
         &self.app_context_classifier
     }
 
+    fn metadata_cache(&self) -> Option<&MetadataCache> {
+        self.metadata_cache.as_deref()
+    }
+
     async fn get_proof(&self, leaf_index: u32, checkpoint: Checkpoint) -> eyre::Result<Proof> {
         const CTX: &str = "When fetching message proof";
         let proof = self
@@ -193,7 +202,7 @@ impl BuildsBaseMetadata for BaseMetadataBuilder {
                     continue;
                 }
 
-                match config.build_and_validate(None).await {
+                match config.build_and_validate(None, None).await {
                     Ok(checkpoint_syncer) => {
                         // found the syncer for this validator
                         checkpoint_syncers.insert(validator.into(), checkpoint_syncer.into());
@@ -0,0 +1,161 @@
+use std::{
+    collections::HashMap,
+    fmt::Debug,
+    hash::Hash,
+    sync::Mutex,
+    time::{Duration, Instant},
+};
+
+use aetherium_core::{ModuleType, H256};
+use tracing::trace;
+
+/// Expired entries are only evicted once a cache holds this many of them
+const MAX_ENTRIES: usize = 100_000;
+
+/// A map whose entries expire `ttl` after they were inserted
+#[derive(Debug)]
+struct TtlCache<K, V> {
+    name: &'static str,
+    ttl: Duration,
+    entries: Mutex<HashMap<K, (V, Instant)>>,
+}
+
+impl<K: Eq + Hash + Debug, V: Clone> TtlCache<K, V> {
+    fn new(name: &'static str, ttl: Duration) -> Self {
+        Self {
+            name,
+            ttl,
+            entries: Default::default(),
+        }
+    }
+
+    fn get(&self, key: &K) -> Option<V> {
+        let entries = self.entries.lock().unwrap_or_else(|e| e.into_inner());
+        let (value, inserted_at) = entries.get(key)?;
+        if inserted_at.elapsed() >= self.ttl {
+            return None;
+        }
+        trace!(cache = self.name, ?key, "Metadata cache hit");
+        Some(value.clone())
+    }
+
+    fn insert(&self, key: K, value: V) {
+        let mut entries = self.entries.lock().unwrap_or_else(|e| e.into_inner());
+        if entries.len() >= MAX_ENTRIES {
+            entries.retain(|_, (_, inserted_at)| inserted_at.elapsed() < self.ttl);
+            if entries.len() >= MAX_ENTRIES {
+                entries.clear();
+            }
+        }
+        entries.insert(key, (value, Instant::now()));
+    }
+
+    fn retain(&self, mut keep: impl FnMut(&K) -> bool) {
+        let mut entries = self.entries.lock().unwrap_or_else(|e| e.into_inner());
+        entries.retain(|key, _| keep(key));
+    }
+}
+
+/// Caches the ISM tree of the messages for one destination, so that retrying a message doesn't
+/// walk its ISM tree all over again.
+///
+/// Results are keyed by ISM address and message id, since an ISM may route or pick validators
+/// based on anything in the message, and expire after the TTL. A change of the ISM config is
+/// picked up once the entries expire, or straight away for a message whose metadata stops
+/// verifying, since all its entries are dropped then.
+///
+/// Checkpoint syncers and the metadata built from them are never cached: building a syncer
+/// checks whether the validators flagged a reorg, which has to be done on every attempt.
+#[derive(Debug)]
+pub struct MetadataCache {
+    module_types: TtlCache<H256, ModuleType>,
+    routes: TtlCache<(H256, H256), H256>,
+    validators: TtlCache<(H256, H256), (Vec<H256>, u8)>,
+    modules: TtlCache<(H256, H256), (Vec<H256>, u8)>,
+}
+
+impl MetadataCache {
+    pub fn new(ttl: Duration) -> Self {
+        Self {
+            module_types: TtlCache::new("module_types", ttl),
+            routes: TtlCache::new("routes", ttl),
+            validators: TtlCache::new("validators", ttl),
+            modules: TtlCache::new("modules", ttl),
+        }
+    }
+
+    pub fn module_type(&self, ism: H256) -> Option<ModuleType> {
+        self.module_types.get(&ism)
+    }
+
+    pub fn set_module_type(&self, ism: H256, module_type: ModuleType) {
+        self.module_types.insert(ism, module_type);
+    }
+
+    /// The ISM a routing ISM routes the message to
+    pub fn route(&self, ism: H256, message_id: H256) -> Option<H256> {
+        self.routes.get(&(ism, message_id))
+    }
+
+    pub fn set_route(&self, ism: H256, message_id: H256, route: H256) {
+        self.routes.insert((ism, message_id), route);
+    }
+
+    /// The validators and threshold of a multisig ISM for the message
+    pub fn validators(&self, ism: H256, message_id: H256) -> Option<(Vec<H256>, u8)> {
+        self.validators.get(&(ism, message_id))
+    }
+
+    pub fn set_validators(&self, ism: H256, message_id: H256, validators: (Vec<H256>, u8)) {
+        self.validators.insert((ism, message_id), validators);
+    }
+
+    /// The modules and threshold of an aggregation ISM for the message
+    pub fn modules(&self, ism: H256, message_id: H256) -> Option<(Vec<H256>, u8)> {
+        self.modules.get(&(ism, message_id))
+    }
+
+    pub fn set_modules(&self, ism: H256, message_id: H256, modules: (Vec<H256>, u8)) {
+        self.modules.insert((ism, message_id), modules);
+    }
+
+    /// Drops every entry of the message, e.g. because its metadata didn't verify
+    pub fn invalidate_message(&self, message_id: H256) {
+        self.routes.retain(|(_, id)| *id != message_id);
+        self.validators.retain(|(_, id)| *id != message_id);
+        self.modules.retain(|(_, id)| *id != message_id);
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_metadata_cache() {
+        let cache = MetadataCache::new(Duration::from_secs(60));
+        let (ism, message_id) = (H256::repeat_byte(1), H256::repeat_byte(2));
+
+        cache.set_module_type(ism, ModuleType::Routing);
+        cache.set_route(ism, message_id, H256::repeat_byte(3));
+        cache.set_validators(ism, message_id, (vec![H256::repeat_byte(4)], 1));
+        assert_eq!(cache.route(ism, message_id), Some(H256::repeat_byte(3)));
+        assert_eq!(
+            cache.validators(ism, message_id),
+            Some((vec![H256::repeat_byte(4)], 1))
+        );
+
+        cache.invalidate_message(message_id);
+        assert!(cache.route(ism, message_id).is_none());
+        assert!(cache.validators(ism, message_id).is_none());
+        // not specific to the message
+        assert_eq!(cache.module_type(ism), Some(ModuleType::Routing));
+    }
+
+    #[test]
+    fn test_entries_expire() {
+        let cache = MetadataCache::new(Duration::ZERO);
+        cache.set_module_type(H256::zero(), ModuleType::Routing);
+        assert!(cache.module_type(H256::zero()).is_none());
+    }
+}
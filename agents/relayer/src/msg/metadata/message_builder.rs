@@ -98,10 +98,22 @@ pub async fn build_message_metadata(
         .await
         .map_err(|err| MetadataBuildError::FailedToBuild(err.to_string()))?;
 
-    let module_type = ism
-        .module_type()
-        .await
-        .map_err(|err| MetadataBuildError::FailedToBuild(err.to_string()))?;
+    // the builder is moved into the builder of the module below
+    let base = message_builder.base_builder().clone();
+    let cache = base.metadata_cache();
+    let module_type = match cache.and_then(|cache| cache.module_type(ism_address)) {
+        Some(module_type) => module_type,
+        None => {
+            let module_type = ism
+                .module_type()
+                .await
+                .map_err(|err| MetadataBuildError::FailedToBuild(err.to_string()))?;
+            if let Some(cache) = cache {
+                cache.set_module_type(ism_address, module_type);
+            }
+            module_type
+        }
+    };
 
     // check if max depth is reached
     if params.ism_depth >= message_builder.max_ism_depth {
@@ -153,7 +165,7 @@ pub async fn build_message_metadata(
 
 #[cfg(test)]
 mod test {
-    use std::sync::Arc;
+    use std::{sync::Arc, time::Duration};
 
     use aetherium_core::{
         AetheriumDomain, AetheriumMessage, KnownAetheriumDomain, Mailbox, ModuleType, H256, U256,
@@ -163,7 +175,7 @@ mod test {
     use crate::{
         msg::metadata::{
             base::MetadataBuildError, message_builder::build_message_metadata,
-            IsmAwareAppContextClassifier, MessageMetadataBuildParams,
+            IsmAwareAppContextClassifier, MessageMetadataBuildParams, MetadataCache,
         },
         settings::matching_list::{Filter, ListElement, MatchingList},
         test_utils::{
@@ -484,4 +496,73 @@ mod test {
         assert_eq!(*(params.ism_count.lock().await), 5);
         assert!(logs_contain("Max ISM count reached ism_count=5"));
     }
+
+    #[tokio::test]
+    async fn cached_ism_tree_is_reused() {
+        let mut base_builder = build_mock_base_builder();
+        base_builder.responses.metadata_cache = Some(MetadataCache::new(Duration::from_secs(60)));
+        insert_mock_routing_isms(&base_builder, &[(H256::zero(), H256::from_low_u64_be(1))]);
+        insert_null_isms(&base_builder, &[H256::from_low_u64_be(1)]);
+        let build_routing_ism = base_builder.responses.build_routing_ism.clone();
+
+        let message = AetheriumMessage::default();
+        let base_builder = Arc::new(base_builder);
+        let message_builder =
+            MessageMetadataBuilder::new(base_builder.clone(), H256::zero(), &message)
+                .await
+                .expect("Failed to build MessageMetadataBuilder");
+        build_message_metadata(
+            message_builder.clone(),
+            H256::zero(),
+            &message,
+            MessageMetadataBuildParams::default(),
+        )
+        .await
+        .expect("Failed to build metadata");
+        assert!(build_routing_ism.lock().unwrap().is_empty());
+
+        // the ISMs are still built on retries, but neither their module type nor the route may be
+        // fetched again, so the responses queued for those calls must be left untouched
+        let mut module_types = vec![];
+        for (ism_address, module_type) in [
+            (H256::zero(), ModuleType::Routing),
+            (H256::from_low_u64_be(1), ModuleType::Null),
+        ] {
+            let mock_ism = MockInterchainSecurityModule::new(ism_address);
+            mock_ism
+                .responses
+                .module_type
+                .lock()
+                .unwrap()
+                .push_back(Ok(module_type));
+            module_types.push(mock_ism.responses.module_type.clone());
+            base_builder
+                .responses
+                .push_build_ism_response(ism_address, Ok(Box::new(mock_ism)));
+        }
+        let routing_ism = MockRoutingIsm::default();
+        routing_ism
+            .responses
+            .route
+            .lock()
+            .unwrap()
+            .push_back(Ok(H256::from_low_u64_be(1)));
+        build_routing_ism
+            .lock()
+            .unwrap()
+            .push_back(Ok(Box::new(routing_ism)));
+
+        build_message_metadata(
+            message_builder,
+            H256::zero(),
+            &message,
+            MessageMetadataBuildParams::default(),
+        )
+        .await
+        .expect("Failed to build metadata");
+        assert_eq!(build_routing_ism.lock().unwrap().len(), 1);
+        for module_type in module_types {
+            assert_eq!(module_type.lock().unwrap().len(), 1);
+        }
+    }
 }
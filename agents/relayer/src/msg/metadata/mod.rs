@@ -1,6 +1,7 @@
 mod aggregation;
 mod base;
 mod base_builder;
+mod cache;
 mod ccip_read;
 mod message_builder;
 mod multisig;
@@ -12,4 +13,5 @@ pub(crate) use base::{
     MetadataBuildError, MetadataBuilder,
 };
 pub(crate) use base_builder::{BaseMetadataBuilder, BuildsBaseMetadata};
+pub(crate) use cache::MetadataCache;
 pub(crate) use message_builder::MessageMetadataBuilder;
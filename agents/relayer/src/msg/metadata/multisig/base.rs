@@ -101,17 +101,27 @@ impl<T: MultisigIsmMetadataBuilder> MetadataBuilder for T {
         _params: MessageMetadataBuildParams,
     ) -> Result<Metadata, MetadataBuildError> {
         const CTX: &str = "When fetching MultisigIsm metadata";
-        let multisig_ism = self
-            .as_ref()
-            .base_builder()
-            .build_multisig_ism(ism_address)
-            .await
-            .map_err(|err| MetadataBuildError::FailedToBuild(err.to_string()))?;
-
-        let (validators, threshold) = multisig_ism
-            .validators_and_threshold(message)
-            .await
-            .map_err(|err| MetadataBuildError::FailedToBuild(err.to_string()))?;
+        let cache = self.as_ref().base_builder().metadata_cache();
+        let (validators, threshold) =
+            match cache.and_then(|cache| cache.validators(ism_address, message.id())) {
+                Some(validators) => validators,
+                None => {
+                    let multisig_ism = self
+                        .as_ref()
+                        .base_builder()
+                        .build_multisig_ism(ism_address)
+                        .await
+                        .map_err(|err| MetadataBuildError::FailedToBuild(err.to_string()))?;
+                    let validators = multisig_ism
+                        .validators_and_threshold(message)
+                        .await
+                        .map_err(|err| MetadataBuildError::FailedToBuild(err.to_string()))?;
+                    if let Some(cache) = cache {
+                        cache.set_validators(ism_address, message.id(), validators.clone());
+                    }
+                    validators
+                }
+            };
 
         if validators.is_empty() {
             info!("Could not fetch metadata: No validator set found for ISM");
@@ -153,7 +163,7 @@ impl<T: MultisigIsmMetadataBuilder> MetadataBuilder for T {
             Ok(Metadata::new(formatted))
         } else {
             info!(
-                aet_message=?message, ?validators, threshold, ism=%multisig_ism.address(),
+                aet_message=?message, ?validators, threshold, ism=?ism_address,
                 "Could not fetch metadata: Unable to reach quorum"
             );
             Err(MetadataBuildError::CouldNotFetch)
@@ -25,6 +25,10 @@ impl MetadataBuilder for RoutingIsmMetadataBuilder {
         message: &AetheriumMessage,
         params: MessageMetadataBuildParams,
     ) -> Result<Metadata, MetadataBuildError> {
+        let cache = self.base_builder().metadata_cache();
+        if let Some(module) = cache.and_then(|cache| cache.route(ism_address, message.id())) {
+            return self.base.build(module, message, params).await;
+        }
         let ism = self
             .base_builder()
             .build_routing_ism(ism_address)
@@ -34,6 +38,9 @@ impl MetadataBuilder for RoutingIsmMetadataBuilder {
             .route(message)
             .await
             .map_err(|err| MetadataBuildError::FailedToBuild(err.to_string()))?;
+        if let Some(cache) = cache {
+            cache.set_route(ism_address, message.id(), module);
+        }
         self.base.build(module, message, params).await
     }
 }
@@ -28,11 +28,17 @@
 //!   switch everyone to new one)
 
 pub(crate) mod blacklist;
+pub(crate) mod dead_letter;
+pub(crate) mod dry_run;
 pub(crate) mod gas_payment;
 pub(crate) mod metadata;
 pub(crate) mod op_queue;
+pub(crate) mod op_scheduler;
 pub(crate) mod op_submitter;
+pub(crate) mod ordered_lanes;
+pub(crate) mod pause;
 pub(crate) mod processor;
+pub(crate) mod relay_lists;
 
 pub mod pending_message;
 
@@ -8,6 +8,8 @@ use tracing::{debug, instrument};
 
 use crate::server::{MessageRetryQueueResponse, MessageRetryRequest};
 
+use super::op_scheduler::{OpScheduler, PriorityScheduler};
+
 pub type OperationPriorityQueue = Arc<Mutex<BinaryHeap<Reverse<QueueOperation>>>>;
 
 /// Queue of generic operations that can be submitted to a destination chain.
@@ -19,9 +21,18 @@ pub struct OpQueue {
     retry_receiver: Arc<Mutex<Receiver<MessageRetryRequest>>>,
     #[new(default)]
     pub queue: OperationPriorityQueue,
+    /// decides which operations are popped next. Defaults to queue order
+    #[new(value = "Arc::new(Mutex::new(Box::new(PriorityScheduler)))")]
+    scheduler: Arc<Mutex<Box<dyn OpScheduler>>>,
 }
 
 impl OpQueue {
+    /// Replace the scheduler that decides which operations are popped next
+    pub fn with_scheduler(mut self, scheduler: Box<dyn OpScheduler>) -> Self {
+        self.scheduler = Arc::new(Mutex::new(scheduler));
+        self
+    }
+
     /// Push an element onto the queue and update metrics
     /// Arguments:
     /// - `op`: the operation to push onto the queue
@@ -47,13 +58,7 @@ impl OpQueue {
     pub async fn pop_many(&mut self, limit: usize) -> Vec<QueueOperation> {
         self.process_retry_requests().await;
         let mut queue = self.queue.lock().await;
-        let mut popped = vec![];
-        while let Some(Reverse(op)) = queue.pop() {
-            popped.push(op);
-            if popped.len() >= limit {
-                break;
-            }
-        }
+        let popped = self.scheduler.lock().await.pop_many(&mut queue, limit);
         // This function is called very often by the op_submitter tasks, so only log when there are operations to pop
         // to avoid spamming the logs
         if !popped.is_empty() {
@@ -186,6 +191,7 @@ pub mod test {
         seconds_to_next_attempt: u64,
         destination_domain: AetheriumDomain,
         retry_count: u32,
+        app_context: Option<String>,
     }
 
     impl MockPendingOperation {
@@ -199,6 +205,7 @@ pub mod test {
                 recipient_address: H256::random(),
                 origin_domain_id: 0,
                 retry_count: 0,
+                app_context: None,
             }
         }
 
@@ -211,6 +218,7 @@ pub mod test {
                 destination_domain_id: message.destination,
                 seconds_to_next_attempt: 0,
                 retry_count: 0,
+                app_context: None,
                 destination_domain: AetheriumDomain::Unknown {
                     domain_id: message.destination,
                     domain_name: "test".to_string(),
@@ -257,6 +265,13 @@ pub mod test {
             self.set_retries(retry_count);
             self
         }
+
+        pub fn with_app_context(self, app_context: &str) -> Self {
+            Self {
+                app_context: Some(app_context.to_owned()),
+                ..self
+            }
+        }
     }
 
     impl TryBatchAs<AetheriumMessage> for MockPendingOperation {}
@@ -313,7 +328,7 @@ pub mod test {
         }
 
         fn app_context(&self) -> Option<String> {
-            todo!()
+            self.app_context.clone()
         }
 
         async fn prepare(&mut self) -> PendingOperationResult {
@@ -0,0 +1,388 @@
+use std::{
+    cmp::Reverse,
+    collections::{BinaryHeap, HashMap, VecDeque},
+    fmt::Debug,
+    hash::Hash,
+    time::Instant,
+};
+
+use aetherium_core::{QueueOperation, H256};
+
+use crate::settings::{matching_list::MatchingList, OpSchedulerConf};
+
+pub type OperationHeap = BinaryHeap<Reverse<QueueOperation>>;
+
+/// Decides the order in which an `OpQueue` hands out its operations
+pub trait OpScheduler: Debug + Send + Sync {
+    /// Removes up to `limit` operations from the queue, in the order they should be processed
+    fn pop_many(&mut self, queue: &mut OperationHeap, limit: usize) -> Vec<QueueOperation>;
+}
+
+/// Builds the scheduler configured for a destination
+pub fn build_op_scheduler(conf: &OpSchedulerConf) -> Box<dyn OpScheduler> {
+    match conf {
+        OpSchedulerConf::Priority => Box::new(PriorityScheduler),
+        OpSchedulerConf::FairShare { weights } => {
+            Box::new(FairShareScheduler::new(weights.clone()))
+        }
+        OpSchedulerConf::AppContextPriority { app_contexts } => {
+            Box::new(AppContextPriorityScheduler::new(app_contexts.clone()))
+        }
+    }
+}
+
+/// Pops operations in queue order: the ones that can be attempted the soonest first, in nonce
+/// order within an origin
+#[derive(Debug, Default)]
+pub struct PriorityScheduler;
+
+impl OpScheduler for PriorityScheduler {
+    fn pop_many(&mut self, queue: &mut OperationHeap, limit: usize) -> Vec<QueueOperation> {
+        pop_in_queue_order(queue, limit)
+    }
+}
+
+/// Weighted fair queuing across flows of operations with the same `origin_domain_id` and
+/// `sender_address`, so that a noisy sender or origin can't starve the others. Every pick
+/// advances the virtual time of its flow by the inverse of the flow's weight, and the ready flow
+/// with the lowest virtual time goes next.
+#[derive(Debug)]
+pub struct FairShareScheduler {
+    /// the weight of an operation is the one of the first list it matches, or 1
+    weights: Vec<(MatchingList, u32)>,
+    /// virtual time of the flows that had ready operations in the last round
+    virtual_times: HashMap<FlowKey, u64>,
+}
+
+type FlowKey = (u32, H256);
+
+/// Virtual time a flow of weight 1 is charged per picked operation
+const VIRTUAL_TIME_PER_OPERATION: u64 = 1_000_000;
+
+impl FairShareScheduler {
+    pub fn new(weights: Vec<(MatchingList, u32)>) -> Self {
+        Self {
+            weights,
+            virtual_times: HashMap::new(),
+        }
+    }
+
+    fn weight(&self, op: &QueueOperation) -> u32 {
+        self.weights
+            .iter()
+            .find(|(matching_list, _)| matching_list.op_matches(op))
+            .map_or(1, |(_, weight)| *weight)
+            .max(1)
+    }
+
+    fn pick(&mut self, ready: &mut Vec<QueueOperation>, limit: usize) -> Vec<QueueOperation> {
+        // operations keep their queue order within a flow, and flows are ordered by their first
+        // operation to break ties
+        let mut flows: Vec<(FlowKey, VecDeque<QueueOperation>)> = vec![];
+        let mut flow_indexes: HashMap<FlowKey, usize> = HashMap::new();
+        for op in ready.drain(..) {
+            let key = flow_key(&op);
+            let index = *flow_indexes.entry(key).or_insert_with(|| {
+                flows.push((key, VecDeque::new()));
+                flows.len() - 1
+            });
+            flows[index].1.push_back(op);
+        }
+
+        // flows that were idle start at the lowest virtual time of the busy ones, so they can't
+        // claim the share they didn't use while idle
+        let floor = flows
+            .iter()
+            .filter_map(|(key, _)| self.virtual_times.get(key))
+            .min()
+            .copied()
+            .unwrap_or_default();
+        let mut virtual_times = flows
+            .iter()
+            .map(|(key, _)| {
+                let virtual_time = self.virtual_times.get(key).copied().unwrap_or_default();
+                virtual_time.max(floor)
+            })
+            .collect::<Vec<_>>();
+
+        let mut picked = vec![];
+        while picked.len() < limit {
+            let Some(index) = (0..flows.len())
+                .filter(|index| !flows[*index].1.is_empty())
+                .min_by_key(|index| virtual_times[*index])
+            else {
+                break;
+            };
+            let Some(op) = flows[index].1.pop_front() else {
+                break;
+            };
+            virtual_times[index] += VIRTUAL_TIME_PER_OPERATION / u64::from(self.weight(&op));
+            picked.push(op);
+        }
+
+        self.virtual_times = flows
+            .iter()
+            .zip(virtual_times)
+            .map(|((key, _), virtual_time)| (*key, virtual_time))
+            .collect();
+        ready.extend(flows.into_iter().flat_map(|(_, ops)| ops));
+        picked
+    }
+}
+
+impl OpScheduler for FairShareScheduler {
+    fn pop_many(&mut self, queue: &mut OperationHeap, limit: usize) -> Vec<QueueOperation> {
+        pop_ready_with(queue, limit, flow_key, |ready, limit| {
+            self.pick(ready, limit)
+        })
+    }
+}
+
+fn flow_key(op: &QueueOperation) -> FlowKey {
+    (op.origin_domain_id(), *op.sender_address())
+}
+
+/// Strict priority classes by app context: ready operations of an earlier app context in the list
+/// always go first. Operations without a listed app context come last.
+#[derive(Debug)]
+pub struct AppContextPriorityScheduler {
+    app_contexts: Vec<String>,
+}
+
+impl AppContextPriorityScheduler {
+    pub fn new(app_contexts: Vec<String>) -> Self {
+        Self { app_contexts }
+    }
+
+    fn class(&self, op: &QueueOperation) -> usize {
+        op.app_context()
+            .and_then(|app_context| self.app_contexts.iter().position(|c| *c == app_context))
+            .unwrap_or(self.app_contexts.len())
+    }
+
+    fn pick(&self, ready: &mut Vec<QueueOperation>, limit: usize) -> Vec<QueueOperation> {
+        // stable, so operations keep their queue order within a class
+        ready.sort_by_cached_key(|op| self.class(op));
+        ready.drain(..limit.min(ready.len())).collect()
+    }
+}
+
+impl OpScheduler for AppContextPriorityScheduler {
+    fn pop_many(&mut self, queue: &mut OperationHeap, limit: usize) -> Vec<QueueOperation> {
+        pop_ready_with(
+            queue,
+            limit,
+            |op| self.class(op),
+            |ready, limit| self.pick(ready, limit),
+        )
+    }
+}
+
+fn pop_in_queue_order(queue: &mut OperationHeap, limit: usize) -> Vec<QueueOperation> {
+    let mut popped = vec![];
+    while popped.len() < limit {
+        let Some(Reverse(op)) = queue.pop() else {
+            break;
+        };
+        popped.push(op);
+    }
+    popped
+}
+
+/// Lets `pick` choose among the first `limit` ready operations of every group, as keyed by
+/// `group`, and returns the rest to the queue. Taking the head of every group rather than the head
+/// of the queue keeps a large backlog of one group from hiding the others, since the queue orders
+/// the operations of an origin by nonce. If fewer than `limit` are ready, the ones due the soonest
+/// make up the difference, like with the `PriorityScheduler`.
+fn pop_ready_with<K: Eq + Hash>(
+    queue: &mut OperationHeap,
+    limit: usize,
+    group: impl Fn(&QueueOperation) -> K,
+    pick: impl FnOnce(&mut Vec<QueueOperation>, usize) -> Vec<QueueOperation>,
+) -> Vec<QueueOperation> {
+    // the heads of the groups can be anywhere in the queue, so every operation is looked at.
+    // Each group only keeps its `limit` first ready operations, in a max-heap so that the last
+    // of them is the one to give up.
+    let mut heads: HashMap<K, BinaryHeap<QueueOperation>> = HashMap::new();
+    let mut rest = vec![];
+    for Reverse(op) in std::mem::take(queue).into_vec() {
+        if op
+            .next_attempt_after()
+            .is_some_and(|after| after > Instant::now())
+        {
+            rest.push(Reverse(op));
+            continue;
+        }
+        let head = heads.entry(group(&op)).or_default();
+        head.push(op);
+        if head.len() > limit {
+            rest.extend(head.pop().map(Reverse));
+        }
+    }
+
+    // groups are ordered by their first operation, and keep their queue order
+    let mut heads = heads
+        .into_values()
+        .map(BinaryHeap::into_sorted_vec)
+        .collect::<Vec<_>>();
+    heads.sort_by(|a, b| a.first().cmp(&b.first()));
+    let mut ready = heads.into_iter().flatten().collect::<Vec<_>>();
+
+    let mut popped = pick(&mut ready, limit);
+    rest.extend(ready.into_iter().map(Reverse));
+    *queue = BinaryHeap::from(rest);
+    let remaining = limit.saturating_sub(popped.len());
+    popped.extend(pop_in_queue_order(queue, remaining));
+    popped
+}
+
+#[cfg(test)]
+mod tests {
+    use aetherium_core::{AetheriumDomain, KnownAetheriumDomain, PendingOperation};
+
+    use crate::{
+        msg::op_queue::test::MockPendingOperation,
+        settings::matching_list::{Filter, ListElement},
+    };
+
+    use super::*;
+
+    const NOISY_SENDER: &str = "0x000000000000000000000000000000000000000000000000000000000000000a";
+    const QUIET_SENDER: &str = "0x000000000000000000000000000000000000000000000000000000000000000b";
+
+    fn ops_from(sender: &str, count: usize) -> Vec<QueueOperation> {
+        (0..count)
+            .map(|_| {
+                Box::new(
+                    MockPendingOperation::new(
+                        0,
+                        AetheriumDomain::Known(KnownAetheriumDomain::Arbitrum),
+                    )
+                    .with_sender_address(sender),
+                ) as QueueOperation
+            })
+            .collect()
+    }
+
+    fn heap_of(ops: Vec<QueueOperation>) -> OperationHeap {
+        ops.into_iter().map(Reverse).collect()
+    }
+
+    fn count_from(ops: &[QueueOperation], sender: &str) -> usize {
+        ops.iter()
+            .filter(|op| *op.sender_address() == sender.parse().unwrap())
+            .count()
+    }
+
+    #[test]
+    fn test_fair_share_interleaves_senders() {
+        let mut queue = heap_of(
+            ops_from(NOISY_SENDER, 10)
+                .into_iter()
+                .chain(ops_from(QUIET_SENDER, 2))
+                .collect(),
+        );
+        let mut scheduler = FairShareScheduler::new(vec![]);
+
+        let popped = scheduler.pop_many(&mut queue, 4);
+        assert_eq!(count_from(&popped, NOISY_SENDER), 2);
+        assert_eq!(count_from(&popped, QUIET_SENDER), 2);
+        assert_eq!(queue.len(), 8);
+
+        // once the quiet sender has nothing left, the noisy one gets the whole batch
+        let popped = scheduler.pop_many(&mut queue, 4);
+        assert_eq!(count_from(&popped, NOISY_SENDER), 4);
+    }
+
+    #[test]
+    fn test_fair_share_sees_quiet_sender_behind_large_backlog() {
+        let mut queue = heap_of(
+            ops_from(NOISY_SENDER, 1_000)
+                .into_iter()
+                .chain(ops_from(QUIET_SENDER, 2))
+                .collect(),
+        );
+        let mut scheduler = FairShareScheduler::new(vec![]);
+
+        let popped = scheduler.pop_many(&mut queue, 4);
+        assert_eq!(count_from(&popped, NOISY_SENDER), 2);
+        assert_eq!(count_from(&popped, QUIET_SENDER), 2);
+        assert_eq!(queue.len(), 998);
+    }
+
+    #[test]
+    fn test_fair_share_respects_weights() {
+        let mut queue = heap_of(
+            ops_from(NOISY_SENDER, 10)
+                .into_iter()
+                .chain(ops_from(QUIET_SENDER, 10))
+                .collect(),
+        );
+        let heavy_sender = MatchingList(Some(vec![ListElement::new(
+            Filter::Wildcard,
+            Filter::Wildcard,
+            Filter::Enumerated(vec![QUIET_SENDER.parse().unwrap()]),
+            Filter::Wildcard,
+            Filter::Wildcard,
+        )]));
+        let mut scheduler = FairShareScheduler::new(vec![(heavy_sender, 3)]);
+
+        let popped = scheduler.pop_many(&mut queue, 8);
+        assert_eq!(count_from(&popped, QUIET_SENDER), 6);
+        assert_eq!(count_from(&popped, NOISY_SENDER), 2);
+    }
+
+    #[test]
+    fn test_app_context_priority_orders_classes() {
+        let domain = AetheriumDomain::Known(KnownAetheriumDomain::Arbitrum);
+        let mut queue = heap_of(vec![
+            Box::new(MockPendingOperation::new(0, domain.clone())) as QueueOperation,
+            Box::new(MockPendingOperation::new(0, domain.clone()).with_app_context("low")),
+            Box::new(MockPendingOperation::new(0, domain.clone()).with_app_context("high")),
+            Box::new(MockPendingOperation::new(0, domain).with_app_context("unlisted")),
+        ]);
+        let mut scheduler =
+            AppContextPriorityScheduler::new(vec!["high".to_owned(), "low".to_owned()]);
+
+        let popped = scheduler.pop_many(&mut queue, 2);
+        let app_contexts = popped.iter().map(|op| op.app_context()).collect::<Vec<_>>();
+        assert_eq!(
+            app_contexts,
+            vec![Some("high".to_owned()), Some("low".to_owned())]
+        );
+        assert_eq!(queue.len(), 2);
+    }
+
+    #[test]
+    fn test_app_context_priority_sees_class_behind_large_backlog() {
+        let domain = AetheriumDomain::Known(KnownAetheriumDomain::Arbitrum);
+        let mut ops = ops_from(NOISY_SENDER, 1_000);
+        ops.push(Box::new(
+            MockPendingOperation::new(0, domain).with_app_context("high"),
+        ));
+        let mut queue = heap_of(ops);
+        let mut scheduler = AppContextPriorityScheduler::new(vec!["high".to_owned()]);
+
+        let popped = scheduler.pop_many(&mut queue, 2);
+        assert_eq!(popped[0].app_context(), Some("high".to_owned()));
+        assert_eq!(queue.len(), 999);
+    }
+
+    #[test]
+    fn test_operations_that_are_not_ready_make_up_the_batch() {
+        let domain = AetheriumDomain::Known(KnownAetheriumDomain::Arbitrum);
+        let not_ready = MockPendingOperation::new(100, domain.clone()).with_app_context("high");
+        let not_ready_id = not_ready.id();
+        let mut queue = heap_of(vec![
+            Box::new(not_ready) as QueueOperation,
+            Box::new(MockPendingOperation::new(0, domain)),
+        ]);
+        let mut scheduler = AppContextPriorityScheduler::new(vec!["high".to_owned()]);
+
+        let popped = scheduler.pop_many(&mut queue, 2);
+        assert_eq!(popped.len(), 2);
+        // the ready operation goes first even though the other one has a higher priority
+        assert_eq!(popped[1].id(), not_ready_id);
+        assert!(queue.is_empty());
+    }
+}
@@ -9,6 +9,7 @@ use futures::future::join_all;
 use futures_util::future::try_join_all;
 use aetherium_core::total_estimated_cost;
 use aetherium_core::BatchResult;
+use aetherium_core::ConfirmReason;
 use aetherium_core::ConfirmReason::*;
 use aetherium_core::PendingOperation;
 use aetherium_core::PendingOperationStatus;
@@ -31,11 +32,15 @@ use aetherium_core::{
     PendingOperationResult, QueueOperation, TxOutcome,
 };
 
+use crate::msg::pause::RelayPauses;
 use crate::msg::pending_message::CONFIRM_DELAY;
 use crate::server::MessageRetryRequest;
 
+use crate::settings::OpSchedulerConf;
+
 use super::op_queue::OpQueue;
 use super::op_queue::OperationPriorityQueue;
+use super::op_scheduler::build_op_scheduler;
 
 /// This is needed for logic where we need to allocate
 /// based on how many queues exist in each OpSubmitter.
@@ -43,6 +48,9 @@ use super::op_queue::OperationPriorityQueue;
 /// update the number of queues an OpSubmitter has.
 pub const SUBMITTER_QUEUE_COUNT: usize = 3;
 
+/// How long a paused operation waits before it is checked against the pauses again
+const PAUSE_RECHECK_DELAY: Duration = Duration::from_secs(10);
+
 /// SerialSubmitter accepts operations over a channel. It is responsible for
 /// executing the right strategy to deliver those messages to the destination
 /// chain. It is designed to be used in a scenario allowing only one
@@ -105,6 +113,8 @@ pub struct SerialSubmitter {
     prepare_queue: OpQueue,
     submit_queue: OpQueue,
     confirm_queue: OpQueue,
+    /// Pauses set through the admin API, which hold matching operations in the prepare queue
+    pauses: Arc<RelayPauses>,
 }
 
 impl SerialSubmitter {
@@ -114,23 +124,27 @@ impl SerialSubmitter {
         retry_op_transmitter: &Sender<MessageRetryRequest>,
         metrics: SerialSubmitterMetrics,
         max_batch_size: u32,
+        op_scheduler: OpSchedulerConf,
         task_monitor: TaskMonitor,
     ) -> Self {
         let prepare_queue = OpQueue::new(
             metrics.submitter_queue_length.clone(),
             "prepare_queue".to_string(),
             Arc::new(Mutex::new(retry_op_transmitter.subscribe())),
-        );
+        )
+        .with_scheduler(build_op_scheduler(&op_scheduler));
         let submit_queue = OpQueue::new(
             metrics.submitter_queue_length.clone(),
             "submit_queue".to_string(),
             Arc::new(Mutex::new(retry_op_transmitter.subscribe())),
-        );
+        )
+        .with_scheduler(build_op_scheduler(&op_scheduler));
         let confirm_queue = OpQueue::new(
             metrics.submitter_queue_length.clone(),
             "confirm_queue".to_string(),
             Arc::new(Mutex::new(retry_op_transmitter.subscribe())),
-        );
+        )
+        .with_scheduler(build_op_scheduler(&op_scheduler));
 
         Self {
             domain,
@@ -142,9 +156,15 @@ impl SerialSubmitter {
             prepare_queue,
             submit_queue,
             confirm_queue,
+            pauses: Default::default(),
         }
     }
 
+    pub fn with_pauses(mut self, pauses: Arc<RelayPauses>) -> Self {
+        self.pauses = pauses;
+        self
+    }
+
     pub async fn prepare_queue(&self) -> OperationPriorityQueue {
         self.prepare_queue.queue.clone()
     }
@@ -208,6 +228,7 @@ impl SerialSubmitter {
                     self.confirm_queue.clone(),
                     self.max_batch_size,
                     self.metrics.clone(),
+                    self.pauses.clone(),
                 ),
             ))
             .expect("spawning tokio task from Builder is infallible")
@@ -226,6 +247,7 @@ impl SerialSubmitter {
                     self.confirm_queue.clone(),
                     self.max_batch_size,
                     self.metrics.clone(),
+                    self.pauses.clone(),
                 ),
             ))
             .expect("spawning tokio task from Builder is infallible")
@@ -278,12 +300,14 @@ async fn prepare_task(
     confirm_queue: OpQueue,
     max_batch_size: u32,
     metrics: SerialSubmitterMetrics,
+    pauses: Arc<RelayPauses>,
 ) {
     // Prepare at most `max_batch_size` ops at a time to avoid getting rate-limited
     let ops_to_prepare = max_batch_size as usize;
     loop {
         // Pop messages here according to the configured batch.
-        let mut batch = prepare_queue.pop_many(ops_to_prepare).await;
+        let batch = prepare_queue.pop_many(ops_to_prepare).await;
+        let mut batch = hold_paused_ops(batch, &pauses, &prepare_queue).await;
         if batch.is_empty() {
             // queue is empty so give some time before checking again to prevent burning CPU
             sleep(Duration::from_millis(100)).await;
@@ -320,6 +344,17 @@ async fn prepare_task(
                 PendingOperationResult::NotReady => {
                     prepare_queue.push(op, None).await;
                 }
+                // Parking a message until a relay budget rolls over, or until the earlier messages
+                // of its lane are delivered, isn't a failure
+                PendingOperationResult::Reprepare(
+                    reason @ (ReprepareReason::RelayBudgetExhausted
+                    | ReprepareReason::WaitingForEarlierMessage),
+                ) => {
+                    metrics.ops_parked.inc();
+                    prepare_queue
+                        .push(op, Some(PendingOperationStatus::Retry(reason)))
+                        .await;
+                }
                 PendingOperationResult::Reprepare(reason) => {
                     metrics.ops_failed.inc();
                     prepare_queue
@@ -353,10 +388,13 @@ async fn submit_task(
     mut confirm_queue: OpQueue,
     max_batch_size: u32,
     metrics: SerialSubmitterMetrics,
+    pauses: Arc<RelayPauses>,
 ) {
     let recv_limit = max_batch_size as usize;
     loop {
-        let mut batch = submit_queue.pop_many(recv_limit).await;
+        let batch = submit_queue.pop_many(recv_limit).await;
+        // operations paused after they were prepared have to be prepared again once resumed
+        let mut batch = hold_paused_ops(batch, &pauses, &prepare_queue).await;
 
         match batch.len().cmp(&1) {
             std::cmp::Ordering::Less => {
@@ -377,6 +415,29 @@ async fn submit_task(
     }
 }
 
+/// Pushes the operations that relaying is paused for back to the prepare queue, and returns the
+/// others
+async fn hold_paused_ops(
+    batch: Vec<QueueOperation>,
+    pauses: &RelayPauses,
+    prepare_queue: &OpQueue,
+) -> Vec<QueueOperation> {
+    let mut unpaused = Vec::with_capacity(batch.len());
+    for mut op in batch {
+        if !pauses.is_paused(&op) {
+            unpaused.push(op);
+            continue;
+        }
+        trace!(?op, "Relaying is paused for operation");
+        let paused_status = PendingOperationStatus::Retry(ReprepareReason::Paused);
+        // only update the status the first time, as it is persisted to the db
+        let new_status = (op.status() != paused_status).then_some(paused_status);
+        op.set_next_attempt_after(PAUSE_RECHECK_DELAY);
+        prepare_queue.push(op, new_status).await;
+    }
+    unpaused
+}
+
 #[instrument(skip(prepare_queue, confirm_queue, metrics), ret, level = "debug")]
 async fn submit_single_operation(
     mut op: QueueOperation,
@@ -407,8 +468,13 @@ async fn submit_single_operation(
             // Not expected to hit this case in `submit`, but it's here for completeness
             op.decrement_metric_if_exists();
         }
+        // operations that weren't broadcast in dry-run mode keep their reason, the others are
+        // persisted as submitted by us, e.g. when they were `AlreadySubmitted`
+        PendingOperationResult::Confirm(DryRun) => {
+            confirm_op(op, confirm_queue, metrics, DryRun).await
+        }
         PendingOperationResult::Success | PendingOperationResult::Confirm(_) => {
-            confirm_op(op, confirm_queue, metrics).await
+            confirm_op(op, confirm_queue, metrics, SubmittedBySelf).await
         }
     }
 }
@@ -417,14 +483,18 @@ async fn confirm_op(
     mut op: QueueOperation,
     confirm_queue: &mut OpQueue,
     metrics: &SerialSubmitterMetrics,
+    reason: ConfirmReason,
 ) {
     let destination = op.destination_domain().clone();
     debug!(?op, "Operation submitted");
     op.set_next_attempt_after(CONFIRM_DELAY);
+    // operations that weren't broadcast, e.g. in dry-run mode, don't count as submitted
+    if reason != DryRun {
+        metrics.ops_submitted.inc();
+    }
     confirm_queue
-        .push(op, Some(PendingOperationStatus::Confirm(SubmittedBySelf)))
+        .push(op, Some(PendingOperationStatus::Confirm(reason)))
         .await;
-    metrics.ops_submitted.inc();
 
     if matches!(
         destination.domain_protocol(),
@@ -526,6 +596,7 @@ pub struct SerialSubmitterMetrics {
     ops_submitted: IntCounter,
     ops_confirmed: IntCounter,
     ops_failed: IntCounter,
+    ops_parked: IntCounter,
     ops_dropped: IntCounter,
 }
 
@@ -546,6 +617,9 @@ impl SerialSubmitterMetrics {
             ops_failed: metrics
                 .operations_processed_count()
                 .with_label_values(&["failed", destination]),
+            ops_parked: metrics
+                .operations_processed_count()
+                .with_label_values(&["parked", destination]),
             ops_dropped: metrics
                 .operations_processed_count()
                 .with_label_values(&["dropped", destination]),
@@ -0,0 +1,423 @@
+use std::{
+    collections::{hash_map::Entry, BTreeSet, HashMap},
+    sync::{Mutex, MutexGuard},
+    time::{Duration, Instant},
+};
+
+use aetherium_base::{db::AetheriumDb, CoreMetrics};
+use aetherium_core::{AetheriumMessage, H256};
+use eyre::Result;
+use prometheus::IntGaugeVec;
+use serde::Serialize;
+use tracing::warn;
+
+use crate::settings::matching_list::MatchingList;
+
+/// Messages from one sender to one recipient
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Lane {
+    pub origin: u32,
+    pub sender: H256,
+    pub destination: u32,
+    pub recipient: H256,
+}
+
+impl From<&AetheriumMessage> for Lane {
+    fn from(message: &AetheriumMessage) -> Self {
+        Self {
+            origin: message.origin,
+            sender: message.sender,
+            destination: message.destination,
+            recipient: message.recipient,
+        }
+    }
+}
+
+/// How many nonces of an origin are scanned at most per check, so that catching up with an
+/// origin that has a long history doesn't block the runtime
+const MAX_SCANNED_NONCES: u32 = 1_000;
+
+/// How long a message can be held back before it's logged as stuck
+const HELD_WARNING_AFTER: Duration = Duration::from_secs(30 * 60);
+
+/// A message held back by an earlier message of its lane
+#[derive(Debug, Clone)]
+struct Held {
+    blocked_by: Option<H256>,
+    /// Names of the origin and destination, to label the metric
+    domains: (String, String),
+    since: Instant,
+    warned: bool,
+}
+
+/// How far the messages of an origin were scanned
+#[derive(Debug, Clone, Copy)]
+enum Scan {
+    /// Looking for the lowest indexed nonce, which the messages were found to be indexed down to
+    Floor(u32),
+    /// The messages from the lowest indexed nonce up to this one, excluded, were scanned
+    Up(u32),
+}
+
+#[derive(Debug, Default)]
+struct LaneState {
+    /// Nonces of the messages of each ordered lane that weren't found to be delivered, up to the
+    /// nonce each origin was scanned to
+    undelivered: HashMap<Lane, BTreeSet<u32>>,
+    /// How far the messages of each origin were scanned, by origin domain id
+    scanned: HashMap<u32, Scan>,
+    held: HashMap<H256, Held>,
+}
+
+/// What holds back a message of an ordered lane
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum EarlierMessage {
+    /// An undelivered message of the lane with a lower nonce
+    Undelivered { nonce: u32, id: H256 },
+    /// A message with a lower nonce that isn't indexed yet, although messages with an even lower
+    /// nonce are. Its lane isn't known, so it holds back every ordered lane of the origin until
+    /// the indexer fills the gap.
+    Unindexed(u32),
+    /// The origin wasn't scanned up to the message yet
+    Unscanned,
+}
+
+/// The lanes selected by the `orderedLanes` matching list, whose messages are delivered in nonce
+/// order. A message on an ordered lane isn't prepared until every message with a lower nonce on the
+/// same lane is delivered.
+///
+/// The head of each lane is derived from the origin's DB, where delivered messages are marked as
+/// processed, so a message that leaves the queue without being delivered, e.g. because it was
+/// dead-lettered or blacklisted, keeps holding back the later messages of its lane. Only the
+/// messages from the lowest nonce indexed when the origin is first scanned are ordered, since the
+/// ones before it may never be indexed, e.g. because `index.from` is past them.
+#[derive(Debug, Default)]
+pub struct OrderedLanes {
+    matching_list: MatchingList,
+    state: Mutex<LaneState>,
+    metric: Option<IntGaugeVec>,
+}
+
+impl OrderedLanes {
+    pub fn new(matching_list: MatchingList) -> Self {
+        Self {
+            matching_list,
+            ..Default::default()
+        }
+    }
+
+    /// Exports the number of held messages by origin and destination as a Prometheus gauge
+    pub fn with_metrics(mut self, metrics: &CoreMetrics) -> Result<Self> {
+        self.metric = Some(metrics.new_int_gauge(
+            "ordered_lane_held_messages",
+            "Messages on an ordered lane waiting for an earlier message of the lane to be delivered",
+            &["origin", "destination"],
+        )?);
+        Ok(self)
+    }
+
+    pub fn is_ordered(&self, message: &AetheriumMessage) -> bool {
+        self.matching_list.msg_matches(message, false)
+    }
+
+    /// The earliest undelivered message of the lane of `message` with a lower nonce, looked up in
+    /// the DB of its origin
+    pub fn earlier_message(
+        &self,
+        message: &AetheriumMessage,
+        origin_db: &dyn AetheriumDb,
+    ) -> Result<Option<EarlierMessage>> {
+        if !self.is_ordered(message) {
+            return Ok(None);
+        }
+        let mut state = self.state();
+        if let Some(earlier) = self.scan(&mut state, message, origin_db)? {
+            return Ok(Some(earlier));
+        }
+        let Some(undelivered) = state.undelivered.get_mut(&message.into()) else {
+            return Ok(None);
+        };
+        while let Some(&nonce) = undelivered.range(..message.nonce).next() {
+            if origin_db.retrieve_processed_by_nonce(&nonce)? == Some(true) {
+                undelivered.remove(&nonce);
+                continue;
+            }
+            let earlier = match origin_db.retrieve_message_id_by_nonce(&nonce)? {
+                Some(id) => EarlierMessage::Undelivered { nonce, id },
+                None => EarlierMessage::Unindexed(nonce),
+            };
+            return Ok(Some(earlier));
+        }
+        Ok(None)
+    }
+
+    /// Removes the message with `nonce` from the lane of `message`, once it was found to be
+    /// delivered by another relayer
+    pub fn delivered(&self, message: &AetheriumMessage, nonce: u32) {
+        if let Some(undelivered) = self.state().undelivered.get_mut(&message.into()) {
+            undelivered.remove(&nonce);
+        }
+    }
+
+    /// Marks the message with `id` as held back by the message with id `blocked_by`, and logs it
+    /// once it's been held back for long
+    pub fn hold(&self, id: H256, blocked_by: Option<H256>, (origin, destination): (&str, &str)) {
+        match self.state().held.entry(id) {
+            Entry::Occupied(mut entry) => {
+                let held = entry.get_mut();
+                held.blocked_by = blocked_by;
+                if !held.warned && held.since.elapsed() > HELD_WARNING_AFTER {
+                    held.warned = true;
+                    warn!(
+                        message_id = ?id,
+                        ?blocked_by,
+                        held_for = ?held.since.elapsed(),
+                        origin,
+                        destination,
+                        "Message of an ordered lane is held back for long; the earlier message may need to be delivered or its lane removed from `orderedLanes`"
+                    );
+                }
+            }
+            Entry::Vacant(entry) => {
+                entry.insert(Held {
+                    blocked_by,
+                    domains: (origin.to_owned(), destination.to_owned()),
+                    since: Instant::now(),
+                    warned: false,
+                });
+                if let Some(metric) = &self.metric {
+                    metric.with_label_values(&[origin, destination]).inc();
+                }
+            }
+        }
+    }
+
+    pub fn unhold(&self, id: &H256) {
+        let Some(held) = self.state().held.remove(id) else {
+            return;
+        };
+        if let Some(metric) = &self.metric {
+            let (origin, destination) = &held.domains;
+            metric.with_label_values(&[origin, destination]).dec();
+        }
+    }
+
+    /// The message holding back the message with `id`, if it's held back by a known message
+    pub fn blocked_by(&self, id: &H256) -> Option<H256> {
+        self.state().held.get(id).and_then(|held| held.blocked_by)
+    }
+
+    /// Adds the undelivered messages of the origin of `message` to their lanes, up to `message`.
+    /// Returns why the scan stopped short of `message`, if it did.
+    ///
+    /// The scan starts at the lowest nonce indexed when the origin is first scanned, found by
+    /// walking down from `message`. Lower nonces are either before the origin's `index.from` or
+    /// still being indexed backwards, and waiting for them could hold back the lanes forever.
+    fn scan(
+        &self,
+        state: &mut LaneState,
+        message: &AetheriumMessage,
+        origin_db: &dyn AetheriumDb,
+    ) -> Result<Option<EarlierMessage>> {
+        let scan = state
+            .scanned
+            .entry(message.origin)
+            .or_insert(Scan::Floor(message.nonce));
+        let mut budget = MAX_SCANNED_NONCES;
+        let mut scanned = match *scan {
+            Scan::Floor(mut lowest) => {
+                while lowest > 0
+                    && origin_db
+                        .retrieve_message_id_by_nonce(&(lowest - 1))?
+                        .is_some()
+                {
+                    lowest -= 1;
+                    budget -= 1;
+                    if budget == 0 {
+                        *scan = Scan::Floor(lowest);
+                        return Ok(Some(EarlierMessage::Unscanned));
+                    }
+                }
+                lowest
+            }
+            Scan::Up(scanned) => scanned,
+        };
+        *scan = Scan::Up(scanned);
+        let end = message.nonce.min(scanned.saturating_add(budget));
+        while scanned < end {
+            let nonce = scanned;
+            if origin_db.retrieve_processed_by_nonce(&nonce)? != Some(true) {
+                let Some(earlier) = origin_db.retrieve_message_by_nonce(nonce)? else {
+                    return Ok(Some(EarlierMessage::Unindexed(nonce)));
+                };
+                if self.is_ordered(&earlier) {
+                    state
+                        .undelivered
+                        .entry((&earlier).into())
+                        .or_default()
+                        .insert(nonce);
+                }
+            }
+            scanned += 1;
+            *scan = Scan::Up(scanned);
+        }
+        Ok((scanned < message.nonce).then_some(EarlierMessage::Unscanned))
+    }
+
+    fn state(&self) -> MutexGuard<'_, LaneState> {
+        self.state.lock().unwrap_or_else(|e| e.into_inner())
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use aetherium_base::db::{test_utils, AetheriumRocksDB};
+    use aetherium_core::AetheriumDomain;
+
+    use crate::settings::matching_list::{Filter, ListElement};
+
+    use super::*;
+
+    const ORDERED_SENDER: H256 = H256::repeat_byte(1);
+
+    fn message(domain: &AetheriumDomain, sender: H256, nonce: u32) -> AetheriumMessage {
+        AetheriumMessage {
+            origin: domain.id(),
+            nonce,
+            sender,
+            ..AetheriumMessage::default()
+        }
+    }
+
+    fn ordered_lanes() -> OrderedLanes {
+        OrderedLanes::new(MatchingList(Some(vec![ListElement::new(
+            Filter::Wildcard,
+            Filter::Wildcard,
+            Filter::Enumerated(vec![ORDERED_SENDER]),
+            Filter::Wildcard,
+            Filter::Wildcard,
+        )])))
+    }
+
+    #[tokio::test]
+    async fn test_messages_wait_for_earlier_ones() {
+        test_utils::run_test_db(|db| async move {
+            let domain = AetheriumDomain::new_test_domain("test_messages_wait_for_earlier_ones");
+            let db = AetheriumRocksDB::new(&domain, db);
+            let lanes = ordered_lanes();
+            let unordered = message(&domain, H256::repeat_byte(2), 0);
+            let (first, second, third) = (
+                message(&domain, ORDERED_SENDER, 1),
+                message(&domain, ORDERED_SENDER, 2),
+                message(&domain, ORDERED_SENDER, 3),
+            );
+            for message in [&unordered, &first, &second] {
+                db.store_message(message, 1).unwrap();
+            }
+
+            assert_eq!(lanes.earlier_message(&unordered, &db).unwrap(), None);
+            assert_eq!(lanes.earlier_message(&first, &db).unwrap(), None);
+            assert_eq!(
+                lanes.earlier_message(&third, &db).unwrap(),
+                Some(EarlierMessage::Undelivered {
+                    nonce: 1,
+                    id: first.id()
+                })
+            );
+
+            // leaving the queue without being delivered doesn't release the lane
+            lanes.hold(third.id(), Some(first.id()), ("origin", "destination"));
+            lanes.unhold(&third.id());
+            assert_eq!(lanes.blocked_by(&third.id()), None);
+            assert!(matches!(
+                lanes.earlier_message(&third, &db).unwrap(),
+                Some(EarlierMessage::Undelivered { nonce: 1, .. })
+            ));
+
+            db.store_processed_by_nonce(&1, &true).unwrap();
+            assert_eq!(
+                lanes.earlier_message(&third, &db).unwrap(),
+                Some(EarlierMessage::Undelivered {
+                    nonce: 2,
+                    id: second.id()
+                })
+            );
+            lanes.delivered(&third, 2);
+            assert_eq!(lanes.earlier_message(&third, &db).unwrap(), None);
+        })
+        .await;
+    }
+
+    #[tokio::test]
+    async fn test_unindexed_messages_hold_back_the_lane() {
+        test_utils::run_test_db(|db| async move {
+            let domain =
+                AetheriumDomain::new_test_domain("test_unindexed_messages_hold_back_the_lane");
+            let db = AetheriumRocksDB::new(&domain, db);
+            let lanes = ordered_lanes();
+            let (first, second, fourth) = (
+                message(&domain, ORDERED_SENDER, 0),
+                message(&domain, ORDERED_SENDER, 1),
+                message(&domain, ORDERED_SENDER, 3),
+            );
+            for message in [&first, &second] {
+                db.store_message(message, 1).unwrap();
+            }
+            db.store_processed_by_nonce(&0, &true).unwrap();
+            assert_eq!(lanes.earlier_message(&second, &db).unwrap(), None);
+
+            // the message with nonce 2 may be on the same lane
+            assert_eq!(
+                lanes.earlier_message(&fourth, &db).unwrap(),
+                Some(EarlierMessage::Unindexed(2))
+            );
+            db.store_message(&message(&domain, H256::repeat_byte(2), 2), 1)
+                .unwrap();
+            assert_eq!(
+                lanes.earlier_message(&fourth, &db).unwrap(),
+                Some(EarlierMessage::Undelivered {
+                    nonce: 1,
+                    id: second.id()
+                })
+            );
+            db.store_processed_by_nonce(&1, &true).unwrap();
+            assert_eq!(lanes.earlier_message(&fourth, &db).unwrap(), None);
+        })
+        .await;
+    }
+
+    #[tokio::test]
+    async fn test_messages_below_the_lowest_indexed_nonce_are_ignored() {
+        test_utils::run_test_db(|db| async move {
+            let domain = AetheriumDomain::new_test_domain(
+                "test_messages_below_the_lowest_indexed_nonce_are_ignored",
+            );
+            let db = AetheriumRocksDB::new(&domain, db);
+            let lanes = ordered_lanes();
+            // indexing started after nonce 5
+            let (first, second) = (
+                message(&domain, ORDERED_SENDER, 5),
+                message(&domain, ORDERED_SENDER, 6),
+            );
+            for message in [&first, &second] {
+                db.store_message(message, 1).unwrap();
+            }
+
+            assert_eq!(
+                lanes.earlier_message(&second, &db).unwrap(),
+                Some(EarlierMessage::Undelivered {
+                    nonce: 5,
+                    id: first.id()
+                })
+            );
+            assert_eq!(lanes.earlier_message(&first, &db).unwrap(), None);
+
+            // a message indexed later below the lowest nonce doesn't hold back the lane either
+            let earliest = message(&domain, ORDERED_SENDER, 4);
+            db.store_message(&earliest, 1).unwrap();
+            assert_eq!(lanes.earlier_message(&first, &db).unwrap(), None);
+        })
+        .await;
+    }
+}
@@ -0,0 +1,271 @@
+use std::sync::{RwLock, RwLockReadGuard, RwLockWriteGuard};
+
+use aetherium_base::{db::DB, CoreMetrics};
+use aetherium_core::{utils::unix_timestamp, QueueOperation};
+use eyre::{bail, Result};
+use prometheus::IntGaugeVec;
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+
+use crate::settings::matching_list::MatchingList;
+
+/// Key prefix of the pauses in the database. Pauses can select operations from any origin, so
+/// unlike message data they aren't scoped to a domain.
+const RELAY_PAUSE_PREFIX: &[u8] = b"relay_pause_";
+
+/// Which operations a pause applies to. An operation has to match every field that is set.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct PauseSelector {
+    /// Matching list in the same format as the whitelist, to select operations by origin,
+    /// destination, sender, recipient or message id
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub matching_list: Option<Value>,
+    /// Name of one of the `metricAppContexts`
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub app_context: Option<String>,
+}
+
+/// A pause on relaying, as stored in the database and returned by the admin API
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct RelayPause {
+    pub id: String,
+    #[serde(flatten)]
+    pub selector: PauseSelector,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub reason: Option<String>,
+    /// Unix timestamp of when the pause was created, in seconds
+    pub paused_at: u64,
+}
+
+#[derive(Debug)]
+struct ActivePause {
+    pause: RelayPause,
+    matching_list: Option<MatchingList>,
+}
+
+impl ActivePause {
+    fn new(pause: RelayPause) -> Result<Self> {
+        let matching_list = pause
+            .selector
+            .matching_list
+            .clone()
+            .map(serde_json::from_value)
+            .transpose()?;
+        Ok(Self {
+            pause,
+            matching_list,
+        })
+    }
+
+    fn matches(&self, op: &QueueOperation) -> bool {
+        let list_matches = self
+            .matching_list
+            .as_ref()
+            .map_or(true, |list| list.op_matches(op));
+        let app_context_matches = self
+            .pause
+            .selector
+            .app_context
+            .as_ref()
+            .map_or(true, |app_context| {
+                op.app_context().as_ref() == Some(app_context)
+            });
+        list_matches && app_context_matches
+    }
+}
+
+/// Pauses on relaying operations, set through the admin API. Operations matching a pause stay
+/// in the prepare queue until the pause is lifted. Pauses are stored in the database so that they
+/// survive restarts.
+#[derive(Debug, Default)]
+pub struct RelayPauses {
+    pauses: RwLock<Vec<ActivePause>>,
+    db: Option<DB>,
+    metric: Option<IntGaugeVec>,
+}
+
+impl RelayPauses {
+    /// Loads the pauses stored in `db`, which new pauses and resumes are then written to
+    pub fn load(db: DB) -> Result<Self> {
+        let mut pauses = db
+            .retrieve_by_prefix(RELAY_PAUSE_PREFIX)?
+            .iter()
+            .map(|raw| ActivePause::new(serde_json::from_slice(raw)?))
+            .collect::<Result<Vec<_>>>()?;
+        pauses.sort_by_key(|active| active.pause.paused_at);
+        Ok(Self {
+            pauses: RwLock::new(pauses),
+            db: Some(db),
+            metric: None,
+        })
+    }
+
+    /// Exports the number of active pauses as a Prometheus gauge
+    pub fn with_metrics(mut self, metrics: &CoreMetrics) -> Result<Self> {
+        self.metric = Some(metrics.new_int_gauge(
+            "relay_pauses",
+            "Number of active pauses on relaying set through the admin API",
+            &[],
+        )?);
+        self.update_metric();
+        Ok(self)
+    }
+
+    /// Pauses relaying the operations matching `selector`
+    pub fn pause(&self, selector: PauseSelector, reason: Option<String>) -> Result<RelayPause> {
+        let selector = PauseSelector {
+            matching_list: selector.matching_list.filter(|list| !list.is_null()),
+            ..selector
+        };
+        if selector.matching_list.is_none() && selector.app_context.is_none() {
+            bail!("A pause needs a `matchingList` or an `appContext`");
+        }
+        let pause = RelayPause {
+            id: uuid::Uuid::new_v4().to_string(),
+            selector,
+            reason,
+            paused_at: unix_timestamp(),
+        };
+        let active = ActivePause::new(pause.clone())?;
+        if let Some(db) = &self.db {
+            db.store(&pause_key(&pause.id), &serde_json::to_vec(&pause)?)?;
+        }
+        self.write().push(active);
+        self.update_metric();
+        Ok(pause)
+    }
+
+    /// Lifts the pause with `id`. Returns `None` if there is no such pause.
+    pub fn resume(&self, id: &str) -> Result<Option<RelayPause>> {
+        let resumed = {
+            let mut pauses = self.write();
+            let Some(index) = pauses.iter().position(|active| active.pause.id == id) else {
+                return Ok(None);
+            };
+            if let Some(db) = &self.db {
+                db.delete(&pause_key(id))?;
+            }
+            pauses.remove(index).pause
+        };
+        self.update_metric();
+        Ok(Some(resumed))
+    }
+
+    /// The active pauses, oldest first
+    pub fn list(&self) -> Vec<RelayPause> {
+        self.read()
+            .iter()
+            .map(|active| active.pause.clone())
+            .collect()
+    }
+
+    /// Whether any of the active pauses applies to `op`
+    pub fn is_paused(&self, op: &QueueOperation) -> bool {
+        self.read().iter().any(|active| active.matches(op))
+    }
+
+    fn update_metric(&self) {
+        if let Some(metric) = &self.metric {
+            metric.with_label_values(&[]).set(self.read().len() as i64);
+        }
+    }
+
+    fn read(&self) -> RwLockReadGuard<'_, Vec<ActivePause>> {
+        self.pauses.read().unwrap_or_else(|e| e.into_inner())
+    }
+
+    fn write(&self) -> RwLockWriteGuard<'_, Vec<ActivePause>> {
+        self.pauses.write().unwrap_or_else(|e| e.into_inner())
+    }
+}
+
+fn pause_key(id: &str) -> Vec<u8> {
+    [RELAY_PAUSE_PREFIX, id.as_bytes()].concat()
+}
+
+#[cfg(test)]
+mod test {
+    use aetherium_base::db::test_utils;
+    use aetherium_core::KnownAetheriumDomain;
+    use serde_json::json;
+
+    use crate::msg::op_queue::test::MockPendingOperation;
+
+    use super::*;
+
+    const PAUSED_DOMAIN: KnownAetheriumDomain = KnownAetheriumDomain::Arbitrum;
+    const OTHER_DOMAIN: KnownAetheriumDomain = KnownAetheriumDomain::Ethereum;
+
+    fn op(destination: KnownAetheriumDomain, app_context: &str) -> QueueOperation {
+        Box::new(MockPendingOperation::new(1, destination.into()).with_app_context(app_context))
+            as QueueOperation
+    }
+
+    #[test]
+    fn test_pause_selectors() {
+        let pauses = RelayPauses::default();
+        let destination_pause = pauses
+            .pause(
+                PauseSelector {
+                    matching_list: Some(json!([{"destinationdomain": PAUSED_DOMAIN as u32}])),
+                    app_context: None,
+                },
+                Some("destination is halted".to_owned()),
+            )
+            .unwrap();
+        pauses
+            .pause(
+                PauseSelector {
+                    matching_list: None,
+                    app_context: Some("paused_app".to_owned()),
+                },
+                None,
+            )
+            .unwrap();
+
+        assert!(pauses.is_paused(&op(PAUSED_DOMAIN, "app")));
+        assert!(pauses.is_paused(&op(OTHER_DOMAIN, "paused_app")));
+        assert!(!pauses.is_paused(&op(OTHER_DOMAIN, "app")));
+
+        assert_eq!(
+            pauses.resume(&destination_pause.id).unwrap(),
+            Some(destination_pause.clone())
+        );
+        assert!(!pauses.is_paused(&op(PAUSED_DOMAIN, "app")));
+        assert_eq!(pauses.resume(&destination_pause.id).unwrap(), None);
+    }
+
+    #[test]
+    fn test_pause_needs_a_selector() {
+        let pauses = RelayPauses::default();
+        let empty = PauseSelector {
+            matching_list: Some(Value::Null),
+            app_context: None,
+        };
+        assert!(pauses.pause(empty, None).is_err());
+        assert!(pauses.list().is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_pauses_are_persisted() {
+        test_utils::run_test_db(|db| async move {
+            let pauses = RelayPauses::load(db.clone()).unwrap();
+            let pause = pauses
+                .pause(
+                    PauseSelector {
+                        matching_list: Some(json!([{"origindomain": 1}])),
+                        app_context: None,
+                    },
+                    None,
+                )
+                .unwrap();
+            assert_eq!(RelayPauses::load(db.clone()).unwrap().list(), vec![pause]);
+
+            pauses.resume(&pauses.list()[0].id).unwrap();
+            assert!(RelayPauses::load(db).unwrap().list().is_empty());
+        })
+        .await;
+    }
+}
@@ -14,19 +14,23 @@ use serde::Serialize;
 use tracing::{debug, error, info, info_span, instrument, trace, warn, Instrument, Level};
 
 use aetherium_base::{db::AetheriumDb, CoreMetrics};
+use aetherium_core::utils::unix_timestamp;
 use aetherium_core::{
     gas_used_by_operation, BatchItem, ChainCommunicationError, ChainResult, ConfirmReason,
-    AetheriumChain, AetheriumDomain, AetheriumMessage, Mailbox, MessageSubmissionData,
-    PendingOperation, PendingOperationResult, PendingOperationStatus, ReprepareReason, TryBatchAs,
-    TxOutcome, H256, U256,
+    AetheriumChain, AetheriumDomain, AetheriumMessage, DeadLetter, InterchainSecurityModule,
+    Mailbox, MessageSubmissionData, OperationAttempt, PendingOperation, PendingOperationResult,
+    PendingOperationStatus, ReprepareReason, TryBatchAs, TxOutcome, H256, U256,
 };
 use aetherium_operation_verifier::ApplicationOperationVerifier;
 
 use crate::msg::metadata::{MessageMetadataBuildParams, MetadataBuildError};
 
 use super::{
+    dry_run::{DryRunDecision, DryRunRecorder},
     gas_payment::{GasPaymentEnforcer, GasPolicyStatus},
     metadata::{BuildsBaseMetadata, MessageMetadataBuilder, MetadataBuilder},
+    ordered_lanes::{EarlierMessage, OrderedLanes},
+    relay_lists::RelayLists,
 };
 
 /// a default of 66 is picked, so messages are retried for 2 weeks (period confirmed by @nambrot) before being skipped.
@@ -40,7 +44,17 @@ pub const CONFIRM_DELAY: Duration = if cfg!(any(test, feature = "test-utils")) {
     Duration::from_secs(60 * 10)
 };
 
+/// How long a message on an ordered lane waits before checking again whether the earlier messages
+/// of the lane were delivered
+pub const ORDERED_LANE_RECHECK_DELAY: Duration = Duration::from_secs(10);
+
+/// How long a message on an ordered lane waits for the next batch of its origin's messages to be
+/// scanned, while the relayer catches up with the origin
+pub const ORDERED_LANE_SCAN_DELAY: Duration = Duration::from_millis(500);
+
 pub const RETRIEVED_MESSAGE_LOG: &str = "Message status retrieved from db";
+/// How many of the most recent failed attempts are kept in the attempt history of a message
+pub const MAX_RECORDED_ATTEMPTS: usize = 20;
 pub const ISM_MAX_DEPTH: u32 = 13;
 pub const ISM_MAX_COUNT: u32 = 100;
 
@@ -63,6 +77,14 @@ pub struct MessageContext {
     pub metrics: MessageSubmissionMetrics,
     /// Application operation verifier
     pub application_operation_verifier: Option<Arc<dyn ApplicationOperationVerifier>>,
+    /// Lists of what may be relayed, checked again before each attempt since they can be
+    /// replaced while the message is queued
+    pub relay_lists: Arc<RelayLists>,
+    /// Set when the relayer runs in dry-run mode: messages are taken through every step up to
+    /// submission, but never broadcast, and what would have been done is recorded here instead
+    pub dry_run: Option<Arc<DryRunRecorder>>,
+    /// Lanes whose messages are delivered in nonce order
+    pub ordered_lanes: Arc<OrderedLanes>,
 }
 
 /// A message that the submitter can and should try to submit.
@@ -96,6 +118,9 @@ pub struct PendingMessage {
     metadata: Option<Vec<u8>>,
     #[new(default)]
     #[serde(skip_serializing)]
+    ism_address: Option<H256>,
+    #[new(default)]
+    #[serde(skip_serializing)]
     metric: Option<Arc<IntGauge>>,
 }
 
@@ -129,8 +154,26 @@ impl PartialEq for PendingMessage {
 
 impl Eq for PendingMessage {}
 
+impl Drop for PendingMessage {
+    fn drop(&mut self) {
+        // the message left the queue, so it's no longer held back. Whether it still holds back the
+        // later messages of its lane only depends on whether it was delivered.
+        self.ctx.ordered_lanes.unhold(&self.message.id());
+        // nor is its submission going to be recorded, e.g. because it was dead-lettered or
+        // blacklisted, so its budget reservation would otherwise block other messages
+        self.ctx
+            .origin_gas_payment_enforcer
+            .release_budget(&self.message);
+    }
+}
+
 impl TryBatchAs<AetheriumMessage> for PendingMessage {
     fn try_batch(&self) -> ChainResult<BatchItem<AetheriumMessage>> {
+        // failing the batch makes the submitter fall back to `submit`, which drops the message
+        // so does dry-run mode, where `submit` doesn't broadcast anything
+        if self.ctx.relay_lists.load().blocks(&self.message) || self.ctx.dry_run.is_some() {
+            return Err(ChainCommunicationError::BatchingFailed);
+        }
         match self.submission_data.as_ref() {
             None => {
                 warn!("Cannot batch message without submission data, returning BatchingFailed");
@@ -157,6 +200,13 @@ impl PendingOperation for PendingMessage {
     }
 
     fn set_status(&mut self, status: PendingOperationStatus) {
+        // a message going back to be prepared, e.g. because its submission failed or it was
+        // paused, reserves its budget again once it's ready to be submitted
+        if matches!(status, PendingOperationStatus::Retry(_)) {
+            self.ctx
+                .origin_gas_payment_enforcer
+                .release_budget(&self.message);
+        }
         if let Err(e) = self
             .ctx
             .origin_db
@@ -208,6 +258,10 @@ impl PendingOperation for PendingMessage {
             return PendingOperationResult::NotReady;
         }
 
+        if self.is_blocked_by_relay_lists() {
+            return PendingOperationResult::Drop;
+        }
+
         // If the message has already been processed, e.g. due to another relayer having
         // already processed, then mark it as already-processed, and move on to
         // the next tick.
@@ -224,11 +278,16 @@ impl PendingOperation for PendingMessage {
         };
         if is_already_delivered {
             debug!("Message has already been delivered, marking as submitted.");
+            self.record_dry_run_delivery();
             self.submitted = true;
             self.set_next_attempt_after(CONFIRM_DELAY);
             return PendingOperationResult::Confirm(ConfirmReason::AlreadySubmitted);
         }
 
+        if let Some(result) = self.wait_for_earlier_messages().await {
+            return result;
+        }
+
         let provider = self.ctx.destination_mailbox.provider();
 
         // We cannot deliver to an address that is not a contract so check and drop if it isn't.
@@ -260,6 +319,7 @@ impl PendingOperation for PendingMessage {
                 return self.on_reprepare(Some(err), ReprepareReason::ErrorFetchingIsmAddress);
             }
         };
+        self.ism_address = Some(ism_address);
 
         let message_metadata_builder = match MessageMetadataBuilder::new(
             self.ctx.metadata_builder.clone(),
@@ -338,6 +398,7 @@ impl PendingOperation for PendingMessage {
         {
             Ok(tx_cost_estimate) => tx_cost_estimate,
             Err(err) => {
+                self.invalidate_cached_metadata();
                 let reason = self
                     .clarify_reason(ReprepareReason::ErrorEstimatingGas)
                     .await
@@ -370,6 +431,25 @@ impl PendingOperation for PendingMessage {
             GasPolicyStatus::PolicyMet(gas_limit) => gas_limit,
         };
 
+        // Park the message until the window rolls over if it would exceed a relay budget. This
+        // isn't a failure of the message, so it doesn't count as an attempt. Otherwise its
+        // estimated cost is reserved until the outcome of the submission is recorded.
+        match self
+            .ctx
+            .origin_gas_payment_enforcer
+            .reserve_budget(&self.message, &tx_cost_estimate)
+        {
+            Ok(Some(resume_at)) => {
+                info!(?resume_at, "Relay budget exhausted, parking message");
+                self.next_attempt_after = Some(resume_at);
+                return PendingOperationResult::Reprepare(ReprepareReason::RelayBudgetExhausted);
+            }
+            Ok(None) => {}
+            Err(err) => {
+                return self.on_reprepare(Some(err), ReprepareReason::ErrorCheckingGasRequirement);
+            }
+        }
+
         // Go ahead and attempt processing of message to destination chain.
         debug!(
             ?gas_limit,
@@ -377,6 +457,9 @@ impl PendingOperation for PendingMessage {
             "Gas payment requirement met, ready to process message"
         );
 
+        // A gas limit set when re-driving the message from the dead-letter queue replaces the
+        // estimate. It is still subject to the `transaction_gas_limit`.
+        let gas_limit = self.gas_limit_override().unwrap_or(gas_limit);
         if let Some(max_limit) = self.ctx.transaction_gas_limit {
             if gas_limit > max_limit {
                 // TODO: consider dropping instead of repreparing in this case
@@ -398,6 +481,10 @@ impl PendingOperation for PendingMessage {
             return PendingOperationResult::Success;
         }
 
+        if self.is_blocked_by_relay_lists() {
+            return PendingOperationResult::Drop;
+        }
+
         let state = self
             .submission_data
             .clone()
@@ -412,6 +499,7 @@ impl PendingOperation for PendingMessage {
                 .await
                 .is_err()
             {
+                self.invalidate_cached_metadata();
                 let reason = self
                     .clarify_reason(ReprepareReason::ErrorEstimatingGas)
                     .await
@@ -420,6 +508,10 @@ impl PendingOperation for PendingMessage {
             }
         }
 
+        if let Some(dry_run) = self.ctx.dry_run.clone() {
+            return self.dry_run_submit(&dry_run, state.gas_limit).await;
+        }
+
         // We use the estimated gas limit from the prior call to
         // `process_estimate_costs` to avoid a second gas estimation.
         let tx_outcome = self
@@ -464,7 +556,15 @@ impl PendingOperation for PendingMessage {
             }
         };
 
+        // In dry-run mode nothing was broadcast, so keep waiting for another relayer to deliver
+        // the message instead of treating it as reverted
+        if self.ctx.dry_run.is_some() && !is_delivered {
+            self.set_next_attempt_after(CONFIRM_DELAY);
+            return PendingOperationResult::NotReady;
+        }
+
         if is_delivered {
+            self.record_dry_run_delivery();
             if let Err(err) = self.record_message_process_success() {
                 return self
                     .on_reconfirm(Some(err), "Error when recording message process success");
@@ -473,6 +573,13 @@ impl PendingOperation for PendingMessage {
                 submission=?self.submission_outcome,
                 "Message successfully processed"
             );
+            if let Err(err) = self.ctx.origin_gas_payment_enforcer.record_delivery(
+                &self.message,
+                self.app_context.as_deref(),
+                self.ctx.destination_mailbox.domain(),
+            ) {
+                warn!(error = ?err, "Failed to record message profitability");
+            }
             PendingOperationResult::Success
         } else {
             warn!(message_id = ?self.message.id(), tx_outcome=?self.submission_outcome, "Transaction attempting to process message either reverted or was reorged");
@@ -597,11 +704,47 @@ impl PendingMessage {
     ) -> Option<u32> {
         let num_retries = Self::get_num_retries(origin_db.clone(), message);
         if Self::should_skip(num_retries, max_retries) {
+            Self::dead_letter_skipped(origin_db, message, num_retries);
             return None;
         }
         Some(num_retries)
     }
 
+    /// Moves a message that is skipped for having exhausted its retries, e.g. before the relayer
+    /// restarted, to the dead-letter queue unless it already went through it
+    fn dead_letter_skipped(
+        origin_db: Arc<dyn AetheriumDb>,
+        message: &AetheriumMessage,
+        num_retries: u32,
+    ) {
+        let id = message.id();
+        match origin_db.retrieve_dead_letter_by_message_id(&id) {
+            Ok(None) => {}
+            // already dead-lettered, or discarded from the queue
+            Ok(Some(_)) => return,
+            Err(e) => {
+                warn!(message_id = ?id, err = %e, "Reading the dead letter failed for message");
+                return;
+            }
+        }
+        let attempts = origin_db
+            .retrieve_pending_message_attempts_by_message_id(&id)
+            .ok()
+            .flatten()
+            .unwrap_or_default();
+        let dead_letter = DeadLetter {
+            message_id: id,
+            destination: message.destination,
+            status: Self::get_message_status(origin_db.clone(), message),
+            retry_count: num_retries,
+            last_error: attempts.last().and_then(|attempt| attempt.error.clone()),
+            attempts,
+            dead_lettered_at: unix_timestamp(),
+            discarded: false,
+        };
+        Self::store_dead_letter(origin_db.as_ref(), dead_letter);
+    }
+
     pub fn should_skip(retry_count: u32, max_retries: u32) -> bool {
         retry_count >= max_retries
     }
@@ -655,14 +798,157 @@ impl PendingMessage {
     ) -> PendingOperationResult {
         self.inc_attempts();
         self.submitted = false;
-        if let Some(e) = err {
+        if let Some(dry_run) = &self.ctx.dry_run {
+            dry_run.record_decision(
+                &self.message,
+                self.app_context.as_deref(),
+                DryRunDecision::Hold {
+                    reason: reason.to_string(),
+                },
+            );
+        }
+        let error = if let Some(e) = err {
             warn!(error = ?e, "Repreparing message: {}", reason.clone());
+            self.persist_last_error(format!("{reason}: {e:?}"));
+            Some(format!("{e:?}"))
         } else {
             warn!("Repreparing message: {}", reason.clone());
+            None
+        };
+        let attempts = self.record_attempt(reason.clone(), error);
+        if Self::should_skip(self.num_retries, self.max_retries) {
+            self.dead_letter(reason, attempts);
+            return PendingOperationResult::Drop;
         }
         PendingOperationResult::Reprepare(reason)
     }
 
+    /// Records what would have been submitted instead of broadcasting the message. The recipient
+    /// ISM's `verify` is dry-run too, so that the metadata is checked on its own.
+    async fn dry_run_submit(
+        &mut self,
+        dry_run: &DryRunRecorder,
+        gas_limit: U256,
+    ) -> PendingOperationResult {
+        let ism_verify_gas = match (self.ism_address, self.metadata.as_ref()) {
+            (Some(ism_address), Some(metadata)) => {
+                let verified = match self.ctx.metadata_builder.build_ism(ism_address).await {
+                    Ok(ism) => ism
+                        .dry_run_verify(&self.message, metadata)
+                        .await
+                        .map_err(Into::into),
+                    Err(err) => Err(err),
+                };
+                match verified {
+                    Ok(ism_verify_gas) => ism_verify_gas,
+                    Err(err) => {
+                        return self
+                            .on_reprepare(Some(err), ReprepareReason::ErrorBuildingMetadata);
+                    }
+                }
+            }
+            _ => None,
+        };
+        dry_run.record_decision(
+            &self.message,
+            self.app_context.as_deref(),
+            DryRunDecision::Submit {
+                gas_limit,
+                ism_verify_gas,
+            },
+        );
+        // nothing is broadcast, so nothing is spent
+        self.ctx
+            .origin_gas_payment_enforcer
+            .release_budget(&self.message);
+        self.submitted = true;
+        PendingOperationResult::Confirm(ConfirmReason::DryRun)
+    }
+
+    /// Holds the message back while a message with a lower nonce on its ordered lane isn't
+    /// delivered. Being held back doesn't count as an attempt.
+    async fn wait_for_earlier_messages(&mut self) -> Option<PendingOperationResult> {
+        let lanes = self.ctx.ordered_lanes.clone();
+        loop {
+            let earlier = match lanes.earlier_message(&self.message, self.ctx.origin_db.as_ref()) {
+                Ok(Some(earlier)) => earlier,
+                Ok(None) => break,
+                Err(err) => {
+                    return Some(
+                        self.on_reprepare(Some(err), ReprepareReason::ErrorCheckingDeliveryStatus),
+                    );
+                }
+            };
+            let (nonce, blocked_by) = match earlier {
+                // catching up with the origin may take a while, so the message goes back to the
+                // queue between batches rather than holding up the runtime
+                EarlierMessage::Unscanned => {
+                    self.next_attempt_after = Some(Instant::now() + ORDERED_LANE_SCAN_DELAY);
+                    return Some(PendingOperationResult::Reprepare(
+                        ReprepareReason::WaitingForEarlierMessage,
+                    ));
+                }
+                EarlierMessage::Unindexed(nonce) => (nonce, None),
+                EarlierMessage::Undelivered { nonce, id } => (nonce, Some(id)),
+            };
+            // the earlier message may have been delivered by another relayer
+            let delivered = match blocked_by {
+                Some(id) => self.ctx.destination_mailbox.delivered(id).await,
+                None => Ok(false),
+            };
+            match delivered {
+                Ok(true) => lanes.delivered(&self.message, nonce),
+                Ok(false) => {
+                    debug!(
+                        ?blocked_by,
+                        earlier_nonce = nonce,
+                        "Waiting for an earlier message of the lane"
+                    );
+                    lanes.hold(
+                        self.message.id(),
+                        blocked_by,
+                        (
+                            self.ctx.origin_db.domain().name(),
+                            self.ctx.destination_mailbox.domain().name(),
+                        ),
+                    );
+                    self.next_attempt_after = Some(Instant::now() + ORDERED_LANE_RECHECK_DELAY);
+                    return Some(PendingOperationResult::Reprepare(
+                        ReprepareReason::WaitingForEarlierMessage,
+                    ));
+                }
+                Err(err) => {
+                    return Some(
+                        self.on_reprepare(Some(err), ReprepareReason::ErrorCheckingDeliveryStatus),
+                    );
+                }
+            }
+        }
+        lanes.unhold(&self.message.id());
+        None
+    }
+
+    /// Drops what was cached to build the metadata of the message, so that it's built from scratch
+    /// on the next attempt in case the ISM config changed
+    fn invalidate_cached_metadata(&self) {
+        if let Some(cache) = self.ctx.metadata_builder.metadata_cache() {
+            cache.invalidate_message(self.message.id());
+        }
+    }
+
+    fn record_dry_run_delivery(&self) {
+        if let Some(dry_run) = &self.ctx.dry_run {
+            dry_run.record_delivery(
+                &self.message,
+                self.app_context.as_deref(),
+                (
+                    self.ctx.origin_db.domain().name(),
+                    self.ctx.destination_mailbox.domain().name(),
+                ),
+            );
+        }
+    }
+
     fn on_reconfirm<E: Debug>(&mut self, err: Option<E>, reason: &str) -> PendingOperationResult {
         self.inc_attempts();
         if let Some(e) = err {
@@ -690,6 +976,14 @@ impl PendingMessage {
         self.ctx
             .origin_db
             .store_processed_by_nonce(&self.message.nonce, &true)?;
+        // the message is delivered, so an error from an earlier attempt and the gas limit it was
+        // re-driven with no longer apply
+        self.ctx
+            .origin_db
+            .delete_pending_message_last_error_by_message_id(&self.message.id())?;
+        self.ctx
+            .origin_db
+            .delete_pending_message_gas_limit_override_by_message_id(&self.message.id())?;
         self.ctx.metrics.update_nonce(&self.message);
         self.ctx.metrics.messages_processed.inc();
         Ok(())
@@ -726,6 +1020,105 @@ impl PendingMessage {
         }
     }
 
+    /// Whether the relay lists were changed to exclude the message since it was queued
+    fn is_blocked_by_relay_lists(&self) -> bool {
+        let blocked = self.ctx.relay_lists.load().blocks(&self.message);
+        if blocked {
+            info!(
+                id = ?self.id(),
+                "Dropping message excluded by the relay lists"
+            );
+            // handed back to the processor if the lists are relaxed again
+            self.ctx.relay_lists.exclude(&self.message);
+        }
+        blocked
+    }
+
+    /// Stores the error that caused the last reprepare, so it can be queried through the API
+    fn persist_last_error(&self, error: String) {
+        if let Err(e) = self
+            .ctx
+            .origin_db
+            .store_pending_message_last_error_by_message_id(&self.message.id(), &error)
+        {
+            warn!(message_id = ?self.message.id(), err = %e, "Persisting the last error failed for message");
+        }
+    }
+
+    /// Appends a failed attempt to the attempt history of the message, keeping the most recent
+    /// `MAX_RECORDED_ATTEMPTS`, and returns the history
+    fn record_attempt(
+        &self,
+        reason: ReprepareReason,
+        error: Option<String>,
+    ) -> Vec<OperationAttempt> {
+        let id = self.message.id();
+        let mut attempts = match self
+            .ctx
+            .origin_db
+            .retrieve_pending_message_attempts_by_message_id(&id)
+        {
+            Ok(attempts) => attempts.unwrap_or_default(),
+            Err(e) => {
+                warn!(message_id = ?id, err = %e, "Reading the attempt history failed for message");
+                vec![]
+            }
+        };
+        attempts.push(OperationAttempt {
+            attempted_at: unix_timestamp(),
+            reason,
+            error,
+        });
+        let excess = attempts.len().saturating_sub(MAX_RECORDED_ATTEMPTS);
+        attempts.drain(..excess);
+        if let Err(e) = self
+            .ctx
+            .origin_db
+            .store_pending_message_attempts_by_message_id(&id, &attempts)
+        {
+            warn!(message_id = ?id, err = %e, "Persisting the attempt history failed for message");
+        }
+        attempts
+    }
+
+    /// Moves the message, which exhausted its retries, to the dead-letter queue
+    fn dead_letter(&self, reason: ReprepareReason, attempts: Vec<OperationAttempt>) {
+        let id = self.message.id();
+        let dead_letter = DeadLetter {
+            message_id: id,
+            destination: self.message.destination,
+            status: PendingOperationStatus::Retry(reason),
+            retry_count: self.num_retries,
+            last_error: attempts.last().and_then(|attempt| attempt.error.clone()),
+            attempts,
+            dead_lettered_at: unix_timestamp(),
+            discarded: false,
+        };
+        warn!(
+            message_id = ?id,
+            max_retries = self.max_retries,
+            "Message exhausted its retries, moving it to the dead-letter queue"
+        );
+        Self::store_dead_letter(self.ctx.origin_db.as_ref(), dead_letter);
+    }
+
+    fn store_dead_letter(origin_db: &dyn AetheriumDb, dead_letter: DeadLetter) {
+        let id = dead_letter.message_id;
+        if let Err(e) = origin_db.store_dead_letter_by_message_id(&id, &dead_letter) {
+            error!(message_id = ?id, err = %e, "Persisting the dead letter failed for message");
+        }
+    }
+
+    fn gas_limit_override(&self) -> Option<U256> {
+        self.ctx
+            .origin_db
+            .retrieve_pending_message_gas_limit_override_by_message_id(&self.message.id())
+            .unwrap_or_else(|e| {
+                warn!(message_id = ?self.message.id(), err = %e, "Reading the gas limit override failed for message");
+                None
+            })
+    }
+
     /// Get duration we should wait before re-attempting to deliver a message
     /// given the number of retries.
     /// `pub(crate)` for testing purposes
@@ -929,6 +1322,50 @@ mod test {
                 &self,
                 message_id: &H256,
             ) -> DbResult<Option<u32>>;
+            fn store_pending_message_last_error_by_message_id(
+                &self,
+                message_id: &H256,
+                error: &str,
+            ) -> DbResult<()>;
+            fn retrieve_pending_message_last_error_by_message_id(
+                &self,
+                message_id: &H256,
+            ) -> DbResult<Option<String>>;
+            fn delete_pending_message_last_error_by_message_id(
+                &self,
+                message_id: &H256,
+            ) -> DbResult<()>;
+            fn store_pending_message_attempts_by_message_id(
+                &self,
+                message_id: &H256,
+                attempts: &[OperationAttempt],
+            ) -> DbResult<()>;
+            fn retrieve_pending_message_attempts_by_message_id(
+                &self,
+                message_id: &H256,
+            ) -> DbResult<Option<Vec<OperationAttempt>>>;
+            fn store_pending_message_gas_limit_override_by_message_id(
+                &self,
+                message_id: &H256,
+                gas_limit: &U256,
+            ) -> DbResult<()>;
+            fn retrieve_pending_message_gas_limit_override_by_message_id(
+                &self,
+                message_id: &H256,
+            ) -> DbResult<Option<U256>>;
+            fn delete_pending_message_gas_limit_override_by_message_id(
+                &self,
+                message_id: &H256,
+            ) -> DbResult<()>;
+            fn store_dead_letter_by_message_id(
+                &self,
+                message_id: &H256,
+                dead_letter: &DeadLetter,
+            ) -> DbResult<()>;
+            fn retrieve_dead_letter_by_message_id(
+                &self,
+                message_id: &H256,
+            ) -> DbResult<Option<DeadLetter>>;
             fn store_merkle_tree_insertion_by_leaf_index(
                 &self,
                 leaf_index: &u32,
@@ -16,22 +16,19 @@ use aetherium_base::{
 };
 use aetherium_core::{AetheriumDomain, AetheriumMessage, QueueOperation};
 use prometheus::IntGauge;
-use tokio::sync::mpsc::UnboundedSender;
+use tokio::sync::mpsc::{UnboundedReceiver, UnboundedSender};
 use tracing::{debug, instrument, trace};
 
-use super::{blacklist::AddressBlacklist, metadata::AppContextClassifier, pending_message::*};
+use super::{metadata::AppContextClassifier, pending_message::*, relay_lists::RelayLists};
 use crate::{processor::ProcessorExt, settings::matching_list::MatchingList};
 
 /// Finds unprocessed messages from an origin and submits then through a channel
 /// for to the appropriate destination.
 #[allow(clippy::too_many_arguments)]
 pub struct MessageProcessor {
-    /// A matching list of messages that should be whitelisted.
-    message_whitelist: Arc<MatchingList>,
-    /// A matching list of messages that should be blacklisted.
-    message_blacklist: Arc<MatchingList>,
-    /// Addresses that messages may not interact with.
-    address_blacklist: Arc<AddressBlacklist>,
+    /// The whitelist, blacklist and addresses that messages may not interact with. They can be
+    /// replaced at runtime.
+    relay_lists: Arc<RelayLists>,
     metrics: MessageProcessorMetrics,
     /// channel for each destination chain to send operations (i.e. message
     /// submissions) to
@@ -41,6 +38,8 @@ pub struct MessageProcessor {
     metric_app_contexts: Vec<(MatchingList, String)>,
     nonce_iterator: ForwardBackwardIterator,
     max_retries: u32,
+    /// Messages re-driven from the dead-letter queue
+    redrive_receiver: Option<UnboundedReceiver<AetheriumMessage>>,
 }
 
 #[derive(Debug)]
@@ -231,8 +230,8 @@ impl Debug for MessageProcessor {
     fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
         write!(
             f,
-            "MessageProcessor {{ message_whitelist: {:?}, message_blacklist: {:?}, address_blacklist: {:?}, nonce_iterator: {:?}}}",
-            self.message_whitelist, self.message_blacklist, self.address_blacklist, self.nonce_iterator
+            "MessageProcessor {{ relay_lists: {:?}, nonce_iterator: {:?}}}",
+            self.relay_lists, self.nonce_iterator
         )
     }
 }
@@ -257,59 +256,51 @@ impl ProcessorExt for MessageProcessor {
         // self.tx_msg and then continue the scan at the next highest
         // nonce.
         // Scan until we find next nonce without delivery confirmation.
-        if let Some(msg) = self.try_get_unprocessed_message().await? {
+        if let Some(msg) = self.try_get_redriven_message() {
+            debug!(?msg, "Processor working on re-driven message");
+            self.send_to_destination(msg).await?;
+        } else if let Some(msg) = self.relay_lists.take_released(self.domain().id()) {
+            debug!(
+                ?msg,
+                "Processor working on message released by the relay lists"
+            );
+            self.send_to_destination(msg).await?;
+        } else if let Some(msg) = self.try_get_unprocessed_message().await? {
             debug!(
                 ?msg,
                 cursor = ?self.nonce_iterator,
                 "Processor working on message"
             );
-            let destination = msg.destination;
+            let relay_lists = self.relay_lists.load();
 
             // Skip if not whitelisted.
-            if !self.message_whitelist.msg_matches(&msg, true) {
-                debug!(?msg, whitelist=?self.message_whitelist, "Message not whitelisted, skipping");
+            if !relay_lists.whitelist.msg_matches(&msg, true) {
+                debug!(?msg, whitelist=?relay_lists.whitelist, "Message not whitelisted, skipping");
+                self.relay_lists.exclude(&msg);
                 return Ok(());
             }
 
             // Skip if the message is blacklisted
-            if self.message_blacklist.msg_matches(&msg, false) {
-                debug!(?msg, blacklist=?self.message_blacklist, "Message blacklisted, skipping");
+            if relay_lists.blacklist.msg_matches(&msg, false) {
+                debug!(?msg, blacklist=?relay_lists.blacklist, "Message blacklisted, skipping");
+                self.relay_lists.exclude(&msg);
                 return Ok(());
             }
 
             // Skip if the message involves a blacklisted address
-            if let Some(blacklisted_address) = self.address_blacklist.find_blacklisted_address(&msg)
+            if let Some(blacklisted_address) =
+                relay_lists.address_blacklist.find_blacklisted_address(&msg)
             {
                 debug!(
                     ?msg,
                     blacklisted_address = hex::encode(blacklisted_address),
                     "Message involves blacklisted address, skipping"
                 );
+                self.relay_lists.exclude(&msg);
                 return Ok(());
             }
 
-            // Skip if the message is intended for a destination we do not service
-            if !self.send_channels.contains_key(&destination) {
-                debug!(?msg, "Message destined for unknown domain, skipping");
-                return Ok(());
-            }
-
-            debug!(%msg, "Sending message to submitter");
-
-            let app_context_classifier =
-                AppContextClassifier::new(self.metric_app_contexts.clone());
-
-            let app_context = app_context_classifier.get_app_context(&msg).await?;
-            // Finally, build the submit arg and dispatch it to the submitter.
-            let pending_msg = PendingMessage::maybe_from_persisted_retries(
-                msg,
-                self.destination_ctxs[&destination].clone(),
-                app_context,
-                self.max_retries,
-            );
-            if let Some(pending_msg) = pending_msg {
-                self.send_channels[&destination].send(Box::new(pending_msg) as QueueOperation)?;
-            }
+            self.send_to_destination(msg).await?;
         } else {
             tokio::time::sleep(Duration::from_secs(1)).await;
         }
@@ -321,9 +312,7 @@ impl MessageProcessor {
     #[allow(clippy::too_many_arguments)]
     pub fn new(
         db: AetheriumRocksDB,
-        message_whitelist: Arc<MatchingList>,
-        message_blacklist: Arc<MatchingList>,
-        address_blacklist: Arc<AddressBlacklist>,
+        relay_lists: Arc<RelayLists>,
         metrics: MessageProcessorMetrics,
         send_channels: HashMap<u32, UnboundedSender<QueueOperation>>,
         destination_ctxs: HashMap<u32, Arc<MessageContext>>,
@@ -331,18 +320,56 @@ impl MessageProcessor {
         max_retries: u32,
     ) -> Self {
         Self {
-            message_whitelist,
-            message_blacklist,
-            address_blacklist,
+            relay_lists,
             metrics,
             send_channels,
             destination_ctxs,
             metric_app_contexts,
             nonce_iterator: ForwardBackwardIterator::new(Arc::new(db) as Arc<dyn AetheriumDb>),
             max_retries,
+            redrive_receiver: None,
         }
     }
 
+    pub fn with_redrive_receiver(
+        mut self,
+        redrive_receiver: UnboundedReceiver<AetheriumMessage>,
+    ) -> Self {
+        self.redrive_receiver = Some(redrive_receiver);
+        self
+    }
+
+    fn try_get_redriven_message(&mut self) -> Option<AetheriumMessage> {
+        self.redrive_receiver.as_mut()?.try_recv().ok()
+    }
+
+    async fn send_to_destination(&self, msg: AetheriumMessage) -> Result<()> {
+        let destination = msg.destination;
+
+        // Skip if the message is intended for a destination we do not service
+        if !self.send_channels.contains_key(&destination) {
+            debug!(?msg, "Message destined for unknown domain, skipping");
+            return Ok(());
+        }
+
+        debug!(%msg, "Sending message to submitter");
+
+        let app_context_classifier = AppContextClassifier::new(self.metric_app_contexts.clone());
+
+        let app_context = app_context_classifier.get_app_context(&msg).await?;
+        // Finally, build the submit arg and dispatch it to the submitter.
+        let pending_msg = PendingMessage::maybe_from_persisted_retries(
+            msg,
+            self.destination_ctxs[&destination].clone(),
+            app_context,
+            self.max_retries,
+        );
+        if let Some(pending_msg) = pending_msg {
+            self.send_channels[&destination].send(Box::new(pending_msg) as QueueOperation)?;
+        }
+        Ok(())
+    }
+
     async fn try_get_unprocessed_message(&mut self) -> Result<Option<AetheriumMessage>> {
         trace!(nonce_iterator=?self.nonce_iterator, "Trying to get the next processor message");
         let next_message = self
@@ -414,8 +441,9 @@ mod test {
         settings::{ChainConf, ChainConnectionConf, Settings},
     };
     use aetherium_core::{
-        test_utils::dummy_domain, GasPaymentKey, InterchainGasPayment, InterchainGasPaymentMeta,
-        MerkleTreeInsertion, PendingOperationStatus, H256,
+        test_utils::dummy_domain, DeadLetter, GasPaymentKey, InterchainGasPayment,
+        InterchainGasPaymentMeta, MerkleTreeInsertion, OperationAttempt, PendingOperationStatus,
+        H256, U256,
     };
     use aetherium_operation_verifier::{
         ApplicationOperationVerifier, ApplicationOperationVerifierReport,
@@ -426,10 +454,11 @@ mod test {
     use crate::{
         merkle_tree::builder::MerkleTreeBuilder,
         msg::{
-            gas_payment::GasPaymentEnforcer,
+            gas_payment::{GasPaymentEnforcer, RelayBudgets},
             metadata::{BaseMetadataBuilder, IsmAwareAppContextClassifier},
         },
         processor::Processor,
+        settings::RelayBudgetConf,
     };
 
     use super::*;
@@ -511,16 +540,17 @@ mod test {
             Arc::new(core_metrics),
             db.clone(),
             IsmAwareAppContextClassifier::new(Arc::new(MockMailboxContract::default()), vec![]),
+            None,
         )
     }
 
-    fn dummy_message_processor(
+    fn dummy_message_context(
         origin_domain: &AetheriumDomain,
         destination_domain: &AetheriumDomain,
         db: &AetheriumRocksDB,
-    ) -> (MessageProcessor, UnboundedReceiver<QueueOperation>) {
+    ) -> MessageContext {
         let base_metadata_builder = dummy_metadata_builder(origin_domain, destination_domain, db);
-        let message_context = Arc::new(MessageContext {
+        MessageContext {
             destination_mailbox: Arc::new(MockMailboxContract::default()),
             origin_db: Arc::new(db.clone()),
             metadata_builder: Arc::new(base_metadata_builder),
@@ -528,15 +558,25 @@ mod test {
             transaction_gas_limit: Default::default(),
             metrics: dummy_submission_metrics(),
             application_operation_verifier: Some(Arc::new(DummyApplicationOperationVerifier {})),
-        });
+            relay_lists: Default::default(),
+            dry_run: None,
+            ordered_lanes: Default::default(),
+        }
+    }
+
+    fn dummy_message_processor(
+        origin_domain: &AetheriumDomain,
+        destination_domain: &AetheriumDomain,
+        db: &AetheriumRocksDB,
+    ) -> (MessageProcessor, UnboundedReceiver<QueueOperation>) {
+        let message_context =
+            Arc::new(dummy_message_context(origin_domain, destination_domain, db));
 
         let (send_channel, receive_channel) = mpsc::unbounded_channel::<QueueOperation>();
         (
             MessageProcessor::new(
                 db.clone(),
                 Default::default(),
-                Default::default(),
-                Default::default(),
                 dummy_processor_metrics(origin_domain.id()),
                 HashMap::from([(destination_domain.id(), send_channel)]),
                 HashMap::from([(destination_domain.id(), message_context)]),
@@ -731,6 +771,71 @@ mod test {
                 message_id: &H256,
             ) -> DbResult<Option<u32>>;
 
+            /// Store the last error hit while processing a pending message by its message id
+            fn store_pending_message_last_error_by_message_id(
+                &self,
+                message_id: &H256,
+                error: &str,
+            ) -> DbResult<()>;
+
+            /// Retrieve the last error hit while processing a pending message by its message id
+            fn retrieve_pending_message_last_error_by_message_id(
+                &self,
+                message_id: &H256,
+            ) -> DbResult<Option<String>>;
+
+            /// Delete the last error hit while processing a pending message, once it is delivered
+            fn delete_pending_message_last_error_by_message_id(
+                &self,
+                message_id: &H256,
+            ) -> DbResult<()>;
+
+            /// Store the most recent failed attempts at processing a pending message by its message id
+            fn store_pending_message_attempts_by_message_id(
+                &self,
+                message_id: &H256,
+                attempts: &[OperationAttempt],
+            ) -> DbResult<()>;
+
+            /// Retrieve the most recent failed attempts at processing a pending message by its message id
+            fn retrieve_pending_message_attempts_by_message_id(
+                &self,
+                message_id: &H256,
+            ) -> DbResult<Option<Vec<OperationAttempt>>>;
+
+            /// Store the gas limit to submit a pending message with instead of the estimate, by its message id
+            fn store_pending_message_gas_limit_override_by_message_id(
+                &self,
+                message_id: &H256,
+                gas_limit: &U256,
+            ) -> DbResult<()>;
+
+            /// Retrieve the gas limit to submit a pending message with instead of the estimate, by its message id
+            fn retrieve_pending_message_gas_limit_override_by_message_id(
+                &self,
+                message_id: &H256,
+            ) -> DbResult<Option<U256>>;
+
+            /// Delete the gas limit to submit a pending message with, once it is delivered or re-driven
+            /// without one
+            fn delete_pending_message_gas_limit_override_by_message_id(
+                &self,
+                message_id: &H256,
+            ) -> DbResult<()>;
+
+            /// Store the dead letter of a message that exhausted its retries by its message id
+            fn store_dead_letter_by_message_id(
+                &self,
+                message_id: &H256,
+                dead_letter: &DeadLetter,
+            ) -> DbResult<()>;
+
+            /// Retrieve the dead letter of a message that exhausted its retries by its message id
+            fn retrieve_dead_letter_by_message_id(
+                &self,
+                message_id: &H256,
+            ) -> DbResult<Option<DeadLetter>>;
+
             fn store_merkle_tree_insertion_by_leaf_index(
                 &self,
                 leaf_index: &u32,
@@ -830,6 +935,46 @@ mod test {
         .await;
     }
 
+    #[tokio::test]
+    async fn test_dropped_message_releases_its_budget_reservation() {
+        test_utils::run_test_db(|db| async move {
+            let origin_domain = dummy_domain(0, "dummy_origin_domain");
+            let destination_domain = dummy_domain(1, "dummy_destination_domain");
+            let db = AetheriumRocksDB::new(&origin_domain, db);
+            let budgets = Arc::new(RelayBudgets::new([RelayBudgetConf {
+                matching_list: Default::default(),
+                window: Duration::from_secs(3600),
+                max_spend: None,
+                max_messages: Some(1),
+            }]));
+            let ctx = Arc::new(MessageContext {
+                origin_gas_payment_enforcer: Arc::new(
+                    GasPaymentEnforcer::new([], db.clone()).with_budgets(budgets.clone()),
+                ),
+                ..dummy_message_context(&origin_domain, &destination_domain, &db)
+            });
+            let (reserved, other) = (
+                dummy_aetherium_message(&destination_domain, 0),
+                dummy_aetherium_message(&destination_domain, 1),
+            );
+
+            let pending_message = PendingMessage::maybe_from_persisted_retries(
+                reserved.clone(),
+                ctx,
+                None,
+                DEFAULT_MAX_MESSAGE_RETRIES,
+            )
+            .unwrap();
+            assert!(budgets.reserve(&reserved, U256::one()).is_none());
+            assert!(budgets.reserve(&other, U256::one()).is_some());
+
+            // e.g. because it was dead-lettered before its submission was recorded
+            drop(pending_message);
+            assert!(budgets.reserve(&other, U256::one()).is_none());
+        })
+        .await;
+    }
+
     #[tokio::test]
     async fn test_forward_backward_iterator() {
         let mut mock_db = MockDb::new();
@@ -0,0 +1,359 @@
+use std::{
+    collections::{HashMap, HashSet},
+    path::{Path, PathBuf},
+    sync::{Arc, Mutex, MutexGuard, RwLock},
+    time::{Duration, SystemTime},
+};
+
+use aetherium_base::db::DB;
+use aetherium_core::{AetheriumMessage, H256};
+use ethers::utils::hex;
+use eyre::{Context, Result};
+use serde_json::Value;
+use tokio::time::sleep;
+use tracing::{debug, info, warn};
+
+use super::blacklist::AddressBlacklist;
+use crate::settings::{matching_list::MatchingList, RelayListsConf};
+
+/// How often the lists file is checked for changes
+pub const RELAY_LISTS_FILE_POLL_INTERVAL: Duration = Duration::from_secs(10);
+
+/// Key of the lists set through the admin API in the database, when there is no lists file
+const RELAY_LISTS_KEY: &[u8] = b"relay_lists";
+
+/// Most messages kept around to be relayed if the lists are relaxed. Messages past that are
+/// only reconsidered when the relayer restarts and scans the origins again.
+const MAX_EXCLUDED_MESSAGES: usize = 10_000;
+
+/// The lists deciding which messages are relayed, as of one point in time
+#[derive(Debug, Default)]
+pub struct ActiveRelayLists {
+    pub whitelist: MatchingList,
+    pub blacklist: MatchingList,
+    pub address_blacklist: AddressBlacklist,
+}
+
+impl ActiveRelayLists {
+    fn new(conf: RelayListsConf) -> Self {
+        Self {
+            whitelist: conf.whitelist,
+            blacklist: conf.blacklist,
+            address_blacklist: AddressBlacklist::new(conf.address_blacklist),
+        }
+    }
+
+    /// Whether the lists exclude `message` from being relayed
+    pub fn blocks(&self, message: &AetheriumMessage) -> bool {
+        !self.whitelist.msg_matches(message, true)
+            || self.blacklist.msg_matches(message, false)
+            || self
+                .address_blacklist
+                .find_blacklisted_address(message)
+                .is_some()
+    }
+}
+
+/// Where lists replaced through the admin API are persisted
+#[derive(Debug)]
+enum RelayListsStore {
+    File(PathBuf),
+    Db(DB),
+}
+
+/// The whitelist, blacklist and address blacklist of the relayer, which can be replaced at runtime
+/// through the admin API or by editing the lists file. Readers get a snapshot of the lists, so a
+/// replacement never shows partially.
+#[derive(Debug, Default)]
+pub struct RelayLists {
+    current: RwLock<Arc<ActiveRelayLists>>,
+    store: Option<RelayListsStore>,
+    /// Messages the lists excluded from relaying and those released by a later replacement,
+    /// which are handed back to the processor of their origin
+    excluded: Mutex<ExcludedMessages>,
+}
+
+#[derive(Debug, Default)]
+struct ExcludedMessages {
+    held: HashMap<H256, AetheriumMessage>,
+    released: HashMap<u32, Vec<AetheriumMessage>>,
+}
+
+impl RelayLists {
+    pub fn new(conf: RelayListsConf) -> Self {
+        Self {
+            current: RwLock::new(Arc::new(ActiveRelayLists::new(conf))),
+            ..Default::default()
+        }
+    }
+
+    /// Loads the lists from `file` if one is given, which lists set through the admin API are
+    /// then written to. Otherwise lists set through the admin API are persisted in `db`, and
+    /// take precedence over `conf`.
+    pub async fn load(conf: RelayListsConf, file: Option<PathBuf>, db: DB) -> Result<Self> {
+        let (conf, store) = match file {
+            Some(path) => (read_lists_file(&path).await?, RelayListsStore::File(path)),
+            None => match db.retrieve(RELAY_LISTS_KEY)? {
+                Some(raw) => {
+                    let conf = RelayListsConf::from_json(serde_json::from_slice(&raw)?)?;
+                    warn!("Using the relay lists set through the admin API instead of the config");
+                    (conf, RelayListsStore::Db(db))
+                }
+                None => (conf, RelayListsStore::Db(db)),
+            },
+        };
+        Ok(Self {
+            store: Some(store),
+            ..Self::new(conf)
+        })
+    }
+
+    /// A snapshot of the current lists
+    pub fn load(&self) -> Arc<ActiveRelayLists> {
+        self.current
+            .read()
+            .unwrap_or_else(|e| e.into_inner())
+            .clone()
+    }
+
+    /// Replaces the lists, logging what changed for auditing. `source` says where the new lists
+    /// come from.
+    pub fn replace(&self, conf: RelayListsConf, source: &str) {
+        let new = Arc::new(ActiveRelayLists::new(conf));
+        let old = {
+            let mut current = self.current.write().unwrap_or_else(|e| e.into_inner());
+            std::mem::replace(&mut *current, new.clone())
+        };
+        log_change(&old, &new, source);
+
+        let mut excluded = self.excluded();
+        let ExcludedMessages { held, released } = &mut *excluded;
+        held.retain(|_, message| {
+            if new.blocks(message) {
+                return true;
+            }
+            released
+                .entry(message.origin)
+                .or_default()
+                .push(message.clone());
+            false
+        });
+    }
+
+    /// Persists `lists`, as given to the admin API, so they survive a restart. With a lists file,
+    /// the file is rewritten and the watcher then picks the lists up again without changes.
+    pub async fn persist(&self, lists: &Value) -> Result<()> {
+        let raw = serde_json::to_vec_pretty(lists)?;
+        match &self.store {
+            Some(RelayListsStore::File(path)) => {
+                // write the whole file at once, so the watcher never reads it partially written
+                let tmp_path = path.with_extension("tmp");
+                tokio::fs::write(&tmp_path, &raw).await?;
+                tokio::fs::rename(&tmp_path, path).await?;
+            }
+            Some(RelayListsStore::Db(db)) => db.store(RELAY_LISTS_KEY, &raw)?,
+            None => {}
+        }
+        Ok(())
+    }
+
+    /// Keeps a message the lists exclude, so it is relayed if the lists are relaxed later on
+    pub fn exclude(&self, message: &AetheriumMessage) {
+        let mut excluded = self.excluded();
+        // checked against the lists while holding the lock, so a replacement that lands in
+        // between either sees the message or is seen here
+        if !self.load().blocks(message) {
+            excluded
+                .released
+                .entry(message.origin)
+                .or_default()
+                .push(message.clone());
+            return;
+        }
+        if excluded.held.len() >= MAX_EXCLUDED_MESSAGES {
+            debug!(id = ?message.id(), "Too many messages excluded by the relay lists, not keeping this one");
+            return;
+        }
+        excluded.held.insert(message.id(), message.clone());
+    }
+
+    /// Takes one of the messages from `origin` that the lists excluded before being relaxed
+    pub fn take_released(&self, origin: u32) -> Option<AetheriumMessage> {
+        self.excluded().released.get_mut(&origin)?.pop()
+    }
+
+    fn excluded(&self) -> MutexGuard<'_, ExcludedMessages> {
+        self.excluded.lock().unwrap_or_else(|e| e.into_inner())
+    }
+
+    /// Replaces the lists with the content of the file at `path` whenever it is modified. Invalid
+    /// files are logged and leave the lists as they are.
+    pub async fn watch_file(&self, path: &Path) {
+        let mut last_modified = None;
+        loop {
+            match modified_at(path).await {
+                Ok(modified) if Some(modified) != last_modified => {
+                    last_modified = Some(modified);
+                    match read_lists_file(path).await {
+                        Ok(conf) => self.replace(conf, &format!("file {}", path.display())),
+                        Err(err) => {
+                            warn!(?err, path = %path.display(), "Failed to reload relay lists")
+                        }
+                    }
+                }
+                Ok(_) => {}
+                Err(err) => warn!(?err, path = %path.display(), "Failed to check relay lists file"),
+            }
+            sleep(RELAY_LISTS_FILE_POLL_INTERVAL).await;
+        }
+    }
+}
+
+async fn modified_at(path: &Path) -> Result<SystemTime> {
+    Ok(tokio::fs::metadata(path).await?.modified()?)
+}
+
+/// Reads lists in the format of the config from the JSON file at `path`
+pub async fn read_lists_file(path: &Path) -> Result<RelayListsConf> {
+    let raw = tokio::fs::read_to_string(path).await?;
+    let value = serde_json::from_str(&raw).context("Relay lists file isn't valid JSON")?;
+    Ok(RelayListsConf::from_json(value)?)
+}
+
+fn log_change(old: &ActiveRelayLists, new: &ActiveRelayLists, source: &str) {
+    let addresses = |lists: &ActiveRelayLists| -> HashSet<String> {
+        lists
+            .address_blacklist
+            .blacklist
+            .iter()
+            .map(hex::encode)
+            .collect()
+    };
+    let (old_addresses, new_addresses) = (addresses(old), addresses(new));
+    let added_addresses: Vec<_> = new_addresses.difference(&old_addresses).collect();
+    let removed_addresses: Vec<_> = old_addresses.difference(&new_addresses).collect();
+    let whitelist_changed = old.whitelist.to_string() != new.whitelist.to_string();
+    let blacklist_changed = old.blacklist.to_string() != new.blacklist.to_string();
+    if !whitelist_changed
+        && !blacklist_changed
+        && added_addresses.is_empty()
+        && removed_addresses.is_empty()
+    {
+        return;
+    }
+    info!(
+        source,
+        old_whitelist = %old.whitelist,
+        new_whitelist = %new.whitelist,
+        old_blacklist = %old.blacklist,
+        new_blacklist = %new.blacklist,
+        ?added_addresses,
+        ?removed_addresses,
+        "Relay lists changed"
+    );
+}
+
+#[cfg(test)]
+mod test {
+    use aetherium_base::db::test_utils;
+    use serde_json::json;
+
+    use super::*;
+
+    fn message(destination: u32) -> AetheriumMessage {
+        AetheriumMessage {
+            destination,
+            body: b"some body with a blocked address".to_vec(),
+            ..AetheriumMessage::default()
+        }
+    }
+
+    #[test]
+    fn test_replace_lists() {
+        let lists = RelayLists::default();
+        let snapshot = lists.load();
+        assert!(!snapshot.blocks(&message(1)));
+
+        let conf = RelayListsConf::from_json(json!({
+            "blacklist": [{"destinationdomain": 1}],
+        }))
+        .unwrap();
+        lists.replace(conf, "test");
+        assert!(lists.load().blocks(&message(1)));
+        assert!(!lists.load().blocks(&message(2)));
+        // snapshots taken before the replacement are unaffected
+        assert!(!snapshot.blocks(&message(1)));
+
+        let conf = RelayListsConf::from_json(json!({
+            "whitelist": [{"destinationdomain": 2}],
+            "addressBlacklist": hex::encode(b"blocked"),
+        }))
+        .unwrap();
+        lists.replace(conf, "test");
+        assert!(lists.load().blocks(&message(1)));
+        assert!(lists.load().blocks(&message(2)));
+    }
+
+    #[test]
+    fn test_invalid_lists_are_rejected() {
+        assert!(RelayListsConf::from_json(json!({ "addressBlacklist": "0xzz" })).is_err());
+        assert!(RelayListsConf::from_json(json!({ "whitelist": "not a list" })).is_err());
+    }
+
+    #[tokio::test]
+    async fn test_read_lists_file() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("relay_lists.json");
+        std::fs::write(&path, r#"{"blacklist": [{"origindomain": 5}]}"#).unwrap();
+
+        let lists = RelayLists::new(read_lists_file(&path).await.unwrap());
+        let message = AetheriumMessage {
+            origin: 5,
+            ..AetheriumMessage::default()
+        };
+        assert!(lists.load().blocks(&message));
+    }
+
+    #[test]
+    fn test_excluded_messages_are_released_when_lists_are_relaxed() {
+        let conf = RelayListsConf::from_json(json!({
+            "blacklist": [{"destinationdomain": [1, 2]}],
+        }))
+        .unwrap();
+        let lists = RelayLists::new(conf);
+        lists.exclude(&message(1));
+        lists.exclude(&message(2));
+        assert_eq!(lists.take_released(0), None);
+
+        let conf = RelayListsConf::from_json(json!({
+            "blacklist": [{"destinationdomain": 2}],
+        }))
+        .unwrap();
+        lists.replace(conf, "test");
+        assert_eq!(lists.take_released(0), Some(message(1)));
+        assert_eq!(lists.take_released(0), None);
+    }
+
+    #[tokio::test]
+    async fn test_lists_set_through_the_api_are_persisted() {
+        test_utils::run_test_db(|db| async move {
+            let lists = json!({ "blacklist": [{"origindomain": 5}] });
+            RelayLists::load(RelayListsConf::default(), None, db.clone())
+                .await
+                .unwrap()
+                .persist(&lists)
+                .await
+                .unwrap();
+
+            let reloaded = RelayLists::load(RelayListsConf::default(), None, db)
+                .await
+                .unwrap();
+            let message = AetheriumMessage {
+                origin: 5,
+                ..AetheriumMessage::default()
+            };
+            assert!(reloaded.load().blocks(&message));
+        })
+        .await;
+    }
+}
@@ -1,6 +1,7 @@
 use std::{
     collections::{HashMap, HashSet},
     fmt::{Debug, Formatter},
+    path::PathBuf,
     sync::Arc,
 };
 
@@ -11,7 +12,7 @@ use futures_util::future::try_join_all;
 use tokio::{
     sync::{
         broadcast::Sender as BroadcastSender,
-        mpsc::{self, Receiver as MpscReceiver, UnboundedSender},
+        mpsc::{self, Receiver as MpscReceiver, UnboundedReceiver, UnboundedSender},
         RwLock,
     },
     task::JoinHandle,
@@ -37,15 +38,19 @@ use aetherium_operation_verifier::ApplicationOperationVerifier;
 use crate::{
     merkle_tree::builder::MerkleTreeBuilder,
     msg::{
-        blacklist::AddressBlacklist,
-        gas_payment::GasPaymentEnforcer,
-        metadata::{BaseMetadataBuilder, IsmAwareAppContextClassifier},
+        dead_letter::DeadLetterQueue,
+        dry_run::DryRunRecorder,
+        gas_payment::{GasPaymentEnforcer, ProfitabilityTracker, RelayBudgets},
+        metadata::{BaseMetadataBuilder, IsmAwareAppContextClassifier, MetadataCache},
         op_submitter::{SerialSubmitter, SerialSubmitterMetrics},
+        ordered_lanes::OrderedLanes,
+        pause::RelayPauses,
         pending_message::{MessageContext, MessageSubmissionMetrics},
         processor::{MessageProcessor, MessageProcessorMetrics},
+        relay_lists::RelayLists,
     },
     server::{self as relayer_server},
-    settings::{matching_list::MatchingList, RelayerSettings},
+    settings::{matching_list::MatchingList, OpSchedulerConf, RelayListsConf, RelayerSettings},
 };
 use crate::{
     merkle_tree::processor::{MerkleTreeProcessor, MerkleTreeProcessorMetrics},
@@ -78,14 +83,19 @@ pub struct Relayer {
     prover_syncs: HashMap<AetheriumDomain, Arc<RwLock<MerkleTreeBuilder>>>,
     merkle_tree_hook_syncs: HashMap<AetheriumDomain, Arc<dyn ContractSyncer<MerkleTreeInsertion>>>,
     dbs: HashMap<AetheriumDomain, AetheriumRocksDB>,
-    message_whitelist: Arc<MatchingList>,
-    message_blacklist: Arc<MatchingList>,
-    address_blacklist: Arc<AddressBlacklist>,
+    relay_lists: Arc<RelayLists>,
+    relay_lists_file: Option<PathBuf>,
     transaction_gas_limit: Option<U256>,
     skip_transaction_gas_limit_for: HashSet<u32>,
     allow_local_checkpoint_syncers: bool,
     metric_app_contexts: Vec<(MatchingList, String)>,
     max_retries: u32,
+    op_schedulers: HashMap<u32, OpSchedulerConf>,
+    profitability: Arc<ProfitabilityTracker>,
+    pauses: Arc<RelayPauses>,
+    ordered_lanes: Arc<OrderedLanes>,
+    admin_api_token: Option<String>,
+    dry_run: Option<Arc<DryRunRecorder>>,
     core_metrics: Arc<CoreMetrics>,
     // TODO: decide whether to consolidate `agent_metrics` and `chain_metrics` into a single struct
     // or move them in `core_metrics`, like the validator metrics
@@ -100,12 +110,10 @@ impl Debug for Relayer {
     fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
         write!(
             f,
-            "Relayer {{ origin_chains: {:?}, destination_chains: {:?}, message_whitelist: {:?}, message_blacklist: {:?}, address_blacklist: {:?}, transaction_gas_limit: {:?}, skip_transaction_gas_limit_for: {:?}, allow_local_checkpoint_syncers: {:?} }}",
+            "Relayer {{ origin_chains: {:?}, destination_chains: {:?}, relay_lists: {:?}, transaction_gas_limit: {:?}, skip_transaction_gas_limit_for: {:?}, allow_local_checkpoint_syncers: {:?} }}",
             self.origin_chains,
             self.destination_chains,
-            self.message_whitelist,
-            self.message_blacklist,
-            self.address_blacklist,
+            self.relay_lists.load(),
             self.transaction_gas_limit,
             self.skip_transaction_gas_limit_for,
             self.allow_local_checkpoint_syncers
@@ -196,16 +204,28 @@ impl BaseAgent for Relayer {
             .map(|(k, v)| (k, v as _))
             .collect();
 
-        let message_whitelist = Arc::new(settings.whitelist);
-        let message_blacklist = Arc::new(settings.blacklist);
-        let address_blacklist = Arc::new(AddressBlacklist::new(settings.address_blacklist));
+        let relay_lists_conf = RelayListsConf {
+            whitelist: settings.whitelist,
+            blacklist: settings.blacklist,
+            address_blacklist: settings.address_blacklist,
+        };
+        let relay_lists = Arc::new(
+            RelayLists::load(
+                relay_lists_conf,
+                settings.relay_lists_file.clone(),
+                db.clone(),
+            )
+            .await?,
+        );
+        let lists = relay_lists.load();
         let skip_transaction_gas_limit_for = settings.skip_transaction_gas_limit_for;
         let transaction_gas_limit = settings.transaction_gas_limit;
 
         info!(
-            %message_whitelist,
-            %message_blacklist,
-            ?address_blacklist,
+            message_whitelist=%lists.whitelist,
+            message_blacklist=%lists.blacklist,
+            address_blacklist=?lists.address_blacklist,
+            relay_lists_file=?settings.relay_lists_file,
             ?transaction_gas_limit,
             ?skip_transaction_gas_limit_for,
             "Whitelist configuration"
@@ -225,6 +245,33 @@ impl BaseAgent for Relayer {
 
         info!(gas_enforcement_policies=?settings.gas_payment_enforcement, "Gas enforcement configuration");
 
+        info!(relay_budgets=?settings.relay_budgets, "Relay budget configuration");
+        let relay_budgets = Arc::new(RelayBudgets::load(
+            settings.relay_budgets.clone(),
+            db.clone(),
+            dbs.values().cloned(),
+        )?);
+        let profitability = Arc::new(
+            ProfitabilityTracker::load(settings.profitability_price_source.as_ref(), db.clone())?
+                .with_metrics(&core_metrics)?,
+        );
+        let pauses = Arc::new(RelayPauses::load(db.clone())?.with_metrics(&core_metrics)?);
+        info!(ordered_lanes=%settings.ordered_lanes, "Ordered lanes configuration");
+        let ordered_lanes = Arc::new(
+            OrderedLanes::new(settings.ordered_lanes.clone()).with_metrics(&core_metrics)?,
+        );
+        let dry_run = if settings.dry_run {
+            warn!("Running in dry-run mode, no message will be submitted");
+            Some(Arc::new(
+                DryRunRecorder::default().with_metrics(&core_metrics)?,
+            ))
+        } else {
+            None
+        };
+        if !pauses.list().is_empty() {
+            warn!(pauses=?pauses.list(), "Relaying is paused for some operations");
+        }
+
         // need one of these per origin chain due to the database scoping even though
         // the config itself is the same
         let gas_payment_enforcers: HashMap<_, _> = settings
@@ -233,10 +280,14 @@ impl BaseAgent for Relayer {
             .map(|domain| {
                 (
                     domain.clone(),
-                    Arc::new(GasPaymentEnforcer::new(
-                        settings.gas_payment_enforcement.clone(),
-                        dbs.get(domain).unwrap().clone(),
-                    )),
+                    Arc::new(
+                        GasPaymentEnforcer::new(
+                            settings.gas_payment_enforcement.clone(),
+                            dbs.get(domain).unwrap().clone(),
+                        )
+                        .with_budgets(relay_budgets.clone())
+                        .with_profitability(profitability.clone()),
+                    ),
                 )
             })
             .collect();
@@ -256,6 +307,9 @@ impl BaseAgent for Relayer {
                 };
 
             let application_operation_verifier = application_operation_verifiers.get(destination);
+            let metadata_cache = settings
+                .metadata_cache_ttl
+                .map(|ttl| Arc::new(MetadataCache::new(ttl)));
 
             // only iterate through origin chains that were successfully instantiated
             for (origin, validator_announce) in validator_announces.iter() {
@@ -272,6 +326,7 @@ impl BaseAgent for Relayer {
                         dest_mailbox.clone(),
                         settings.metric_app_contexts.clone(),
                     ),
+                    metadata_cache.clone(),
                 );
 
                 msg_ctxs.insert(
@@ -287,6 +342,9 @@ impl BaseAgent for Relayer {
                         transaction_gas_limit,
                         metrics: MessageSubmissionMetrics::new(&core_metrics, origin, destination),
                         application_operation_verifier: application_operation_verifier.cloned(),
+                        relay_lists: relay_lists.clone(),
+                        dry_run: dry_run.clone(),
+                        ordered_lanes: ordered_lanes.clone(),
                     }),
                 );
             }
@@ -302,14 +360,19 @@ impl BaseAgent for Relayer {
             interchain_gas_payment_syncs,
             prover_syncs,
             merkle_tree_hook_syncs,
-            message_whitelist,
-            message_blacklist,
-            address_blacklist,
+            relay_lists,
+            relay_lists_file: settings.relay_lists_file,
             transaction_gas_limit,
             skip_transaction_gas_limit_for,
             allow_local_checkpoint_syncers: settings.allow_local_checkpoint_syncers,
             metric_app_contexts: settings.metric_app_contexts,
             max_retries: settings.max_retries,
+            op_schedulers: settings.op_schedulers,
+            profitability,
+            pauses,
+            ordered_lanes,
+            admin_api_token: settings.admin_api_token,
+            dry_run,
             core_metrics,
             agent_metrics,
             chain_metrics,
@@ -351,14 +414,21 @@ impl BaseAgent for Relayer {
                 receive_channel,
                 &sender,
                 SerialSubmitterMetrics::new(&self.core.metrics, dest_domain),
-                // Default to submitting one message at a time if there is no batch config
+                // Default to submitting one message at a time if there is no batch config, and
+                // always in dry-run mode, where messages can't be batched
                 self.core.settings.chains[dest_domain.name()]
                     .connection
                     .operation_batch_config()
+                    .filter(|_| self.dry_run.is_none())
                     .map(|c| c.max_batch_size)
                     .unwrap_or(1),
+                self.op_schedulers
+                    .get(&dest_domain.id())
+                    .cloned()
+                    .unwrap_or_default(),
                 task_monitor.clone(),
-            );
+            )
+            .with_pauses(self.pauses.clone());
             prep_queues.insert(dest_domain.id(), serial_submitter.prepare_queue().await);
 
             tasks.push(self.run_destination_submitter(
@@ -405,10 +475,30 @@ impl BaseAgent for Relayer {
                 .await,
             );
         }
+        let dbs_by_domain: HashMap<_, _> = self
+            .dbs
+            .iter()
+            .map(|(domain, db)| (domain.id(), db.clone()))
+            .collect();
+        let mut dead_letters = DeadLetterQueue::new(dbs_by_domain.clone());
+        let mut redrive_receivers: HashMap<_, _> = self
+            .origin_chains
+            .iter()
+            .map(|origin| (origin.id(), dead_letters.redrive_receiver(origin.id())))
+            .collect();
+
         // run server
         let custom_routes = relayer_server::Server::new(self.destination_chains.len())
             .with_op_retry(sender.clone())
             .with_message_queue(prep_queues)
+            .with_profitability(self.profitability.clone())
+            .with_pauses(self.pauses.clone())
+            .with_ordered_lanes(self.ordered_lanes.clone())
+            .with_relay_lists(self.relay_lists.clone())
+            .with_dead_letters(Arc::new(dead_letters))
+            .with_dry_run(self.dry_run.clone())
+            .with_admin_api_token(self.admin_api_token.clone())
+            .with_dbs(dbs_by_domain)
             .routes();
 
         let server = self
@@ -424,11 +514,20 @@ impl BaseAgent for Relayer {
         );
         tasks.push(server_task);
 
+        if let Some(path) = self.relay_lists_file.clone() {
+            let relay_lists = self.relay_lists.clone();
+            tasks.push(tokio::spawn(
+                async move { relay_lists.watch_file(&path).await }
+                    .instrument(info_span!("RelayListsWatcher")),
+            ));
+        }
+
         // each message process attempts to send messages from a chain
         for origin in &self.origin_chains {
             tasks.push(self.run_message_processor(
                 origin,
                 send_channels.clone(),
+                redrive_receivers.remove(&origin.id()),
                 task_monitor.clone(),
             ));
             tasks.push(self.run_merkle_tree_processor(origin, task_monitor.clone()));
@@ -593,6 +692,7 @@ impl Relayer {
         &self,
         origin: &AetheriumDomain,
         send_channels: HashMap<u32, UnboundedSender<QueueOperation>>,
+        redrive_receiver: Option<UnboundedReceiver<AetheriumMessage>>,
         task_monitor: TaskMonitor,
     ) -> JoinHandle<()> {
         let metrics = MessageProcessorMetrics::new(
@@ -615,17 +715,18 @@ impl Relayer {
             })
             .collect();
 
-        let message_processor = MessageProcessor::new(
+        let mut message_processor = MessageProcessor::new(
             self.dbs.get(origin).unwrap().clone(),
-            self.message_whitelist.clone(),
-            self.message_blacklist.clone(),
-            self.address_blacklist.clone(),
+            self.relay_lists.clone(),
             metrics,
             send_channels,
             destination_ctxs,
             self.metric_app_contexts.clone(),
             self.max_retries,
         );
+        if let Some(redrive_receiver) = redrive_receiver {
+            message_processor = message_processor.with_redrive_receiver(redrive_receiver);
+        }
 
         let span = info_span!("MessageProcessor", origin=%message_processor.domain());
         let processor = Processor::new(Box::new(message_processor), task_monitor.clone());
@@ -827,6 +928,9 @@ mod test {
                         gas_limit: None,
                         max_fee_per_gas: None,
                         max_priority_fee_per_gas: None,
+                        gas_price_escalation_percent: None,
+                        gas_price_escalation_interval_blocks: None,
+                        max_gas_price: None,
                     },
                     operation_batch: OperationBatchConfig {
                         batch_contract_address: None,
@@ -875,6 +979,14 @@ mod test {
             allow_local_checkpoint_syncers: true,
             metric_app_contexts: Vec::new(),
             max_retries: 1,
+            op_schedulers: HashMap::new(),
+            relay_budgets: vec![],
+            profitability_price_source: None,
+            admin_api_token: None,
+            relay_lists_file: None,
+            dry_run: false,
+            metadata_cache_ttl: None,
+            ordered_lanes: Default::default(),
         }
     }
 
@@ -0,0 +1,242 @@
+use std::sync::Arc;
+
+use aetherium_core::{H256, U256};
+use axum::{
+    extract::{Path, State},
+    http::{HeaderMap, StatusCode},
+    response::{IntoResponse, Response},
+    routing, Json, Router,
+};
+use derive_new::new;
+use serde::Deserialize;
+use tracing::warn;
+
+use super::admin_auth::authorize;
+use crate::msg::dead_letter::DeadLetterQueue;
+
+const DEAD_LETTERS_API_BASE: &str = "/admin/dead_letters";
+
+/// Lists, inspects, re-drives and discards the messages that exhausted their retries. Every
+/// request needs an `Authorization: Bearer <adminApiToken>` header.
+#[derive(new, Clone)]
+pub struct DeadLettersApi {
+    dead_letters: Arc<DeadLetterQueue>,
+    api_token: String,
+}
+
+#[derive(Clone, Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct RedriveRequest {
+    /// Gas limit to submit the message with instead of the estimate. It is still subject to the
+    /// `transactionGasLimit` of the destination.
+    gas_limit: Option<u64>,
+}
+
+fn not_found(id: H256) -> Response {
+    (
+        StatusCode::NOT_FOUND,
+        format!("No dead-lettered message with id {id:?}"),
+    )
+        .into_response()
+}
+
+fn internal_error(err: eyre::Report) -> Response {
+    (StatusCode::INTERNAL_SERVER_ERROR, err.to_string()).into_response()
+}
+
+async fn list_dead_letters(State(api): State<DeadLettersApi>, headers: HeaderMap) -> Response {
+    if let Err(response) = authorize(&headers, &api.api_token) {
+        return response;
+    }
+    match api.dead_letters.list() {
+        Ok(dead_letters) => Json(dead_letters).into_response(),
+        Err(err) => {
+            warn!(?err, "Failed to list dead-lettered messages");
+            internal_error(err)
+        }
+    }
+}
+
+async fn get_dead_letter(
+    State(api): State<DeadLettersApi>,
+    headers: HeaderMap,
+    Path(id): Path<H256>,
+) -> Response {
+    if let Err(response) = authorize(&headers, &api.api_token) {
+        return response;
+    }
+    match api.dead_letters.get(&id) {
+        Ok(Some(dead_lettered)) => Json(dead_lettered).into_response(),
+        Ok(None) => not_found(id),
+        Err(err) => {
+            warn!(?err, message_id = ?id, "Failed to read dead-lettered message");
+            internal_error(err)
+        }
+    }
+}
+
+async fn redrive(
+    State(api): State<DeadLettersApi>,
+    headers: HeaderMap,
+    Path(id): Path<H256>,
+    Json(request): Json<RedriveRequest>,
+) -> Response {
+    if let Err(response) = authorize(&headers, &api.api_token) {
+        return response;
+    }
+    match api
+        .dead_letters
+        .redrive(&id, request.gas_limit.map(U256::from))
+    {
+        Ok(Some(dead_lettered)) => Json(dead_lettered).into_response(),
+        Ok(None) => not_found(id),
+        Err(err) => {
+            warn!(?err, message_id = ?id, "Failed to re-drive dead-lettered message");
+            internal_error(err)
+        }
+    }
+}
+
+async fn discard(
+    State(api): State<DeadLettersApi>,
+    headers: HeaderMap,
+    Path(id): Path<H256>,
+) -> Response {
+    if let Err(response) = authorize(&headers, &api.api_token) {
+        return response;
+    }
+    match api.dead_letters.discard(&id) {
+        Ok(Some(dead_lettered)) => Json(dead_lettered).into_response(),
+        Ok(None) => not_found(id),
+        Err(err) => {
+            warn!(?err, message_id = ?id, "Failed to discard dead-lettered message");
+            internal_error(err)
+        }
+    }
+}
+
+impl DeadLettersApi {
+    pub fn router(&self) -> Router {
+        Router::new()
+            .route("/", routing::get(list_dead_letters))
+            .route("/:id", routing::get(get_dead_letter).delete(discard))
+            .route("/:id/redrive", routing::post(redrive))
+            .with_state(self.clone())
+    }
+
+    pub fn get_route(&self) -> (&'static str, Router) {
+        (DEAD_LETTERS_API_BASE, self.router())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::{collections::HashMap, net::SocketAddr};
+
+    use aetherium_base::db::{test_utils, AetheriumDb, AetheriumRocksDB};
+    use aetherium_core::{
+        AetheriumDomain, AetheriumMessage, DeadLetter, PendingOperationStatus, ReprepareReason,
+    };
+    use serde_json::{json, Value};
+    use tokio::sync::mpsc::UnboundedReceiver;
+
+    use super::*;
+
+    const API_TOKEN: &str = "secret";
+
+    fn setup_test_server(
+        db: AetheriumRocksDB,
+    ) -> (SocketAddr, UnboundedReceiver<AetheriumMessage>) {
+        let origin = db.domain().id();
+        let mut dead_letters = DeadLetterQueue::new(HashMap::from([(origin, db)]));
+        let receiver = dead_letters.redrive_receiver(origin);
+        let (path, router) =
+            DeadLettersApi::new(Arc::new(dead_letters), API_TOKEN.to_owned()).get_route();
+        let app = Router::new().nest(path, router);
+
+        let server =
+            axum::Server::bind(&"127.0.0.1:0".parse().unwrap()).serve(app.into_make_service());
+        let addr = server.local_addr();
+        tokio::spawn(server);
+        (addr, receiver)
+    }
+
+    #[tokio::test]
+    async fn test_dead_letters_api() {
+        test_utils::run_test_db(|db| async move {
+            let domain = AetheriumDomain::new_test_domain("test_dead_letters_api");
+            let db = AetheriumRocksDB::new(&domain, db);
+            let message = AetheriumMessage {
+                origin: domain.id(),
+                ..AetheriumMessage::default()
+            };
+            let id = message.id();
+            db.store_message(&message, 1).unwrap();
+            db.store_dead_letter_by_message_id(
+                &id,
+                &DeadLetter {
+                    message_id: id,
+                    destination: message.destination,
+                    status: PendingOperationStatus::Retry(ReprepareReason::ErrorEstimatingGas),
+                    retry_count: 66,
+                    last_error: Some("execution reverted".to_owned()),
+                    attempts: vec![],
+                    dead_lettered_at: 100,
+                    discarded: false,
+                },
+            )
+            .unwrap();
+            let (addr, mut receiver) = setup_test_server(db.clone());
+            let client = reqwest::Client::new();
+
+            let listed: Value = client
+                .get(format!("http://{addr}{DEAD_LETTERS_API_BASE}"))
+                .bearer_auth(API_TOKEN)
+                .send()
+                .await
+                .unwrap()
+                .json()
+                .await
+                .unwrap();
+            assert_eq!(listed[0]["lastError"], "execution reverted");
+            assert_eq!(listed[0]["origin"], domain.id());
+
+            let response = client
+                .post(format!(
+                    "http://{addr}{DEAD_LETTERS_API_BASE}/{id:?}/redrive"
+                ))
+                .bearer_auth("wrong")
+                .json(&json!({}))
+                .send()
+                .await
+                .unwrap();
+            assert_eq!(response.status(), StatusCode::UNAUTHORIZED);
+
+            let response = client
+                .post(format!(
+                    "http://{addr}{DEAD_LETTERS_API_BASE}/{id:?}/redrive"
+                ))
+                .bearer_auth(API_TOKEN)
+                .json(&json!({ "gasLimit": 300000 }))
+                .send()
+                .await
+                .unwrap();
+            assert_eq!(response.status(), StatusCode::OK);
+            assert_eq!(receiver.try_recv().unwrap(), message);
+            assert_eq!(
+                db.retrieve_pending_message_gas_limit_override_by_message_id(&id)
+                    .unwrap(),
+                Some(U256::from(300000))
+            );
+
+            let response = client
+                .delete(format!("http://{addr}{DEAD_LETTERS_API_BASE}/{id:?}"))
+                .bearer_auth(API_TOKEN)
+                .send()
+                .await
+                .unwrap();
+            assert_eq!(response.status(), StatusCode::NOT_FOUND);
+        })
+        .await;
+    }
+}
@@ -0,0 +1,121 @@
+use std::{collections::BTreeMap, sync::Arc};
+
+use axum::{
+    extract::{Query, State},
+    routing, Json, Router,
+};
+use derive_new::new;
+use serde::{Deserialize, Serialize};
+
+use crate::msg::dry_run::{DryRunOutcome, DryRunRecord, DryRunRecorder};
+
+const DRY_RUN_API_BASE: &str = "/dry_run";
+
+#[derive(Clone, Debug, Deserialize)]
+pub struct DryRunRequest {
+    /// Only return the messages with this outcome
+    outcome: Option<DryRunOutcome>,
+}
+
+#[derive(Clone, Debug, Serialize)]
+pub struct DryRunResponse {
+    summary: BTreeMap<DryRunOutcome, u64>,
+    messages: Vec<DryRunRecord>,
+}
+
+/// Serves what a relayer running in dry-run mode would have submitted, and how it compares to the
+/// deliveries observed on-chain. Filter the messages with `?outcome=deliveredWhileHeld`.
+#[derive(new, Clone)]
+pub struct DryRunApi {
+    recorder: Arc<DryRunRecorder>,
+}
+
+async fn get_dry_run(
+    State(recorder): State<Arc<DryRunRecorder>>,
+    Query(request): Query<DryRunRequest>,
+) -> Json<DryRunResponse> {
+    Json(DryRunResponse {
+        summary: recorder.summary(),
+        messages: recorder.records(request.outcome),
+    })
+}
+
+impl DryRunApi {
+    pub fn router(&self) -> Router {
+        Router::new()
+            .route("/", routing::get(get_dry_run))
+            .with_state(self.recorder.clone())
+    }
+
+    pub fn get_route(&self) -> (&'static str, Router) {
+        (DRY_RUN_API_BASE, self.router())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::net::SocketAddr;
+
+    use aetherium_core::{AetheriumMessage, U256};
+    use axum::http::StatusCode;
+
+    use super::*;
+    use crate::msg::dry_run::DryRunDecision;
+
+    fn setup_test_server() -> SocketAddr {
+        let recorder = Arc::new(DryRunRecorder::default());
+        for nonce in 0..3 {
+            let message = AetheriumMessage {
+                nonce,
+                ..AetheriumMessage::default()
+            };
+            recorder.record_decision(
+                &message,
+                Some("app"),
+                DryRunDecision::Submit {
+                    gas_limit: U256::from(100_000),
+                    ism_verify_gas: Some(U256::from(50_000)),
+                },
+            );
+            if nonce == 0 {
+                recorder.record_delivery(&message, Some("app"), ("origin", "destination"));
+            }
+        }
+
+        let (path, router) = DryRunApi::new(recorder).get_route();
+        let app = Router::new().nest(path, router);
+
+        let server =
+            axum::Server::bind(&"127.0.0.1:0".parse().unwrap()).serve(app.into_make_service());
+        let addr = server.local_addr();
+        tokio::spawn(server);
+        addr
+    }
+
+    #[tokio::test]
+    async fn test_dry_run() {
+        let addr = setup_test_server();
+
+        let response = reqwest::get(format!("http://{addr}{DRY_RUN_API_BASE}"))
+            .await
+            .unwrap();
+        assert_eq!(response.status(), StatusCode::OK);
+        let dry_run: serde_json::Value = response.json().await.unwrap();
+        assert_eq!(dry_run["summary"]["matched"], 1);
+        assert_eq!(dry_run["summary"]["wouldSubmit"], 2);
+        assert_eq!(dry_run["messages"].as_array().unwrap().len(), 3);
+
+        let dry_run: serde_json::Value =
+            reqwest::get(format!("http://{addr}{DRY_RUN_API_BASE}?outcome=matched"))
+                .await
+                .unwrap()
+                .json()
+                .await
+                .unwrap();
+        let messages = dry_run["messages"].as_array().unwrap();
+        assert_eq!(messages.len(), 1);
+        assert_eq!(messages[0]["appContext"], "app");
+        assert_eq!(messages[0]["decision"]["action"], "submit");
+        assert_eq!(messages[0]["decision"]["gasLimit"], "0x186a0");
+    }
+}
@@ -5,9 +5,11 @@ use axum::{
 use derive_new::new;
 use aetherium_core::{QueueOperation, H256};
 use serde::{Deserialize, Serialize};
-use std::collections::HashMap;
+use std::{collections::HashMap, sync::Arc};
 
-use crate::msg::op_queue::OperationPriorityQueue;
+use crate::msg::{
+    op_queue::OperationPriorityQueue, ordered_lanes::OrderedLanes, pause::RelayPauses,
+};
 
 const LIST_OPERATIONS_API_BASE: &str = "/list_operations";
 
@@ -25,35 +27,56 @@ impl PartialEq<QueueOperation> for &ListOperationsRequest {
 #[derive(new, Clone)]
 pub struct ListOperationsApi {
     op_queues: HashMap<u32, OperationPriorityQueue>,
+    /// used to flag the operations that relaying is paused for
+    #[new(default)]
+    pauses: Arc<RelayPauses>,
+    /// used to flag the operations held back by an earlier message of their lane
+    #[new(default)]
+    ordered_lanes: Arc<OrderedLanes>,
 }
 
 async fn list_operations(
-    State(queues): State<HashMap<u32, OperationPriorityQueue>>,
+    State(api): State<ListOperationsApi>,
     Query(request): Query<ListOperationsRequest>,
 ) -> String {
     let domain = request.destination_domain;
-    let Some(op_queue) = queues.get(&domain) else {
+    let Some(op_queue) = api.op_queues.get(&domain) else {
         return format!("No queue found for domain {}", domain);
     };
-    format_queue(op_queue.clone()).await
+    format_queue(op_queue.clone(), &api.pauses, &api.ordered_lanes).await
 }
 
 #[derive(Debug, Serialize)]
 struct OperationWithId<'a> {
     id: H256,
     operation: &'a QueueOperation,
+    #[serde(skip_serializing_if = "std::ops::Not::not")]
+    paused: bool,
+    /// Id of the earlier message of the lane that the operation waits for
+    #[serde(skip_serializing_if = "Option::is_none")]
+    blocked_by: Option<H256>,
 }
 
 impl<'a> OperationWithId<'a> {
-    fn new(operation: &'a QueueOperation) -> Self {
+    fn new(
+        operation: &'a QueueOperation,
+        pauses: &RelayPauses,
+        ordered_lanes: &OrderedLanes,
+    ) -> Self {
         Self {
             id: operation.id(),
             operation,
+            paused: pauses.is_paused(operation),
+            blocked_by: ordered_lanes.blocked_by(&operation.id()),
         }
     }
 }
 
-pub async fn format_queue(queue: OperationPriorityQueue) -> String {
+pub async fn format_queue(
+    queue: OperationPriorityQueue,
+    pauses: &RelayPauses,
+    ordered_lanes: &OrderedLanes,
+) -> String {
     let mut sorted_operations: Vec<_> = queue
         .lock()
         .await
@@ -61,7 +84,7 @@ pub async fn format_queue(queue: OperationPriorityQueue) -> String {
         .map(|reverse| {
             (
                 reverse.0.get_retries(),
-                serde_json::to_value(OperationWithId::new(&reverse.0)),
+                serde_json::to_value(OperationWithId::new(&reverse.0, pauses, ordered_lanes)),
             )
         })
         .collect();
@@ -84,10 +107,20 @@ pub async fn format_queue(queue: OperationPriorityQueue) -> String {
 }
 
 impl ListOperationsApi {
+    pub fn with_pauses(mut self, pauses: Arc<RelayPauses>) -> Self {
+        self.pauses = pauses;
+        self
+    }
+
+    pub fn with_ordered_lanes(mut self, ordered_lanes: Arc<OrderedLanes>) -> Self {
+        self.ordered_lanes = ordered_lanes;
+        self
+    }
+
     pub fn router(&self) -> Router {
         Router::new()
             .route("/", routing::get(list_operations))
-            .with_state(self.op_queues.clone())
+            .with_state(self.clone())
     }
 
     pub fn get_route(&self) -> (&'static str, Router) {
@@ -103,9 +136,12 @@ mod tests {
 
     use aetherium_core::KnownAetheriumDomain;
 
-    use crate::msg::op_queue::{
-        test::{dummy_metrics_and_label, MockPendingOperation},
-        OpQueue,
+    use crate::msg::{
+        op_queue::{
+            test::{dummy_metrics_and_label, MockPendingOperation},
+            OpQueue,
+        },
+        pause::PauseSelector,
     };
 
     use super::*;
@@ -119,6 +155,13 @@ mod tests {
         "0x586d41b02fb35df0f84ecb2b73e076b40c929ee3e1ceeada9a078aa7b46d3b08";
 
     fn setup_test_server() -> (SocketAddr, OperationPriorityQueue) {
+        setup_test_server_with_pauses(Default::default(), Default::default())
+    }
+
+    fn setup_test_server_with_pauses(
+        pauses: Arc<RelayPauses>,
+        ordered_lanes: Arc<OrderedLanes>,
+    ) -> (SocketAddr, OperationPriorityQueue) {
         let (metrics, queue_metrics_label) = dummy_metrics_and_label();
         let broadcaster = sync::broadcast::Sender::new(100);
         let op_queue = OpQueue::new(
@@ -129,7 +172,9 @@ mod tests {
         let mut op_queues_map = HashMap::new();
         op_queues_map.insert(DUMMY_DOMAIN as u32, op_queue.queue.clone());
 
-        let list_operations_api = ListOperationsApi::new(op_queues_map);
+        let list_operations_api = ListOperationsApi::new(op_queues_map)
+            .with_pauses(pauses)
+            .with_ordered_lanes(ordered_lanes);
         let (path, router) = list_operations_api.get_route();
 
         let app = Router::new().nest(path, router);
@@ -290,4 +335,75 @@ mod tests {
         let response_text = response.text().await.unwrap();
         assert_eq!(response_text, expected_response);
     }
+
+    #[tokio::test]
+    async fn test_paused_operations_are_flagged() {
+        let pauses = Arc::new(RelayPauses::default());
+        pauses
+            .pause(
+                PauseSelector {
+                    matching_list: Some(serde_json::json!([{ "messageid": MESSAGE_ID_1 }])),
+                    app_context: None,
+                },
+                None,
+            )
+            .unwrap();
+        let (addr, op_queue) = setup_test_server_with_pauses(pauses, Default::default());
+        op_queue
+            .lock()
+            .await
+            .push(Reverse(generate_dummy_operation_1(0)));
+        op_queue
+            .lock()
+            .await
+            .push(Reverse(generate_dummy_operation_2(1)));
+
+        let response = reqwest::get(format!(
+            "http://{}{}?destination_domain={}",
+            addr, LIST_OPERATIONS_API_BASE, DUMMY_DOMAIN as u32
+        ))
+        .await
+        .unwrap();
+        assert_eq!(response.status(), StatusCode::OK);
+
+        let operations: serde_json::Value =
+            serde_json::from_str(&response.text().await.unwrap()).unwrap();
+        assert_eq!(operations[0]["id"], MESSAGE_ID_1);
+        assert_eq!(operations[0]["paused"], true);
+        assert_eq!(operations[1].get("paused"), None);
+    }
+
+    #[tokio::test]
+    async fn test_blocked_operations_are_flagged() {
+        let ordered_lanes = Arc::new(OrderedLanes::default());
+        let message_id_2: H256 = MESSAGE_ID_2.parse().unwrap();
+        ordered_lanes.hold(
+            message_id_2,
+            Some(MESSAGE_ID_1.parse().unwrap()),
+            ("origin", "destination"),
+        );
+        let (addr, op_queue) = setup_test_server_with_pauses(Default::default(), ordered_lanes);
+        op_queue
+            .lock()
+            .await
+            .push(Reverse(generate_dummy_operation_1(0)));
+        op_queue
+            .lock()
+            .await
+            .push(Reverse(generate_dummy_operation_2(1)));
+
+        let response = reqwest::get(format!(
+            "http://{}{}?destination_domain={}",
+            addr, LIST_OPERATIONS_API_BASE, DUMMY_DOMAIN as u32
+        ))
+        .await
+        .unwrap();
+        assert_eq!(response.status(), StatusCode::OK);
+
+        let operations: serde_json::Value =
+            serde_json::from_str(&response.text().await.unwrap()).unwrap();
+        assert_eq!(operations[0].get("blocked_by"), None);
+        assert_eq!(operations[1]["id"], MESSAGE_ID_2);
+        assert_eq!(operations[1]["blocked_by"], MESSAGE_ID_1);
+    }
 }
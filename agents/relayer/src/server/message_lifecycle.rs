@@ -0,0 +1,193 @@
+use std::{collections::HashMap, sync::Arc};
+
+use aetherium_base::db::{AetheriumDb, AetheriumRocksDB, DbResult};
+use aetherium_core::{AetheriumMessage, GasPaymentKey, PendingOperationStatus, H256, U256};
+use axum::{
+    extract::{Path, State},
+    http::StatusCode,
+    response::{IntoResponse, Response},
+    routing, Json, Router,
+};
+use derive_new::new;
+use serde::Serialize;
+use tracing::warn;
+
+const MESSAGE_LIFECYCLE_API_BASE: &str = "/messages";
+
+/// Everything the relayer has recorded about a message, from its dispatch to its delivery
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct MessageLifecycle {
+    pub id: H256,
+    pub message: AetheriumMessage,
+    pub dispatched_block_number: Option<u64>,
+    pub merkle_leaf_index: Option<u32>,
+    pub gas_payment: Option<GasPaymentInfo>,
+    pub gas_expenditure: GasExpenditureInfo,
+    /// The status of the message in the relayer queues, if it has been queued
+    pub status: Option<PendingOperationStatus>,
+    pub retry_count: u32,
+    /// The error that caused the last attempt to prepare the message to fail
+    pub last_error: Option<String>,
+    pub processed: bool,
+}
+
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct GasPaymentInfo {
+    pub payment: U256,
+    pub gas_amount: U256,
+}
+
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct GasExpenditureInfo {
+    pub gas_used: U256,
+    pub tokens_used: U256,
+}
+
+/// Looks a message up by id in the databases of every origin and returns its lifecycle
+#[derive(new, Clone)]
+pub struct MessageLifecycleApi {
+    dbs: Arc<HashMap<u32, AetheriumRocksDB>>,
+}
+
+async fn get_message_lifecycle(
+    State(dbs): State<Arc<HashMap<u32, AetheriumRocksDB>>>,
+    Path(id): Path<H256>,
+) -> Response {
+    for db in dbs.values() {
+        match message_lifecycle(db, id) {
+            Ok(Some(lifecycle)) => return Json(lifecycle).into_response(),
+            Ok(None) => {}
+            Err(err) => {
+                warn!(?err, message_id = ?id, domain = ?db.domain(), "Failed to read message lifecycle");
+                return (StatusCode::INTERNAL_SERVER_ERROR, err.to_string()).into_response();
+            }
+        }
+    }
+    (StatusCode::NOT_FOUND, format!("Message {id:?} not found")).into_response()
+}
+
+fn message_lifecycle(db: &AetheriumRocksDB, id: H256) -> DbResult<Option<MessageLifecycle>> {
+    let Some(message) = db.retrieve_message_by_id(&id)? else {
+        return Ok(None);
+    };
+    let gas_payment = db
+        .retrieve_gas_payment_by_gas_payment_key(GasPaymentKey {
+            message_id: id,
+            destination: message.destination,
+        })?
+        .map(|payment| GasPaymentInfo {
+            payment: payment.payment,
+            gas_amount: payment.gas_amount,
+        });
+    let expenditure = db.retrieve_gas_expenditure_by_message_id(id)?;
+    Ok(Some(MessageLifecycle {
+        id,
+        dispatched_block_number: db.retrieve_dispatched_block_number_by_nonce(&message.nonce)?,
+        merkle_leaf_index: db.retrieve_merkle_leaf_index_by_message_id(&id)?,
+        gas_payment,
+        gas_expenditure: GasExpenditureInfo {
+            gas_used: expenditure.gas_used,
+            tokens_used: expenditure.tokens_used,
+        },
+        status: db.retrieve_status_by_message_id(&id)?,
+        retry_count: db
+            .retrieve_pending_message_retry_count_by_message_id(&id)?
+            .unwrap_or_default(),
+        last_error: db.retrieve_pending_message_last_error_by_message_id(&id)?,
+        processed: db
+            .retrieve_processed_by_nonce(&message.nonce)?
+            .unwrap_or_default(),
+        message,
+    }))
+}
+
+impl MessageLifecycleApi {
+    pub fn router(&self) -> Router {
+        Router::new()
+            .route("/:id", routing::get(get_message_lifecycle))
+            .with_state(self.dbs.clone())
+    }
+
+    pub fn get_route(&self) -> (&'static str, Router) {
+        (MESSAGE_LIFECYCLE_API_BASE, self.router())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::net::SocketAddr;
+
+    use aetherium_base::db::test_utils;
+    use aetherium_core::{AetheriumDomain, InterchainGasPayment, LogMeta};
+
+    use super::*;
+
+    fn setup_test_server(db: AetheriumRocksDB) -> SocketAddr {
+        let dbs = HashMap::from([(db.domain().id(), db)]);
+        let (path, router) = MessageLifecycleApi::new(Arc::new(dbs)).get_route();
+        let app = Router::new().nest(path, router);
+
+        let server =
+            axum::Server::bind(&"127.0.0.1:0".parse().unwrap()).serve(app.into_make_service());
+        let addr = server.local_addr();
+        tokio::spawn(server);
+        addr
+    }
+
+    #[tokio::test]
+    async fn test_message_lifecycle() {
+        test_utils::run_test_db(|db| async move {
+            let domain = AetheriumDomain::new_test_domain("test_message_lifecycle");
+            let db = AetheriumRocksDB::new(&domain, db);
+            let message = AetheriumMessage {
+                nonce: 7,
+                origin: domain.id(),
+                destination: 2,
+                ..AetheriumMessage::default()
+            };
+            let id = message.id();
+            db.store_message(&message, 100).unwrap();
+            db.process_gas_payment(
+                InterchainGasPayment {
+                    message_id: id,
+                    destination: 2,
+                    payment: U256::from(1000u32),
+                    gas_amount: U256::from(50u32),
+                },
+                &LogMeta::random(),
+            )
+            .unwrap();
+            db.store_pending_message_retry_count_by_message_id(&id, &3)
+                .unwrap();
+            db.store_pending_message_last_error_by_message_id(&id, "Error building metadata")
+                .unwrap();
+            let addr = setup_test_server(db);
+
+            let response =
+                reqwest::get(format!("http://{addr}{MESSAGE_LIFECYCLE_API_BASE}/{id:?}"))
+                    .await
+                    .unwrap();
+            assert_eq!(response.status(), StatusCode::OK);
+
+            let lifecycle: serde_json::Value = response.json().await.unwrap();
+            assert_eq!(lifecycle["message"]["nonce"], 7);
+            assert_eq!(lifecycle["dispatchedBlockNumber"], 100);
+            assert_eq!(lifecycle["gasPayment"]["payment"], "0x3e8");
+            assert_eq!(lifecycle["retryCount"], 3);
+            assert_eq!(lifecycle["lastError"], "Error building metadata");
+            assert_eq!(lifecycle["processed"], false);
+
+            let response = reqwest::get(format!(
+                "http://{addr}{MESSAGE_LIFECYCLE_API_BASE}/{:?}",
+                H256::repeat_byte(1)
+            ))
+            .await
+            .unwrap();
+            assert_eq!(response.status(), StatusCode::NOT_FOUND);
+        })
+        .await;
+    }
+}
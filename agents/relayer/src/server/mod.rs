@@ -1,17 +1,35 @@
+use aetherium_base::db::AetheriumRocksDB;
 use axum::Router;
 use derive_new::new;
-use std::collections::HashMap;
+use std::{collections::HashMap, sync::Arc};
 use tokio::sync::broadcast::Sender;
 
-use crate::msg::op_queue::OperationPriorityQueue;
+use crate::msg::{
+    dead_letter::DeadLetterQueue, dry_run::DryRunRecorder, gas_payment::ProfitabilityTracker,
+    op_queue::OperationPriorityQueue, ordered_lanes::OrderedLanes, pause::RelayPauses,
+    relay_lists::RelayLists,
+};
 
 pub const ENDPOINT_MESSAGES_QUEUE_SIZE: usize = 100;
 
+pub use dead_letters::*;
+pub use dry_run::*;
 pub use list_messages::*;
+pub use message_lifecycle::*;
 pub use message_retry::*;
+pub use profitability::*;
+pub use relay_lists::*;
+pub use relay_pause::*;
 
+mod admin_auth;
+mod dead_letters;
+mod dry_run;
 mod list_messages;
+mod message_lifecycle;
 mod message_retry;
+mod profitability;
+mod relay_lists;
+mod relay_pause;
 
 #[derive(new)]
 pub struct Server {
@@ -20,6 +38,22 @@ pub struct Server {
     retry_transmitter: Option<Sender<MessageRetryRequest>>,
     #[new(default)]
     op_queues: Option<HashMap<u32, OperationPriorityQueue>>,
+    #[new(default)]
+    profitability: Option<Arc<ProfitabilityTracker>>,
+    #[new(default)]
+    dbs: Option<HashMap<u32, AetheriumRocksDB>>,
+    #[new(default)]
+    pauses: Option<Arc<RelayPauses>>,
+    #[new(default)]
+    ordered_lanes: Option<Arc<OrderedLanes>>,
+    #[new(default)]
+    relay_lists: Option<Arc<RelayLists>>,
+    #[new(default)]
+    dead_letters: Option<Arc<DeadLetterQueue>>,
+    #[new(default)]
+    dry_run: Option<Arc<DryRunRecorder>>,
+    #[new(default)]
+    admin_api_token: Option<String>,
 }
 
 impl Server {
@@ -33,6 +67,49 @@ impl Server {
         self
     }
 
+    pub fn with_profitability(mut self, profitability: Arc<ProfitabilityTracker>) -> Self {
+        self.profitability = Some(profitability);
+        self
+    }
+
+    pub fn with_dbs(mut self, dbs: HashMap<u32, AetheriumRocksDB>) -> Self {
+        self.dbs = Some(dbs);
+        self
+    }
+
+    pub fn with_pauses(mut self, pauses: Arc<RelayPauses>) -> Self {
+        self.pauses = Some(pauses);
+        self
+    }
+
+    pub fn with_ordered_lanes(mut self, ordered_lanes: Arc<OrderedLanes>) -> Self {
+        self.ordered_lanes = Some(ordered_lanes);
+        self
+    }
+
+    pub fn with_relay_lists(mut self, relay_lists: Arc<RelayLists>) -> Self {
+        self.relay_lists = Some(relay_lists);
+        self
+    }
+
+    pub fn with_dead_letters(mut self, dead_letters: Arc<DeadLetterQueue>) -> Self {
+        self.dead_letters = Some(dead_letters);
+        self
+    }
+
+    /// What the relayer would have submitted is only served if it runs in dry-run mode
+    pub fn with_dry_run(mut self, dry_run: Option<Arc<DryRunRecorder>>) -> Self {
+        self.dry_run = dry_run;
+        self
+    }
+
+    /// The admin routes, to pause relaying, replace the relay lists and manage the dead-letter
+    /// queue, are only served if `admin_api_token` is set
+    pub fn with_admin_api_token(mut self, admin_api_token: Option<String>) -> Self {
+        self.admin_api_token = admin_api_token;
+        self
+    }
+
     /// Returns a vector of agent-specific endpoint routes to be served.
     /// Can be extended with additional routes and feature flags to enable/disable individually.
     pub fn routes(self) -> Vec<(&'static str, Router)> {
@@ -41,7 +118,30 @@ impl Server {
             routes.push(MessageRetryApi::new(tx, self.destination_chains).get_route());
         }
         if let Some(op_queues) = self.op_queues {
-            routes.push(ListOperationsApi::new(op_queues).get_route());
+            let list_operations_api = ListOperationsApi::new(op_queues)
+                .with_pauses(self.pauses.clone().unwrap_or_default())
+                .with_ordered_lanes(self.ordered_lanes.clone().unwrap_or_default());
+            routes.push(list_operations_api.get_route());
+        }
+        if let Some(profitability) = self.profitability {
+            routes.push(ProfitabilityApi::new(profitability).get_route());
+        }
+        if let Some(dry_run) = self.dry_run {
+            routes.push(DryRunApi::new(dry_run).get_route());
+        }
+        if let Some(dbs) = self.dbs {
+            routes.push(MessageLifecycleApi::new(Arc::new(dbs)).get_route());
+        }
+        if let Some(api_token) = self.admin_api_token {
+            if let Some(pauses) = self.pauses {
+                routes.push(RelayPauseApi::new(pauses, api_token.clone()).get_route());
+            }
+            if let Some(relay_lists) = self.relay_lists {
+                routes.push(RelayListsApi::new(relay_lists, api_token.clone()).get_route());
+            }
+            if let Some(dead_letters) = self.dead_letters {
+                routes.push(DeadLettersApi::new(dead_letters, api_token).get_route());
+            }
         }
 
         routes
@@ -0,0 +1,136 @@
+use std::sync::Arc;
+
+use axum::{
+    extract::{Query, State},
+    http::header,
+    response::{IntoResponse, Response},
+    routing, Json, Router,
+};
+use derive_new::new;
+use serde::Deserialize;
+
+use crate::msg::gas_payment::ProfitabilityTracker;
+
+const PROFITABILITY_API_BASE: &str = "/profitability";
+
+#[derive(Clone, Debug, Default, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum ProfitabilityFormat {
+    #[default]
+    Json,
+    Csv,
+}
+
+#[derive(Clone, Debug, Deserialize)]
+pub struct ProfitabilityRequest {
+    #[serde(default)]
+    format: ProfitabilityFormat,
+}
+
+/// Serves the revenue, cost and margin of delivered messages by app context, origin and
+/// destination, as JSON or as CSV with `?format=csv`
+#[derive(new, Clone)]
+pub struct ProfitabilityApi {
+    tracker: Arc<ProfitabilityTracker>,
+}
+
+async fn get_profitability(
+    State(tracker): State<Arc<ProfitabilityTracker>>,
+    Query(request): Query<ProfitabilityRequest>,
+) -> Response {
+    match request.format {
+        ProfitabilityFormat::Json => Json(tracker.lanes()).into_response(),
+        ProfitabilityFormat::Csv => (
+            [
+                (header::CONTENT_TYPE, "text/csv"),
+                (
+                    header::CONTENT_DISPOSITION,
+                    "attachment; filename=\"profitability.csv\"",
+                ),
+            ],
+            tracker.to_csv(),
+        )
+            .into_response(),
+    }
+}
+
+impl ProfitabilityApi {
+    pub fn router(&self) -> Router {
+        Router::new()
+            .route("/", routing::get(get_profitability))
+            .with_state(self.tracker.clone())
+    }
+
+    pub fn get_route(&self) -> (&'static str, Router) {
+        (PROFITABILITY_API_BASE, self.router())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::net::SocketAddr;
+
+    use aetherium_core::U256;
+    use axum::http::StatusCode;
+
+    use super::*;
+
+    async fn setup_test_server() -> SocketAddr {
+        let tracker = Arc::new(ProfitabilityTracker::default());
+        tracker
+            .record_delivery(
+                Some("app"),
+                ("origin", 1),
+                ("destination", 2),
+                U256::from(100u32),
+                U256::from(40u32),
+            )
+            .await;
+
+        let (path, router) = ProfitabilityApi::new(tracker).get_route();
+        let app = Router::new().nest(path, router);
+
+        let server =
+            axum::Server::bind(&"127.0.0.1:0".parse().unwrap()).serve(app.into_make_service());
+        let addr = server.local_addr();
+        tokio::spawn(server);
+        addr
+    }
+
+    #[tokio::test]
+    async fn test_profitability_json() {
+        let addr = setup_test_server().await;
+
+        let response = reqwest::get(format!("http://{addr}{PROFITABILITY_API_BASE}"))
+            .await
+            .unwrap();
+        assert_eq!(response.status(), StatusCode::OK);
+
+        let lanes: serde_json::Value = response.json().await.unwrap();
+        assert_eq!(lanes[0]["appContext"], "app");
+        assert_eq!(lanes[0]["messages"], 1);
+        assert_eq!(lanes[0]["unpricedMessages"], 1);
+    }
+
+    #[tokio::test]
+    async fn test_profitability_csv() {
+        let addr = setup_test_server().await;
+
+        let response = reqwest::get(format!("http://{addr}{PROFITABILITY_API_BASE}?format=csv"))
+            .await
+            .unwrap();
+        assert_eq!(response.status(), StatusCode::OK);
+        assert_eq!(
+            response.headers()[header::CONTENT_TYPE.as_str()],
+            "text/csv"
+        );
+
+        let csv = response.text().await.unwrap();
+        let mut lines = csv.lines();
+        assert!(lines.next().unwrap().starts_with("app_context,"));
+        assert!(lines
+            .next()
+            .unwrap()
+            .starts_with("app,origin,destination,1,100,40,"));
+    }
+}
@@ -0,0 +1,176 @@
+use std::sync::Arc;
+
+use axum::{
+    extract::State,
+    http::{HeaderMap, StatusCode},
+    response::{IntoResponse, Response},
+    routing, Json, Router,
+};
+use derive_new::new;
+use ethers::utils::hex;
+use serde::Serialize;
+use serde_json::Value;
+use tracing::warn;
+
+use super::admin_auth::authorize;
+use crate::{
+    msg::relay_lists::{ActiveRelayLists, RelayLists},
+    settings::RelayListsConf,
+};
+
+const RELAY_LISTS_API_BASE: &str = "/admin/relay_lists";
+
+/// Shows and replaces the whitelist, blacklist and address blacklist of the relayer. Every
+/// request needs an `Authorization: Bearer <adminApiToken>` header.
+#[derive(new, Clone)]
+pub struct RelayListsApi {
+    relay_lists: Arc<RelayLists>,
+    api_token: String,
+}
+
+/// The current relay lists, as returned by the admin API
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct RelayListsView {
+    pub whitelist: String,
+    pub blacklist: String,
+    pub address_blacklist: Vec<String>,
+}
+
+impl From<&ActiveRelayLists> for RelayListsView {
+    fn from(lists: &ActiveRelayLists) -> Self {
+        Self {
+            whitelist: lists.whitelist.to_string(),
+            blacklist: lists.blacklist.to_string(),
+            address_blacklist: lists
+                .address_blacklist
+                .blacklist
+                .iter()
+                .map(|address| format!("0x{}", hex::encode(address)))
+                .collect(),
+        }
+    }
+}
+
+async fn get_relay_lists(State(api): State<RelayListsApi>, headers: HeaderMap) -> Response {
+    if let Err(response) = authorize(&headers, &api.api_token) {
+        return response;
+    }
+    Json(RelayListsView::from(&*api.relay_lists.load())).into_response()
+}
+
+/// Replaces all the lists; lists missing from the request are cleared. The new lists are
+/// persisted before being applied, so they survive a restart.
+async fn replace_relay_lists(
+    State(api): State<RelayListsApi>,
+    headers: HeaderMap,
+    Json(request): Json<Value>,
+) -> Response {
+    if let Err(response) = authorize(&headers, &api.api_token) {
+        return response;
+    }
+    let conf = match RelayListsConf::from_json(request.clone()) {
+        Ok(conf) => conf,
+        Err(err) => {
+            warn!(%err, "Rejected invalid relay lists");
+            return (StatusCode::BAD_REQUEST, err.to_string()).into_response();
+        }
+    };
+    if let Err(err) = api.relay_lists.persist(&request).await {
+        warn!(?err, "Failed to persist relay lists");
+        return (StatusCode::INTERNAL_SERVER_ERROR, err.to_string()).into_response();
+    }
+    api.relay_lists.replace(conf, "admin API");
+    Json(RelayListsView::from(&*api.relay_lists.load())).into_response()
+}
+
+impl RelayListsApi {
+    pub fn router(&self) -> Router {
+        Router::new()
+            .route("/", routing::get(get_relay_lists).put(replace_relay_lists))
+            .with_state(self.clone())
+    }
+
+    pub fn get_route(&self) -> (&'static str, Router) {
+        (RELAY_LISTS_API_BASE, self.router())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::net::SocketAddr;
+
+    use aetherium_core::AetheriumMessage;
+    use serde_json::json;
+
+    use super::*;
+
+    const API_TOKEN: &str = "secret";
+
+    fn setup_test_server() -> (SocketAddr, Arc<RelayLists>) {
+        let relay_lists = Arc::new(RelayLists::default());
+        let (path, router) =
+            RelayListsApi::new(relay_lists.clone(), API_TOKEN.to_owned()).get_route();
+        let app = Router::new().nest(path, router);
+
+        let server =
+            axum::Server::bind(&"127.0.0.1:0".parse().unwrap()).serve(app.into_make_service());
+        let addr = server.local_addr();
+        tokio::spawn(server);
+        (addr, relay_lists)
+    }
+
+    #[tokio::test]
+    async fn test_replace_relay_lists() {
+        let (addr, relay_lists) = setup_test_server();
+        let client = reqwest::Client::new();
+        let message = AetheriumMessage {
+            destination: 3,
+            ..AetheriumMessage::default()
+        };
+
+        let response = client
+            .put(format!("http://{addr}{RELAY_LISTS_API_BASE}"))
+            .bearer_auth(API_TOKEN)
+            .json(&json!({
+                "blacklist": [{"destinationDomain": 3}],
+                "addressBlacklist": "0x1234",
+            }))
+            .send()
+            .await
+            .unwrap();
+        assert_eq!(response.status(), StatusCode::OK);
+        assert!(relay_lists.load().blocks(&message));
+
+        let lists: Value = client
+            .get(format!("http://{addr}{RELAY_LISTS_API_BASE}"))
+            .bearer_auth(API_TOKEN)
+            .send()
+            .await
+            .unwrap()
+            .json()
+            .await
+            .unwrap();
+        assert_eq!(lists["addressBlacklist"], json!(["0x1234"]));
+
+        let response = client
+            .put(format!("http://{addr}{RELAY_LISTS_API_BASE}"))
+            .bearer_auth(API_TOKEN)
+            .json(&json!({ "addressBlacklist": "not hex" }))
+            .send()
+            .await
+            .unwrap();
+        assert_eq!(response.status(), StatusCode::BAD_REQUEST);
+        assert!(relay_lists.load().blocks(&message));
+
+        let response = client
+            .put(format!("http://{addr}{RELAY_LISTS_API_BASE}"))
+            .bearer_auth("wrong")
+            .json(&json!({}))
+            .send()
+            .await
+            .unwrap();
+        assert_eq!(response.status(), StatusCode::UNAUTHORIZED);
+        assert!(relay_lists.load().blocks(&message));
+    }
+}
@@ -0,0 +1,186 @@
+use std::sync::Arc;
+
+use axum::{
+    extract::{Path, State},
+    http::{HeaderMap, StatusCode},
+    response::{IntoResponse, Response},
+    routing, Json, Router,
+};
+use derive_new::new;
+use serde::Deserialize;
+use tracing::{info, warn};
+
+use super::admin_auth::authorize;
+use crate::msg::pause::{PauseSelector, RelayPauses};
+
+const RELAY_PAUSE_API_BASE: &str = "/admin/pauses";
+
+/// Lists, creates and lifts pauses on relaying. Every request needs an
+/// `Authorization: Bearer <adminApiToken>` header.
+#[derive(new, Clone)]
+pub struct RelayPauseApi {
+    pauses: Arc<RelayPauses>,
+    api_token: String,
+}
+
+#[derive(Clone, Debug, Deserialize)]
+pub struct PauseRequest {
+    #[serde(flatten)]
+    selector: PauseSelector,
+    reason: Option<String>,
+}
+
+async fn list_pauses(State(api): State<RelayPauseApi>, headers: HeaderMap) -> Response {
+    if let Err(response) = authorize(&headers, &api.api_token) {
+        return response;
+    }
+    Json(api.pauses.list()).into_response()
+}
+
+async fn pause(
+    State(api): State<RelayPauseApi>,
+    headers: HeaderMap,
+    Json(request): Json<PauseRequest>,
+) -> Response {
+    if let Err(response) = authorize(&headers, &api.api_token) {
+        return response;
+    }
+    match api.pauses.pause(request.selector, request.reason) {
+        Ok(pause) => {
+            info!(?pause, "Paused relaying");
+            Json(pause).into_response()
+        }
+        Err(err) => {
+            warn!(?err, "Failed to pause relaying");
+            (StatusCode::BAD_REQUEST, err.to_string()).into_response()
+        }
+    }
+}
+
+async fn resume(
+    State(api): State<RelayPauseApi>,
+    headers: HeaderMap,
+    Path(id): Path<String>,
+) -> Response {
+    if let Err(response) = authorize(&headers, &api.api_token) {
+        return response;
+    }
+    match api.pauses.resume(&id) {
+        Ok(Some(pause)) => {
+            info!(?pause, "Resumed relaying");
+            Json(pause).into_response()
+        }
+        Ok(None) => (StatusCode::NOT_FOUND, format!("No pause with id {id}")).into_response(),
+        Err(err) => {
+            warn!(?err, id, "Failed to resume relaying");
+            (StatusCode::INTERNAL_SERVER_ERROR, err.to_string()).into_response()
+        }
+    }
+}
+
+impl RelayPauseApi {
+    pub fn router(&self) -> Router {
+        Router::new()
+            .route("/", routing::get(list_pauses).post(pause))
+            .route("/:id", routing::delete(resume))
+            .with_state(self.clone())
+    }
+
+    pub fn get_route(&self) -> (&'static str, Router) {
+        (RELAY_PAUSE_API_BASE, self.router())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::net::SocketAddr;
+
+    use serde_json::{json, Value};
+
+    use crate::msg::pause::RelayPause;
+
+    use super::*;
+
+    const API_TOKEN: &str = "secret";
+
+    fn setup_test_server() -> (SocketAddr, Arc<RelayPauses>) {
+        let pauses = Arc::new(RelayPauses::default());
+        let (path, router) = RelayPauseApi::new(pauses.clone(), API_TOKEN.to_owned()).get_route();
+        let app = Router::new().nest(path, router);
+
+        let server =
+            axum::Server::bind(&"127.0.0.1:0".parse().unwrap()).serve(app.into_make_service());
+        let addr = server.local_addr();
+        tokio::spawn(server);
+        (addr, pauses)
+    }
+
+    #[tokio::test]
+    async fn test_pause_and_resume() {
+        let (addr, pauses) = setup_test_server();
+        let client = reqwest::Client::new();
+
+        let response = client
+            .post(format!("http://{addr}{RELAY_PAUSE_API_BASE}"))
+            .bearer_auth(API_TOKEN)
+            .json(&json!({ "appContext": "app", "reason": "maintenance" }))
+            .send()
+            .await
+            .unwrap();
+        assert_eq!(response.status(), StatusCode::OK);
+        let pause: RelayPause = response.json().await.unwrap();
+        assert_eq!(pause.selector.app_context.as_deref(), Some("app"));
+        assert_eq!(pauses.list(), vec![pause.clone()]);
+
+        let listed: Value = client
+            .get(format!("http://{addr}{RELAY_PAUSE_API_BASE}"))
+            .bearer_auth(API_TOKEN)
+            .send()
+            .await
+            .unwrap()
+            .json()
+            .await
+            .unwrap();
+        assert_eq!(listed[0]["reason"], "maintenance");
+
+        let response = client
+            .delete(format!("http://{addr}{RELAY_PAUSE_API_BASE}/{}", pause.id))
+            .bearer_auth(API_TOKEN)
+            .send()
+            .await
+            .unwrap();
+        assert_eq!(response.status(), StatusCode::OK);
+        assert!(pauses.list().is_empty());
+
+        let response = client
+            .delete(format!("http://{addr}{RELAY_PAUSE_API_BASE}/{}", pause.id))
+            .bearer_auth(API_TOKEN)
+            .send()
+            .await
+            .unwrap();
+        assert_eq!(response.status(), StatusCode::NOT_FOUND);
+    }
+
+    #[tokio::test]
+    async fn test_requires_api_token() {
+        let (addr, pauses) = setup_test_server();
+        let client = reqwest::Client::new();
+
+        let response = client
+            .post(format!("http://{addr}{RELAY_PAUSE_API_BASE}"))
+            .bearer_auth("wrong")
+            .json(&json!({ "appContext": "app" }))
+            .send()
+            .await
+            .unwrap();
+        assert_eq!(response.status(), StatusCode::UNAUTHORIZED);
+
+        let response = client
+            .get(format!("http://{addr}{RELAY_PAUSE_API_BASE}"))
+            .send()
+            .await
+            .unwrap();
+        assert_eq!(response.status(), StatusCode::UNAUTHORIZED);
+        assert!(pauses.list().is_empty());
+    }
+}
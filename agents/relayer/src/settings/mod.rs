@@ -4,7 +4,11 @@
 //! and validations it defines are not applied here, we should mirror them.
 //! ANY CHANGES HERE NEED TO BE REFLECTED IN THE TYPESCRIPT SDK.
 
-use std::{collections::HashSet, path::PathBuf};
+use std::{
+    collections::{HashMap, HashSet},
+    path::PathBuf,
+    time::Duration,
+};
 
 use convert_case::Case;
 use derive_more::{AsMut, AsRef, Deref, DerefMut};
@@ -17,8 +21,9 @@ use aetherium_base::{
         Settings,
     },
 };
-use aetherium_core::{cfg_unwrap_all, config::*, AetheriumDomain, U256};
+use aetherium_core::{cfg_unwrap_all, config::*, AetheriumDomain, FixedPointNumber, U256};
 use itertools::Itertools;
+use reqwest::Url;
 use serde::Deserialize;
 use serde_json::Value;
 
@@ -65,6 +70,75 @@ pub struct RelayerSettings {
     pub metric_app_contexts: Vec<(MatchingList, String)>,
     /// Maximum number of retries per operation
     pub max_retries: u32,
+    /// Operation schedulers by destination domain id. Destinations without one use
+    /// `OpSchedulerConf::Priority`.
+    pub op_schedulers: HashMap<u32, OpSchedulerConf>,
+    /// Limits on the destination gas spent on, and the number of, messages relayed in a window
+    pub relay_budgets: Vec<RelayBudgetConf>,
+    /// Token prices used to value the revenue and cost of delivered messages in USD. Without it,
+    /// profitability is only reported in native tokens.
+    pub profitability_price_source: Option<TokenPriceSourceConf>,
+    /// Bearer token required by the admin routes of the server, which are disabled if it isn't set
+    pub admin_api_token: Option<String>,
+    /// JSON file with a `whitelist`, `blacklist` and `addressBlacklist`, which replace the ones
+    /// of the config and are reloaded whenever the file changes
+    pub relay_lists_file: Option<PathBuf>,
+    /// Go through every step up to submission without broadcasting anything, and record what
+    /// would have been submitted to compare it with the deliveries of other relayers
+    pub dry_run: bool,
+    /// How long the ISM tree of messages is cached for across retries. `None`, the default,
+    /// disables the cache.
+    pub metadata_cache_ttl: Option<Duration>,
+    /// Lanes, i.e. (origin, sender, destination, recipient), whose messages are delivered in
+    /// nonce order: a message isn't submitted until the earlier messages of its lane are delivered
+    pub ordered_lanes: MatchingList,
+}
+
+/// The lists deciding which messages are relayed, in the format of the `whitelist`, `blacklist`
+/// and `addressBlacklist` keys of the config. They can be replaced at runtime.
+#[derive(Debug, Clone, Default)]
+pub struct RelayListsConf {
+    pub whitelist: MatchingList,
+    pub blacklist: MatchingList,
+    pub address_blacklist: Vec<Vec<u8>>,
+}
+
+impl RelayListsConf {
+    /// Parses lists given outside of the config, e.g. in the lists file or an admin API request
+    pub fn from_json(value: Value) -> ConfigResult<Self> {
+        let mut err = ConfigParsingError::default();
+        let value = recase_json_value(value, Case::Flat);
+        let lists = parse_relay_lists(&ValueParser::new(ConfigPath::default(), &value), &mut err);
+        err.into_result(lists)
+    }
+}
+
+/// Caps what can be relayed for the messages matching a list within a time window. Windows are
+/// aligned to the unix epoch, so a one hour window rolls over at the top of every hour.
+#[derive(Debug, Clone)]
+pub struct RelayBudgetConf {
+    /// Messages the budget applies to. A single budget is shared by all of them.
+    pub matching_list: MatchingList,
+    /// Length of the window
+    pub window: Duration,
+    /// Maximum destination native tokens spent in a window, in the smallest unit
+    pub max_spend: Option<U256>,
+    /// Maximum number of messages submitted in a window
+    pub max_messages: Option<u64>,
+}
+
+/// Config for the scheduler deciding which operations a destination's submitter processes next
+#[derive(Debug, Clone, Default)]
+pub enum OpSchedulerConf {
+    /// Operations that can be attempted the soonest go first, in nonce order within an origin
+    #[default]
+    Priority,
+    /// Weighted fair queuing across flows of operations with the same origin and sender. The
+    /// weight of an operation is the one of the first matching list it matches, or 1.
+    FairShare { weights: Vec<(MatchingList, u32)> },
+    /// Strict priority classes by app context, in the order listed. Operations without a listed
+    /// app context go last.
+    AppContextPriority { app_contexts: Vec<String> },
 }
 
 /// Config for gas payment enforcement
@@ -98,6 +172,43 @@ pub enum GasPaymentEnforcementPolicy {
         gas_fraction_numerator: u64,
         gas_fraction_denominator: u64,
     },
+    /// The payment covers the estimated cost of the transaction on the destination plus
+    /// `margin_percent`, after both are converted to USD at the price of the native token of
+    /// their chain. Gas already spent on the message is deducted from the payment. UsdValue
+    /// requires a payment to exist on the IGP specified in the config.
+    UsdValue {
+        price_source: TokenPriceSourceConf,
+        margin_percent: u64,
+    },
+}
+
+/// How long fetched token prices keep being used while they can't be refreshed, unless the
+/// price source sets `maxStaleness`
+pub const DEFAULT_TOKEN_PRICE_MAX_STALENESS: Duration = Duration::from_secs(10 * 60);
+
+/// Where the `UsdValue` gas payment enforcement policy gets token prices from
+#[derive(Debug, Clone)]
+pub enum TokenPriceSourceConf {
+    /// Prices set in the config, by domain
+    Static { prices: HashMap<u32, TokenPrice> },
+    /// A local JSON file with a list of prices, re-read when the cached prices expire. Prices
+    /// older than `max_staleness` aren't used, even if the file can't be read.
+    File {
+        path: PathBuf,
+        max_staleness: Duration,
+    },
+    /// An HTTP endpoint responding with a list of prices, polled when the cached prices expire.
+    /// Prices older than `max_staleness` aren't used, even if the endpoint is unavailable.
+    Http { url: Url, max_staleness: Duration },
+}
+
+/// The USD price of the native token of a chain
+#[derive(Debug, Clone, PartialEq)]
+pub struct TokenPrice {
+    /// Price of a whole token, in USD
+    pub usd_price: FixedPointNumber,
+    /// Number of decimals of the native token
+    pub decimals: u8,
 }
 
 #[derive(Debug, Deserialize)]
@@ -217,6 +328,20 @@ impl FromRawConf<RawRelayerSettings> for RelayerSettings {
                                 .unwrap_or(1),
                         })
                     }
+                    Some("usdValue") => {
+                        let margin_percent = policy.chain(&mut err)
+                            .get_opt_key("marginPercent")
+                            .parse_u64()
+                            .unwrap_or(0);
+                        policy.chain(&mut err)
+                            .get_key("priceSource")
+                            .end()
+                            .and_then(|source| parse_token_price_source(&source, &mut err))
+                            .map(|price_source| GasPaymentEnforcementPolicy::UsdValue {
+                                price_source,
+                                margin_percent,
+                            })
+                    }
                     Some(pt) => Err(eyre!("Unknown gas payment enforcement policy type `{pt}`"))
                         .take_err(&mut err, || cwp + "type"),
                 }.map(|policy| GasPaymentEnforcementConf {
@@ -230,24 +355,26 @@ impl FromRawConf<RawRelayerSettings> for RelayerSettings {
             gas_payment_enforcement.push(GasPaymentEnforcementConf::default());
         }
 
-        let whitelist = p
-            .chain(&mut err)
-            .get_opt_key("whitelist")
-            .and_then(parse_matching_list)
-            .unwrap_or_default();
-        let blacklist = p
-            .chain(&mut err)
-            .get_opt_key("blacklist")
-            .and_then(parse_matching_list)
-            .unwrap_or_default();
+        let RelayListsConf {
+            whitelist,
+            blacklist,
+            address_blacklist,
+        } = parse_relay_lists(&p, &mut err);
 
-        let address_blacklist = p
+        let relay_lists_file = p
             .chain(&mut err)
-            .get_opt_key("addressBlacklist")
-            .parse_string()
-            .end()
-            .map(|str| parse_address_list(str, &mut err, || &p.cwp + "address_blacklist"))
-            .unwrap_or_default();
+            .get_opt_key("relayListsFile")
+            .parse_from_str("Expected relay lists file path")
+            .end();
+        let lists_in_config = ["whitelist", "blacklist", "addressBlacklist"]
+            .into_iter()
+            .any(|key| matches!(p.get_opt_key(key), Ok(Some(_))));
+        if relay_lists_file.is_some() && lists_in_config {
+            err.push(
+                &p.cwp + "relay_lists_file",
+                eyre!("The relay lists can't be set in the config when a relay lists file is used"),
+            );
+        }
 
         let transaction_gas_limit = p
             .chain(&mut err)
@@ -268,6 +395,26 @@ impl FromRawConf<RawRelayerSettings> for RelayerSettings {
             .parse_bool()
             .unwrap_or(false);
 
+        let dry_run = p
+            .chain(&mut err)
+            .get_opt_key("dryRun")
+            .parse_bool()
+            .unwrap_or(false);
+
+        let metadata_cache_ttl = p
+            .chain(&mut err)
+            .get_opt_key("metadataCacheTtl")
+            .parse_u64()
+            .end()
+            .filter(|ttl| *ttl > 0)
+            .map(Duration::from_secs);
+
+        let ordered_lanes = p
+            .chain(&mut err)
+            .get_opt_key("orderedLanes")
+            .and_then(parse_matching_list)
+            .unwrap_or_default();
+
         cfg_unwrap_all!(cwp, err: [base]);
 
         let skip_transaction_gas_limit_for = skip_transaction_gas_limit_for_names
@@ -292,6 +439,64 @@ impl FromRawConf<RawRelayerSettings> for RelayerSettings {
             })
             .collect();
 
+        let (raw_op_schedulers_path, raw_op_schedulers) = p
+            .get_opt_key("opSchedulers")
+            .take_config_err_flat(&mut err)
+            .and_then(parse_json_array)
+            .unwrap_or_else(|| (&p.cwp + "op_schedulers", Value::Array(vec![])));
+
+        let op_schedulers_parser = ValueParser::new(raw_op_schedulers_path, &raw_op_schedulers);
+        let op_schedulers = op_schedulers_parser
+            .into_array_iter()
+            .map(|itr| {
+                itr.filter_map(|scheduler| {
+                    let destination = scheduler
+                        .chain(&mut err)
+                        .get_key("destination")
+                        .parse_string()
+                        .end()
+                        .and_then(|chain| {
+                            base.lookup_domain(chain)
+                                .context("Missing configuration for a chain in `opSchedulers`")
+                                .into_config_result(|| &scheduler.cwp + "destination")
+                                .take_config_err(&mut err)
+                        })?;
+                    let scheduler_type = scheduler
+                        .chain(&mut err)
+                        .get_opt_key("type")
+                        .parse_string()
+                        .end();
+                    let conf = match scheduler_type {
+                        Some("priority") | None => OpSchedulerConf::Priority,
+                        Some("fairShare") => OpSchedulerConf::FairShare {
+                            weights: parse_op_scheduler_weights(&scheduler, &mut err),
+                        },
+                        Some("appContextPriority") => OpSchedulerConf::AppContextPriority {
+                            app_contexts: scheduler
+                                .chain(&mut err)
+                                .get_key("appContexts")
+                                .into_array_iter()
+                                .map(|itr| {
+                                    itr.filter_map(|app_context| {
+                                        app_context
+                                            .chain(&mut err)
+                                            .parse_string()
+                                            .end()
+                                            .map(str::to_owned)
+                                    })
+                                    .collect_vec()
+                                })
+                                .unwrap_or_default(),
+                        },
+                        Some(ty) => Err(eyre!("Unknown op scheduler type `{ty}`"))
+                            .take_err(&mut err, || &scheduler.cwp + "type")?,
+                    };
+                    Some((destination.id(), conf))
+                })
+                .collect::<HashMap<_, _>>()
+            })
+            .unwrap_or_default();
+
         let (raw_metric_app_contexts_path, raw_metric_app_contexts) = p
             .get_opt_key("metricAppContexts")
             .take_config_err_flat(&mut err)
@@ -318,12 +523,83 @@ impl FromRawConf<RawRelayerSettings> for RelayerSettings {
             })
             .unwrap_or_default();
 
+        let (raw_relay_budgets_path, raw_relay_budgets) = p
+            .get_opt_key("relayBudgets")
+            .take_config_err_flat(&mut err)
+            .and_then(parse_json_array)
+            .unwrap_or_else(|| (&p.cwp + "relay_budgets", Value::Array(vec![])));
+
+        let relay_budgets_parser = ValueParser::new(raw_relay_budgets_path, &raw_relay_budgets);
+        let relay_budgets = relay_budgets_parser
+            .into_array_iter()
+            .map(|itr| {
+                itr.filter_map(|budget| {
+                    let matching_list = budget
+                        .chain(&mut err)
+                        .get_opt_key("matchingList")
+                        .and_then(parse_matching_list)
+                        .unwrap_or_default();
+                    let window_seconds = budget
+                        .chain(&mut err)
+                        .get_key("windowSeconds")
+                        .parse_u64()
+                        .end()?;
+                    if window_seconds == 0 {
+                        return Err(eyre!("Relay budget windows must be at least one second"))
+                            .take_err(&mut err, || &budget.cwp + "window_seconds");
+                    }
+                    let max_spend = budget
+                        .chain(&mut err)
+                        .get_opt_key("maxSpend")
+                        .parse_u256()
+                        .end();
+                    let max_messages = budget
+                        .chain(&mut err)
+                        .get_opt_key("maxMessages")
+                        .parse_u64()
+                        .end();
+                    if max_spend.is_none() && max_messages.is_none() {
+                        return Err(eyre!(
+                            "Relay budgets need at least one of `maxSpend` and `maxMessages`"
+                        ))
+                        .take_err(&mut err, || budget.cwp.clone());
+                    }
+                    Some(RelayBudgetConf {
+                        matching_list,
+                        window: Duration::from_secs(window_seconds),
+                        max_spend,
+                        max_messages,
+                    })
+                })
+                .collect_vec()
+            })
+            .unwrap_or_default();
+
+        let profitability_price_source = p
+            .chain(&mut err)
+            .get_opt_key("profitabilityPriceSource")
+            .end()
+            .and_then(|source| parse_token_price_source(&source, &mut err));
+
         let max_message_retries = p
             .chain(&mut err)
             .get_opt_key("maxMessageRetries")
             .parse_u32()
             .unwrap_or(DEFAULT_MAX_MESSAGE_RETRIES);
 
+        let admin_api_token = p
+            .chain(&mut err)
+            .get_opt_key("adminApiToken")
+            .parse_string()
+            .end()
+            .map(str::to_owned);
+        if admin_api_token.as_deref() == Some("") {
+            err.push(
+                &p.cwp + "admin_api_token",
+                eyre!("The admin API token can't be empty"),
+            );
+        }
+
         err.into_result(RelayerSettings {
             base,
             db,
@@ -338,10 +614,124 @@ impl FromRawConf<RawRelayerSettings> for RelayerSettings {
             allow_local_checkpoint_syncers,
             metric_app_contexts,
             max_retries: max_message_retries,
+            op_schedulers,
+            relay_budgets,
+            profitability_price_source,
+            admin_api_token,
+            relay_lists_file,
+            dry_run,
+            metadata_cache_ttl,
+            ordered_lanes,
         })
     }
 }
 
+fn parse_op_scheduler_weights(
+    scheduler: &ValueParser,
+    err: &mut ConfigParsingError,
+) -> Vec<(MatchingList, u32)> {
+    scheduler
+        .chain(err)
+        .get_opt_key("weights")
+        .into_array_iter()
+        .map(|itr| {
+            itr.filter_map(|weight| {
+                let matching_list = weight
+                    .chain(err)
+                    .get_key("matchingList")
+                    .and_then(parse_matching_list)
+                    .unwrap_or_default();
+                let value = weight.chain(err).get_key("weight").parse_u32().end()?;
+                if value == 0 {
+                    return Err(eyre!("Op scheduler weights must be positive"))
+                        .take_err(err, || &weight.cwp + "weight");
+                }
+                Some((matching_list, value))
+            })
+            .collect_vec()
+        })
+        .unwrap_or_default()
+}
+
+fn parse_token_price_source(
+    source: &ValueParser,
+    err: &mut ConfigParsingError,
+) -> Option<TokenPriceSourceConf> {
+    let source_type = source.chain(err).get_key("type").parse_string().end()?;
+    match source_type {
+        "static" => {
+            let prices = source
+                .chain(err)
+                .get_key("prices")
+                .into_array_iter()
+                .map(|itr| {
+                    itr.filter_map(|price| {
+                        let domain = price.chain(err).get_key("domain").parse_u32().end()?;
+                        let usd_price = price
+                            .chain(err)
+                            .get_key("usdPrice")
+                            .parse_from_str("Expected a decimal USD price")
+                            .end()?;
+                        let decimals = price
+                            .chain(err)
+                            .get_opt_key("decimals")
+                            .parse_u32()
+                            .end()
+                            .map_or(Ok(18), u8::try_from)
+                            .context("Token decimals must fit in a u8")
+                            .take_err(err, || &price.cwp + "decimals")?;
+                        Some((
+                            domain,
+                            TokenPrice {
+                                usd_price,
+                                decimals,
+                            },
+                        ))
+                    })
+                    .collect()
+                })
+                .unwrap_or_default();
+            Some(TokenPriceSourceConf::Static { prices })
+        }
+        "file" => {
+            let path = source
+                .chain(err)
+                .get_key("path")
+                .parse_from_str("Expected a file path")
+                .end();
+            let max_staleness = parse_max_staleness(source, err);
+            Some(TokenPriceSourceConf::File {
+                path: path?,
+                max_staleness,
+            })
+        }
+        "http" => {
+            let url = source
+                .chain(err)
+                .get_key("url")
+                .parse_from_str("Expected a URL")
+                .end();
+            let max_staleness = parse_max_staleness(source, err);
+            Some(TokenPriceSourceConf::Http {
+                url: url?,
+                max_staleness,
+            })
+        }
+        _ => Err(eyre!("Unknown token price source type `{source_type}`"))
+            .take_err(err, || &source.cwp + "type"),
+    }
+}
+
+/// `maxStaleness` of a fetched token price source, in seconds
+fn parse_max_staleness(source: &ValueParser, err: &mut ConfigParsingError) -> Duration {
+    source
+        .chain(err)
+        .get_opt_key("maxStaleness")
+        .parse_u64()
+        .end()
+        .map_or(DEFAULT_TOKEN_PRICE_MAX_STALENESS, Duration::from_secs)
+}
+
 fn parse_json_array(p: ValueParser) -> Option<(ConfigPath, Value)> {
     let mut err = ConfigParsingError::default();
 
@@ -378,6 +768,33 @@ fn parse_matching_list(p: ValueParser) -> ConfigResult<MatchingList> {
     err.into_result(ml)
 }
 
+fn parse_relay_lists(p: &ValueParser, err: &mut ConfigParsingError) -> RelayListsConf {
+    let whitelist = p
+        .chain(err)
+        .get_opt_key("whitelist")
+        .and_then(parse_matching_list)
+        .unwrap_or_default();
+    let blacklist = p
+        .chain(err)
+        .get_opt_key("blacklist")
+        .and_then(parse_matching_list)
+        .unwrap_or_default();
+
+    let address_blacklist = p
+        .chain(err)
+        .get_opt_key("addressBlacklist")
+        .parse_string()
+        .end()
+        .map(|str| parse_address_list(str, err, || &p.cwp + "address_blacklist"))
+        .unwrap_or_default();
+
+    RelayListsConf {
+        whitelist,
+        blacklist,
+        address_blacklist,
+    }
+}
+
 fn parse_address_list(
     str: &str,
     err: &mut ConfigParsingError,
@@ -397,7 +814,8 @@ fn parse_address_list(
 #[cfg(test)]
 mod test {
     use super::*;
-    use aetherium_core::H160;
+    use aetherium_core::{AetheriumMessage, H160};
+    use serde_json::json;
 
     #[test]
     fn test_parse_address_blacklist() {
@@ -426,4 +844,281 @@ mod test {
         assert_eq!(res, vec![valid_address1, valid_address2]);
         assert!(!err.is_ok());
     }
+
+    const TEST1_DOMAIN: u32 = 9913371;
+
+    /// Parses a relayer config with a single `test1` chain and the given keys
+    fn parse_relayer_settings(keys: Value) -> ConfigResult<RelayerSettings> {
+        let mut raw = json!({
+            "relayChains": "test1",
+            "chains": {
+                "test1": {
+                    "name": "test1",
+                    "domainId": TEST1_DOMAIN,
+                    "protocol": "ethereum",
+                    "rpcUrls": [{ "http": "http://127.0.0.1:8545" }],
+                    "mailbox": "0x0000000000000000000000000000000000000001",
+                    "interchainGasPaymaster": "0x0000000000000000000000000000000000000002",
+                    "validatorAnnounce": "0x0000000000000000000000000000000000000003",
+                    "merkleTreeHook": "0x0000000000000000000000000000000000000004",
+                },
+            },
+        });
+        raw.as_object_mut()
+            .unwrap()
+            .extend(keys.as_object().unwrap().clone());
+        RelayerSettings::from_config(
+            RawRelayerSettings(recase_json_value(raw, Case::Flat)),
+            &ConfigPath::default(),
+        )
+    }
+
+    fn assert_rejected(keys: Value, expected_error: &str) {
+        let err = parse_relayer_settings(keys).unwrap_err().to_string();
+        assert!(
+            err.contains(expected_error),
+            "expected `{expected_error}` in {err}"
+        );
+    }
+
+    #[test]
+    fn test_parse_new_keys_defaults() {
+        let settings = parse_relayer_settings(json!({})).unwrap();
+        assert!(settings.op_schedulers.is_empty());
+        assert!(settings.relay_budgets.is_empty());
+        assert!(settings.profitability_price_source.is_none());
+        assert!(settings.relay_lists_file.is_none());
+        assert!(!settings.dry_run);
+        assert!(settings.metadata_cache_ttl.is_none());
+        let message = AetheriumMessage {
+            destination: TEST1_DOMAIN,
+            ..Default::default()
+        };
+        assert!(!settings.ordered_lanes.msg_matches(&message, false));
+    }
+
+    #[test]
+    fn test_parse_op_schedulers() {
+        let settings = parse_relayer_settings(json!({
+            "opSchedulers": [{
+                "destination": "test1",
+                "type": "fairShare",
+                "weights": [{ "matchingList": [{ "originDomain": 1 }], "weight": 3 }],
+            }],
+        }))
+        .unwrap();
+        let OpSchedulerConf::FairShare { weights } = &settings.op_schedulers[&TEST1_DOMAIN] else {
+            panic!("Expected a fair share scheduler");
+        };
+        assert_eq!(weights.len(), 1);
+        assert_eq!(weights[0].1, 3);
+
+        let settings = parse_relayer_settings(json!({
+            "opSchedulers": [{
+                "destination": "test1",
+                "type": "appContextPriority",
+                "appContexts": ["urgent", "bulk"],
+            }],
+        }))
+        .unwrap();
+        assert!(matches!(
+            &settings.op_schedulers[&TEST1_DOMAIN],
+            OpSchedulerConf::AppContextPriority { app_contexts } if app_contexts == &["urgent", "bulk"]
+        ));
+
+        assert_rejected(
+            json!({ "opSchedulers": [{ "destination": "test1", "type": "lottery" }] }),
+            "Unknown op scheduler type",
+        );
+        assert_rejected(
+            json!({ "opSchedulers": [{ "destination": "test2" }] }),
+            "Missing configuration for a chain in `opSchedulers`",
+        );
+        assert_rejected(
+            json!({
+                "opSchedulers": [{
+                    "destination": "test1",
+                    "type": "fairShare",
+                    "weights": [{ "matchingList": [], "weight": 0 }],
+                }],
+            }),
+            "Op scheduler weights must be positive",
+        );
+    }
+
+    #[test]
+    fn test_parse_relay_budgets() {
+        let settings = parse_relayer_settings(json!({
+            "relayBudgets": [{
+                "matchingList": [{ "destinationDomain": TEST1_DOMAIN }],
+                "windowSeconds": 3600,
+                "maxSpend": "1000000000000000000",
+                "maxMessages": 10,
+            }],
+        }))
+        .unwrap();
+        let [budget] = settings.relay_budgets.as_slice() else {
+            panic!("Expected a single relay budget");
+        };
+        assert_eq!(budget.window, Duration::from_secs(3600));
+        assert_eq!(budget.max_spend, Some(U256::exp10(18)));
+        assert_eq!(budget.max_messages, Some(10));
+
+        assert_rejected(
+            json!({ "relayBudgets": [{ "windowSeconds": 0, "maxMessages": 10 }] }),
+            "Relay budget windows must be at least one second",
+        );
+        assert_rejected(
+            json!({ "relayBudgets": [{ "windowSeconds": 3600 }] }),
+            "Relay budgets need at least one of `maxSpend` and `maxMessages`",
+        );
+    }
+
+    #[test]
+    fn test_parse_profitability_price_source() {
+        let settings = parse_relayer_settings(json!({
+            "profitabilityPriceSource": {
+                "type": "static",
+                "prices": [{ "domain": TEST1_DOMAIN, "usdPrice": "3150.25", "decimals": 6 }],
+            },
+        }))
+        .unwrap();
+        let Some(TokenPriceSourceConf::Static { prices }) = settings.profitability_price_source
+        else {
+            panic!("Expected a static price source");
+        };
+        assert_eq!(
+            prices[&TEST1_DOMAIN],
+            TokenPrice {
+                usd_price: "3150.25".parse().unwrap(),
+                decimals: 6,
+            }
+        );
+
+        let settings = parse_relayer_settings(json!({
+            "profitabilityPriceSource": {
+                "type": "file",
+                "path": "/etc/relayer/token-prices.json",
+                "maxStaleness": 120,
+            },
+        }))
+        .unwrap();
+        assert!(matches!(
+            settings.profitability_price_source,
+            Some(TokenPriceSourceConf::File { path, max_staleness })
+                if path == PathBuf::from("/etc/relayer/token-prices.json")
+                    && max_staleness == Duration::from_secs(120)
+        ));
+
+        let settings = parse_relayer_settings(json!({
+            "profitabilityPriceSource": { "type": "http", "url": "https://prices.example.com" },
+        }))
+        .unwrap();
+        assert!(matches!(
+            settings.profitability_price_source,
+            Some(TokenPriceSourceConf::Http { max_staleness, .. })
+                if max_staleness == DEFAULT_TOKEN_PRICE_MAX_STALENESS
+        ));
+
+        assert_rejected(
+            json!({ "profitabilityPriceSource": { "type": "oracle" } }),
+            "Unknown token price source type",
+        );
+        assert_rejected(
+            json!({ "profitabilityPriceSource": { "type": "http", "url": "not a url" } }),
+            "Expected a URL",
+        );
+    }
+
+    #[test]
+    fn test_parse_usd_value_gas_payment_enforcement() {
+        let settings = parse_relayer_settings(json!({
+            "gasPaymentEnforcement": [{
+                "type": "usdValue",
+                "marginPercent": 10,
+                "priceSource": {
+                    "type": "http",
+                    "url": "https://prices.example.com",
+                    "maxStaleness": 30,
+                },
+            }],
+        }))
+        .unwrap();
+        assert!(matches!(
+            &settings.gas_payment_enforcement[0].policy,
+            GasPaymentEnforcementPolicy::UsdValue {
+                price_source: TokenPriceSourceConf::Http { max_staleness, .. },
+                margin_percent: 10,
+            } if *max_staleness == Duration::from_secs(30)
+        ));
+
+        assert_rejected(
+            json!({ "gasPaymentEnforcement": [{ "type": "usdValue" }] }),
+            "priceSource",
+        );
+    }
+
+    #[test]
+    fn test_parse_relay_lists_file() {
+        let settings = parse_relayer_settings(json!({
+            "relayListsFile": "/etc/relayer/relay-lists.json",
+        }))
+        .unwrap();
+        assert_eq!(
+            settings.relay_lists_file,
+            Some(PathBuf::from("/etc/relayer/relay-lists.json"))
+        );
+
+        for (key, value) in [
+            ("whitelist", json!([{ "originDomain": 1 }])),
+            ("blacklist", json!([{ "originDomain": 1 }])),
+            ("addressBlacklist", json!("0x01")),
+        ] {
+            assert_rejected(
+                json!({ "relayListsFile": "/etc/relayer/relay-lists.json", key: value }),
+                "The relay lists can't be set in the config when a relay lists file is used",
+            );
+        }
+    }
+
+    #[test]
+    fn test_parse_dry_run_and_metadata_cache_ttl() {
+        let settings = parse_relayer_settings(json!({
+            "dryRun": true,
+            "metadataCacheTtl": 30,
+        }))
+        .unwrap();
+        assert!(settings.dry_run);
+        assert_eq!(settings.metadata_cache_ttl, Some(Duration::from_secs(30)));
+
+        // a ttl of zero disables the cache
+        let settings = parse_relayer_settings(json!({ "metadataCacheTtl": 0 })).unwrap();
+        assert!(settings.metadata_cache_ttl.is_none());
+
+        assert_rejected(json!({ "dryRun": "sometimes" }), "Expected a boolean");
+    }
+
+    #[test]
+    fn test_parse_ordered_lanes() {
+        let settings = parse_relayer_settings(json!({
+            "orderedLanes": [{ "originDomain": 1, "destinationDomain": TEST1_DOMAIN }],
+        }))
+        .unwrap();
+        let ordered = AetheriumMessage {
+            origin: 1,
+            destination: TEST1_DOMAIN,
+            ..Default::default()
+        };
+        let unordered = AetheriumMessage {
+            origin: 2,
+            ..ordered.clone()
+        };
+        assert!(settings.ordered_lanes.msg_matches(&ordered, false));
+        assert!(!settings.ordered_lanes.msg_matches(&unordered, false));
+
+        assert_rejected(
+            json!({ "orderedLanes": [{ "originDomain": "first" }] }),
+            "Expected matching list",
+        );
+    }
 }
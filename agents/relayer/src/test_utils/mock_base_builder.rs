@@ -9,7 +9,7 @@ use aetherium_core::{
     AetheriumMessage, InterchainSecurityModule, MultisigIsm, RoutingIsm, H256,
 };
 
-use crate::msg::metadata::{BuildsBaseMetadata, IsmAwareAppContextClassifier};
+use crate::msg::metadata::{BuildsBaseMetadata, IsmAwareAppContextClassifier, MetadataCache};
 
 type ResponseList<T> = Arc<Mutex<VecDeque<T>>>;
 
@@ -18,6 +18,7 @@ pub struct MockBaseMetadataBuilderResponses {
     pub origin_domain: Option<AetheriumDomain>,
     pub destination_domain: Option<AetheriumDomain>,
     pub app_context_classifier: Option<IsmAwareAppContextClassifier>,
+    pub metadata_cache: Option<MetadataCache>,
     pub get_proof: ResponseList<eyre::Result<Proof>>,
     pub highest_known_leaf_index: ResponseList<Option<u32>>,
     pub get_merkle_leaf_id_by_message_id: ResponseList<eyre::Result<Option<u32>>>,
@@ -86,6 +87,9 @@ impl BuildsBaseMetadata for MockBaseMetadataBuilder {
             .as_ref()
             .expect("No mock app_context_classifier response set")
     }
+    fn metadata_cache(&self) -> Option<&MetadataCache> {
+        self.responses.metadata_cache.as_ref()
+    }
 
     async fn get_proof(&self, _leaf_index: u32, _checkpoint: Checkpoint) -> eyre::Result<Proof> {
         self.responses
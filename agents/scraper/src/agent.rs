@@ -437,6 +437,9 @@ mod test {
                         gas_limit: None,
                         max_fee_per_gas: None,
                         max_priority_fee_per_gas: None,
+                        gas_price_escalation_percent: None,
+                        gas_price_escalation_interval_blocks: None,
+                        max_gas_price: None,
                     },
                     operation_batch: OperationBatchConfig {
                         batch_contract_address: None,
@@ -0,0 +1,509 @@
+//! Audits the checkpoints in a checkpoint syncer against the merkle tree built from the DB.
+
+use std::{ops::RangeInclusive, sync::Arc, time::Duration};
+
+use aetherium_base::{db::AetheriumDb, CheckpointSyncer};
+use aetherium_core::{
+    accumulator::incremental::IncrementalMerkle, AetheriumChain, AetheriumContract,
+    AetheriumSignerExt, Checkpoint, CheckpointWithMessageId, MerkleTreeHook, ReorgEvent,
+    ReorgPeriod, H160,
+};
+use aetherium_ethereum::SingletonSignerHandle;
+use eyre::{Context, Result};
+use serde::{Deserialize, Serialize};
+use tokio::time::sleep;
+use tracing::{debug, error, info, warn};
+
+use crate::slashing_protection::SlashingProtectionDb;
+
+/// What the validator does when started with the `audit` setting
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum AuditMode {
+    /// Only report the problems found
+    Check,
+    /// Also sign and write the checkpoints missing from the checkpoint syncer
+    Repair,
+}
+
+/// A checkpoint in the checkpoint syncer that isn't the one of the local merkle tree
+#[derive(Debug, Clone, Serialize)]
+pub struct CheckpointMismatch {
+    /// The checkpoint of the local merkle tree
+    pub expected: CheckpointWithMessageId,
+    /// The checkpoint in the checkpoint syncer
+    pub found: CheckpointWithMessageId,
+}
+
+/// The problems found in a checkpoint syncer
+#[derive(Debug, Default, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct AuditReport {
+    /// The latest index of the checkpoint syncer, if it has any checkpoint
+    pub latest_index: Option<u32>,
+    /// Indices of the checkpoint syncer beyond the count of the merkle tree hook, which no
+    /// canonical checkpoint exists for
+    pub excess: Option<RangeInclusive<u32>>,
+    /// The reorg flagged on the checkpoint syncer, either before or during the audit
+    pub reorg: Option<ReorgEvent>,
+    /// Indices without a checkpoint
+    pub gaps: Vec<u32>,
+    /// Indices whose checkpoint was signed by another validator, and the signer
+    pub wrong_signers: Vec<(u32, H160)>,
+    /// Indices whose signature couldn't be recovered
+    pub invalid_signatures: Vec<u32>,
+    /// Checkpoints that don't match the local merkle tree
+    pub mismatches: Vec<CheckpointMismatch>,
+    /// Indices of the gaps that were filled in repair mode
+    pub repaired: Vec<u32>,
+}
+
+impl AuditReport {
+    /// Whether the checkpoint syncer holds a checkpoint of the validator for every index, each
+    /// matching the local merkle tree
+    pub fn is_healthy(&self) -> bool {
+        self.excess.is_none()
+            && self.reorg.is_none()
+            && self.gaps.is_empty()
+            && self.wrong_signers.is_empty()
+            && self.invalid_signatures.is_empty()
+            && self.mismatches.is_empty()
+    }
+
+    /// Logs every problem found, and a summary
+    pub fn log(&self) {
+        if let Some(reorg_event) = &self.reorg {
+            warn!(?reorg_event, "A reorg is flagged on the checkpoint syncer");
+        }
+        if let Some(excess) = &self.excess {
+            warn!(
+                from = excess.start(),
+                to = excess.end(),
+                "Checkpoint syncer is ahead of the merkle tree hook"
+            );
+        }
+        for index in &self.gaps {
+            warn!(index, "Checkpoint missing from checkpoint syncer");
+        }
+        for (index, signer) in &self.wrong_signers {
+            warn!(index, ?signer, "Checkpoint signed by another validator");
+        }
+        for index in &self.invalid_signatures {
+            warn!(index, "Checkpoint signature is invalid");
+        }
+        for CheckpointMismatch { expected, found } in &self.mismatches {
+            warn!(
+                index = expected.index,
+                ?expected,
+                ?found,
+                "Checkpoint doesn't match the local merkle tree"
+            );
+        }
+        info!(
+            latest_index = ?self.latest_index,
+            excess = ?self.excess,
+            reorg = self.reorg.is_some(),
+            healthy = self.is_healthy(),
+            gaps = self.gaps.len(),
+            wrong_signers = self.wrong_signers.len(),
+            invalid_signatures = self.invalid_signatures.len(),
+            mismatches = self.mismatches.len(),
+            repaired = self.repaired.len(),
+            "Checkpoint syncer audit finished"
+        );
+    }
+}
+
+/// Walks a checkpoint syncer from index 0 to its latest index, checking that every checkpoint
+/// was signed by the validator and matches the merkle tree built from the DB.
+pub struct CheckpointAuditor {
+    checkpoint_syncer: Arc<dyn CheckpointSyncer>,
+    db: Arc<dyn AetheriumDb>,
+    validator: H160,
+    merkle_tree_hook: Arc<dyn MerkleTreeHook>,
+    reorg_period: ReorgPeriod,
+    repair: Option<(SingletonSignerHandle, SlashingProtectionDb)>,
+}
+
+impl CheckpointAuditor {
+    pub fn new(
+        checkpoint_syncer: Arc<dyn CheckpointSyncer>,
+        db: Arc<dyn AetheriumDb>,
+        validator: H160,
+        merkle_tree_hook: Arc<dyn MerkleTreeHook>,
+        reorg_period: ReorgPeriod,
+    ) -> Self {
+        Self {
+            checkpoint_syncer,
+            db,
+            validator,
+            merkle_tree_hook,
+            reorg_period,
+            repair: None,
+        }
+    }
+
+    /// Signs and writes the missing checkpoints, as long as slashing protection allows it and the
+    /// local merkle tree matches the merkle tree hook. Mismatching checkpoints are never
+    /// overwritten, since they need to be looked into.
+    pub fn with_repair(
+        mut self,
+        signer: SingletonSignerHandle,
+        slashing_protection: SlashingProtectionDb,
+    ) -> Self {
+        self.repair = Some((signer, slashing_protection));
+        self
+    }
+
+    pub async fn audit(&self) -> Result<AuditReport> {
+        let mut report = AuditReport {
+            latest_index: self.checkpoint_syncer.latest_index().await?,
+            reorg: self.checkpoint_syncer.reorg_status().await?,
+            ..Default::default()
+        };
+        let Some(latest_index) = report.latest_index else {
+            return Ok(report);
+        };
+
+        // The DB can only catch up with the leaves the merkle tree hook has, so the indices
+        // beyond its count are reported instead of waited for
+        let count = self.merkle_tree_hook.count(&self.reorg_period).await?;
+        if latest_index >= count {
+            report.excess = Some(count..=latest_index);
+        }
+
+        let mut tree = IncrementalMerkle::default();
+        let mut missing = vec![];
+        for index in 0..count.min(latest_index.saturating_add(1)) {
+            let expected = self.expected_checkpoint(&mut tree, index).await?;
+            let Some(signed) = self.checkpoint_syncer.fetch_checkpoint(index).await? else {
+                report.gaps.push(index);
+                missing.push(expected);
+                continue;
+            };
+            match signed.recover() {
+                Ok(signer) if signer != self.validator => {
+                    report.wrong_signers.push((index, signer))
+                }
+                Ok(_) => {}
+                Err(err) => {
+                    debug!(index, ?err, "Failed to recover checkpoint signer");
+                    report.invalid_signatures.push(index);
+                }
+            }
+            if signed.value != expected {
+                report.mismatches.push(CheckpointMismatch {
+                    expected,
+                    found: signed.value,
+                });
+            }
+        }
+        if !missing.is_empty() {
+            self.fill_gaps(&mut report, tree, missing).await?;
+        }
+        Ok(report)
+    }
+
+    /// Ingests the leaf at `index` into `tree`, and returns the resulting checkpoint
+    async fn expected_checkpoint(
+        &self,
+        tree: &mut IncrementalMerkle,
+        index: u32,
+    ) -> Result<CheckpointWithMessageId> {
+        // the leaf may not be indexed yet, in which case the merkle tree hook sync will add it,
+        // since only the leaves the merkle tree hook has are asked for
+        let insertion = loop {
+            if let Some(insertion) = self
+                .db
+                .retrieve_merkle_tree_insertion_by_leaf_index(&index)
+                .with_context(|| format!("Failed to fetch merkle tree insertion {index}"))?
+            {
+                break insertion;
+            }
+            debug!(index, "Waiting for merkle tree insertion to be indexed");
+            sleep(Duration::from_secs(1)).await;
+        };
+        let message_id = insertion.message_id();
+        tree.ingest(message_id);
+        Ok(CheckpointWithMessageId {
+            checkpoint: Checkpoint {
+                merkle_tree_hook_address: self.merkle_tree_hook.address(),
+                mailbox_domain: self.merkle_tree_hook.domain().id(),
+                root: tree.root(),
+                index: tree.index(),
+            },
+            message_id,
+        })
+    }
+
+    /// Signs and writes the `missing` checkpoints in repair mode, once `tree` is caught up with
+    /// the latest checkpoint of the merkle tree hook and matches it. A mismatch is flagged as a
+    /// reorg on the checkpoint syncer, like the validator submitter does.
+    async fn fill_gaps(
+        &self,
+        report: &mut AuditReport,
+        mut tree: IncrementalMerkle,
+        missing: Vec<CheckpointWithMessageId>,
+    ) -> Result<()> {
+        let Some((signer, slashing_protection)) = &self.repair else {
+            return Ok(());
+        };
+        if report.reorg.is_some() {
+            warn!("Not filling checkpoint gaps, since a reorg is flagged on the checkpoint syncer");
+            return Ok(());
+        }
+
+        let latest_checkpoint = self
+            .merkle_tree_hook
+            .latest_checkpoint(&self.reorg_period)
+            .await?;
+        for index in tree.count() as u32..=latest_checkpoint.index {
+            self.expected_checkpoint(&mut tree, index).await?;
+        }
+        if tree.index() != latest_checkpoint.index || tree.root() != latest_checkpoint.root {
+            let reorg_event = ReorgEvent::new(
+                tree.root(),
+                latest_checkpoint.root,
+                latest_checkpoint.index,
+                chrono::Utc::now().timestamp() as u64,
+                self.reorg_period.clone(),
+            );
+            error!(
+                ?reorg_event,
+                "Local merkle tree doesn't match the merkle tree hook; not filling checkpoint gaps"
+            );
+            self.checkpoint_syncer
+                .write_reorg_status(&reorg_event)
+                .await?;
+            report.reorg = Some(reorg_event);
+            return Ok(());
+        }
+
+        for checkpoint in missing {
+            if let Err(err) = slashing_protection.check_and_record(&checkpoint) {
+                warn!(index = checkpoint.index, ?err, "Not filling checkpoint gap");
+                continue;
+            }
+            let signed_checkpoint = signer.sign(checkpoint).await?;
+            self.checkpoint_syncer
+                .write_checkpoint(&signed_checkpoint)
+                .await?;
+            info!(index = checkpoint.index, "Filled checkpoint gap");
+            report.gaps.retain(|index| *index != checkpoint.index);
+            report.repaired.push(checkpoint.index);
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use std::fmt::Debug;
+
+    use aetherium_base::{
+        db::{test_utils, AetheriumRocksDB},
+        LocalStorage,
+    };
+    use aetherium_core::{
+        test_utils::dummy_domain, AetheriumDomain, AetheriumProvider, AetheriumSigner, ChainResult,
+        MerkleTreeInsertion, H256,
+    };
+    use aetherium_ethereum::{Signers, SingletonSigner};
+    use async_trait::async_trait;
+    use ethers::signers::LocalWallet;
+
+    use super::*;
+
+    const KEY: &str = "1111111111111111111111111111111111111111111111111111111111111111";
+    const OTHER_KEY: &str = "2222222222222222222222222222222222222222222222222222222222222222";
+
+    mockall::mock! {
+        pub MerkleTreeHook {}
+
+        impl Debug for MerkleTreeHook {
+            fn fmt<'a>(&self, f: &mut std::fmt::Formatter<'a>) -> std::fmt::Result;
+        }
+
+        impl AetheriumChain for MerkleTreeHook {
+            fn domain(&self) -> &AetheriumDomain;
+            fn provider(&self) -> Box<dyn AetheriumProvider>;
+        }
+
+        impl AetheriumContract for MerkleTreeHook {
+            fn address(&self) -> H256;
+        }
+
+        #[async_trait]
+        impl MerkleTreeHook for MerkleTreeHook {
+            async fn tree(&self, reorg_period: &ReorgPeriod) -> ChainResult<IncrementalMerkle>;
+            async fn count(&self, reorg_period: &ReorgPeriod) -> ChainResult<u32>;
+            async fn latest_checkpoint(&self, reorg_period: &ReorgPeriod) -> ChainResult<Checkpoint>;
+        }
+    }
+
+    /// A merkle tree hook whose merkle tree holds `leaves`
+    fn merkle_tree_hook(domain: &AetheriumDomain, leaves: &[H256]) -> Arc<dyn MerkleTreeHook> {
+        let mut tree = IncrementalMerkle::default();
+        for leaf in leaves {
+            tree.ingest(*leaf);
+        }
+        let checkpoint = Checkpoint {
+            merkle_tree_hook_address: H256::repeat_byte(1),
+            mailbox_domain: domain.id(),
+            root: tree.root(),
+            index: tree.index(),
+        };
+        let mut merkle_tree_hook = MockMerkleTreeHook::new();
+        merkle_tree_hook
+            .expect_address()
+            .return_const(checkpoint.merkle_tree_hook_address);
+        merkle_tree_hook
+            .expect_domain()
+            .return_const(domain.clone());
+        merkle_tree_hook
+            .expect_count()
+            .returning(move |_| Ok(tree.count() as u32));
+        merkle_tree_hook
+            .expect_latest_checkpoint()
+            .returning(move |_| Ok(checkpoint));
+        Arc::new(merkle_tree_hook)
+    }
+
+    /// Indexes `leaves`, and writes checkpoints to the checkpoint syncer such that index 0 is
+    /// right, 1 is missing, 2 doesn't match the tree, 3 is signed by another key and 4 is beyond
+    /// the merkle tree hook. Returns the checkpoint of index 2 that was written.
+    async fn write_checkpoints(
+        auditor: &CheckpointAuditor,
+        db: &AetheriumRocksDB,
+        leaves: &[H256],
+        signer: &SingletonSignerHandle,
+    ) -> CheckpointWithMessageId {
+        for (index, leaf) in leaves.iter().enumerate() {
+            db.process_tree_insertion(&MerkleTreeInsertion::new(index as u32, *leaf), index as u64)
+                .unwrap();
+        }
+        let mut tree = IncrementalMerkle::default();
+        let mut expected = vec![];
+        for index in 0..4 {
+            expected.push(auditor.expected_checkpoint(&mut tree, index).await.unwrap());
+        }
+        let mut wrong_root = expected[2];
+        wrong_root.checkpoint.root = H256::zero();
+        let other = Signers::from(OTHER_KEY.parse::<LocalWallet>().unwrap());
+        for signed in [
+            signer.sign(expected[0]).await.unwrap(),
+            signer.sign(wrong_root).await.unwrap(),
+            other.sign(expected[3]).await.unwrap(),
+        ] {
+            auditor
+                .checkpoint_syncer
+                .write_checkpoint(&signed)
+                .await
+                .unwrap();
+        }
+        auditor
+            .checkpoint_syncer
+            .write_latest_index(4)
+            .await
+            .unwrap();
+        wrong_root
+    }
+
+    #[tokio::test]
+    async fn test_audit_and_repair() {
+        test_utils::run_test_db(|db| async move {
+            let domain = dummy_domain(1, "origin");
+            let db = AetheriumRocksDB::new(&domain, db);
+            let leaves: Vec<_> = (0..4).map(H256::from_low_u64_be).collect();
+            let dir = tempfile::tempdir().unwrap();
+            let checkpoint_syncer: Arc<dyn CheckpointSyncer> =
+                Arc::new(LocalStorage::new(dir.path().to_owned(), None).unwrap());
+
+            let (signer_instance, signer) =
+                SingletonSigner::new(Signers::from(KEY.parse::<LocalWallet>().unwrap()));
+            let signer_task = tokio::spawn(signer_instance.run());
+            let auditor = CheckpointAuditor::new(
+                checkpoint_syncer.clone(),
+                Arc::new(db.clone()),
+                signer.eth_address(),
+                merkle_tree_hook(&domain, &leaves),
+                ReorgPeriod::from_blocks(1),
+            );
+            let wrong_root = write_checkpoints(&auditor, &db, &leaves, &signer).await;
+
+            let report = auditor.audit().await.unwrap();
+            assert!(!report.is_healthy());
+            assert_eq!(report.excess, Some(4..=4));
+            assert_eq!(report.gaps, vec![1]);
+            assert_eq!(report.wrong_signers.len(), 1);
+            assert_eq!(report.wrong_signers[0].0, 3);
+            assert_eq!(report.mismatches.len(), 1);
+            assert_eq!(report.mismatches[0].found, wrong_root);
+            assert!(report.reorg.is_none());
+
+            test_utils::run_test_db(|slashing_protection_db| async move {
+                let auditor =
+                    auditor.with_repair(signer, SlashingProtectionDb::new(slashing_protection_db));
+                let report = auditor.audit().await.unwrap();
+                assert_eq!(report.repaired, vec![1]);
+                assert!(report.gaps.is_empty());
+                assert_eq!(auditor.audit().await.unwrap().repaired, Vec::<u32>::new());
+            })
+            .await;
+            signer_task.abort();
+        })
+        .await;
+    }
+
+    #[tokio::test]
+    async fn test_repair_flags_reorg() {
+        test_utils::run_test_db(|db| async move {
+            let domain = dummy_domain(1, "origin");
+            let db = AetheriumRocksDB::new(&domain, db);
+            let leaves: Vec<_> = (0..4).map(H256::from_low_u64_be).collect();
+            // the last leaf is different onchain
+            let mut canonical_leaves = leaves.clone();
+            canonical_leaves[3] = H256::repeat_byte(2);
+            let dir = tempfile::tempdir().unwrap();
+            let checkpoint_syncer: Arc<dyn CheckpointSyncer> =
+                Arc::new(LocalStorage::new(dir.path().to_owned(), None).unwrap());
+
+            let (signer_instance, signer) =
+                SingletonSigner::new(Signers::from(KEY.parse::<LocalWallet>().unwrap()));
+            let signer_task = tokio::spawn(signer_instance.run());
+
+            test_utils::run_test_db(|slashing_protection_db| async move {
+                let auditor = CheckpointAuditor::new(
+                    checkpoint_syncer.clone(),
+                    Arc::new(db.clone()),
+                    signer.eth_address(),
+                    merkle_tree_hook(&domain, &canonical_leaves),
+                    ReorgPeriod::from_blocks(1),
+                )
+                .with_repair(
+                    signer.clone(),
+                    SlashingProtectionDb::new(slashing_protection_db),
+                );
+                write_checkpoints(&auditor, &db, &leaves, &signer).await;
+
+                let report = auditor.audit().await.unwrap();
+                assert!(report.repaired.is_empty());
+                assert_eq!(report.gaps, vec![1]);
+                let reorg_event = report.reorg.unwrap();
+                assert_eq!(reorg_event.checkpoint_index, 3);
+                assert_eq!(
+                    checkpoint_syncer.reorg_status().await.unwrap(),
+                    Some(reorg_event.clone())
+                );
+
+                // the flagged reorg keeps later audits from filling gaps too
+                let report = auditor.audit().await.unwrap();
+                assert!(report.repaired.is_empty());
+                assert_eq!(report.reorg, Some(reorg_event));
+            })
+            .await;
+            signer_task.abort();
+        })
+        .await;
+    }
+}
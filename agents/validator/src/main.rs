@@ -9,8 +9,10 @@ use aetherium_base::agent_main;
 
 use crate::validator::Validator;
 
+mod checkpoint_audit;
 mod server;
 mod settings;
+mod slashing_protection;
 mod submit;
 mod validator;
 
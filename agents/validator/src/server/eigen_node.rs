@@ -52,7 +52,7 @@ struct Service {
 
 #[derive(new)]
 pub struct EigenNodeApi {
-    origin_chain: AetheriumDomain,
+    origin_chains: Vec<AetheriumDomain>,
     core_metrics: Arc<CoreMetrics>,
 }
 
@@ -63,12 +63,12 @@ impl EigenNodeApi {
 
     pub fn router(&self) -> Router {
         let core_metrics_clone = self.core_metrics.clone();
-        let origin_chain = self.origin_chain.clone();
+        let origin_chains = self.origin_chains.clone();
 
         tracing::info!("Serving the EigenNodeAPI routes...");
 
         let health_route = get(move || {
-            Self::node_health_handler(origin_chain.clone(), core_metrics_clone.clone())
+            Self::node_health_handler(origin_chains.clone(), core_metrics_clone.clone())
         });
         let services_route = Router::new()
             .route("/", get(Self::node_services_handler))
@@ -91,15 +91,19 @@ impl EigenNodeApi {
         Json(node_info)
     }
 
-    /// Method to return the NodeInfo data
+    /// Method to return the NodeInfo data, from the origin chain that lags the most
     /// if signed_checkpoint - observed_checkpoint <= 1 return 200 - healthy
     /// else if observed_checkpoint - signed_checkpoint <= 10 return 203 - partially healthy
     /// else return 503 - unhealthy
     pub async fn node_health_handler(
-        origin_chain: AetheriumDomain,
+        origin_chains: Vec<AetheriumDomain>,
         core_metrics: Arc<CoreMetrics>,
     ) -> impl IntoResponse {
-        let checkpoint_delta = core_metrics.get_latest_checkpoint_validator_delta(origin_chain);
+        let checkpoint_delta = origin_chains
+            .into_iter()
+            .map(|origin_chain| core_metrics.get_latest_checkpoint_validator_delta(origin_chain))
+            .max()
+            .unwrap_or_default();
 
         // logic to check if the node is healthy
         if checkpoint_delta <= 1 {
@@ -163,7 +167,7 @@ mod tests {
             .set(HEALTHY_OBSERVED_CHECKPOINT);
 
         let node_api = EigenNodeApi::new(
-            AetheriumDomain::new_test_domain("ethereum"),
+            vec![AetheriumDomain::new_test_domain("ethereum")],
             Arc::clone(&core_metrics),
         );
         let app = node_api.router();
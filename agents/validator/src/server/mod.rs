@@ -1,19 +1,29 @@
 pub mod eigen_node;
+pub mod slashing_protection;
 use std::{sync::Arc, vec};
 
 use axum::Router;
 pub use eigen_node::EigenNodeApi;
+pub use slashing_protection::SlashingProtectionApi;
 
 use aetherium_base::CoreMetrics;
-use aetherium_core::AetheriumDomain;
+use aetherium_core::{AetheriumDomain, H160};
+
+use crate::slashing_protection::SlashingProtectionDb;
 
 /// Returns a vector of validator-specific endpoint routes to be served.
 /// Can be extended with additional routes and feature flags to enable/disable individually.
 pub fn routes(
-    origin_chain: AetheriumDomain,
+    origin_chains: Vec<AetheriumDomain>,
     metrics: Arc<CoreMetrics>,
+    slashing_protection: SlashingProtectionDb,
+    validator: H160,
 ) -> Vec<(&'static str, Router)> {
-    let eigen_node_api = EigenNodeApi::new(origin_chain, metrics);
+    let eigen_node_api = EigenNodeApi::new(origin_chains, metrics);
+    let slashing_protection_api = SlashingProtectionApi::new(slashing_protection, validator);
 
-    vec![eigen_node_api.get_route()]
+    vec![
+        eigen_node_api.get_route(),
+        slashing_protection_api.get_route(),
+    ]
 }
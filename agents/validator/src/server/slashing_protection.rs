@@ -0,0 +1,39 @@
+//! Serves the slashing protection history of the validator, in the interchange format that the
+//! `slashingProtection.import` setting of another validator host accepts.
+//!
+//! Base URL /slashing_protection
+//! Routes
+//! - / - The history of every signed checkpoint
+
+use aetherium_core::H160;
+use axum::{http::StatusCode, response::IntoResponse, routing::get, Json, Router};
+use derive_new::new;
+
+use crate::slashing_protection::SlashingProtectionDb;
+
+const SLASHING_PROTECTION_API_BASE: &str = "/slashing_protection";
+
+#[derive(new)]
+pub struct SlashingProtectionApi {
+    db: SlashingProtectionDb,
+    validator: H160,
+}
+
+impl SlashingProtectionApi {
+    pub fn get_route(&self) -> (&'static str, Router) {
+        let db = self.db.clone();
+        let validator = self.validator;
+        let router = Router::new().route(
+            "/",
+            get(move || async move {
+                match db.export(validator) {
+                    Ok(interchange) => Json(interchange).into_response(),
+                    Err(err) => {
+                        (StatusCode::INTERNAL_SERVER_ERROR, err.to_string()).into_response()
+                    }
+                }
+            }),
+        );
+        (SLASHING_PROTECTION_API_BASE, router)
+    }
+}
@@ -21,6 +21,8 @@ use aetherium_core::{
 use serde::Deserialize;
 use serde_json::Value;
 
+use crate::checkpoint_audit::AuditMode;
+
 /// Settings for `Validator`
 #[derive(Debug, AsRef, AsMut, Deref, DerefMut)]
 pub struct ValidatorSettings {
@@ -32,16 +34,32 @@ pub struct ValidatorSettings {
 
     /// Database path
     pub db: PathBuf,
-    /// Chain to validate messages on
-    pub origin_chain: AetheriumDomain,
+    /// Path of the slashing protection database. It's kept apart from the main database so that
+    /// wiping the latter doesn't lose the signing history. Defaults to a sibling of the main
+    /// database.
+    pub slashing_protection_db: PathBuf,
+    /// Signing history to import into the slashing protection database on startup
+    pub slashing_protection_import: Option<PathBuf>,
+    /// Chains to validate messages on
+    pub origin_chains: Vec<OriginChainConf>,
     /// The validator attestation signer
     pub validator: SignerConf,
+    /// How frequently to check for new checkpoints
+    pub interval: Duration,
+    /// If set, audit the checkpoint syncers of the origin chains instead of validating, and exit
+    /// with a non-zero code unless every checkpoint syncer is healthy
+    pub audit: Option<AuditMode>,
+}
+
+/// Settings of a chain the validator validates messages on
+#[derive(Debug, Clone)]
+pub struct OriginChainConf {
+    /// The chain
+    pub domain: AetheriumDomain,
     /// The checkpoint syncer configuration
     pub checkpoint_syncer: CheckpointSyncerConf,
     /// The reorg configuration
     pub reorg_period: ReorgPeriod,
-    /// How frequently to check for new checkpoints
-    pub interval: Duration,
 }
 
 #[derive(Debug, Deserialize)]
@@ -60,29 +78,46 @@ impl FromRawConf<RawValidatorSettings> for ValidatorSettings {
 
         let p = ValueParser::new(cwp.clone(), &raw.0);
 
+        let origins = parse_origins(&p, &mut err);
+        // only used to name the default db, its errors are reported by `parse_origins`
         let origin_chain_name = p
-            .chain(&mut err)
-            .get_key("originChainName")
-            .parse_string()
-            .end();
-
-        let origin_chain_name_set = origin_chain_name.map(|s| HashSet::from([s]));
+            .get_opt_key("originChainName")
+            .ok()
+            .flatten()
+            .and_then(|name| name.parse_string().ok());
+        let origin_chain_names: HashSet<&str> = origins.iter().map(|(name, _)| *name).collect();
 
         let base: Option<Settings> = p
             .parse_from_raw_config::<Settings, RawAgentConf, Option<&HashSet<&str>>>(
-                origin_chain_name_set.as_ref(),
+                Some(&origin_chain_names),
                 "Expected valid base agent configuration",
             )
             .take_config_err(&mut err);
 
-        let origin_chain = if let (Some(base), Some(origin_chain_name)) = (&base, origin_chain_name)
-        {
-            base.lookup_domain(origin_chain_name)
-                .context("Missing configuration for the origin chain")
-                .take_err(&mut err, || cwp + "origin_chain_name")
-        } else {
-            None
-        };
+        let origin_chains = origins
+            .iter()
+            .filter_map(|(name, origin)| {
+                let domain = base.as_ref().and_then(|base| {
+                    base.lookup_domain(name)
+                        .context("Missing configuration for the origin chain")
+                        .take_err(&mut err, || &origin.cwp + "name")
+                });
+
+                let checkpoint_syncer = origin
+                    .chain(&mut err)
+                    .get_key("checkpointSyncer")
+                    .and_then(parse_checkpoint_syncer)
+                    .end();
+
+                let reorg_period = parse_reorg_period(&p, origin, name, &mut err);
+
+                Some(OriginChainConf {
+                    domain: domain?,
+                    checkpoint_syncer: checkpoint_syncer?,
+                    reorg_period,
+                })
+            })
+            .collect::<Vec<_>>();
 
         let validator = p
             .chain(&mut err)
@@ -98,15 +133,30 @@ impl FromRawConf<RawValidatorSettings> for ValidatorSettings {
             .get_opt_key("db")
             .parse_from_str("Expected db file path")
             .unwrap_or_else(|| {
-                std::env::current_dir()
-                    .unwrap()
-                    .join(format!("validator_db_{}", origin_chain_name.unwrap_or("")))
+                let db_name = match origin_chain_name {
+                    Some(origin_chain_name) => format!("validator_db_{origin_chain_name}"),
+                    None => "validator_db".to_owned(),
+                };
+                std::env::current_dir().unwrap().join(db_name)
             });
 
-        let checkpoint_syncer = p
+        let slashing_protection_db = p
             .chain(&mut err)
-            .get_key("checkpointSyncer")
-            .and_then(parse_checkpoint_syncer)
+            .get_opt_key("slashingProtection")
+            .get_opt_key("db")
+            .parse_from_str("Expected slashing protection db file path")
+            .unwrap_or_else(|| {
+                // next to the db, so that it ends up on the same volume without being wiped with it
+                let mut name = db.file_name().unwrap_or_default().to_owned();
+                name.push("_slashing_protection");
+                db.with_file_name(name)
+            });
+
+        let slashing_protection_import = p
+            .chain(&mut err)
+            .get_opt_key("slashingProtection")
+            .get_opt_key("import")
+            .parse_from_str("Expected slashing protection import file path")
             .end();
 
         let interval = p
@@ -116,37 +166,109 @@ impl FromRawConf<RawValidatorSettings> for ValidatorSettings {
             .map(Duration::from_secs)
             .unwrap_or(Duration::from_secs(5));
 
-        cfg_unwrap_all!(cwp, err: [origin_chain_name]);
-
-        let reorg_period = p
+        let audit = p
             .chain(&mut err)
-            .get_key("chains")
-            .get_key(origin_chain_name)
-            .get_opt_key("blocks")
-            .get_opt_key("reorgPeriod")
-            .parse_value("Invalid reorgPeriod")
-            .unwrap_or(ReorgPeriod::from_blocks(1));
+            .get_opt_key("audit")
+            .parse_value("Invalid audit mode, expected `check` or `repair`")
+            .end();
 
-        cfg_unwrap_all!(cwp, err: [base, origin_chain, validator, checkpoint_syncer]);
+        cfg_unwrap_all!(cwp, err: [base, validator]);
 
         let mut base: Settings = base;
-        // If the origin chain is an EVM chain, then we can use the validator as the signer if needed.
-        if origin_chain.domain_protocol() == AetheriumDomainProtocol::Ethereum {
-            if let Some(origin) = base.chains.get_mut(origin_chain.name()) {
-                origin.signer.get_or_insert_with(|| validator.clone());
+        // If an origin chain is an EVM chain, then we can use the validator as the signer if needed.
+        // Remote signers only sign messages, so they can't sign the announcement transaction.
+        let validator_signs_txs = !matches!(validator, SignerConf::Remote { .. });
+        for origin_chain in &origin_chains {
+            if validator_signs_txs
+                && origin_chain.domain.domain_protocol() == AetheriumDomainProtocol::Ethereum
+            {
+                if let Some(origin) = base.chains.get_mut(origin_chain.domain.name()) {
+                    origin.signer.get_or_insert_with(|| validator.clone());
+                }
             }
         }
 
         err.into_result(Self {
             base,
             db,
-            origin_chain,
+            slashing_protection_db,
+            slashing_protection_import,
+            origin_chains,
             validator,
-            checkpoint_syncer,
-            reorg_period,
             interval,
+            audit,
+        })
+    }
+}
+
+/// The origin chains by name, with the configuration of each. Either a single `originChainName`,
+/// whose checkpoint syncer is configured at the top level, or a list of `originChains` that each
+/// configure their own.
+fn parse_origins<'v>(
+    p: &ValueParser<'v>,
+    err: &mut ConfigParsingError,
+) -> Vec<(&'v str, ValueParser<'v>)> {
+    let origin_chain_name = p
+        .chain(err)
+        .get_opt_key("originChainName")
+        .parse_string()
+        .end();
+    let mut origins: Vec<(&str, ValueParser)> = p
+        .chain(err)
+        .get_opt_key("originChains")
+        .into_array_iter()
+        .map(|itr| {
+            itr.filter_map(|origin| {
+                let name = origin.chain(err).get_key("name").parse_string().end()?;
+                Some((name, origin))
+            })
+            .collect()
         })
+        .unwrap_or_default();
+    match (origin_chain_name, origins.is_empty()) {
+        (Some(origin_chain_name), true) => origins.push((origin_chain_name, p.clone())),
+        (Some(_), false) => err.push(
+            &p.cwp + "origin_chains",
+            eyre!("Expected either `originChainName` or `originChains`, not both"),
+        ),
+        (None, true) => err.push(
+            &p.cwp + "origin_chain_name",
+            eyre!("Expected `originChainName` or `originChains` to be defined"),
+        ),
+        (None, false) => {}
     }
+
+    let origin_chain_names: HashSet<&str> = origins.iter().map(|(name, _)| *name).collect();
+    if origin_chain_names.len() < origins.len() {
+        err.push(
+            &p.cwp + "origin_chains",
+            eyre!("Expected every origin chain to be listed once"),
+        );
+    }
+    origins
+}
+
+/// The reorg period of an origin chain, falling back to the one of its chain configuration
+fn parse_reorg_period(
+    p: &ValueParser,
+    origin: &ValueParser,
+    name: &str,
+    err: &mut ConfigParsingError,
+) -> ReorgPeriod {
+    origin
+        .chain(err)
+        .get_opt_key("reorgPeriod")
+        .parse_value("Invalid reorgPeriod")
+        .end()
+        .unwrap_or_else(|| {
+            p.chain(err)
+                .get_key("chains")
+                .get_key(name)
+                .get_opt_key("blocks")
+                .get_opt_key("reorgPeriod")
+                .parse_value("Invalid reorgPeriod")
+                .unwrap_or(ReorgPeriod::from_blocks(1))
+        })
 }
 
 /// Expects ValidatorAgentConfig.checkpointSyncer
@@ -224,9 +346,118 @@ fn parse_checkpoint_syncer(syncer: ValueParser) -> ConfigResult<CheckpointSyncer
                 user_secrets,
             })
         }
+        Some("composite") => {
+            let syncers = syncer
+                .chain(&mut err)
+                .get_key("syncers")
+                .into_array_iter()
+                .map(|replicas| {
+                    replicas
+                        .filter_map(|replica| {
+                            parse_checkpoint_syncer(replica).take_config_err(&mut err)
+                        })
+                        .collect::<Vec<_>>()
+                });
+            cfg_unwrap_all!(&syncer.cwp, err: [syncers]);
+            if syncers.is_empty() {
+                return Err(eyre!("Expected at least one checkpoint syncer"))
+                    .into_config_result(|| &syncer.cwp + "syncers");
+            }
+
+            let write_quorum = match syncer.chain(&mut err).get_opt_key("writeQuorum").end() {
+                Some(quorum) => {
+                    let write_quorum = quorum.chain(&mut err).parse_u64().end();
+                    if write_quorum.is_some_and(|q| !(1..=syncers.len() as u64).contains(&q)) {
+                        // reported under the path of the key, like its parsing errors
+                        return Err(eyre!(
+                            "Expected a write quorum between 1 and the number of checkpoint syncers"
+                        ))
+                        .into_config_result(|| quorum.cwp.clone());
+                    }
+                    write_quorum.map(|quorum| quorum as usize)
+                }
+                // a majority of the replicas by default, so that a minority of them can be down
+                None => Some(syncers.len() / 2 + 1),
+            };
+            cfg_unwrap_all!(&syncer.cwp, err: [write_quorum]);
+
+            err.into_result(CheckpointSyncerConf::Composite {
+                syncers,
+                write_quorum,
+            })
+        }
         Some(_) => {
             Err(eyre!("Unknown checkpoint syncer type")).into_config_result(|| &syncer.cwp + "type")
         }
         None => Err(err),
     }
 }
+
+#[cfg(test)]
+mod test {
+    use serde_json::json;
+
+    use super::*;
+
+    fn origin_names(raw: &Value) -> (Vec<&str>, ConfigParsingError) {
+        let mut err = ConfigParsingError::default();
+        let p = ValueParser::new(ConfigPath::default(), raw);
+        let origins = parse_origins(&p, &mut err);
+        (origins.into_iter().map(|(name, _)| name).collect(), err)
+    }
+
+    #[test]
+    fn test_parse_origins() {
+        let raw = json!({ "originchainname": "ethereum" });
+        let (names, err) = origin_names(&raw);
+        assert_eq!(names, vec!["ethereum"]);
+        assert!(err.is_ok());
+
+        let raw = json!({ "originchains": [{ "name": "ethereum" }, { "name": "optimism" }] });
+        let (names, err) = origin_names(&raw);
+        assert_eq!(names, vec!["ethereum", "optimism"]);
+        assert!(err.is_ok());
+
+        let raw = json!({
+            "originchainname": "ethereum",
+            "originchains": [{ "name": "optimism" }],
+        });
+        assert!(!origin_names(&raw).1.is_ok());
+
+        let raw = json!({ "originchains": [{ "name": "ethereum" }, { "name": "ethereum" }] });
+        assert!(!origin_names(&raw).1.is_ok());
+
+        assert!(!origin_names(&json!({})).1.is_ok());
+    }
+
+    #[test]
+    fn test_reorg_period_falls_back_to_the_chain_config() {
+        let raw = json!({
+            "originchains": [
+                { "name": "ethereum", "reorgperiod": 20 },
+                { "name": "optimism" },
+                { "name": "arbitrum" },
+            ],
+            "chains": {
+                "ethereum": { "blocks": { "reorgperiod": 10 } },
+                "optimism": { "blocks": { "reorgperiod": 5 } },
+                "arbitrum": {},
+            },
+        });
+        let mut err = ConfigParsingError::default();
+        let p = ValueParser::new(ConfigPath::default(), &raw);
+        let reorg_periods = parse_origins(&p, &mut err)
+            .iter()
+            .map(|(name, origin)| parse_reorg_period(&p, origin, name, &mut err))
+            .collect::<Vec<_>>();
+        assert!(err.is_ok());
+        assert_eq!(
+            reorg_periods,
+            vec![
+                ReorgPeriod::from_blocks(20),
+                ReorgPeriod::from_blocks(5),
+                ReorgPeriod::from_blocks(1),
+            ]
+        );
+    }
+}
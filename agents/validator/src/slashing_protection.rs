@@ -0,0 +1,267 @@
+//! Slashing protection for the checkpoints the validator signs.
+//!
+//! Every checkpoint is recorded before it's signed, and a checkpoint that conflicts with a
+//! recorded one, i.e. has the same merkle tree hook and index but another root or message id,
+//! is never signed. The history can be exported and imported in the interchange format of
+//! [`SlashingProtectionInterchange`], to move a validator key between hosts.
+
+use std::{
+    collections::HashMap,
+    path::Path,
+    sync::{Arc, Mutex},
+};
+
+use aetherium_base::db::DB;
+use aetherium_core::{CheckpointWithMessageId, H160, H256};
+use eyre::{bail, ensure, Context, Result};
+use serde::{Deserialize, Serialize};
+
+const SIGNED_CHECKPOINT: &[u8] = b"signed_checkpoint_";
+
+/// The version of the interchange format
+pub const INTERCHANGE_VERSION: u32 = 1;
+
+/// A checkpoint the validator signed
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SignedCheckpointRecord {
+    /// The mailbox / merkle tree hook domain
+    pub mailbox_domain: u32,
+    /// The merkle tree hook address
+    pub merkle_tree_hook_address: H256,
+    /// The index of the checkpoint
+    pub index: u32,
+    /// The checkpointed root
+    pub root: H256,
+    /// The id of the message at the index
+    pub message_id: H256,
+}
+
+impl From<&CheckpointWithMessageId> for SignedCheckpointRecord {
+    fn from(checkpoint: &CheckpointWithMessageId) -> Self {
+        Self {
+            mailbox_domain: checkpoint.mailbox_domain,
+            merkle_tree_hook_address: checkpoint.merkle_tree_hook_address,
+            index: checkpoint.index,
+            root: checkpoint.root,
+            message_id: checkpoint.message_id,
+        }
+    }
+}
+
+impl SignedCheckpointRecord {
+    fn key(&self) -> Vec<u8> {
+        SIGNED_CHECKPOINT
+            .iter()
+            .copied()
+            .chain(self.mailbox_domain.to_be_bytes())
+            .chain(self.merkle_tree_hook_address.to_fixed_bytes())
+            .chain(self.index.to_be_bytes())
+            .collect()
+    }
+
+    fn conflicts_with(&self, other: &Self) -> bool {
+        self.root != other.root || self.message_id != other.message_id
+    }
+}
+
+/// The signing history of a validator, as exported and imported between hosts
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SlashingProtectionInterchange {
+    /// The version of the format, [`INTERCHANGE_VERSION`]
+    pub version: u32,
+    /// The address of the validator that signed the checkpoints
+    pub validator: H160,
+    /// The signed checkpoints
+    pub checkpoints: Vec<SignedCheckpointRecord>,
+}
+
+/// A database of the checkpoints the validator signed, shared by every origin chain
+#[derive(Debug, Clone)]
+pub struct SlashingProtectionDb {
+    db: DB,
+    /// Makes checking for a conflict and recording a checkpoint atomic
+    lock: Arc<Mutex<()>>,
+}
+
+impl SlashingProtectionDb {
+    /// Uses `db`, which should be dedicated to slashing protection
+    pub fn new(db: DB) -> Self {
+        Self {
+            db,
+            lock: Default::default(),
+        }
+    }
+
+    /// Records `checkpoint` as signed, unless it conflicts with a checkpoint signed before, in
+    /// which case it must not be signed. Recording a checkpoint again is a no-op.
+    pub fn check_and_record(&self, checkpoint: &CheckpointWithMessageId) -> Result<()> {
+        let record = SignedCheckpointRecord::from(checkpoint);
+        let _guard = self.lock.lock().unwrap_or_else(|e| e.into_inner());
+        if let Some(signed) = self.retrieve(&record)? {
+            ensure!(
+                !signed.conflicts_with(&record),
+                "Checkpoint {record:?} conflicts with the signed checkpoint {signed:?}"
+            );
+            return Ok(());
+        }
+        self.store(&record)
+    }
+
+    /// Every signed checkpoint, ordered by domain, merkle tree hook and index
+    pub fn export(&self, validator: H160) -> Result<SlashingProtectionInterchange> {
+        let checkpoints = self
+            .db
+            .retrieve_by_prefix(SIGNED_CHECKPOINT)?
+            .into_iter()
+            .map(|value| serde_json::from_slice(&value).map_err(Into::into))
+            .collect::<Result<Vec<_>>>()?;
+        Ok(SlashingProtectionInterchange {
+            version: INTERCHANGE_VERSION,
+            validator,
+            checkpoints,
+        })
+    }
+
+    /// Merges the history in `interchange` into the database, returning how many checkpoints
+    /// were new. Nothing is imported if any checkpoint conflicts with the history.
+    pub fn import(
+        &self,
+        interchange: &SlashingProtectionInterchange,
+        validator: H160,
+    ) -> Result<usize> {
+        ensure!(
+            interchange.version == INTERCHANGE_VERSION,
+            "Unsupported slashing protection interchange version {}",
+            interchange.version
+        );
+        ensure!(
+            interchange.validator == validator,
+            "The slashing protection history is of validator {:?}, not {validator:?}",
+            interchange.validator
+        );
+
+        let _guard = self.lock.lock().unwrap_or_else(|e| e.into_inner());
+        let mut new = HashMap::new();
+        for record in &interchange.checkpoints {
+            let signed = match new.get(&record.key()) {
+                Some(signed) => Some(*signed),
+                None => self.retrieve(record)?,
+            };
+            match signed {
+                Some(signed) if signed.conflicts_with(record) => {
+                    bail!("Checkpoint {record:?} conflicts with the signed checkpoint {signed:?}")
+                }
+                Some(_) => {}
+                None => {
+                    new.insert(record.key(), *record);
+                }
+            }
+        }
+        for record in new.values() {
+            self.store(record)?;
+        }
+        Ok(new.len())
+    }
+
+    /// Imports the history in the interchange file at `path`
+    pub fn import_file(&self, path: &Path, validator: H160) -> Result<usize> {
+        let file = std::fs::File::open(path)
+            .with_context(|| format!("Failed to open {}", path.display()))?;
+        let interchange =
+            serde_json::from_reader(std::io::BufReader::new(file)).with_context(|| {
+                format!("Invalid slashing protection history in {}", path.display())
+            })?;
+        self.import(&interchange, validator)
+    }
+
+    fn retrieve(&self, record: &SignedCheckpointRecord) -> Result<Option<SignedCheckpointRecord>> {
+        self.db
+            .retrieve(&record.key())?
+            .map(|value| serde_json::from_slice(&value))
+            .transpose()
+            .map_err(Into::into)
+    }
+
+    fn store(&self, record: &SignedCheckpointRecord) -> Result<()> {
+        Ok(self.db.store(&record.key(), &serde_json::to_vec(record)?)?)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use aetherium_base::db::test_utils;
+    use aetherium_core::Checkpoint;
+
+    use super::*;
+
+    fn checkpoint(index: u32, message_id: H256) -> CheckpointWithMessageId {
+        CheckpointWithMessageId {
+            checkpoint: Checkpoint {
+                merkle_tree_hook_address: H256::repeat_byte(1),
+                mailbox_domain: 1,
+                root: H256::repeat_byte(2),
+                index,
+            },
+            message_id,
+        }
+    }
+
+    #[tokio::test]
+    async fn test_conflicting_checkpoints_are_refused() {
+        test_utils::run_test_db(|db| async move {
+            let db = SlashingProtectionDb::new(db);
+            let signed = checkpoint(3, H256::repeat_byte(3));
+            db.check_and_record(&signed).unwrap();
+            db.check_and_record(&signed).unwrap();
+            assert!(db
+                .check_and_record(&checkpoint(3, H256::repeat_byte(4)))
+                .is_err());
+            db.check_and_record(&checkpoint(4, H256::repeat_byte(4)))
+                .unwrap();
+        })
+        .await;
+    }
+
+    #[tokio::test]
+    async fn test_history_moves_between_hosts() {
+        let validator = H160::repeat_byte(1);
+        let mut exported = None;
+        let exported_ref = &mut exported;
+        test_utils::run_test_db(|db| async move {
+            let db = SlashingProtectionDb::new(db);
+            db.check_and_record(&checkpoint(4, H256::repeat_byte(4)))
+                .unwrap();
+            db.check_and_record(&checkpoint(3, H256::repeat_byte(3)))
+                .unwrap();
+            *exported_ref = Some(db.export(validator).unwrap());
+        })
+        .await;
+        let exported = exported.unwrap();
+        assert_eq!(
+            exported
+                .checkpoints
+                .iter()
+                .map(|record| record.index)
+                .collect::<Vec<_>>(),
+            vec![3, 4]
+        );
+
+        test_utils::run_test_db(|db| async move {
+            let db = SlashingProtectionDb::new(db);
+            db.check_and_record(&checkpoint(3, H256::repeat_byte(3)))
+                .unwrap();
+            assert_eq!(db.import(&exported, validator).unwrap(), 1);
+            assert!(db
+                .check_and_record(&checkpoint(4, H256::repeat_byte(5)))
+                .is_err());
+            assert!(db.import(&exported, H160::repeat_byte(2)).is_err());
+
+            let mut conflicting = exported.clone();
+            conflicting.checkpoints[0].root = H256::zero();
+            assert!(db.import(&conflicting, validator).is_err());
+        })
+        .await;
+    }
+}
@@ -16,6 +16,8 @@ use aetherium_core::{
 use aetherium_core::{ChainResult, MerkleTreeHook, ReorgEvent, ReorgPeriod};
 use aetherium_ethereum::SingletonSignerHandle;
 
+use crate::slashing_protection::SlashingProtectionDb;
+
 #[derive(Clone)]
 pub(crate) struct ValidatorSubmitter {
     interval: Duration,
@@ -24,6 +26,7 @@ pub(crate) struct ValidatorSubmitter {
     merkle_tree_hook: Arc<dyn MerkleTreeHook>,
     checkpoint_syncer: Arc<dyn CheckpointSyncer>,
     db: Arc<dyn AetheriumDb>,
+    slashing_protection: SlashingProtectionDb,
     metrics: ValidatorSubmitterMetrics,
 }
 
@@ -35,6 +38,7 @@ impl ValidatorSubmitter {
         signer: SingletonSignerHandle,
         checkpoint_syncer: Arc<dyn CheckpointSyncer>,
         db: Arc<dyn AetheriumDb>,
+        slashing_protection: SlashingProtectionDb,
         metrics: ValidatorSubmitterMetrics,
     ) -> Self {
         Self {
@@ -44,6 +48,7 @@ impl ValidatorSubmitter {
             signer,
             checkpoint_syncer,
             db,
+            slashing_protection,
             metrics,
         }
     }
@@ -280,6 +285,19 @@ impl ValidatorSubmitter {
         // since those are the most likely to make messages become processable.
         // A side effect is that new checkpoints will also be submitted in reverse order.
         for queued_checkpoint in checkpoints.into_iter().rev() {
+            // Never sign a checkpoint that conflicts with one signed before, e.g. after the db was
+            // wiped or a reorg went unnoticed, since that's what gets a validator slashed.
+            if let Err(err) = self
+                .slashing_protection
+                .check_and_record(&queued_checkpoint)
+            {
+                error!(
+                    ?queued_checkpoint,
+                    ?err,
+                    "Refusing to sign checkpoint, slashing protection failed"
+                );
+                panic!("Refusing to sign checkpoint: {err}");
+            }
             // certain checkpoint stores rate limit very aggressively, so we retry indefinitely
             call_and_retry_indefinitely(|| {
                 let self_clone = self.clone();
@@ -340,14 +358,18 @@ mod test {
     use async_trait::async_trait;
     use eyre::Result;
     use aetherium_base::{
-        db::{DbResult, AetheriumDb, InterchainGasExpenditureData, InterchainGasPaymentData},
+        db::{
+            test_utils, DbResult, AetheriumDb, InterchainGasExpenditureData,
+            InterchainGasPaymentData,
+        },
         AgentMetadata,
     };
     use aetherium_core::{
-        test_utils::dummy_domain, GasPaymentKey, AetheriumChain, AetheriumContract,
+        test_utils::dummy_domain, DeadLetter, GasPaymentKey, AetheriumChain, AetheriumContract,
         AetheriumDomain, AetheriumMessage, AetheriumProvider, InterchainGasPayment,
-        InterchainGasPaymentMeta, MerkleTreeHook, MerkleTreeInsertion, PendingOperationStatus,
-        ReorgEvent, SignedAnnouncement, SignedCheckpointWithMessageId, H160, H256,
+        InterchainGasPaymentMeta, MerkleTreeHook, MerkleTreeInsertion, OperationAttempt,
+        PendingOperationStatus, ReorgEvent, SignedAnnouncement, SignedCheckpointWithMessageId,
+        H160, H256, U256,
     };
     use prometheus::Registry;
     use std::{fmt::Debug, sync::Arc, time::Duration};
@@ -438,6 +460,50 @@ mod test {
                 &self,
                 message_id: &H256,
             ) -> DbResult<Option<u32>>;
+            fn store_pending_message_last_error_by_message_id(
+                &self,
+                message_id: &H256,
+                error: &str,
+            ) -> DbResult<()>;
+            fn retrieve_pending_message_last_error_by_message_id(
+                &self,
+                message_id: &H256,
+            ) -> DbResult<Option<String>>;
+            fn delete_pending_message_last_error_by_message_id(
+                &self,
+                message_id: &H256,
+            ) -> DbResult<()>;
+            fn store_pending_message_attempts_by_message_id(
+                &self,
+                message_id: &H256,
+                attempts: &[OperationAttempt],
+            ) -> DbResult<()>;
+            fn retrieve_pending_message_attempts_by_message_id(
+                &self,
+                message_id: &H256,
+            ) -> DbResult<Option<Vec<OperationAttempt>>>;
+            fn store_pending_message_gas_limit_override_by_message_id(
+                &self,
+                message_id: &H256,
+                gas_limit: &U256,
+            ) -> DbResult<()>;
+            fn retrieve_pending_message_gas_limit_override_by_message_id(
+                &self,
+                message_id: &H256,
+            ) -> DbResult<Option<U256>>;
+            fn delete_pending_message_gas_limit_override_by_message_id(
+                &self,
+                message_id: &H256,
+            ) -> DbResult<()>;
+            fn store_dead_letter_by_message_id(
+                &self,
+                message_id: &H256,
+                dead_letter: &DeadLetter,
+            ) -> DbResult<()>;
+            fn retrieve_dead_letter_by_message_id(
+                &self,
+                message_id: &H256,
+            ) -> DbResult<Option<DeadLetter>>;
             fn store_merkle_tree_insertion_by_leaf_index(
                 &self,
                 leaf_index: &u32,
@@ -620,17 +686,6 @@ mod test {
                 Ok(())
             });
 
-        // instantiate the validator submitter
-        let validator_submitter = ValidatorSubmitter::new(
-            Duration::from_secs(1),
-            ReorgPeriod::from_blocks(expected_reorg_period),
-            Arc::new(mock_merkle_tree_hook),
-            dummy_singleton_handle(),
-            Arc::new(mock_checkpoint_syncer),
-            Arc::new(db),
-            dummy_metrics(),
-        );
-
         // mock the correctness checkpoint response
         let mock_onchain_checkpoint = Checkpoint {
             root: mock_onchain_merkle_tree.root(),
@@ -639,13 +694,66 @@ mod test {
             mailbox_domain: dummy_domain.id(),
         };
 
-        // Start the submitter with an empty merkle tree, so it gets rebuilt from the db.
-        // A panic is expected here, as the merkle root inconsistency is a critical error that may indicate fraud.
-        validator_submitter
-            .submit_checkpoints_until_correctness_checkpoint(
-                &mut IncrementalMerkle::default(),
-                &mock_onchain_checkpoint,
-            )
-            .await;
+        test_utils::run_test_db(|slashing_protection_db| async move {
+            // instantiate the validator submitter
+            let validator_submitter = ValidatorSubmitter::new(
+                Duration::from_secs(1),
+                ReorgPeriod::from_blocks(expected_reorg_period),
+                Arc::new(mock_merkle_tree_hook),
+                dummy_singleton_handle(),
+                Arc::new(mock_checkpoint_syncer),
+                Arc::new(db),
+                SlashingProtectionDb::new(slashing_protection_db),
+                dummy_metrics(),
+            );
+
+            // Start the submitter with an empty merkle tree, so it gets rebuilt from the db.
+            // A panic is expected here, as the merkle root inconsistency is a critical error that may indicate fraud.
+            validator_submitter
+                .submit_checkpoints_until_correctness_checkpoint(
+                    &mut IncrementalMerkle::default(),
+                    &mock_onchain_checkpoint,
+                )
+                .await;
+        })
+        .await;
+    }
+
+    #[tokio::test]
+    #[should_panic(expected = "Refusing to sign checkpoint")]
+    async fn conflicting_checkpoint_is_not_signed() {
+        let checkpoint = |root| CheckpointWithMessageId {
+            checkpoint: Checkpoint {
+                root,
+                index: 2,
+                merkle_tree_hook_address: H256::from_low_u64_be(0),
+                mailbox_domain: 0,
+            },
+            message_id: H256::repeat_byte(1),
+        };
+
+        test_utils::run_test_db(|slashing_protection_db| async move {
+            let slashing_protection = SlashingProtectionDb::new(slashing_protection_db);
+            slashing_protection
+                .check_and_record(&checkpoint(H256::repeat_byte(2)))
+                .unwrap();
+            // the checkpoint syncer and the signer aren't expected to be called, so that signing or
+            // submitting the checkpoint fails the test with another panic
+            let validator_submitter = ValidatorSubmitter::new(
+                Duration::from_secs(1),
+                ReorgPeriod::from_blocks(1),
+                Arc::new(MockMerkleTreeHook::new()),
+                dummy_singleton_handle(),
+                Arc::new(MockCheckpointSyncer::new()),
+                Arc::new(MockDb::new()),
+                slashing_protection,
+                dummy_metrics(),
+            );
+
+            validator_submitter
+                .sign_and_submit_checkpoints(vec![checkpoint(H256::repeat_byte(3))])
+                .await;
+        })
+        .await;
     }
 }
@@ -3,9 +3,9 @@ use std::{sync::Arc, time::Duration};
 use crate::server as validator_server;
 use async_trait::async_trait;
 use derive_more::AsRef;
-use eyre::Result;
+use eyre::{bail, Context, Result};
 
-use futures_util::future::try_join_all;
+use futures_util::future::{join_all, try_join_all};
 use tokio::{task::JoinHandle, time::sleep};
 use tracing::{error, info, info_span, warn, Instrument};
 
@@ -14,45 +14,62 @@ use aetherium_base::{
     metrics::AgentMetrics,
     settings::ChainConf,
     AgentMetadata, BaseAgent, ChainMetrics, ChainSpecificMetricsUpdater, CheckpointSyncer,
-    ContractSyncMetrics, ContractSyncer, CoreMetrics, AetheriumAgentCore, RuntimeMetrics,
-    SequencedDataContractSync,
+    ContractSyncMetrics, ContractSyncer, CoreMetrics, AetheriumAgentCore, ReplicaMetrics,
+    RuntimeMetrics, SequencedDataContractSync,
 };
 
 use aetherium_core::{
     Announcement, ChainResult, AetheriumChain, AetheriumContract, AetheriumDomain, AetheriumSigner,
     AetheriumSignerExt, Mailbox, MerkleTreeHook, MerkleTreeInsertion, ReorgPeriod, TxOutcome,
-    ValidatorAnnounce, H256, U256,
+    ValidatorAnnounce, H160, H256, U256,
 };
 use aetherium_ethereum::{SingletonSigner, SingletonSignerHandle};
 
 use crate::{
+    checkpoint_audit::{AuditMode, AuditReport, CheckpointAuditor},
     settings::ValidatorSettings,
+    slashing_protection::SlashingProtectionDb,
     submit::{ValidatorSubmitter, ValidatorSubmitterMetrics},
 };
 
+/// How long to wait before restarting the tasks of an origin chain after one of them failed
+const ORIGIN_RESTART_DELAY: Duration = Duration::from_secs(30);
+
 /// A validator agent
 #[derive(Debug, AsRef)]
 pub struct Validator {
-    origin_chain: AetheriumDomain,
-    origin_chain_conf: ChainConf,
     #[as_ref]
     core: AetheriumAgentCore,
+    origins: Vec<Arc<OriginValidator>>,
+    slashing_protection: SlashingProtectionDb,
+    validator_address: H160,
+    audit: Option<AuditMode>,
+    // temporary holder until `run` is called
+    signer_instance: Option<Box<SingletonSigner>>,
+    core_metrics: Arc<CoreMetrics>,
+    runtime_metrics: RuntimeMetrics,
+}
+
+/// Signs the checkpoints of one origin chain. Each origin chain has its own DB namespace,
+/// checkpoint syncer and tasks, and all of them share the validator signer.
+#[derive(Debug)]
+struct OriginValidator {
+    origin_chain: AetheriumDomain,
+    origin_chain_conf: ChainConf,
     db: AetheriumRocksDB,
     merkle_tree_hook_sync: Arc<SequencedDataContractSync<MerkleTreeInsertion>>,
     mailbox: Arc<dyn Mailbox>,
     merkle_tree_hook: Arc<dyn MerkleTreeHook>,
     validator_announce: Arc<dyn ValidatorAnnounce>,
     signer: SingletonSignerHandle,
-    // temporary holder until `run` is called
-    signer_instance: Option<Box<SingletonSigner>>,
     reorg_period: ReorgPeriod,
     interval: Duration,
     checkpoint_syncer: Arc<dyn CheckpointSyncer>,
+    slashing_protection: SlashingProtectionDb,
     core_metrics: Arc<CoreMetrics>,
     agent_metrics: AgentMetrics,
     chain_metrics: ChainMetrics,
-    runtime_metrics: RuntimeMetrics,
-    agent_metadata: AgentMetadata,
+    agent_metadata: Arc<AgentMetadata>,
 }
 
 #[async_trait]
@@ -74,80 +91,161 @@ impl BaseAgent for Validator {
         Self: Sized,
     {
         let db = DB::from_path(&settings.db)?;
-        let msg_db = AetheriumRocksDB::new(&settings.origin_chain, db);
 
         // Intentionally using aetherium_ethereum for the validator's signer
         let (signer_instance, signer) = SingletonSigner::new(settings.validator.build().await?);
+        let validator_address = signer.eth_address();
+
+        let slashing_protection =
+            SlashingProtectionDb::new(DB::from_path(&settings.slashing_protection_db)?);
+        if let Some(path) = &settings.slashing_protection_import {
+            let imported = slashing_protection
+                .import_file(path, validator_address)
+                .context("Failed to import the slashing protection history")?;
+            info!(
+                imported,
+                path = %path.display(),
+                "Imported slashing protection history"
+            );
+        }
 
         let core = settings.build_aetherium_core(metrics.clone());
-        // Be extra sure to panic checkpoint syncer fails, which indicates
-        // a fatal startup error.
-        let checkpoint_syncer = settings
-            .checkpoint_syncer
-            .build_and_validate(None)
-            .await
-            .expect("Failed to build checkpoint syncer")
-            .into();
-
-        let mailbox = settings
-            .build_mailbox(&settings.origin_chain, &metrics)
-            .await?;
-
-        let merkle_tree_hook = settings
-            .build_merkle_tree_hook(&settings.origin_chain, &metrics)
-            .await?;
-
-        let validator_announce = settings
-            .build_validator_announce(&settings.origin_chain, &metrics)
-            .await?;
-
-        let origin_chain_conf = core
-            .settings
-            .chain_setup(&settings.origin_chain)
-            .unwrap()
-            .clone();
-
         let contract_sync_metrics = Arc::new(ContractSyncMetrics::new(&metrics));
-
-        let merkle_tree_hook_sync = settings
-            .sequenced_contract_sync::<MerkleTreeInsertion, _>(
-                &settings.origin_chain,
-                &metrics,
-                &contract_sync_metrics,
-                msg_db.clone().into(),
-                false,
-            )
-            .await?;
+        let replica_metrics = ReplicaMetrics::new(&metrics)?;
+        let agent_metadata = Arc::new(agent_metadata);
+
+        let mut origins = Vec::with_capacity(settings.origin_chains.len());
+        for origin in &settings.origin_chains {
+            let origin_chain = &origin.domain;
+            // A checkpoint syncer that fails to build, e.g. because a reorg was flagged on it,
+            // only stops the validation of its own chain. Audits look into the checkpoint syncers
+            // with a flagged reorg too, and report it instead.
+            let checkpoint_syncer = if settings.audit.is_some() {
+                origin
+                    .checkpoint_syncer
+                    .build_unvalidated(None, Some(replica_metrics.clone()))
+                    .await
+            } else {
+                origin
+                    .checkpoint_syncer
+                    .build_and_validate(None, Some(replica_metrics.clone()))
+                    .await
+            };
+            let checkpoint_syncer = match checkpoint_syncer {
+                Ok(checkpoint_syncer) => checkpoint_syncer.into(),
+                Err(err) => {
+                    error!(
+                        origin_chain = origin_chain.name(),
+                        ?err,
+                        "Failed to build checkpoint syncer; not validating the origin chain"
+                    );
+                    continue;
+                }
+            };
+
+            let msg_db = AetheriumRocksDB::new(origin_chain, db.clone());
+
+            // So does a chain whose contracts can't be built, e.g. because its RPC is down
+            let contracts = async {
+                let mailbox = settings.build_mailbox(origin_chain, &metrics).await?;
+
+                let merkle_tree_hook = settings
+                    .build_merkle_tree_hook(origin_chain, &metrics)
+                    .await?;
+
+                let validator_announce = settings
+                    .build_validator_announce(origin_chain, &metrics)
+                    .await?;
+
+                let merkle_tree_hook_sync = settings
+                    .sequenced_contract_sync::<MerkleTreeInsertion, _>(
+                        origin_chain,
+                        &metrics,
+                        &contract_sync_metrics,
+                        msg_db.clone().into(),
+                        false,
+                    )
+                    .await?;
+                Ok::<_, eyre::Report>((
+                    mailbox,
+                    merkle_tree_hook,
+                    validator_announce,
+                    merkle_tree_hook_sync,
+                ))
+            };
+            let (mailbox, merkle_tree_hook, validator_announce, merkle_tree_hook_sync) =
+                match contracts.await {
+                    Ok(contracts) => contracts,
+                    Err(err) => {
+                        error!(
+                            origin_chain = origin_chain.name(),
+                            ?err,
+                            "Failed to build the contracts of the origin chain; not validating it"
+                        );
+                        continue;
+                    }
+                };
+
+            let origin_chain_conf = core.settings.chain_setup(origin_chain).unwrap().clone();
+
+            origins.push(Arc::new(OriginValidator {
+                origin_chain: origin_chain.clone(),
+                origin_chain_conf,
+                db: msg_db,
+                merkle_tree_hook_sync,
+                mailbox: mailbox.into(),
+                merkle_tree_hook: merkle_tree_hook.into(),
+                validator_announce: validator_announce.into(),
+                signer: signer.clone(),
+                reorg_period: origin.reorg_period.clone(),
+                interval: settings.interval,
+                checkpoint_syncer,
+                slashing_protection: slashing_protection.clone(),
+                core_metrics: metrics.clone(),
+                agent_metrics: agent_metrics.clone(),
+                chain_metrics: chain_metrics.clone(),
+                agent_metadata: agent_metadata.clone(),
+            }));
+        }
+        // Be extra sure to fail if no origin chain could be set up, which indicates a fatal
+        // startup error.
+        if origins.is_empty() {
+            bail!("Failed to set up every origin chain");
+        }
 
         Ok(Self {
-            origin_chain: settings.origin_chain,
-            origin_chain_conf,
             core,
-            db: msg_db,
-            mailbox: mailbox.into(),
-            merkle_tree_hook: merkle_tree_hook.into(),
-            merkle_tree_hook_sync,
-            validator_announce: validator_announce.into(),
-            signer,
+            origins,
+            slashing_protection,
+            validator_address,
+            audit: settings.audit,
             signer_instance: Some(Box::new(signer_instance)),
-            reorg_period: settings.reorg_period,
-            interval: settings.interval,
-            checkpoint_syncer,
-            agent_metrics,
-            chain_metrics,
             core_metrics: metrics,
             runtime_metrics,
-            agent_metadata,
         })
     }
 
     #[allow(clippy::async_yields_async)]
     async fn run(mut self) {
+        if let Some(mode) = self.audit {
+            self.run_audit(mode).await;
+            return;
+        }
+
         let mut tasks = vec![];
 
         // run server
-        let custom_routes =
-            validator_server::routes(self.origin_chain.clone(), self.core.metrics.clone());
+        let origin_chains = self
+            .origins
+            .iter()
+            .map(|origin| origin.origin_chain.clone())
+            .collect();
+        let custom_routes = validator_server::routes(
+            origin_chains,
+            self.core.metrics.clone(),
+            self.slashing_protection.clone(),
+            self.validator_address,
+        );
         let server = self
             .core
             .settings
@@ -170,15 +268,135 @@ impl BaseAgent for Validator {
             ));
         }
 
+        for origin in &self.origins {
+            let span = info_span!("OriginValidator", origin_chain = origin.origin_chain.name());
+            tasks.push(tokio::spawn(origin.clone().supervise().instrument(span)));
+        }
+        tasks.push(self.runtime_metrics.spawn());
+
+        // Note that this only returns an error if one of the tasks panics
+        if let Err(err) = try_join_all(tasks).await {
+            error!(?err, "One of the validator tasks returned an error");
+        }
+    }
+}
+
+impl Validator {
+    /// Audits the checkpoint syncer of every origin chain, instead of validating
+    async fn run_audit(mut self, mode: AuditMode) {
+        let signer_task = self.signer_instance.take().map(|signer_instance| {
+            tokio::spawn(
+                async move {
+                    signer_instance.run().await;
+                }
+                .instrument(info_span!("SingletonSigner")),
+            )
+        });
+
+        let results = join_all(self.origins.iter().map(|origin| {
+            let span = info_span!("CheckpointAudit", origin_chain = origin.origin_chain.name());
+            async move {
+                let result = origin.audit(mode).await;
+                match &result {
+                    Ok(report) => report.log(),
+                    Err(err) => error!(?err, "Failed to audit the checkpoint syncer"),
+                }
+                (origin.origin_chain.name().to_owned(), result)
+            }
+            .instrument(span)
+        }))
+        .await;
+
+        if let Some(signer_task) = signer_task {
+            signer_task.abort();
+        }
+
+        // The reports go to stdout as JSON, keyed by origin chain, so that the audit can be
+        // scripted; the exit code tells whether every checkpoint syncer is healthy
+        let mut healthy = true;
+        let mut reports = serde_json::Map::new();
+        for (origin_chain, result) in results {
+            let report = match result {
+                Ok(report) => {
+                    healthy &= report.is_healthy();
+                    serde_json::to_value(report)
+                        .unwrap_or_else(|err| serde_json::json!({ "error": err.to_string() }))
+                }
+                Err(err) => {
+                    healthy = false;
+                    serde_json::json!({ "error": format!("{err:?}") })
+                }
+            };
+            reports.insert(origin_chain, report);
+        }
+        println!("{}", serde_json::Value::Object(reports));
+        if !healthy {
+            std::process::exit(1);
+        }
+    }
+}
+
+impl OriginValidator {
+    /// Audits the checkpoint syncer against the merkle tree built from the DB, indexing the merkle
+    /// tree hook meanwhile so that the DB catches up with the checkpoint syncer
+    async fn audit(&self, mode: AuditMode) -> Result<AuditReport> {
+        let merkle_tree_hook_sync = self.run_merkle_tree_hook_sync().await?;
+        let mut auditor = CheckpointAuditor::new(
+            self.checkpoint_syncer.clone(),
+            Arc::new(self.db.clone()),
+            self.signer.eth_address(),
+            self.merkle_tree_hook.clone(),
+            self.reorg_period.clone(),
+        );
+        if mode == AuditMode::Repair {
+            auditor = auditor.with_repair(self.signer.clone(), self.slashing_protection.clone());
+        }
+        let report = auditor.audit().await;
+        merkle_tree_hook_sync.abort();
+        report
+    }
+
+    /// Runs the tasks of the origin chain, and restarts them after one of them fails, so that a
+    /// stalled or failing RPC of one chain doesn't affect the other chains. The chain is given up
+    /// on once a reorg was flagged on its checkpoint syncer.
+    async fn supervise(self: Arc<Self>) {
+        loop {
+            match tokio::spawn(self.clone().run()).await {
+                Ok(Ok(())) => warn!("Validator tasks of the origin chain exited"),
+                Ok(Err(err)) => error!(?err, "Validator tasks of the origin chain failed"),
+                Err(err) => error!(?err, "Validator tasks of the origin chain panicked"),
+            }
+            if let Ok(Some(reorg_event)) = self.checkpoint_syncer.reorg_status().await {
+                error!(
+                    ?reorg_event,
+                    "A reorg was detected on the origin chain; it won't be validated until the reorg is resolved and the validator restarted"
+                );
+                return;
+            }
+            info!(delay=?ORIGIN_RESTART_DELAY, "Restarting the validator tasks of the origin chain");
+            sleep(ORIGIN_RESTART_DELAY).await;
+        }
+    }
+
+    async fn run(self: Arc<Self>) -> Result<()> {
+        let mut tasks = vec![];
+        let result = self.run_tasks(&mut tasks).await;
+        // stop the remaining tasks, so that they aren't run twice once restarted
+        for task in &tasks {
+            task.abort();
+        }
+        result
+    }
+
+    async fn run_tasks(&self, tasks: &mut Vec<JoinHandle<()>>) -> Result<()> {
         let metrics_updater = ChainSpecificMetricsUpdater::new(
             &self.origin_chain_conf,
             self.core_metrics.clone(),
             self.agent_metrics.clone(),
             self.chain_metrics.clone(),
-            Self::AGENT_NAME.to_string(),
+            Validator::AGENT_NAME.to_string(),
         )
-        .await
-        .unwrap();
+        .await?;
         tasks.push(tokio::spawn(
             async move {
                 metrics_updater.spawn().await.unwrap();
@@ -189,67 +407,51 @@ impl BaseAgent for Validator {
         // report agent metadata
         self.metadata()
             .await
-            .expect("Failed to report agent metadata");
+            .context("Failed to report agent metadata")?;
 
         // announce the validator after spawning the signer task
-        self.announce().await.expect("Failed to announce validator");
+        self.announce()
+            .await
+            .context("Failed to announce validator")?;
 
         // Ensure that the merkle tree hook has count > 0 before we begin indexing
         // messages or submitting checkpoints.
-        loop {
-            match self.merkle_tree_hook.count(&self.reorg_period).await {
-                Ok(0) => {
-                    info!("Waiting for first message in merkle tree hook");
-                    sleep(self.interval).await;
-                }
-                Ok(_) => {
-                    tasks.push(self.run_merkle_tree_hook_sync().await);
-                    for checkpoint_sync_task in self.run_checkpoint_submitters().await {
-                        tasks.push(checkpoint_sync_task);
-                    }
-                    break;
-                }
-                _ => {
-                    // Future that immediately resolves
-                    return;
-                }
-            }
+        while self.merkle_tree_hook.count(&self.reorg_period).await? == 0 {
+            info!("Waiting for first message in merkle tree hook");
+            sleep(self.interval).await;
         }
-        tasks.push(self.runtime_metrics.spawn());
+        tasks.push(self.run_merkle_tree_hook_sync().await?);
+        tasks.extend(self.run_checkpoint_submitters().await?);
 
         // Note that this only returns an error if one of the tasks panics
-        if let Err(err) = try_join_all(tasks).await {
-            error!(?err, "One of the validator tasks returned an error");
-        }
+        try_join_all(tasks.iter_mut()).await?;
+        Ok(())
     }
-}
 
-impl Validator {
-    async fn run_merkle_tree_hook_sync(&self) -> JoinHandle<()> {
-        let index_settings =
-            self.as_ref().settings.chains[self.origin_chain.name()].index_settings();
+    async fn run_merkle_tree_hook_sync(&self) -> Result<JoinHandle<()>> {
+        let index_settings = self.origin_chain_conf.index_settings();
         let contract_sync = self.merkle_tree_hook_sync.clone();
         let cursor = contract_sync
             .cursor(index_settings)
             .await
-            .unwrap_or_else(|err| {
-                panic!(
-                    "Error getting merkle tree hook cursor for origin {0}: {err}",
+            .with_context(|| {
+                format!(
+                    "Error getting merkle tree hook cursor for origin {}",
                     self.origin_chain
                 )
-            });
+            })?;
         let origin = self.origin_chain.name().to_string();
-        tokio::spawn(
+        Ok(tokio::spawn(
             async move {
                 let label = "merkle_tree_hook";
                 contract_sync.clone().sync(label, cursor.into()).await;
                 info!(chain = origin, label, "contract sync task exit");
             }
             .instrument(info_span!("MerkleTreeHookSyncer")),
-        )
+        ))
     }
 
-    async fn run_checkpoint_submitters(&self) -> Vec<JoinHandle<()>> {
+    async fn run_checkpoint_submitters(&self) -> Result<Vec<JoinHandle<()>>> {
         let submitter = ValidatorSubmitter::new(
             self.interval,
             self.reorg_period.clone(),
@@ -257,14 +459,15 @@ impl Validator {
             self.signer.clone(),
             self.checkpoint_syncer.clone(),
             Arc::new(self.db.clone()) as Arc<dyn AetheriumDb>,
-            ValidatorSubmitterMetrics::new(&self.core.metrics, &self.origin_chain),
+            self.slashing_protection.clone(),
+            ValidatorSubmitterMetrics::new(&self.core_metrics, &self.origin_chain),
         );
 
         let tip_tree = self
             .merkle_tree_hook
             .tree(&self.reorg_period)
             .await
-            .expect("failed to get merkle tree");
+            .context("Failed to get merkle tree")?;
         // This function is only called after we have already checked that the
         // merkle tree hook has count > 0, but we assert to be extra sure this is
         // the case.
@@ -288,7 +491,7 @@ impl Validator {
                 .instrument(info_span!("TipCheckpointSubmitter")),
         ));
 
-        tasks
+        Ok(tasks)
     }
 
     fn log_on_announce_failure(result: ChainResult<TxOutcome>, chain_signer: &String) {
@@ -329,8 +532,14 @@ impl Validator {
     }
 
     async fn announce(&self) -> Result<()> {
+        for announcement_location in self.checkpoint_syncer.announcement_locations() {
+            self.announce_location(announcement_location).await?;
+        }
+        Ok(())
+    }
+
+    async fn announce_location(&self, announcement_location: String) -> Result<()> {
         let address = self.signer.eth_address();
-        let announcement_location = self.checkpoint_syncer.announcement_location();
 
         // Sign and post the validator announcement
         let announcement = Announcement {
@@ -370,10 +579,7 @@ impl Validator {
                     "Validator has not announced signature storage location"
                 );
 
-                if let Some(chain_signer) = self.core.settings.chains[self.origin_chain.name()]
-                    .chain_signer()
-                    .await?
-                {
+                if let Some(chain_signer) = self.origin_chain_conf.chain_signer().await? {
                     let chain_signer = chain_signer.address_string();
                     info!(eth_validator_address=?announcement.validator, ?chain_signer, "Attempting self announce");
                     let balance_delta = self
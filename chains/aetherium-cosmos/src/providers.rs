@@ -1,4 +1,6 @@
-pub use cosmos::CosmosProvider;
+pub use cosmos::{
+    CosmWasmContractCall, CosmosProvider, CosmosProviderForSubmitter, CosmosTxFee, CosmosTxResult,
+};
 
 /// cosmos provider
 mod cosmos;
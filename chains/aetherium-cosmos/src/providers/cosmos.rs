@@ -1,3 +1,5 @@
-pub use provider::CosmosProvider;
+pub use provider::{
+    CosmWasmContractCall, CosmosProvider, CosmosProviderForSubmitter, CosmosTxFee, CosmosTxResult,
+};
 
 mod provider;
@@ -369,6 +369,57 @@ impl CosmosProvider {
         Ok(fee / gas_limit)
     }
 
+    /// Fetches a transaction included in a block, checking that the node returned the right one
+    async fn tx_by_hash(
+        &self,
+        hash: &H512,
+    ) -> ChainResult<(H256, tendermint_rpc::endpoint::tx::Response)> {
+        let hash: H256 = H256::from_slice(&h512_to_bytes(hash));
+
+        let tendermint_hash = Hash::from_bytes(Algorithm::Sha256, hash.as_bytes())
+            .expect("transaction hash should be of correct size");
+
+        let response = self
+            .rpc_client
+            .call(|provider| {
+                Box::pin(async move { provider.get_tx_by_hash(tendermint_hash).await })
+            })
+            .await?;
+
+        let received_hash = H256::from_slice(response.hash.as_bytes());
+
+        if received_hash != hash {
+            return Err(ChainCommunicationError::from_other_str(&format!(
+                "received incorrect transaction, expected hash: {:?}, received hash: {:?}",
+                hash, received_hash,
+            )));
+        }
+
+        Ok((hash, response))
+    }
+
+    /// Wraps contract calls into `MsgExecuteContract`s sent by the signer
+    fn execute_contract_msgs(&self, calls: &[CosmWasmContractCall]) -> ChainResult<Vec<Any>> {
+        let signer = self.grpc_provider.get_signer()?;
+        calls
+            .iter()
+            .map(|call| {
+                let contract = CosmosAddress::from_h256(
+                    call.contract,
+                    &self.connection_conf.get_bech32_prefix(),
+                    self.connection_conf.get_contract_address_bytes(),
+                )?;
+                let msg = proto::cosmwasm::wasm::v1::MsgExecuteContract {
+                    sender: signer.address.clone(),
+                    contract: contract.address(),
+                    msg: call.msg.clone(),
+                    funds: vec![],
+                };
+                Any::from_msg(&msg).map_err(ChainCommunicationError::from_other)
+            })
+            .collect()
+    }
+
     async fn block_info_by_height(&self, height: u64) -> ChainResult<BlockInfo> {
         let response = self
             .rpc_client
@@ -415,26 +466,7 @@ impl AetheriumProvider for CosmosProvider {
     }
 
     async fn get_txn_by_hash(&self, hash: &H512) -> ChainResult<TxnInfo> {
-        let hash: H256 = H256::from_slice(&h512_to_bytes(hash));
-
-        let tendermint_hash = Hash::from_bytes(Algorithm::Sha256, hash.as_bytes())
-            .expect("transaction hash should be of correct size");
-
-        let response = self
-            .rpc_client
-            .call(|provider| {
-                Box::pin(async move { provider.get_tx_by_hash(tendermint_hash).await })
-            })
-            .await?;
-
-        let received_hash = H256::from_slice(response.hash.as_bytes());
-
-        if received_hash != hash {
-            return Err(ChainCommunicationError::from_other_str(&format!(
-                "received incorrect transaction, expected hash: {:?}, received hash: {:?}",
-                hash, received_hash,
-            )));
-        }
+        let (hash, response) = self.tx_by_hash(hash).await?;
 
         let tx = Tx::from_bytes(&response.tx)?;
 
@@ -486,3 +518,142 @@ impl AetheriumProvider for CosmosProvider {
         Ok(Some(chain_info))
     }
 }
+
+/// A call to a CosmWasm contract, sent as a `MsgExecuteContract` without funds
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct CosmWasmContractCall {
+    /// The contract to execute
+    pub contract: H256,
+    /// The JSON encoded execute message
+    pub msg: Vec<u8>,
+}
+
+/// The fee a signed transaction pays
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct CosmosTxFee {
+    /// Amount paid, in the smallest unit of `denom`
+    pub amount: u128,
+    /// Denom the fee is paid in (e.g. `untrn`)
+    pub denom: String,
+}
+
+impl From<Coin> for CosmosTxFee {
+    fn from(coin: Coin) -> Self {
+        Self {
+            amount: coin.amount,
+            denom: coin.denom.to_string(),
+        }
+    }
+}
+
+/// The result of executing a transaction included in a block
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct CosmosTxResult {
+    /// Height of the block the transaction was included in
+    pub height: u64,
+    /// ABCI result code, 0 if the transaction succeeded
+    pub code: u32,
+    /// Raw log of the execution, which names the message that failed, if any
+    pub log: String,
+}
+
+/// Methods of the provider used by the submitter's CosmWasm transaction adapter.
+#[async_trait]
+pub trait CosmosProviderForSubmitter: Send + Sync {
+    /// Address of the signer that sends the transactions
+    fn signer_address(&self) -> ChainResult<H256>;
+
+    /// Get the sequence the signer's next transaction must use, as far as the chain knows
+    async fn signer_sequence(&self) -> ChainResult<u64>;
+
+    /// Estimate the gas limit of a transaction made of the calls by simulating it
+    async fn estimate_gas(&self, calls: &[CosmWasmContractCall]) -> ChainResult<u64>;
+
+    /// Sign a transaction made of the calls, paying the minimum gas price for the gas limit.
+    /// Returns the encoded transaction and the fee it pays.
+    async fn sign(
+        &self,
+        calls: &[CosmWasmContractCall],
+        gas_limit: u64,
+        sequence: u64,
+    ) -> ChainResult<(Vec<u8>, CosmosTxFee)>;
+
+    /// Broadcast a signed transaction, returning its hash once it passed `CheckTx`
+    async fn broadcast(&self, tx_bytes: Vec<u8>) -> ChainResult<H256>;
+
+    /// Get the result of a transaction included in a block, or `None` if the node doesn't know
+    /// about the transaction, e.g. because it is still in the mempool or was dropped
+    async fn get_tx_result(&self, hash: &H512) -> ChainResult<Option<CosmosTxResult>>;
+
+    /// Get the height of the latest block
+    async fn latest_block_height(&self) -> ChainResult<u64>;
+}
+
+#[async_trait]
+impl CosmosProviderForSubmitter for CosmosProvider {
+    fn signer_address(&self) -> ChainResult<H256> {
+        let signer = self.grpc_provider.get_signer()?;
+        Ok(CosmosAddress::from_str(&signer.address)?.digest())
+    }
+
+    async fn signer_sequence(&self) -> ChainResult<u64> {
+        let signer = self.grpc_provider.get_signer()?;
+        let account = self
+            .grpc_provider
+            .account_query(signer.address.clone())
+            .await?;
+        Ok(account.sequence)
+    }
+
+    async fn estimate_gas(&self, calls: &[CosmWasmContractCall]) -> ChainResult<u64> {
+        let msgs = self.execute_contract_msgs(calls)?;
+        self.grpc_provider.estimate_gas(msgs).await
+    }
+
+    async fn sign(
+        &self,
+        calls: &[CosmWasmContractCall],
+        gas_limit: u64,
+        sequence: u64,
+    ) -> ChainResult<(Vec<u8>, CosmosTxFee)> {
+        let msgs = self.execute_contract_msgs(calls)?;
+        let (tx_bytes, fee) = self
+            .grpc_provider
+            .generate_raw_signed_tx_and_fee(msgs, Some(gas_limit), Some(sequence))
+            .await?;
+        Ok((tx_bytes, fee.into()))
+    }
+
+    async fn broadcast(&self, tx_bytes: Vec<u8>) -> ChainResult<H256> {
+        let response = self.grpc_provider.broadcast_tx(tx_bytes).await?;
+        if response.code != 0 {
+            return Err(ChainCommunicationError::from_other_str(&format!(
+                "transaction rejected with code {}: {}",
+                response.code, response.raw_log
+            )));
+        }
+        Ok(H256::from_slice(
+            hex::decode(response.txhash)
+                .map_err(ChainCommunicationError::from_other)?
+                .as_slice(),
+        ))
+    }
+
+    async fn get_tx_result(&self, hash: &H512) -> ChainResult<Option<CosmosTxResult>> {
+        let response = match self.tx_by_hash(hash).await {
+            Ok((_, response)) => response,
+            // CometBFT answers `tx (<hash>) not found` for transactions it didn't index
+            Err(err) if err.to_string().contains("not found") => return Ok(None),
+            Err(err) => return Err(err),
+        };
+        Ok(Some(CosmosTxResult {
+            height: response.height.value(),
+            code: response.tx_result.code.value(),
+            log: response.tx_result.log,
+        }))
+    }
+
+    async fn latest_block_height(&self) -> ChainResult<u64> {
+        self.grpc_provider.latest_block_height().await
+    }
+}
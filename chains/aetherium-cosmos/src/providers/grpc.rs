@@ -347,7 +347,7 @@ impl WasmGrpcProvider {
     }
 
     /// Gets a signer, or returns an error if one is not available.
-    fn get_signer(&self) -> ChainResult<&Signer> {
+    pub(crate) fn get_signer(&self) -> ChainResult<&Signer> {
         self.signer
             .as_ref()
             .ok_or(ChainCommunicationError::SignerUnavailable)
@@ -359,11 +359,13 @@ impl WasmGrpcProvider {
     }
 
     /// Generates an unsigned SignDoc for a transaction and the Coin amount
-    /// required to pay for tx fees.
+    /// required to pay for tx fees. Uses the signer's current account sequence
+    /// unless one is provided.
     async fn generate_unsigned_sign_doc_and_fee(
         &self,
         msgs: Vec<cosmrs::Any>,
         gas_limit: u64,
+        sequence: Option<u64>,
     ) -> ChainResult<(SignDoc, Coin)> {
         // As this function is only used for estimating gas or sending transactions,
         // we can reasonably expect to have a signer.
@@ -378,7 +380,8 @@ impl WasmGrpcProvider {
             TryInto::<u32>::try_into(timeout_height)
                 .map_err(ChainCommunicationError::from_other)?,
         );
-        let signer_info = SignerInfo::single_direct(Some(signer.public_key), account_info.sequence);
+        let sequence = sequence.unwrap_or(account_info.sequence);
+        let signer_info = SignerInfo::single_direct(Some(signer.public_key), sequence);
 
         let amount: u128 = (FixedPointNumber::from(gas_limit) * self.gas_price())
             .ceil_to_integer()
@@ -409,11 +412,13 @@ impl WasmGrpcProvider {
     }
 
     /// Generates a raw signed transaction including `msgs`, estimating gas if a limit is not provided,
-    /// and the Coin amount required to pay for tx fees.
-    async fn generate_raw_signed_tx_and_fee(
+    /// and the Coin amount required to pay for tx fees. Uses the signer's current account sequence
+    /// unless one is provided.
+    pub(crate) async fn generate_raw_signed_tx_and_fee(
         &self,
         msgs: Vec<cosmrs::Any>,
         gas_limit: Option<u64>,
+        sequence: Option<u64>,
     ) -> ChainResult<(Vec<u8>, Coin)> {
         let gas_limit = if let Some(l) = gas_limit {
             l
@@ -422,7 +427,7 @@ impl WasmGrpcProvider {
         };
 
         let (sign_doc, fee) = self
-            .generate_unsigned_sign_doc_and_fee(msgs, gas_limit)
+            .generate_unsigned_sign_doc_and_fee(msgs, gas_limit, sequence)
             .await?;
 
         let signer = self.get_signer()?;
@@ -440,9 +445,11 @@ impl WasmGrpcProvider {
     }
 
     /// Estimates gas for a transaction containing `msgs`.
-    async fn estimate_gas(&self, msgs: Vec<cosmrs::Any>) -> ChainResult<u64> {
+    pub(crate) async fn estimate_gas(&self, msgs: Vec<cosmrs::Any>) -> ChainResult<u64> {
         // Get a sign doc with 0 gas, because we plan to simulate
-        let (sign_doc, _) = self.generate_unsigned_sign_doc_and_fee(msgs, 0).await?;
+        let (sign_doc, _) = self
+            .generate_unsigned_sign_doc_and_fee(msgs, 0, None)
+            .await?;
 
         let raw_tx = TxRaw {
             body_bytes: sign_doc.body_bytes,
@@ -469,6 +476,17 @@ impl WasmGrpcProvider {
         Ok(gas_estimate)
     }
 
+    /// Broadcasts a raw signed transaction, returning once it passed `CheckTx`.
+    pub(crate) async fn broadcast_tx(&self, tx_bytes: Vec<u8>) -> ChainResult<TxResponse> {
+        self.provider
+            .call(move |provider| {
+                let tx_bytes = tx_bytes.clone();
+                let future = async move { provider.wasm_send(tx_bytes).await };
+                Box::pin(future)
+            })
+            .await
+    }
+
     /// Fetches balance for a given `address` and `denom`
     pub async fn get_balance(&self, address: String, denom: String) -> ChainResult<U256> {
         let response = self
@@ -633,7 +651,9 @@ impl WasmProvider for WasmGrpcProvider {
                 None
             }
         });
-        let (tx_bytes, fee) = self.generate_raw_signed_tx_and_fee(msgs, gas_limit).await?;
+        let (tx_bytes, fee) = self
+            .generate_raw_signed_tx_and_fee(msgs, gas_limit, None)
+            .await?;
 
         // Check if the signer has enough funds to pay for the fee so we can get
         // a more informative error.
@@ -648,14 +668,7 @@ impl WasmProvider for WasmGrpcProvider {
             });
         }
 
-        let tx_res = self
-            .provider
-            .call(move |provider| {
-                let tx_bytes = tx_bytes.clone();
-                let future = async move { provider.wasm_send(tx_bytes).await };
-                Box::pin(future)
-            })
-            .await?;
+        let tx_res = self.broadcast_tx(tx_bytes).await?;
         debug!(tx_result=?tx_res, domain=?self.domain, ?payload, "Wasm transaction sent");
         Ok(tx_res)
     }
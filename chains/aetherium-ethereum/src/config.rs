@@ -55,6 +55,28 @@ pub struct TransactionOverrides {
     pub max_fee_per_gas: Option<U256>,
     /// Max priority fee per gas to use for EIP-1559 transactions.
     pub max_priority_fee_per_gas: Option<U256>,
+    /// Percentage by which the gas price of a stuck transaction is raised when it is replaced.
+    /// Defaults to 10%, the smallest bump most nodes accept for a replacement.
+    pub gas_price_escalation_percent: Option<u32>,
+    /// Number of blocks a transaction may sit in the mempool before it is replaced with the same
+    /// nonce and an escalated gas price. If unspecified, transactions sent directly by the agents
+    /// are never replaced, while the submitter replaces them every 3 blocks.
+    pub gas_price_escalation_interval_blocks: Option<u32>,
+    /// Maximum gas price escalation may raise a transaction to, in wei. Caps the gas price of
+    /// legacy transactions and the max fee per gas of EIP-1559 ones. Prices that are already
+    /// above it are left as they are.
+    pub max_gas_price: Option<U256>,
+}
+
+/// Escalation percentage used when `gas_price_escalation_percent` isn't set
+pub const DEFAULT_GAS_PRICE_ESCALATION_PERCENT: u32 = 10;
+
+impl TransactionOverrides {
+    /// The percentage by which gas prices are escalated
+    pub fn gas_price_escalation_percent(&self) -> u32 {
+        self.gas_price_escalation_percent
+            .unwrap_or(DEFAULT_GAS_PRICE_ESCALATION_PERCENT)
+    }
 }
 
 /// Ethereum reorg period
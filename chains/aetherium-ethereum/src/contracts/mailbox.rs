@@ -428,12 +428,17 @@ impl<M: Middleware + 'static> SubmittableBatch<M> {
     pub async fn submit(self) -> ChainResult<TxOutcome> {
         let call_with_gas_overrides = fill_tx_gas_params(
             self.call,
-            self.provider,
+            self.provider.clone(),
             &self.transaction_overrides,
             &self.domain,
         )
         .await?;
-        let outcome = report_tx(call_with_gas_overrides).await?;
+        let outcome = report_tx(
+            call_with_gas_overrides,
+            self.provider,
+            &self.transaction_overrides,
+        )
+        .await?;
         Ok(outcome.into())
     }
 }
@@ -506,7 +511,12 @@ where
         let contract_call = self
             .process_contract_call(message, metadata, tx_gas_limit)
             .await?;
-        let receipt = report_tx(contract_call).await?;
+        let receipt = report_tx(
+            contract_call,
+            self.provider.clone(),
+            &self.conn.transaction_overrides,
+        )
+        .await?;
         Ok(receipt.into())
     }
 
@@ -172,7 +172,12 @@ where
     #[allow(clippy::blocks_in_conditions)] // TODO: `rustc` 1.80.1 clippy issue
     async fn announce(&self, announcement: SignedType<Announcement>) -> ChainResult<TxOutcome> {
         let contract_call = self.announce_contract_call(announcement).await?;
-        let receipt = report_tx(contract_call).await?;
+        let receipt = report_tx(
+            contract_call,
+            self.provider.clone(),
+            &self.conn.transaction_overrides,
+        )
+        .await?;
         Ok(receipt.into())
     }
 }
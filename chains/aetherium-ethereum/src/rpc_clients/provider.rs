@@ -5,8 +5,14 @@ use std::time::Duration;
 
 use async_trait::async_trait;
 use derive_new::new;
-use ethers::prelude::Middleware;
-use ethers_core::{abi::Address, types::BlockNumber};
+use ethers::abi::AbiDecode;
+use ethers::prelude::{Middleware, SignerMiddleware, TransactionReceipt};
+use ethers_contract::multicall_contract::{Aggregate3Call, Call3, Multicall3};
+use ethers_core::{
+    abi::Address,
+    types::{transaction::eip2718::TypedTransaction, BlockNumber, NameOrAddress},
+};
+use ethers_signers::Signer;
 use aetherium_core::{ethers_core_types, ChainInfo, AetheriumCustomErrorWrapper, H512, U256};
 use tokio::time::sleep;
 use tracing::instrument;
@@ -16,7 +22,10 @@ use aetherium_core::{
     AetheriumDomain, AetheriumProvider, AetheriumProviderError, TxnInfo, TxnReceiptInfo, H256,
 };
 
-use crate::{BuildableWithProvider, ConnectionConf};
+use crate::{
+    tx::apply_gas_params, BuildableWithProvider, ConnectionConf, EthereumReorgPeriod, Signers,
+    TransactionOverrides,
+};
 
 /// Connection to an ethereum provider. Useful for querying information about
 /// the blockchain.
@@ -216,6 +225,233 @@ impl BuildableWithProvider for AetheriumProviderBuilder {
     }
 }
 
+/// Methods of the provider used by the submitter's EVM transaction adapter.
+#[async_trait]
+pub trait EvmProviderForSubmitter: Send + Sync {
+    /// Get the receipt of a transaction, if it was included in a block
+    async fn get_transaction_receipt(
+        &self,
+        transaction_hash: H256,
+    ) -> ChainResult<Option<TransactionReceipt>>;
+
+    /// Whether a transaction is known to the node, e.g. because it sits in the mempool
+    async fn transaction_exists(&self, transaction_hash: H256) -> ChainResult<bool>;
+
+    /// Get the number of the latest block that is considered final given the reorg period
+    async fn get_finalized_block_number(
+        &self,
+        reorg_period: &EthereumReorgPeriod,
+    ) -> ChainResult<u32>;
+
+    /// Estimate the gas limit of a transaction
+    async fn estimate_gas_limit(&self, tx: &TypedTransaction) -> ChainResult<U256>;
+
+    /// Set the gas limit and price of a transaction, given an estimate of its gas limit
+    async fn fill_gas_params(
+        &self,
+        tx: TypedTransaction,
+        estimated_gas_limit: U256,
+        transaction_overrides: &TransactionOverrides,
+    ) -> ChainResult<TypedTransaction>;
+
+    /// Set the sender, nonce and any other field of the transaction that is still missing
+    async fn fill_transaction(&self, tx: &mut TypedTransaction) -> ChainResult<()>;
+
+    /// Simulate a transaction against the latest block, returning its output
+    async fn call(&self, tx: &TypedTransaction) -> ChainResult<Vec<u8>>;
+
+    /// Sign and broadcast a transaction, returning its hash
+    async fn send(&self, tx: &TypedTransaction) -> ChainResult<H256>;
+
+    /// Address of the signer that sends the transactions, if the provider has one
+    fn signer_address(&self) -> Option<H256>;
+
+    /// Get the nonce the next transaction of `address` must use, as of the latest block
+    async fn get_next_nonce_on_chain(&self, address: H256) -> ChainResult<u64>;
+
+    /// Batch several transactions into a single Multicall3 `aggregate3` transaction,
+    /// allowing individual calls to fail
+    fn batch(&self, batch_contract_address: H256, calls: &[TypedTransaction]) -> TypedTransaction;
+
+    /// Simulate a batch built with `batch`, returning whether each of its calls succeeded
+    async fn simulate_batch(&self, batch: &TypedTransaction) -> ChainResult<Vec<bool>>;
+}
+
+#[async_trait]
+impl<M> EvmProviderForSubmitter for EthereumProvider<M>
+where
+    M: Middleware + 'static,
+{
+    async fn get_transaction_receipt(
+        &self,
+        transaction_hash: H256,
+    ) -> ChainResult<Option<TransactionReceipt>> {
+        self.provider
+            .get_transaction_receipt(transaction_hash)
+            .await
+            .map_err(ChainCommunicationError::from_other)
+    }
+
+    async fn transaction_exists(&self, transaction_hash: H256) -> ChainResult<bool> {
+        let tx = self
+            .provider
+            .get_transaction(transaction_hash)
+            .await
+            .map_err(ChainCommunicationError::from_other)?;
+        Ok(tx.is_some())
+    }
+
+    async fn get_finalized_block_number(
+        &self,
+        reorg_period: &EthereumReorgPeriod,
+    ) -> ChainResult<u32> {
+        crate::get_finalized_block_number(&*self.provider, reorg_period).await
+    }
+
+    async fn estimate_gas_limit(&self, tx: &TypedTransaction) -> ChainResult<U256> {
+        let gas_limit = self
+            .provider
+            .estimate_gas(tx, None)
+            .await
+            .map_err(ChainCommunicationError::from_other)?;
+        Ok(gas_limit.into())
+    }
+
+    async fn fill_gas_params(
+        &self,
+        tx: TypedTransaction,
+        estimated_gas_limit: U256,
+        transaction_overrides: &TransactionOverrides,
+    ) -> ChainResult<TypedTransaction> {
+        apply_gas_params(
+            tx,
+            estimated_gas_limit,
+            self.provider.clone(),
+            transaction_overrides,
+            &self.domain,
+        )
+        .await
+    }
+
+    async fn fill_transaction(&self, tx: &mut TypedTransaction) -> ChainResult<()> {
+        self.provider
+            .fill_transaction(tx, None)
+            .await
+            .map_err(ChainCommunicationError::from_other)
+    }
+
+    async fn call(&self, tx: &TypedTransaction) -> ChainResult<Vec<u8>> {
+        let output = self
+            .provider
+            .call(tx, None)
+            .await
+            .map_err(ChainCommunicationError::from_other)?;
+        Ok(output.to_vec())
+    }
+
+    async fn send(&self, tx: &TypedTransaction) -> ChainResult<H256> {
+        let pending_tx = self
+            .provider
+            .send_transaction(tx.clone(), None)
+            .await
+            .map_err(ChainCommunicationError::from_other)?;
+        Ok(pending_tx.tx_hash().into())
+    }
+
+    fn signer_address(&self) -> Option<H256> {
+        self.provider.default_sender().map(Into::into)
+    }
+
+    async fn get_next_nonce_on_chain(&self, address: H256) -> ChainResult<u64> {
+        let nonce = self
+            .provider
+            .get_transaction_count(Address::from(address), Some(BlockNumber::Latest.into()))
+            .await
+            .map_err(ChainCommunicationError::from_other)?;
+        Ok(nonce.as_u64())
+    }
+
+    fn batch(&self, batch_contract_address: H256, calls: &[TypedTransaction]) -> TypedTransaction {
+        let multicall = Multicall3::new(
+            ethers_core_types::H160::from(batch_contract_address),
+            self.provider.clone(),
+        );
+        let calls = calls
+            .iter()
+            .filter_map(|call| {
+                let Some(NameOrAddress::Address(target)) = call.to() else {
+                    return None;
+                };
+                Some(Call3 {
+                    target: *target,
+                    allow_failure: true,
+                    call_data: call.data().cloned().unwrap_or_default(),
+                })
+            })
+            .collect();
+        multicall.aggregate_3(calls).tx
+    }
+
+    async fn simulate_batch(&self, batch: &TypedTransaction) -> ChainResult<Vec<bool>> {
+        let Some(NameOrAddress::Address(batch_contract_address)) = batch.to() else {
+            return Err(ChainCommunicationError::BatchingFailed);
+        };
+        let calldata = batch.data().cloned().unwrap_or_default();
+        let Aggregate3Call { calls } = Aggregate3Call::decode(calldata)
+            .map_err(|_| ChainCommunicationError::BatchingFailed)?;
+        let multicall = Multicall3::new(*batch_contract_address, self.provider.clone());
+        let results = multicall.aggregate_3(calls).call().await?;
+        Ok(results.into_iter().map(|result| result.success).collect())
+    }
+}
+
+/// Builder for the provider used by the submitter's EVM transaction adapter.
+pub struct SubmitterProviderBuilder {}
+
+#[async_trait]
+impl BuildableWithProvider for SubmitterProviderBuilder {
+    type Output = Box<dyn EvmProviderForSubmitter>;
+    const NEEDS_SIGNER: bool = true;
+
+    /// Only the signer middleware is applied, since the submitter prices its
+    /// transactions and assigns their nonces itself.
+    async fn build_with_signer<M>(
+        &self,
+        provider: M,
+        conn: &ConnectionConf,
+        locator: &ContractLocator,
+        signer: Option<Signers>,
+    ) -> ChainResult<Self::Output>
+    where
+        M: Middleware + 'static,
+    {
+        let Some(signer) = signer else {
+            return Err(ChainCommunicationError::SignerUnavailable);
+        };
+        let provider_chain_id = provider
+            .get_chainid()
+            .await
+            .map_err(ChainCommunicationError::from_other)?;
+        let signer = Signer::with_chain_id(signer, provider_chain_id.as_u64());
+        let signing_provider = SignerMiddleware::new(provider, signer);
+        Ok(self
+            .build_with_provider(signing_provider, conn, locator)
+            .await)
+    }
+
+    async fn build_with_provider<M: Middleware + 'static>(
+        &self,
+        provider: M,
+        _conn: &ConnectionConf,
+        locator: &ContractLocator,
+    ) -> Self::Output {
+        Box::new(EthereumProvider::new(
+            Arc::new(provider),
+            locator.domain.clone(),
+        ))
+    }
+}
+
 /// Call a get function that returns a Result<Option<T>> and retry if the inner
 /// option is None. This can happen because the provider has not discovered the
 /// object we are looking for yet.
@@ -8,7 +8,13 @@ use aetherium_core::{
     AetheriumSigner, AetheriumSignerError, Signature as AetheriumSignature, H160, H256,
 };
 
+#[cfg(feature = "pkcs11")]
+mod pkcs11;
+mod remote;
 mod singleton;
+#[cfg(feature = "pkcs11")]
+pub use pkcs11::*;
+pub use remote::*;
 pub use singleton::*;
 
 /// Ethereum-supported signer types
@@ -18,6 +24,11 @@ pub enum Signers {
     Local(LocalWallet),
     /// A signer using a key stored in aws kms
     Aws(AwsSigner),
+    /// A signer using a key held by a remote signer, which only signs messages
+    Remote(RemoteSigner),
+    /// A signer using a key held by an HSM
+    #[cfg(feature = "pkcs11")]
+    Pkcs11(Pkcs11Signer),
 }
 
 impl From<LocalWallet> for Signers {
@@ -32,6 +43,19 @@ impl From<AwsSigner> for Signers {
     }
 }
 
+impl From<RemoteSigner> for Signers {
+    fn from(s: RemoteSigner) -> Self {
+        Signers::Remote(s)
+    }
+}
+
+#[cfg(feature = "pkcs11")]
+impl From<Pkcs11Signer> for Signers {
+    fn from(s: Pkcs11Signer) -> Self {
+        Signers::Pkcs11(s)
+    }
+}
+
 #[async_trait]
 impl Signer for Signers {
     type Error = SignersError;
@@ -43,6 +67,9 @@ impl Signer for Signers {
         match self {
             Signers::Local(signer) => Ok(signer.sign_message(message).await?),
             Signers::Aws(signer) => Ok(signer.sign_message(message).await?),
+            Signers::Remote(signer) => Ok(signer.sign_message(message).await?),
+            #[cfg(feature = "pkcs11")]
+            Signers::Pkcs11(signer) => Ok(signer.sign_message(message).await?),
         }
     }
 
@@ -50,6 +77,9 @@ impl Signer for Signers {
         match self {
             Signers::Local(signer) => Ok(signer.sign_transaction(message).await?),
             Signers::Aws(signer) => Ok(signer.sign_transaction(message).await?),
+            Signers::Remote(signer) => Ok(signer.sign_transaction(message).await?),
+            #[cfg(feature = "pkcs11")]
+            Signers::Pkcs11(signer) => Ok(signer.sign_transaction(message).await?),
         }
     }
 
@@ -60,6 +90,9 @@ impl Signer for Signers {
         match self {
             Signers::Local(signer) => Ok(signer.sign_typed_data(payload).await?),
             Signers::Aws(signer) => Ok(signer.sign_typed_data(payload).await?),
+            Signers::Remote(signer) => Ok(signer.sign_typed_data(payload).await?),
+            #[cfg(feature = "pkcs11")]
+            Signers::Pkcs11(signer) => Ok(signer.sign_typed_data(payload).await?),
         }
     }
 
@@ -67,6 +100,9 @@ impl Signer for Signers {
         match self {
             Signers::Local(signer) => signer.address(),
             Signers::Aws(signer) => signer.address(),
+            Signers::Remote(signer) => signer.address(),
+            #[cfg(feature = "pkcs11")]
+            Signers::Pkcs11(signer) => signer.address(),
         }
     }
 
@@ -74,6 +110,9 @@ impl Signer for Signers {
         match self {
             Signers::Local(signer) => signer.chain_id(),
             Signers::Aws(signer) => signer.chain_id(),
+            Signers::Remote(signer) => signer.chain_id(),
+            #[cfg(feature = "pkcs11")]
+            Signers::Pkcs11(signer) => signer.chain_id(),
         }
     }
 
@@ -81,6 +120,9 @@ impl Signer for Signers {
         match self {
             Signers::Local(signer) => signer.with_chain_id(chain_id).into(),
             Signers::Aws(signer) => signer.with_chain_id(chain_id).into(),
+            Signers::Remote(signer) => signer.with_chain_id(chain_id).into(),
+            #[cfg(feature = "pkcs11")]
+            Signers::Pkcs11(signer) => signer.with_chain_id(chain_id).into(),
         }
     }
 }
@@ -109,6 +151,13 @@ pub enum SignersError {
     /// Wallet Signer Error
     #[error("{0}")]
     WalletError(#[from] WalletError),
+    /// Remote Signer Error
+    #[error("{0}")]
+    RemoteSignerError(#[from] RemoteSignerError),
+    /// PKCS#11 Signer Error
+    #[cfg(feature = "pkcs11")]
+    #[error("{0}")]
+    Pkcs11SignerError(#[from] Pkcs11SignerError),
 }
 
 impl From<std::convert::Infallible> for SignersError {
@@ -0,0 +1,208 @@
+use std::{
+    fmt,
+    path::Path,
+    sync::{Arc, Mutex},
+};
+
+use async_trait::async_trait;
+use cryptoki::{
+    context::{CInitializeArgs, Pkcs11},
+    mechanism::Mechanism,
+    object::{Attribute, AttributeType, ObjectClass, ObjectHandle},
+    session::{Session, UserType},
+    types::AuthPin,
+};
+use ethers::core::k256::ecdsa::{RecoveryId, Signature as K256Signature, VerifyingKey};
+use ethers::prelude::{Address, Signature, U256};
+use ethers::types::transaction::eip2718::TypedTransaction;
+use ethers::types::transaction::eip712::Eip712;
+use ethers::utils::{hash_message, public_key_to_address, to_eip155_v};
+use ethers_signers::Signer;
+
+/// A signer using a secp256k1 key held by an HSM, through its PKCS#11 module. The key never
+/// leaves the HSM.
+///
+/// The key is looked up by label on the token with the given label, and both its private and its
+/// public key object must carry that label.
+#[derive(Clone)]
+pub struct Pkcs11Signer {
+    session: Arc<Mutex<Session>>,
+    key: ObjectHandle,
+    verifying_key: VerifyingKey,
+    address: Address,
+    chain_id: u64,
+}
+
+impl fmt::Debug for Pkcs11Signer {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("Pkcs11Signer")
+            .field("address", &self.address)
+            .field("chain_id", &self.chain_id)
+            .finish()
+    }
+}
+
+impl Pkcs11Signer {
+    /// Load the PKCS#11 `module`, log into the token labeled `token_label` with `pin`, and look
+    /// up the key labeled `key_label`
+    pub fn connect(
+        module: &Path,
+        token_label: &str,
+        key_label: &str,
+        pin: &str,
+    ) -> Result<Self, Pkcs11SignerError> {
+        let pkcs11 = Pkcs11::new(module)?;
+        pkcs11.initialize(CInitializeArgs::OsThreads)?;
+        let slot = pkcs11
+            .get_slots_with_token()?
+            .into_iter()
+            .find(|slot| {
+                pkcs11
+                    .get_token_info(*slot)
+                    .map(|info| info.label() == token_label)
+                    .unwrap_or(false)
+            })
+            .ok_or_else(|| Pkcs11SignerError::TokenNotFound(token_label.to_owned()))?;
+        let session = pkcs11.open_ro_session(slot)?;
+        session.login(UserType::User, Some(&AuthPin::new(pin.to_owned())))?;
+
+        let find_key = |class| -> Result<ObjectHandle, Pkcs11SignerError> {
+            session
+                .find_objects(&[
+                    Attribute::Class(class),
+                    Attribute::Label(key_label.as_bytes().to_vec()),
+                ])?
+                .into_iter()
+                .next()
+                .ok_or_else(|| Pkcs11SignerError::KeyNotFound(key_label.to_owned()))
+        };
+        let key = find_key(ObjectClass::PRIVATE_KEY)?;
+        let public_key = find_key(ObjectClass::PUBLIC_KEY)?;
+        let ec_point = match session
+            .get_attributes(public_key, &[AttributeType::EcPoint])?
+            .pop()
+        {
+            Some(Attribute::EcPoint(ec_point)) => ec_point,
+            _ => return Err(Pkcs11SignerError::InvalidPublicKey),
+        };
+        // the point is usually wrapped in a DER octet string
+        let point = match ec_point.as_slice() {
+            [0x04, 0x41, point @ ..] if point.len() == 0x41 => point,
+            point => point,
+        };
+        let verifying_key = VerifyingKey::from_sec1_bytes(point)
+            .map_err(|_| Pkcs11SignerError::InvalidPublicKey)?;
+
+        Ok(Self {
+            session: Arc::new(Mutex::new(session)),
+            key,
+            address: public_key_to_address(&verifying_key),
+            verifying_key,
+            chain_id: 1,
+        })
+    }
+
+    /// Sign `digest` on the HSM, returning a signature with `v` as 27 or 28. PKCS#11 calls block
+    /// until the HSM answers, so they're kept off the async runtime.
+    async fn sign_digest(&self, digest: [u8; 32]) -> Result<Signature, Pkcs11SignerError> {
+        let signer = self.clone();
+        tokio::task::spawn_blocking(move || signer.sign_digest_blocking(digest)).await?
+    }
+
+    fn sign_digest_blocking(&self, digest: [u8; 32]) -> Result<Signature, Pkcs11SignerError> {
+        let bytes = self
+            .session
+            .lock()
+            .unwrap_or_else(|e| e.into_inner())
+            .sign(&Mechanism::Ecdsa, self.key, &digest)?;
+        let signature =
+            K256Signature::from_slice(&bytes).map_err(|_| Pkcs11SignerError::InvalidSignature)?;
+        // the HSM may return either s, while Ethereum only accepts the low one
+        let signature = signature.normalize_s().unwrap_or(signature);
+        let recovery_id = (0..=1)
+            .filter_map(RecoveryId::from_byte)
+            .find(|recovery_id| {
+                VerifyingKey::recover_from_prehash(&digest, &signature, *recovery_id)
+                    .map(|key| key == self.verifying_key)
+                    .unwrap_or(false)
+            })
+            .ok_or(Pkcs11SignerError::InvalidSignature)?;
+
+        let (r, s) = signature.split_bytes();
+        Ok(Signature {
+            r: U256::from_big_endian(&r),
+            s: U256::from_big_endian(&s),
+            v: recovery_id.to_byte() as u64 + 27,
+        })
+    }
+}
+
+#[async_trait]
+impl Signer for Pkcs11Signer {
+    type Error = Pkcs11SignerError;
+
+    async fn sign_message<S: Send + Sync + AsRef<[u8]>>(
+        &self,
+        message: S,
+    ) -> Result<Signature, Self::Error> {
+        self.sign_digest(hash_message(message).into()).await
+    }
+
+    async fn sign_transaction(&self, message: &TypedTransaction) -> Result<Signature, Self::Error> {
+        let mut tx = message.clone();
+        let chain_id = tx.chain_id().map(|id| id.as_u64()).unwrap_or(self.chain_id);
+        tx.set_chain_id(chain_id);
+        let mut signature = self.sign_digest(tx.sighash().into()).await?;
+        signature.v = to_eip155_v((signature.v - 27) as u8, chain_id);
+        Ok(signature)
+    }
+
+    async fn sign_typed_data<T: Eip712 + Send + Sync>(
+        &self,
+        payload: &T,
+    ) -> Result<Signature, Self::Error> {
+        let digest = payload
+            .encode_eip712()
+            .map_err(|err| Pkcs11SignerError::Eip712(err.to_string()))?;
+        self.sign_digest(digest).await
+    }
+
+    fn address(&self) -> Address {
+        self.address
+    }
+
+    fn chain_id(&self) -> u64 {
+        self.chain_id
+    }
+
+    fn with_chain_id<T: Into<u64>>(mut self, chain_id: T) -> Self {
+        self.chain_id = chain_id.into();
+        self
+    }
+}
+
+/// Error types for the PKCS#11 signer
+#[derive(Debug, thiserror::Error)]
+pub enum Pkcs11SignerError {
+    /// The PKCS#11 module returned an error
+    #[error(transparent)]
+    Pkcs11(#[from] cryptoki::error::Error),
+    /// No token has the configured label
+    #[error("No PKCS#11 token labeled {0}")]
+    TokenNotFound(String),
+    /// No key has the configured label
+    #[error("No PKCS#11 key labeled {0}")]
+    KeyNotFound(String),
+    /// The public key isn't a secp256k1 point
+    #[error("The PKCS#11 key isn't a secp256k1 key")]
+    InvalidPublicKey,
+    /// The HSM returned something that isn't a signature of the key
+    #[error("The PKCS#11 module returned an invalid signature")]
+    InvalidSignature,
+    /// The typed data couldn't be encoded
+    #[error("Failed to encode typed data: {0}")]
+    Eip712(String),
+    /// The blocking task signing on the HSM panicked or was cancelled
+    #[error(transparent)]
+    Task(#[from] tokio::task::JoinError),
+}
@@ -0,0 +1,205 @@
+use std::{str::FromStr, time::Duration};
+
+use async_trait::async_trait;
+use ethers::prelude::{Address, Signature};
+use ethers::types::transaction::eip2718::TypedTransaction;
+use ethers::types::transaction::eip712::Eip712;
+use ethers::utils::hash_message;
+use ethers_signers::Signer;
+use reqwest::Client;
+use serde::Deserialize;
+use serde_json::json;
+use url::Url;
+
+/// The timeout of requests to the remote signer
+const REMOTE_SIGNER_TIMEOUT: Duration = Duration::from_secs(30);
+
+/// A signer that has a remote signer sign messages over HTTP, using the `eth_sign` JSON-RPC
+/// method as served by Web3Signer. The key never leaves the remote signer.
+///
+/// Only messages can be signed this way, e.g. the checkpoints and announcements of validators,
+/// not transactions.
+#[derive(Debug, Clone)]
+pub struct RemoteSigner {
+    client: Client,
+    url: Url,
+    address: Address,
+    chain_id: u64,
+}
+
+#[derive(Debug, Deserialize)]
+struct JsonRpcError {
+    code: i64,
+    message: String,
+}
+
+#[derive(Debug, Deserialize)]
+struct JsonRpcResponse {
+    result: Option<String>,
+    error: Option<JsonRpcError>,
+}
+
+impl RemoteSigner {
+    /// Create a signer for the key of `address` held by the remote signer at `url`
+    pub fn new(url: Url, address: Address) -> Result<Self, RemoteSignerError> {
+        let client = Client::builder().timeout(REMOTE_SIGNER_TIMEOUT).build()?;
+        Ok(Self {
+            client,
+            url,
+            address,
+            chain_id: 1,
+        })
+    }
+
+    async fn eth_sign(&self, data: &[u8]) -> Result<Signature, RemoteSignerError> {
+        let request = json!({
+            "jsonrpc": "2.0",
+            "id": 1,
+            "method": "eth_sign",
+            "params": [self.address, format!("0x{}", hex::encode(data))],
+        });
+        let response: JsonRpcResponse = self
+            .client
+            .post(self.url.clone())
+            .json(&request)
+            .send()
+            .await?
+            .error_for_status()?
+            .json()
+            .await?;
+        let signature = match response {
+            JsonRpcResponse {
+                error: Some(error), ..
+            } => return Err(RemoteSignerError::Rpc(error.code, error.message)),
+            JsonRpcResponse {
+                result: Some(signature),
+                ..
+            } => Signature::from_str(&signature)
+                .map_err(|err| RemoteSignerError::InvalidSignature(err.to_string()))?,
+            _ => {
+                return Err(RemoteSignerError::InvalidSignature(
+                    "empty response".to_owned(),
+                ))
+            }
+        };
+
+        // make sure the remote signer signed with the expected key, and the expected prefix
+        let signer = signature
+            .recover(hash_message(data))
+            .map_err(|err| RemoteSignerError::InvalidSignature(err.to_string()))?;
+        if signer != self.address {
+            return Err(RemoteSignerError::WrongSigner(signer));
+        }
+        Ok(signature)
+    }
+}
+
+#[async_trait]
+impl Signer for RemoteSigner {
+    type Error = RemoteSignerError;
+
+    async fn sign_message<S: Send + Sync + AsRef<[u8]>>(
+        &self,
+        message: S,
+    ) -> Result<Signature, Self::Error> {
+        self.eth_sign(message.as_ref()).await
+    }
+
+    async fn sign_transaction(
+        &self,
+        _message: &TypedTransaction,
+    ) -> Result<Signature, Self::Error> {
+        Err(RemoteSignerError::Unsupported("transactions"))
+    }
+
+    async fn sign_typed_data<T: Eip712 + Send + Sync>(
+        &self,
+        _payload: &T,
+    ) -> Result<Signature, Self::Error> {
+        Err(RemoteSignerError::Unsupported("typed data"))
+    }
+
+    fn address(&self) -> Address {
+        self.address
+    }
+
+    fn chain_id(&self) -> u64 {
+        self.chain_id
+    }
+
+    fn with_chain_id<T: Into<u64>>(mut self, chain_id: T) -> Self {
+        self.chain_id = chain_id.into();
+        self
+    }
+}
+
+/// Error types for the remote signer
+#[derive(Debug, thiserror::Error)]
+pub enum RemoteSignerError {
+    /// The request to the remote signer failed
+    #[error(transparent)]
+    Http(#[from] reqwest::Error),
+    /// The remote signer returned an error
+    #[error("Remote signer returned error {0}: {1}")]
+    Rpc(i64, String),
+    /// The remote signer returned something that isn't a signature
+    #[error("Remote signer returned an invalid signature: {0}")]
+    InvalidSignature(String),
+    /// The remote signer signed with another key than the configured one
+    #[error("Remote signer signed with {0:?} instead of the configured address")]
+    WrongSigner(Address),
+    /// The remote signer can't sign this
+    #[error("Remote signers can't sign {0}")]
+    Unsupported(&'static str),
+}
+
+#[cfg(test)]
+mod test {
+    use axum::{routing::post, Json, Router};
+    use ethers::signers::LocalWallet;
+    use serde_json::Value;
+
+    use super::*;
+
+    const KEY: &str = "1111111111111111111111111111111111111111111111111111111111111111";
+
+    /// Serves `eth_sign` with a local key, the way Web3Signer does
+    fn setup_remote_signer() -> Url {
+        let app = Router::new().route(
+            "/",
+            post(|Json(request): Json<Value>| async move {
+                let wallet: LocalWallet = KEY.parse().unwrap();
+                let data = hex::decode(
+                    request["params"][1]
+                        .as_str()
+                        .unwrap()
+                        .trim_start_matches("0x"),
+                )
+                .unwrap();
+                let signature = wallet.sign_message(data).await.unwrap();
+                Json(json!({ "jsonrpc": "2.0", "id": 1, "result": format!("0x{signature}") }))
+            }),
+        );
+        let server =
+            axum::Server::bind(&"127.0.0.1:0".parse().unwrap()).serve(app.into_make_service());
+        let addr = server.local_addr();
+        tokio::spawn(server);
+        format!("http://{addr}").parse().unwrap()
+    }
+
+    #[tokio::test]
+    async fn test_remote_signer() {
+        let url = setup_remote_signer();
+        let address = KEY.parse::<LocalWallet>().unwrap().address();
+
+        let signer = RemoteSigner::new(url.clone(), address).unwrap();
+        let signature = signer.sign_message([1u8; 32]).await.unwrap();
+        assert_eq!(signature.recover(hash_message([1u8; 32])).unwrap(), address);
+
+        let signer = RemoteSigner::new(url, Address::repeat_byte(1)).unwrap();
+        assert!(matches!(
+            signer.sign_message([1u8; 32]).await,
+            Err(RemoteSignerError::WrongSigner(_))
+        ));
+    }
+}
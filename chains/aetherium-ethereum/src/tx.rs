@@ -52,10 +52,18 @@ pub fn apply_gas_estimate_buffer(gas: U256, domain: &AetheriumDomain) -> ChainRe
 }
 
 const PENDING_TRANSACTION_POLLING_INTERVAL: Duration = Duration::from_secs(2);
+const PENDING_TRANSACTION_TIMEOUT: Duration = Duration::from_secs(150);
+/// Replacements restart the wait for a receipt, so escalated transactions get more time
+const ESCALATED_TRANSACTION_TIMEOUT: Duration = Duration::from_secs(300);
 const EVM_RELAYER_ADDRESS: &str = "0x74cae0ecc47b02ed9b9d32e000fd70b9417970c5";
 
-/// Dispatches a transaction, logs the tx id, and returns the result
-pub(crate) async fn report_tx<M, D>(tx: ContractCall<M, D>) -> ChainResult<TransactionReceipt>
+/// Dispatches a transaction, logs the tx id, and returns the result. If the overrides set an
+/// escalation interval, the transaction is replaced with an escalated gas price while it is stuck.
+pub(crate) async fn report_tx<M, D>(
+    tx: ContractCall<M, D>,
+    provider: Arc<M>,
+    transaction_overrides: &TransactionOverrides,
+) -> ChainResult<TransactionReceipt>
 where
     M: Middleware + 'static,
     D: Detokenize,
@@ -73,6 +81,9 @@ where
         .unwrap_or_else(|| NameOrAddress::Address(Default::default()));
 
     info!(?to, %data, tx=?tx.tx, "Dispatching transaction");
+    if let Some(interval_blocks) = transaction_overrides.gas_price_escalation_interval_blocks {
+        return track_escalated_tx(tx, provider, interval_blocks, transaction_overrides).await;
+    }
     let dispatch_fut = tx.send();
     let dispatched = dispatch_fut
         .await?
@@ -80,6 +91,99 @@ where
     track_pending_tx(dispatched).await
 }
 
+/// Sends a transaction and replaces it with the same nonce and an escalated gas price every
+/// `interval_blocks` blocks, until one of the sent versions is included
+async fn track_escalated_tx<M, D>(
+    tx: ContractCall<M, D>,
+    provider: Arc<M>,
+    interval_blocks: u32,
+    transaction_overrides: &TransactionOverrides,
+) -> ChainResult<TransactionReceipt>
+where
+    M: Middleware + 'static,
+    D: Detokenize,
+{
+    let mut tx = tx;
+    // the nonce is set up front, so that replacements reuse it
+    provider
+        .fill_transaction(&mut tx.tx, tx.block)
+        .await
+        .map_err(ChainCommunicationError::from_other)?;
+    let tx_hash: TxHash = *tx.send().await?;
+    info!(?tx_hash, "Dispatched tx");
+
+    let mut tx_hashes = vec![tx_hash];
+    let escalation = escalate_until_included(
+        &mut tx,
+        &mut tx_hashes,
+        &provider,
+        interval_blocks,
+        transaction_overrides,
+    );
+    match tokio::time::timeout(ESCALATED_TRANSACTION_TIMEOUT, escalation).await {
+        Ok(result) => result,
+        Err(x) => {
+            error!(?tx_hashes, error = ?x, "waiting for receipt timed out");
+            Err(ChainCommunicationError::TransactionTimeout)
+        }
+    }
+}
+
+async fn escalate_until_included<M, D>(
+    tx: &mut ContractCall<M, D>,
+    tx_hashes: &mut Vec<TxHash>,
+    provider: &M,
+    interval_blocks: u32,
+    transaction_overrides: &TransactionOverrides,
+) -> ChainResult<TransactionReceipt>
+where
+    M: Middleware + 'static,
+    D: Detokenize,
+{
+    let mut last_sent_at_block = provider
+        .get_block_number()
+        .await
+        .map_err(ChainCommunicationError::from_other)?;
+    loop {
+        tokio::time::sleep(PENDING_TRANSACTION_POLLING_INTERVAL).await;
+        // any of the sent versions may land, since they all share the nonce
+        for tx_hash in tx_hashes.iter() {
+            if let Some(receipt) = provider
+                .get_transaction_receipt(*tx_hash)
+                .await
+                .map_err(ChainCommunicationError::from_other)?
+            {
+                info!(?tx_hash, "confirmed transaction");
+                return Ok(receipt);
+            }
+        }
+
+        let block = provider
+            .get_block_number()
+            .await
+            .map_err(ChainCommunicationError::from_other)?;
+        if block < last_sent_at_block + interval_blocks {
+            continue;
+        }
+        last_sent_at_block = block;
+        let previous_tx = tx.tx.clone();
+        escalate_gas_price(&mut tx.tx, transaction_overrides, false);
+        if tx.tx == previous_tx {
+            debug!(tx = ?tx.tx, "Gas price reached its cap, not replacing stuck tx");
+            continue;
+        }
+        match tx.send().await {
+            Ok(pending_tx) => {
+                let tx_hash = *pending_tx;
+                info!(?tx_hash, replaced = ?tx_hashes.last(), tx = ?tx.tx, "Replaced stuck tx with an escalated gas price");
+                tx_hashes.push(tx_hash);
+            }
+            // the previous version may have landed in the meantime
+            Err(err) => warn!(?err, "Failed to replace stuck tx"),
+        }
+    }
+}
+
 pub(crate) async fn track_pending_tx<P: JsonRpcClient>(
     pending_tx: PendingTransaction<'_, P>,
 ) -> ChainResult<TransactionReceipt> {
@@ -87,7 +191,7 @@ pub(crate) async fn track_pending_tx<P: JsonRpcClient>(
 
     info!(?tx_hash, "Dispatched tx");
 
-    match tokio::time::timeout(Duration::from_secs(150), pending_tx).await {
+    match tokio::time::timeout(PENDING_TRANSACTION_TIMEOUT, pending_tx).await {
         // all good
         Ok(Ok(Some(receipt))) => {
             info!(?tx_hash, "confirmed transaction");
@@ -121,12 +225,38 @@ where
     D: Detokenize,
 {
     // either use the pre-estimated gas limit or estimate it
-    let mut estimated_gas_limit: U256 = match tx.tx.gas() {
+    let estimated_gas_limit: U256 = match tx.tx.gas() {
         Some(&estimate) => estimate.into(),
         None => tx.estimate_gas().await?.into(),
     };
 
-    estimated_gas_limit = apply_gas_estimate_buffer(estimated_gas_limit, domain)?;
+    let mut filled_tx = tx;
+    filled_tx.tx = apply_gas_params(
+        filled_tx.tx.clone(),
+        estimated_gas_limit,
+        provider,
+        transaction_overrides,
+        domain,
+    )
+    .await?;
+    Ok(filled_tx)
+}
+
+/// Sets the gas limit and price of a transaction, given an estimate of its gas limit.
+///
+/// The estimate is buffered and capped to the block gas limit, and the price is set
+/// using the overrides if they exist, falling back to EIP 1559 estimation.
+pub(crate) async fn apply_gas_params<M>(
+    tx: TypedTransaction,
+    estimated_gas_limit: U256,
+    provider: Arc<M>,
+    transaction_overrides: &TransactionOverrides,
+    domain: &AetheriumDomain,
+) -> ChainResult<TypedTransaction>
+where
+    M: Middleware + 'static,
+{
+    let estimated_gas_limit = apply_gas_estimate_buffer(estimated_gas_limit, domain)?;
     let gas_limit: U256 = if let Some(gas_limit) = transaction_overrides.gas_limit {
         estimated_gas_limit.max(gas_limit)
     } else {
@@ -152,16 +282,20 @@ where
     };
     debug!(?estimated_gas_limit, gas_override=?transaction_overrides.gas_limit, used_gas_limit=?gas_limit, "Gas limit set for transaction");
 
+    let mut tx = tx;
     if let Some(gas_price) = transaction_overrides.gas_price {
         // If the gas price is set, we treat as a non-EIP-1559 chain.
-        return Ok(tx.gas_price(gas_price).gas(gas_limit));
+        tx.set_gas_price(gas_price);
+        tx.set_gas(gas_limit);
+        return Ok(tx);
     }
 
     let Ok((base_fee, max_fee, max_priority_fee)) =
-        estimate_eip1559_fees(provider, None, &latest_block, domain, &tx.tx).await
+        estimate_eip1559_fees(provider, None, &latest_block, domain, &tx).await
     else {
         // Is not EIP 1559 chain
-        return Ok(tx.gas(gas_limit));
+        tx.set_gas(gas_limit);
+        return Ok(tx);
     };
 
     // If the base fee is zero, just treat the chain as a non-EIP-1559 chain.
@@ -170,7 +304,8 @@ where
     // fee lower than 3 gwei because of privileged transactions being included by block
     // producers that have a lower priority fee.
     if base_fee.is_zero() {
-        return Ok(tx.gas(gas_limit));
+        tx.set_gas(gas_limit);
+        return Ok(tx);
     }
 
     // Apply overrides for EIP 1559 tx params if they exist.
@@ -185,23 +320,64 @@ where
 
     // Is EIP 1559 chain
     let mut request = Eip1559TransactionRequest::new();
-    if let Some(from) = tx.tx.from() {
+    if let Some(from) = tx.from() {
         request = request.from(*from);
     }
-    if let Some(to) = tx.tx.to() {
+    if let Some(to) = tx.to() {
         request = request.to(to.clone());
     }
-    if let Some(data) = tx.tx.data() {
+    if let Some(data) = tx.data() {
         request = request.data(data.clone());
     }
-    if let Some(value) = tx.tx.value() {
+    if let Some(value) = tx.value() {
         request = request.value(*value);
     }
+    if let Some(nonce) = tx.nonce() {
+        request = request.nonce(*nonce);
+    }
     request = request.max_fee_per_gas(max_fee);
     request = request.max_priority_fee_per_gas(max_priority_fee);
-    let mut eip_1559_tx = tx;
-    eip_1559_tx.tx = ethers::types::transaction::eip2718::TypedTransaction::Eip1559(request);
-    Ok(eip_1559_tx.gas(gas_limit))
+    let mut eip_1559_tx = TypedTransaction::Eip1559(request);
+    eip_1559_tx.set_gas(gas_limit);
+    Ok(eip_1559_tx)
+}
+
+/// Raises the gas price of a transaction by the escalation percentage in the overrides, so it can
+/// replace a stuck transaction with the same nonce. The escalated price is capped by
+/// `max_gas_price`, unless `lift_cap` is set, e.g. because the transaction is past its deadline.
+pub fn escalate_gas_price(
+    tx: &mut TypedTransaction,
+    transaction_overrides: &TransactionOverrides,
+    lift_cap: bool,
+) {
+    let percent = 100 + transaction_overrides.gas_price_escalation_percent();
+    // rounding up, so the bump is never below the percentage nodes require
+    let escalate = |price: EthersU256| {
+        price.saturating_mul(percent.into()) / EthersU256::from(100) + EthersU256::one()
+    };
+    let cap = |price: EthersU256, previous: EthersU256| match transaction_overrides.max_gas_price {
+        // never lower a price, or the replacement would be rejected
+        Some(max) if !lift_cap => price.min(EthersU256::from(max).max(previous)),
+        _ => price,
+    };
+
+    match tx {
+        TypedTransaction::Eip1559(request) => {
+            let max_fee = request
+                .max_fee_per_gas
+                .map(|max_fee| cap(escalate(max_fee), max_fee));
+            request.max_priority_fee_per_gas = request
+                .max_priority_fee_per_gas
+                .map(escalate)
+                .map(|priority_fee| max_fee.map_or(priority_fee, |max| priority_fee.min(max)));
+            request.max_fee_per_gas = max_fee;
+        }
+        _ => {
+            if let Some(gas_price) = tx.gas_price() {
+                tx.set_gas_price(cap(escalate(gas_price), gas_price));
+            }
+        }
+    }
 }
 
 type FeeEstimator = fn(EthersU256, Vec<Vec<EthersU256>>) -> (EthersU256, EthersU256);
@@ -340,13 +516,14 @@ mod test {
         providers::{Http, Provider},
         types::{
             transaction::eip2718::TypedTransaction, Address, Bytes, Eip1559TransactionRequest,
-            NameOrAddress,
+            NameOrAddress, TransactionRequest,
         },
     };
     use std::str::FromStr;
     use url::Url;
 
-    use crate::tx::zksync_estimate_fee;
+    use crate::tx::{escalate_gas_price, zksync_estimate_fee};
+    use crate::TransactionOverrides;
 
     #[ignore = "Not running a flaky test requiring network"]
     #[tokio::test]
@@ -367,4 +544,56 @@ mod test {
         // Require a parsing success
         let _response = zksync_estimate_fee(provider, &tx).await.unwrap();
     }
+
+    #[test]
+    fn test_escalate_gas_price() {
+        let mut tx = TypedTransaction::Eip1559(
+            Eip1559TransactionRequest::new()
+                .max_fee_per_gas(100)
+                .max_priority_fee_per_gas(10),
+        );
+        escalate_gas_price(&mut tx, &TransactionOverrides::default(), false);
+        let TypedTransaction::Eip1559(request) = tx else {
+            panic!("Expected an EIP-1559 transaction");
+        };
+        assert_eq!(request.max_fee_per_gas, Some(111.into()));
+        assert_eq!(request.max_priority_fee_per_gas, Some(12.into()));
+
+        let overrides = TransactionOverrides {
+            gas_price_escalation_percent: Some(50),
+            ..Default::default()
+        };
+        let mut tx = TypedTransaction::Legacy(TransactionRequest::new().gas_price(100));
+        escalate_gas_price(&mut tx, &overrides, false);
+        assert_eq!(tx.gas_price(), Some(151.into()));
+    }
+
+    #[test]
+    fn test_escalate_gas_price_respects_cap() {
+        let overrides = TransactionOverrides {
+            max_gas_price: Some(105.into()),
+            ..Default::default()
+        };
+        let mut tx = TypedTransaction::Eip1559(
+            Eip1559TransactionRequest::new()
+                .max_fee_per_gas(100)
+                .max_priority_fee_per_gas(100),
+        );
+        escalate_gas_price(&mut tx, &overrides, false);
+        let TypedTransaction::Eip1559(request) = &tx else {
+            panic!("Expected an EIP-1559 transaction");
+        };
+        assert_eq!(request.max_fee_per_gas, Some(105.into()));
+        // the priority fee can't exceed the max fee
+        assert_eq!(request.max_priority_fee_per_gas, Some(105.into()));
+
+        // prices above the cap are never lowered
+        let mut tx = TypedTransaction::Legacy(TransactionRequest::new().gas_price(200));
+        escalate_gas_price(&mut tx, &overrides, false);
+        assert_eq!(tx.gas_price(), Some(200.into()));
+
+        // unless the cap is lifted
+        escalate_gas_price(&mut tx, &overrides, true);
+        assert_eq!(tx.gas_price(), Some(221.into()));
+    }
 }
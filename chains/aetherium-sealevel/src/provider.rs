@@ -1,10 +1,13 @@
 use std::sync::Arc;
 
 use async_trait::async_trait;
-use solana_sdk::signature::Signature;
+use solana_client::rpc_response::RpcSimulateTransactionResult;
+use solana_sdk::{
+    hash::Hash, instruction::Instruction, signature::Signature, transaction::Transaction,
+};
 use solana_transaction_status::{
-    option_serializer::OptionSerializer, EncodedTransactionWithStatusMeta, UiTransaction,
-    UiTransactionStatusMeta,
+    option_serializer::OptionSerializer, EncodedTransactionWithStatusMeta, TransactionStatus,
+    UiTransaction, UiTransactionStatusMeta,
 };
 use tracing::warn;
 
@@ -18,7 +21,10 @@ use crate::error::AetheriumSealevelError;
 use crate::provider::recipient::RecipientProvider;
 use crate::provider::transaction::{parsed_message, txn};
 use crate::utils::{decode_h256, decode_h512, decode_pubkey};
-use crate::{ConnectionConf, SealevelRpcClient};
+use crate::{
+    ConnectionConf, PriorityFeeOracle, SealevelKeypair, SealevelRpcClient, SealevelTxCostEstimate,
+    TransactionSubmitter,
+};
 
 mod recipient;
 mod transaction;
@@ -216,3 +222,107 @@ impl AetheriumProvider for SealevelProvider {
         Ok(Some(chain_info))
     }
 }
+
+/// Methods of the provider used by the submitter's Sealevel transaction adapter.
+#[async_trait]
+pub trait SealevelProviderForSubmitter: Send + Sync {
+    /// Estimate the compute unit limit and price of an instruction by simulating it
+    async fn get_estimated_costs_for_instruction(
+        &self,
+        instruction: Instruction,
+        payer: &SealevelKeypair,
+        tx_submitter: &dyn TransactionSubmitter,
+        priority_fee_oracle: &dyn PriorityFeeOracle,
+    ) -> ChainResult<SealevelTxCostEstimate>;
+
+    /// Build a transaction for an instruction with the given compute unit limit and price.
+    /// If `sign` is true, the transaction is signed over the latest finalized blockhash.
+    async fn create_transaction_for_instruction(
+        &self,
+        compute_unit_limit: u32,
+        compute_unit_price_micro_lamports: u64,
+        instruction: Instruction,
+        payer: &SealevelKeypair,
+        tx_submitter: &dyn TransactionSubmitter,
+        sign: bool,
+    ) -> ChainResult<Transaction>;
+
+    /// Simulate a transaction against the latest blockhash
+    async fn simulate_transaction(
+        &self,
+        transaction: &Transaction,
+    ) -> ChainResult<RpcSimulateTransactionResult>;
+
+    /// Get the status of a transaction by its signature, if the node knows about it
+    async fn get_signature_status(
+        &self,
+        signature: &Signature,
+    ) -> ChainResult<Option<TransactionStatus>>;
+
+    /// Whether transactions signed over the blockhash can still be included
+    async fn is_blockhash_valid(&self, hash: &Hash) -> ChainResult<bool>;
+}
+
+#[async_trait]
+impl SealevelProviderForSubmitter for SealevelProvider {
+    async fn get_estimated_costs_for_instruction(
+        &self,
+        instruction: Instruction,
+        payer: &SealevelKeypair,
+        tx_submitter: &dyn TransactionSubmitter,
+        priority_fee_oracle: &dyn PriorityFeeOracle,
+    ) -> ChainResult<SealevelTxCostEstimate> {
+        self.rpc_client
+            .get_estimated_costs_for_instruction(
+                instruction,
+                payer,
+                tx_submitter,
+                priority_fee_oracle,
+            )
+            .await
+    }
+
+    async fn create_transaction_for_instruction(
+        &self,
+        compute_unit_limit: u32,
+        compute_unit_price_micro_lamports: u64,
+        instruction: Instruction,
+        payer: &SealevelKeypair,
+        tx_submitter: &dyn TransactionSubmitter,
+        sign: bool,
+    ) -> ChainResult<Transaction> {
+        self.rpc_client
+            .create_transaction_for_instruction(
+                compute_unit_limit,
+                compute_unit_price_micro_lamports,
+                instruction,
+                payer,
+                tx_submitter,
+                sign,
+            )
+            .await
+    }
+
+    async fn simulate_transaction(
+        &self,
+        transaction: &Transaction,
+    ) -> ChainResult<RpcSimulateTransactionResult> {
+        self.rpc_client.simulate_transaction(transaction).await
+    }
+
+    async fn get_signature_status(
+        &self,
+        signature: &Signature,
+    ) -> ChainResult<Option<TransactionStatus>> {
+        let statuses = self
+            .rpc_client
+            .get_signature_statuses(&[*signature])
+            .await?
+            .value;
+        Ok(statuses.into_iter().next().flatten())
+    }
+
+    async fn is_blockhash_valid(&self, hash: &Hash) -> ChainResult<bool> {
+        self.rpc_client.is_blockhash_valid(hash).await
+    }
+}
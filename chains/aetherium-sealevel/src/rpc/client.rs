@@ -40,9 +40,13 @@ const COMPUTE_UNIT_MULTIPLIER_DENOMINATOR: u32 = 10;
 const PRIORITY_FEE_MULTIPLIER_NUMERATOR: u64 = 110;
 const PRIORITY_FEE_MULTIPLIER_DENOMINATOR: u64 = 100;
 
+/// Compute unit limit and price of a transaction, as estimated by simulating it
+#[derive(Debug, Clone, Copy, PartialEq, Eq, serde::Serialize, serde::Deserialize)]
 pub struct SealevelTxCostEstimate {
-    compute_units: u32,
-    compute_unit_price_micro_lamports: u64,
+    /// The compute unit limit, with some headroom over the simulated usage
+    pub compute_units: u32,
+    /// The priority fee, in micro-lamports per compute unit
+    pub compute_unit_price_micro_lamports: u64,
 }
 
 /// Wrapper struct around Solana's RpcClient
@@ -50,7 +54,7 @@ pub struct SealevelRpcClient(RpcClient);
 
 impl SealevelRpcClient {
     /// The max amount of compute units for a transaction.
-    const MAX_COMPUTE_UNITS: u32 = 1_400_000;
+    pub const MAX_COMPUTE_UNITS: u32 = 1_400_000;
 
     /// constructor
     pub fn new(rpc_endpoint: String) -> Self {
@@ -1,4 +1,4 @@
-pub use client::SealevelRpcClient;
+pub use client::{SealevelRpcClient, SealevelTxCostEstimate};
 
 mod client;
 /// SealevelRpcClientBuilder
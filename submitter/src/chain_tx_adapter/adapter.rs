@@ -1,6 +1,8 @@
 // TODO: re-enable clippy warnings
 #![allow(dead_code)]
 
+use std::time::Duration;
+
 use async_trait::async_trait;
 use eyre::Result;
 use uuid::Uuid;
@@ -14,9 +16,13 @@ use crate::{
 
 pub type GasLimit = U256;
 
+/// Number of estimated block times a transaction may stay pending before it is resubmitted
+/// with a higher gas price, unless the adapter configures otherwise
+pub const DEFAULT_BLOCKS_BEFORE_RESUBMISSION: u32 = 3;
+
 /// The `AdaptsChain` trait is implemented by adapters for different VMs, stacks and chains, allowing the `PayloadDispatcher` to interact with them in a generic way.
 #[async_trait]
-pub trait AdaptsChain {
+pub trait AdaptsChain: Send + Sync {
     /// Simulates Payload and returns its gas limit. Called in the Building Stage (PayloadDispatcher)
     async fn estimate_gas_limit(&self, payload: &FullPayload) -> Result<GasLimit>;
 
@@ -35,15 +41,33 @@ pub trait AdaptsChain {
     /// uses BatchManager, returns any reverted Payload IDs sent in a Transaction. Called in the Finality Stage (PayloadDispatcher)
     async fn reverted_payloads(&self, tx: &Transaction) -> Result<Vec<Uuid>>;
 
-    // methods below are excluded from the MVP
+    /// Runs the success criteria view calls of the payloads in an included Transaction, returning the IDs of the payloads whose criteria didn't pass. Called in the Inclusion Stage (PayloadDispatcher)
+    async fn payloads_failing_success_criteria(&self, _tx: &Transaction) -> Result<Vec<Uuid>> {
+        Ok(vec![])
+    }
 
-    /// Checks if a nonce gap exists in transactions submitted by the PayloadDispatcher via this adapter
+    /// Expected time between blocks. Used by the Inclusion Stage (PayloadDispatcher) to pace status polling and resubmissions
+    fn estimated_block_time(&self) -> Duration;
+
+    /// Number of blocks a Transaction may wait for inclusion before it is resubmitted (escalating gas). Called in the Inclusion Stage (PayloadDispatcher)
+    fn blocks_before_resubmission(&self) -> u32 {
+        DEFAULT_BLOCKS_BEFORE_RESUBMISSION
+    }
+
+    // methods below only apply to VMs with account nonces
+
+    /// Checks if a nonce gap exists in transactions submitted by the PayloadDispatcher via this adapter. Called in the Inclusion Stage (PayloadDispatcher)
     async fn nonce_gap_exists(&self) -> bool {
-        todo!()
+        false
+    }
+
+    /// Fills nonce gaps by rebroadcasting the transactions stuck on them, or with transfers-to-self. Called in the Inclusion Stage (PayloadDispatcher)
+    async fn fill_nonce_gaps(&self) -> Result<()> {
+        Ok(())
     }
 
-    /// Replaces calldata in this tx with a transfer-to-self, to use its payload(s) for filling a nonce gap
+    /// Replaces calldata in this tx with a transfer-to-self, to use its nonce for filling a nonce gap once its payload(s) were dropped
     async fn replace_tx(&self, _tx: &Transaction) -> Result<()> {
-        todo!()
+        Ok(())
     }
 }
@@ -1,6 +1,22 @@
+use std::time::Duration;
+
+use aetherium_base::settings::RawChainConf;
+
 mod builder;
 mod cosmos;
 mod ethereum;
 mod sealevel;
 
 pub use builder::ChainTxAdapterBuilder;
+pub use cosmos::CosmWasmTxPrecursor;
+pub use sealevel::SealevelTxPrecursor;
+
+/// Reads `blocks.estimateBlockTime` from the chain metadata, falling back to `default`
+fn estimated_block_time(raw_conf: &RawChainConf, default: Duration) -> Duration {
+    raw_conf
+        .pointer("/blocks/estimateBlockTime")
+        .and_then(serde_json::Value::as_f64)
+        .filter(|secs| *secs > 0.0)
+        .map(Duration::from_secs_f64)
+        .unwrap_or(default)
+}
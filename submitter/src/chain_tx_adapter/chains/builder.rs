@@ -1,7 +1,15 @@
 // TODO: re-enable clippy warnings
 #![allow(dead_code)]
 
-use aetherium_base::settings::{ChainConf, RawChainConf};
+use std::sync::Arc;
+
+use eyre::Result;
+
+use aetherium_base::{
+    db::AetheriumRocksDB,
+    settings::{ChainConf, RawChainConf},
+    CoreMetrics,
+};
 use aetherium_core::{AetheriumDomain, AetheriumDomainProtocol};
 
 use crate::chain_tx_adapter::{
@@ -12,16 +20,27 @@ use crate::chain_tx_adapter::{
 pub struct ChainTxAdapterBuilder {}
 
 impl ChainTxAdapterBuilder {
-    pub fn build(conf: &ChainConf, raw_conf: &RawChainConf) -> Box<dyn AdaptsChain> {
+    pub async fn build(
+        conf: &ChainConf,
+        raw_conf: &RawChainConf,
+        db: Arc<AetheriumRocksDB>,
+        metrics: &CoreMetrics,
+    ) -> Result<Box<dyn AdaptsChain>> {
         use AetheriumDomainProtocol::*;
 
         let adapter: Box<dyn AdaptsChain> = match conf.domain.domain_protocol() {
-            Ethereum => Box::new(EthereumTxAdapter::new(conf.clone(), raw_conf.clone())),
+            Ethereum => {
+                Box::new(EthereumTxAdapter::new(conf.clone(), raw_conf.clone(), db, metrics).await?)
+            }
             Fuel => todo!(),
-            Sealevel => Box::new(SealevelTxAdapter::new(conf.clone(), raw_conf.clone())),
-            Cosmos => Box::new(CosmosTxAdapter::new(conf.clone(), raw_conf.clone())),
+            Sealevel => {
+                Box::new(SealevelTxAdapter::new(conf.clone(), raw_conf.clone(), metrics).await?)
+            }
+            Cosmos => {
+                Box::new(CosmosTxAdapter::new(conf.clone(), raw_conf.clone(), metrics).await?)
+            }
         };
 
-        adapter
+        Ok(adapter)
     }
 }
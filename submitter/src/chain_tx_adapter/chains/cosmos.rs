@@ -1,51 +1,664 @@
+use std::time::Duration;
+
 use async_trait::async_trait;
+use eyre::{eyre, Result};
+use sha2::{Digest, Sha256};
+use tokio::sync::Mutex;
+use tracing::{info, warn};
 use uuid::Uuid;
 
-use aetherium_base::settings::{ChainConf, RawChainConf};
+use aetherium_base::{
+    settings::{ChainConf, ChainConnectionConf, RawChainConf},
+    CoreMetrics,
+};
+use aetherium_core::{ReorgPeriod, H256, U256};
+use aetherium_cosmos::{CosmWasmContractCall, CosmosProviderForSubmitter, CosmosTxFee};
 
 use crate::{
     chain_tx_adapter::{AdaptsChain, GasLimit},
     payload::FullPayload,
-    transaction::{Transaction, TransactionStatus},
+    transaction::{Transaction, TransactionStatus, VmSpecificTxData},
 };
 
+/// Used when the chain metadata doesn't specify `blocks.estimateBlockTime`
+const DEFAULT_ESTIMATED_BLOCK_TIME: Duration = Duration::from_secs(6);
+
+/// Prefix of the Cosmos SDK log of a transaction that failed in one of its messages
+const FAILED_MESSAGE_LOG_PREFIX: &str = "message index: ";
+
+/// Everything needed to (re)sign the transaction of a payload
+#[derive(Debug, Clone, serde::Deserialize, serde::Serialize, PartialEq, Eq)]
+pub struct CosmWasmTxPrecursor {
+    /// one `MsgExecuteContract` per execute msg in the payload data
+    pub calls: Vec<CosmWasmContractCall>,
+    /// estimated by simulating the calls on the first submission
+    pub gas_limit: Option<u64>,
+    /// account sequence assigned on the first submission. Reset once another tx used it
+    pub sequence: Option<u64>,
+    /// the last transaction sent
+    pub signed_tx: Option<Vec<u8>>,
+    /// fee paid by `signed_tx`, at the minimum gas price of the chain
+    pub fee: Option<CosmosTxFee>,
+}
+
+impl CosmWasmTxPrecursor {
+    pub fn new(calls: Vec<CosmWasmContractCall>) -> Self {
+        Self {
+            calls,
+            gas_limit: None,
+            sequence: None,
+            signed_tx: None,
+            fee: None,
+        }
+    }
+}
+
 pub struct CosmosTxAdapter {
     _conf: ChainConf,
     _raw_conf: RawChainConf,
+    provider: Box<dyn CosmosProviderForSubmitter>,
+    reorg_period_blocks: u64,
+    estimated_block_time: Duration,
+    /// sequence to assign to the next transaction, `None` until fetched from the chain
+    next_sequence: Mutex<Option<u64>>,
 }
 
 impl CosmosTxAdapter {
-    pub fn new(conf: ChainConf, raw_conf: RawChainConf) -> Self {
-        Self {
+    pub async fn new(
+        conf: ChainConf,
+        raw_conf: RawChainConf,
+        metrics: &CoreMetrics,
+    ) -> Result<Self> {
+        let provider = conf.build_cosmos_provider_for_submitter(metrics).await?;
+        Self::from_provider(conf, raw_conf, provider)
+    }
+
+    fn from_provider(
+        conf: ChainConf,
+        raw_conf: RawChainConf,
+        provider: Box<dyn CosmosProviderForSubmitter>,
+    ) -> Result<Self> {
+        if !matches!(conf.connection, ChainConnectionConf::Cosmos(_)) {
+            return Err(eyre!("CosmosTxAdapter requires a Cosmos connection config"));
+        }
+        let reorg_period_blocks = match &conf.reorg_period {
+            ReorgPeriod::None => 0,
+            ReorgPeriod::Blocks(blocks) => blocks.get() as u64,
+            ReorgPeriod::Tag(_) => {
+                return Err(eyre!(
+                    "Cosmos chains don't support block tags as reorg period"
+                ))
+            }
+        };
+        let estimated_block_time =
+            super::estimated_block_time(&raw_conf, DEFAULT_ESTIMATED_BLOCK_TIME);
+        Ok(Self {
             _conf: conf,
             _raw_conf: raw_conf,
+            provider,
+            reorg_period_blocks,
+            estimated_block_time,
+            next_sequence: Mutex::new(None),
+        })
+    }
+
+    /// The payload data is a JSON array of execute msgs for the contract at `payload.to`
+    fn payload_to_calls(payload: &FullPayload) -> Result<Vec<CosmWasmContractCall>> {
+        let msgs: Vec<serde_json::Value> =
+            serde_json::from_slice(&payload.data).map_err(|err| {
+                eyre!(
+                    "Payload {} is not a list of execute msgs: {err}",
+                    payload.id()
+                )
+            })?;
+        if msgs.is_empty() {
+            return Err(eyre!("Payload {} has no execute msgs", payload.id()));
+        }
+        msgs.iter()
+            .map(|msg| {
+                Ok(CosmWasmContractCall {
+                    contract: payload.to,
+                    msg: serde_json::to_vec(msg)?,
+                })
+            })
+            .collect()
+    }
+
+    fn precursor(tx: &Transaction) -> Result<&CosmWasmTxPrecursor> {
+        match &tx.vm_specific_data {
+            VmSpecificTxData::CosmWasm(precursor) => Ok(precursor),
+            _ => Err(eyre!(
+                "Transaction {} is not a CosmWasm transaction",
+                tx.id()
+            )),
+        }
+    }
+
+    /// Hands out consecutive sequences, so that several transactions can be pending at once.
+    /// The chain's view wins if it is ahead, e.g. because another process used the signer.
+    async fn assign_sequence(&self) -> Result<u64> {
+        let mut next_sequence = self.next_sequence.lock().await;
+        let chain_sequence = self.provider.signer_sequence().await?;
+        let sequence = next_sequence.map_or(chain_sequence, |next| next.max(chain_sequence));
+        *next_sequence = Some(sequence + 1);
+        Ok(sequence)
+    }
+
+    /// Forgets the cached sequence, so that the next one is fetched from the chain again
+    async fn reset_sequence(&self) {
+        *self.next_sequence.lock().await = None;
+    }
+
+    async fn sign(&self, precursor: &mut CosmWasmTxPrecursor) -> Result<Vec<u8>> {
+        let gas_limit = match precursor.gas_limit {
+            Some(gas_limit) => gas_limit,
+            None => {
+                let gas_limit = self.provider.estimate_gas(&precursor.calls).await?;
+                precursor.gas_limit = Some(gas_limit);
+                gas_limit
+            }
+        };
+        let sequence = match precursor.sequence {
+            Some(sequence) => sequence,
+            None => {
+                let sequence = self.assign_sequence().await?;
+                precursor.sequence = Some(sequence);
+                sequence
+            }
+        };
+        let (signed_tx, fee) = self
+            .provider
+            .sign(&precursor.calls, gas_limit, sequence)
+            .await?;
+        precursor.fee = Some(fee);
+        Ok(signed_tx)
+    }
+
+    /// Whether no transaction of the signer used the sequence of the last signed transaction yet
+    async fn sequence_is_unused(&self, precursor: &CosmWasmTxPrecursor) -> Result<bool> {
+        let Some(sequence) = precursor.sequence else {
+            return Ok(false);
+        };
+        Ok(self.provider.signer_sequence().await? <= sequence)
+    }
+
+    /// Whether the last signed transaction can still land, in which case it must not be
+    /// re-signed: both transactions could end up included. That's the case until its sequence
+    /// is used, and if it was included already.
+    async fn can_still_land(&self, precursor: &CosmWasmTxPrecursor, hash: H256) -> Result<bool> {
+        if self.sequence_is_unused(precursor).await? {
+            return Ok(true);
         }
+        Ok(self.provider.get_tx_result(&hash.into()).await?.is_some())
+    }
+
+    /// Cosmos SDK transactions are identified by the SHA-256 hash of their encoding
+    fn tx_hash(signed_tx: &[u8]) -> H256 {
+        H256::from_slice(&Sha256::digest(signed_tx))
+    }
+
+    /// Index of the message a failed transaction's log blames, e.g.
+    /// `failed to execute message; message index: 0: ...`
+    fn failed_message_index(log: &str) -> Option<usize> {
+        let (_, rest) = log.split_once(FAILED_MESSAGE_LOG_PREFIX)?;
+        let digits = rest
+            .chars()
+            .take_while(char::is_ascii_digit)
+            .collect::<String>();
+        digits.parse().ok()
     }
 }
 
 #[async_trait]
 impl AdaptsChain for CosmosTxAdapter {
-    async fn estimate_gas_limit(&self, _payload: &FullPayload) -> eyre::Result<GasLimit> {
-        todo!()
+    async fn estimate_gas_limit(&self, payload: &FullPayload) -> Result<GasLimit> {
+        let calls = Self::payload_to_calls(payload)?;
+        let gas_limit = self.provider.estimate_gas(&calls).await?;
+        Ok(U256::from(gas_limit))
+    }
+
+    /// Transactions aren't batched: Cosmos transactions are atomic, so one failing payload
+    /// would revert all the others in its batch
+    async fn build_transactions(&self, payloads: Vec<FullPayload>) -> Vec<Transaction> {
+        payloads
+            .iter()
+            .filter_map(|payload| match Self::payload_to_calls(payload) {
+                Ok(calls) => {
+                    let precursor = CosmWasmTxPrecursor::new(calls);
+                    Some(Transaction::new(
+                        VmSpecificTxData::CosmWasm(precursor),
+                        &[payload.clone()],
+                    ))
+                }
+                Err(err) => {
+                    warn!(?err, payload = ?payload.details, "Failed to build transaction");
+                    None
+                }
+            })
+            .collect()
+    }
+
+    async fn simulate_tx(&self, tx: &Transaction) -> Result<bool> {
+        let precursor = Self::precursor(tx)?;
+        match self.provider.estimate_gas(&precursor.calls).await {
+            Ok(_) => Ok(true),
+            Err(err) => {
+                warn!(tx_id = %tx.id(), ?err, "Transaction simulation failed");
+                Ok(false)
+            }
+        }
+    }
+
+    async fn submit(&self, tx: &mut Transaction) -> Result<()> {
+        let mut precursor = Self::precursor(tx)?.clone();
+        let signed_tx = match precursor.signed_tx.take() {
+            // rebroadcasting the same transaction is idempotent, it can only land once
+            Some(signed_tx)
+                if self
+                    .can_still_land(&precursor, Self::tx_hash(&signed_tx))
+                    .await? =>
+            {
+                signed_tx
+            }
+            Some(_) => {
+                // another transaction used the sequence, e.g. one sent by another process
+                warn!(tx_id = %tx.id(), sequence = ?precursor.sequence, "Transaction sequence was used, signing it again");
+                precursor.sequence = None;
+                self.reset_sequence().await;
+                self.sign(&mut precursor).await?
+            }
+            None => self.sign(&mut precursor).await?,
+        };
+        let hash = Self::tx_hash(&signed_tx);
+        precursor.signed_tx = Some(signed_tx.clone());
+        tx.vm_specific_data = VmSpecificTxData::CosmWasm(precursor.clone());
+        tx.submission_attempts += 1;
+        // set before broadcasting, so that the transaction is tracked even if the RPC errors
+        // after forwarding it
+        tx.hash = Some(hash.into());
+
+        self.provider.broadcast(signed_tx).await?;
+        info!(tx_id = %tx.id(), ?hash, sequence = ?precursor.sequence, "Sent transaction");
+        Ok(())
     }
 
-    async fn build_transactions(&self, _payloads: Vec<FullPayload>) -> Vec<Transaction> {
-        todo!()
+    async fn tx_status(&self, tx: &Transaction) -> Result<TransactionStatus> {
+        let Some(hash) = tx.hash else {
+            return Ok(TransactionStatus::PendingInclusion);
+        };
+        let signer = self.provider.signer_address()?;
+        let Some(result) = self.provider.get_tx_result(&hash).await? else {
+            // not in a block yet. Once its sequence is used it was dropped, and the Inclusion
+            // Stage signs it again
+            if self.sequence_is_unused(Self::precursor(tx)?).await? {
+                return Ok(TransactionStatus::Mempool(signer));
+            }
+            return Ok(TransactionStatus::PendingInclusion);
+        };
+        let latest_height = self.provider.latest_block_height().await?;
+        if result.height + self.reorg_period_blocks <= latest_height {
+            Ok(TransactionStatus::Finalized(signer))
+        } else {
+            Ok(TransactionStatus::Included(signer))
+        }
     }
 
-    async fn simulate_tx(&self, _tx: &Transaction) -> eyre::Result<bool> {
-        todo!()
+    async fn reverted_payloads(&self, tx: &Transaction) -> Result<Vec<Uuid>> {
+        let Some(hash) = tx.hash else {
+            return Ok(vec![]);
+        };
+        let Some(result) = self.provider.get_tx_result(&hash).await? else {
+            return Ok(vec![]);
+        };
+        if result.code == 0 {
+            return Ok(vec![]);
+        }
+        let failed_call = Self::failed_message_index(&result.log)
+            .and_then(|index| Self::precursor(tx).ok()?.calls.get(index).cloned());
+        warn!(tx_id = %tx.id(), code = result.code, log = %result.log, ?failed_call, "Transaction failed");
+        // a failing call only reverts the payload it belongs to, and transactions carry a single
+        // payload. Failures no call is blamed for, e.g. running out of gas, revert every payload
+        let reverted = match failed_call {
+            Some(_) => tx.payload_details.first().into_iter().collect(),
+            None => tx.payload_details.iter().collect::<Vec<_>>(),
+        };
+        Ok(reverted.into_iter().map(|d| *d.id).collect())
     }
 
-    async fn submit(&self, _tx: &mut Transaction) -> eyre::Result<()> {
-        todo!()
+    fn estimated_block_time(&self) -> Duration {
+        self.estimated_block_time
     }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::num::NonZeroU32;
+
+    use aetherium_base::settings::CoreContractAddresses;
+    use aetherium_core::{
+        AetheriumDomain, ChainCommunicationError, ChainResult, KnownAetheriumDomain, NativeToken,
+        OperationBatchConfig, H256, H512,
+    };
+    use aetherium_cosmos::{ConnectionConf, CosmosTxResult, RawCosmosAmount};
+
+    use super::*;
+
+    mockall::mock! {
+        pub Provider {}
+
+        #[async_trait]
+        impl CosmosProviderForSubmitter for Provider {
+            fn signer_address(&self) -> ChainResult<H256>;
+            async fn signer_sequence(&self) -> ChainResult<u64>;
+            async fn estimate_gas(&self, calls: &[CosmWasmContractCall]) -> ChainResult<u64>;
+            async fn sign(
+                &self,
+                calls: &[CosmWasmContractCall],
+                gas_limit: u64,
+                sequence: u64,
+            ) -> ChainResult<(Vec<u8>, CosmosTxFee)>;
+            async fn broadcast(&self, tx_bytes: Vec<u8>) -> ChainResult<H256>;
+            async fn get_tx_result(&self, hash: &H512) -> ChainResult<Option<CosmosTxResult>>;
+            async fn latest_block_height(&self) -> ChainResult<u64>;
+        }
+    }
+
+    const GAS_LIMIT: u64 = 150_000;
+
+    fn test_fee(gas_limit: u64) -> CosmosTxFee {
+        CosmosTxFee {
+            amount: gas_limit as u128 / 100,
+            denom: "untrn".to_owned(),
+        }
+    }
+
+    fn test_adapter(provider: MockProvider) -> CosmosTxAdapter {
+        let connection_conf = ConnectionConf::new(
+            vec!["http://127.0.0.1:9090".parse().unwrap()],
+            vec!["http://127.0.0.1:26657".parse().unwrap()],
+            "neutron-1".to_owned(),
+            "neutron".to_owned(),
+            "untrn".to_owned(),
+            RawCosmosAmount::new("untrn".to_owned(), "0.0053".to_owned()),
+            32,
+            OperationBatchConfig::default(),
+            NativeToken::default(),
+        );
+        let conf = ChainConf {
+            domain: AetheriumDomain::Known(KnownAetheriumDomain::Neutron),
+            signer: None,
+            reorg_period: ReorgPeriod::Blocks(NonZeroU32::new(2).unwrap()),
+            addresses: CoreContractAddresses::default(),
+            connection: ChainConnectionConf::Cosmos(connection_conf),
+            metrics_conf: Default::default(),
+            index: Default::default(),
+        };
+        CosmosTxAdapter::from_provider(conf, RawChainConf::default(), Box::new(provider)).unwrap()
+    }
+
+    fn test_payload(data: &str) -> FullPayload {
+        FullPayload::new("test payload", data.as_bytes().to_vec(), H256::random())
+    }
+
+    fn test_tx() -> Transaction {
+        let payload = test_payload(r#"[{"process":{}},{"process":{}}]"#);
+        let calls = CosmosTxAdapter::payload_to_calls(&payload).unwrap();
+        Transaction::new(
+            VmSpecificTxData::CosmWasm(CosmWasmTxPrecursor::new(calls)),
+            &[payload],
+        )
+    }
+
+    fn precursor(tx: &Transaction) -> &CosmWasmTxPrecursor {
+        CosmosTxAdapter::precursor(tx).unwrap()
+    }
+
+    #[tokio::test]
+    async fn test_build_transactions_skips_invalid_payloads() {
+        let adapter = test_adapter(MockProvider::new());
+        let valid = test_payload(r#"[{"process":{"message":"","metadata":""}}]"#);
+        let payloads = vec![test_payload("[]"), test_payload("not json"), valid.clone()];
+
+        let txs = adapter.build_transactions(payloads).await;
+
+        assert_eq!(txs.len(), 1);
+        assert_eq!(txs[0].payload_details, vec![valid.details.clone()]);
+        let calls = &precursor(&txs[0]).calls;
+        assert_eq!(calls.len(), 1);
+        assert_eq!(calls[0].contract, valid.to);
+        assert_eq!(calls[0].msg, br#"{"process":{"message":"","metadata":""}}"#);
+    }
+
+    #[tokio::test]
+    async fn test_simulate_tx_fails_on_error() {
+        let mut provider = MockProvider::new();
+        provider
+            .expect_estimate_gas()
+            .returning(|_| Err(ChainCommunicationError::from_other_str("out of gas")));
+        let adapter = test_adapter(provider);
+
+        assert!(!adapter.simulate_tx(&test_tx()).await.unwrap());
+    }
+
+    #[tokio::test]
+    async fn test_submit_assigns_consecutive_sequences() {
+        let mut provider = MockProvider::new();
+        provider.expect_estimate_gas().returning(|_| Ok(GAS_LIMIT));
+        // the chain doesn't know about the first tx yet when the second one is signed
+        provider.expect_signer_sequence().returning(|| Ok(7));
+        provider.expect_sign().returning(|_, gas_limit, sequence| {
+            Ok((vec![gas_limit as u8, sequence as u8], test_fee(gas_limit)))
+        });
+        provider
+            .expect_broadcast()
+            .returning(|_| Ok(H256::random()));
+        let adapter = test_adapter(provider);
+
+        let mut first = test_tx();
+        let mut second = test_tx();
+        adapter.submit(&mut first).await.unwrap();
+        adapter.submit(&mut second).await.unwrap();
+
+        for (tx, sequence) in [(&first, 7), (&second, 8)] {
+            let precursor = precursor(tx);
+            assert_eq!(precursor.sequence, Some(sequence));
+            assert_eq!(precursor.gas_limit, Some(GAS_LIMIT));
+            assert_eq!(
+                precursor.signed_tx,
+                Some(vec![GAS_LIMIT as u8, sequence as u8])
+            );
+            assert_eq!(precursor.fee, Some(test_fee(GAS_LIMIT)));
+            assert!(tx.hash.is_some());
+            assert_eq!(tx.submission_attempts, 1);
+        }
+    }
+
+    #[tokio::test]
+    async fn test_resubmission_rebroadcasts_signed_tx() {
+        let mut provider = MockProvider::new();
+        // sequence 3 wasn't used yet
+        provider.expect_signer_sequence().returning(|| Ok(3));
+        provider.expect_sign().never();
+        provider
+            .expect_broadcast()
+            .withf(|tx_bytes| tx_bytes == &vec![1, 2, 3])
+            .times(1)
+            .returning(|_| Ok(H256::random()));
+        let adapter = test_adapter(provider);
+        let mut tx = test_tx();
+        if let VmSpecificTxData::CosmWasm(precursor) = &mut tx.vm_specific_data {
+            precursor.gas_limit = Some(GAS_LIMIT);
+            precursor.sequence = Some(3);
+            precursor.signed_tx = Some(vec![1, 2, 3]);
+        }
+        tx.submission_attempts = 1;
+
+        adapter.submit(&mut tx).await.unwrap();
+
+        assert_eq!(precursor(&tx).sequence, Some(3));
+        assert_eq!(tx.hash, Some(CosmosTxAdapter::tx_hash(&[1, 2, 3]).into()));
+        assert_eq!(tx.submission_attempts, 2);
+    }
+
+    #[tokio::test]
+    async fn test_rejected_tx_is_signed_again_once_its_sequence_is_used() {
+        let mut provider = MockProvider::new();
+        provider.expect_estimate_gas().returning(|_| Ok(GAS_LIMIT));
+        provider
+            .expect_signer_sequence()
+            .times(1)
+            .returning(|| Ok(7));
+        // another process used sequence 7 in the meantime
+        provider.expect_signer_sequence().returning(|| Ok(8));
+        provider.expect_get_tx_result().returning(|_| Ok(None));
+        provider
+            .expect_sign()
+            .returning(|_, gas_limit, sequence| Ok((vec![sequence as u8], test_fee(gas_limit))));
+        provider.expect_broadcast().times(1).returning(|_| {
+            Err(ChainCommunicationError::from_other_str(
+                "account sequence mismatch",
+            ))
+        });
+        provider
+            .expect_broadcast()
+            .times(1)
+            .returning(|_| Ok(H256::random()));
+        let adapter = test_adapter(provider);
+        let mut tx = test_tx();
+
+        // the tx may have been forwarded before the error, so it is kept and tracked
+        assert!(adapter.submit(&mut tx).await.is_err());
+        assert_eq!(precursor(&tx).sequence, Some(7));
+        assert_eq!(precursor(&tx).signed_tx, Some(vec![7]));
+        assert_eq!(tx.hash, Some(CosmosTxAdapter::tx_hash(&[7]).into()));
+        assert_eq!(tx.submission_attempts, 1);
+
+        adapter.submit(&mut tx).await.unwrap();
+        assert_eq!(precursor(&tx).sequence, Some(8));
+        assert_eq!(precursor(&tx).signed_tx, Some(vec![8]));
+        assert_eq!(tx.hash, Some(CosmosTxAdapter::tx_hash(&[8]).into()));
+    }
+
+    #[tokio::test]
+    async fn test_tx_status() {
+        let signer = H256::random();
+        let mut provider = MockProvider::new();
+        provider
+            .expect_signer_address()
+            .returning(move || Ok(signer));
+        provider
+            .expect_get_tx_result()
+            .times(2)
+            .returning(|_| Ok(None));
+        provider.expect_get_tx_result().returning(|_| {
+            Ok(Some(CosmosTxResult {
+                height: 100,
+                code: 0,
+                log: String::new(),
+            }))
+        });
+        provider
+            .expect_signer_sequence()
+            .times(1)
+            .returning(|| Ok(3));
+        provider
+            .expect_signer_sequence()
+            .times(1)
+            .returning(|| Ok(4));
+        provider
+            .expect_latest_block_height()
+            .times(1)
+            .returning(|| Ok(101));
+        provider
+            .expect_latest_block_height()
+            .times(1)
+            .returning(|| Ok(102));
+        let adapter = test_adapter(provider);
+
+        let unsubmitted = test_tx();
+        assert_eq!(
+            adapter.tx_status(&unsubmitted).await.unwrap(),
+            TransactionStatus::PendingInclusion
+        );
+
+        let mut tx = test_tx();
+        if let VmSpecificTxData::CosmWasm(precursor) = &mut tx.vm_specific_data {
+            precursor.sequence = Some(3);
+        }
+        tx.hash = Some(H512::random());
+        assert_eq!(
+            adapter.tx_status(&tx).await.unwrap(),
+            TransactionStatus::Mempool(signer)
+        );
+        // sequence 3 was used by another transaction
+        assert_eq!(
+            adapter.tx_status(&tx).await.unwrap(),
+            TransactionStatus::PendingInclusion
+        );
+        assert_eq!(
+            adapter.tx_status(&tx).await.unwrap(),
+            TransactionStatus::Included(signer)
+        );
+        assert_eq!(
+            adapter.tx_status(&tx).await.unwrap(),
+            TransactionStatus::Finalized(signer)
+        );
+    }
+
+    #[tokio::test]
+    async fn test_tx_status_propagates_rpc_errors() {
+        let mut provider = MockProvider::new();
+        provider
+            .expect_signer_address()
+            .returning(|| Ok(H256::random()));
+        provider.expect_get_tx_result().returning(|_| {
+            Err(ChainCommunicationError::from_other_str(
+                "connection refused",
+            ))
+        });
+        let adapter = test_adapter(provider);
+        let mut tx = test_tx();
+        tx.hash = Some(H512::random());
+
+        assert!(adapter.tx_status(&tx).await.is_err());
+    }
+
+    #[tokio::test]
+    async fn test_reverted_payloads() {
+        let mut provider = MockProvider::new();
+        provider.expect_get_tx_result().times(1).returning(|_| {
+            Ok(Some(CosmosTxResult {
+                height: 100,
+                code: 0,
+                log: String::new(),
+            }))
+        });
+        provider.expect_get_tx_result().times(1).returning(|_| {
+            Ok(Some(CosmosTxResult {
+                height: 100,
+                code: 5,
+                log: "failed to execute message; message index: 1: delivery failed".to_owned(),
+            }))
+        });
+        let adapter = test_adapter(provider);
+        let mut tx = test_tx();
+        tx.hash = Some(H512::random());
 
-    async fn tx_status(&self, _tx: &Transaction) -> eyre::Result<TransactionStatus> {
-        todo!()
+        assert!(adapter.reverted_payloads(&tx).await.unwrap().is_empty());
+        assert_eq!(
+            adapter.reverted_payloads(&tx).await.unwrap(),
+            vec![*tx.payload_details[0].id]
+        );
     }
 
-    async fn reverted_payloads(&self, _tx: &Transaction) -> eyre::Result<Vec<Uuid>> {
-        todo!()
+    #[test]
+    fn test_failed_message_index() {
+        assert_eq!(
+            CosmosTxAdapter::failed_message_index(
+                "failed to execute message; message index: 12: out of gas"
+            ),
+            Some(12)
+        );
+        assert_eq!(CosmosTxAdapter::failed_message_index("out of gas"), None);
     }
 }
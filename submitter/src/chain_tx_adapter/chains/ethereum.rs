@@ -1,51 +1,849 @@
+use std::{sync::Arc, time::Duration};
+
 use async_trait::async_trait;
+use ethers::{
+    abi::{self, ParamType, Token},
+    types::{
+        transaction::eip2718::TypedTransaction, TransactionReceipt, TransactionRequest,
+        H160 as EthersH160, U256 as EthersU256,
+    },
+};
+use eyre::{eyre, Result};
+use tracing::{error, info, warn};
 use uuid::Uuid;
 
-use aetherium_base::settings::{ChainConf, RawChainConf};
+use aetherium_base::{
+    db::AetheriumRocksDB,
+    settings::{ChainConf, ChainConnectionConf, RawChainConf},
+    CoreMetrics,
+};
+use aetherium_core::{
+    utils::{bytes_to_hex, hex_or_base58_to_h256},
+    H256, U256,
+};
+use aetherium_ethereum::{
+    escalate_gas_price, ConnectionConf, EthereumReorgPeriod, EvmProviderForSubmitter,
+    TransactionOverrides,
+};
 
 use crate::{
-    chain_tx_adapter::{AdaptsChain, GasLimit},
+    chain_tx_adapter::{AdaptsChain, GasLimit, DEFAULT_BLOCKS_BEFORE_RESUBMISSION},
     payload::FullPayload,
-    transaction::{Transaction, TransactionStatus},
+    transaction::{Transaction, TransactionStatus, VmSpecificTxData},
 };
 
+use nonce::{NonceGap, NonceManager};
+
+mod nonce;
+
+/// Multicall3 is deployed at the same address on most EVM chains
+const DEFAULT_BATCH_CONTRACT_ADDRESS: &str = "0xcA11bde05977b3631167028862bE2a173976CA11";
+
+/// Used when the chain metadata doesn't specify `blocks.estimateBlockTime`
+const DEFAULT_ESTIMATED_BLOCK_TIME: Duration = Duration::from_secs(12);
+
+/// Gas used by a plain transfer, which is what fills nonce gaps
+const TRANSFER_GAS_LIMIT: u64 = 21_000;
+
 pub struct EthereumTxAdapter {
     _conf: ChainConf,
     _raw_conf: RawChainConf,
+    connection_conf: ConnectionConf,
+    provider: Box<dyn EvmProviderForSubmitter>,
+    reorg_period: EthereumReorgPeriod,
+    estimated_block_time: Duration,
+    nonce_manager: NonceManager,
 }
 
 impl EthereumTxAdapter {
-    pub fn new(conf: ChainConf, raw_conf: RawChainConf) -> Self {
-        Self {
+    pub async fn new(
+        conf: ChainConf,
+        raw_conf: RawChainConf,
+        db: Arc<AetheriumRocksDB>,
+        metrics: &CoreMetrics,
+    ) -> Result<Self> {
+        let provider = conf.build_evm_provider_for_submitter(metrics).await?;
+        Self::from_provider(conf, raw_conf, provider, db)
+    }
+
+    fn from_provider(
+        conf: ChainConf,
+        raw_conf: RawChainConf,
+        provider: Box<dyn EvmProviderForSubmitter>,
+        db: Arc<AetheriumRocksDB>,
+    ) -> Result<Self> {
+        let ChainConnectionConf::Ethereum(connection_conf) = conf.connection.clone() else {
+            return Err(eyre!(
+                "EthereumTxAdapter requires an Ethereum connection config"
+            ));
+        };
+        let reorg_period = EthereumReorgPeriod::try_from(&conf.reorg_period)?;
+        let estimated_block_time =
+            super::estimated_block_time(&raw_conf, DEFAULT_ESTIMATED_BLOCK_TIME);
+        let signer = provider
+            .signer_address()
+            .ok_or_else(|| eyre!("EthereumTxAdapter requires a signer"))?;
+        let nonce_manager = NonceManager::new(signer, db.clone(), db);
+        Ok(Self {
             _conf: conf,
             _raw_conf: raw_conf,
+            connection_conf,
+            provider,
+            reorg_period,
+            estimated_block_time,
+            nonce_manager,
+        })
+    }
+
+    fn transaction_overrides(&self) -> &TransactionOverrides {
+        &self.connection_conf.transaction_overrides
+    }
+
+    fn max_batch_size(&self) -> usize {
+        self.connection_conf.operation_batch.max_batch_size as usize
+    }
+
+    fn batch_contract_address(&self) -> H256 {
+        self.connection_conf
+            .operation_batch
+            .batch_contract_address
+            .unwrap_or_else(|| hex_or_base58_to_h256(DEFAULT_BATCH_CONTRACT_ADDRESS).unwrap())
+    }
+
+    /// Builds a legacy transaction, like contract calls do. It is turned into an EIP-1559
+    /// transaction when gas params are filled in, if the chain supports it.
+    fn payload_to_evm_tx(payload: &FullPayload) -> TypedTransaction {
+        let mut request = TransactionRequest::new()
+            .to(EthersH160::from(payload.to))
+            .data(payload.data.clone());
+        if let Some(value) = payload.value {
+            request = request.value(EthersU256::from(value));
+        }
+        TypedTransaction::Legacy(request)
+    }
+
+    fn evm_tx(tx: &Transaction) -> Result<&TypedTransaction> {
+        match &tx.vm_specific_data {
+            VmSpecificTxData::Evm(evm_tx) => Ok(evm_tx),
+            _ => Err(eyre!("Transaction {} is not an EVM transaction", tx.id())),
+        }
+    }
+
+    /// Batches the payloads into a single multicall transaction, falling back to one
+    /// transaction per payload if the batch can't be estimated.
+    async fn build_batch(&self, payloads: &[FullPayload]) -> Vec<Transaction> {
+        let calls = payloads
+            .iter()
+            .map(Self::payload_to_evm_tx)
+            .collect::<Vec<_>>();
+        let mut batch = self.provider.batch(self.batch_contract_address(), &calls);
+        match self.provider.estimate_gas_limit(&batch).await {
+            Ok(gas_limit) => {
+                batch.set_gas(gas_limit);
+                vec![Transaction::new(VmSpecificTxData::Evm(batch), payloads)]
+            }
+            Err(err) => {
+                warn!(
+                    ?err,
+                    payloads = payloads.len(),
+                    "Failed to estimate gas for batch, submitting payloads individually"
+                );
+                payloads
+                    .iter()
+                    .zip(calls)
+                    .map(|(payload, call)| {
+                        Transaction::new(VmSpecificTxData::Evm(call), &[payload.clone()])
+                    })
+                    .collect()
+            }
+        }
+    }
+
+    async fn on_chain_nonce(&self) -> Result<u64> {
+        let signer = self.nonce_manager.signer();
+        Ok(self.provider.get_next_nonce_on_chain(signer).await?)
+    }
+
+    /// Nonce gaps that won't be filled without intervention. A pending transaction only
+    /// blocks its nonce if the node doesn't know about it, e.g. after an RPC hiccup.
+    async fn nonce_gaps(&self) -> Result<Vec<NonceGap>> {
+        let on_chain_nonce = self.on_chain_nonce().await?;
+        let mut gaps = vec![];
+        for gap in self.nonce_manager.gaps(on_chain_nonce)? {
+            if let NonceGap::Pending(Transaction {
+                hash: Some(hash), ..
+            }) = &gap
+            {
+                if self.provider.transaction_exists((*hash).into()).await? {
+                    continue;
+                }
+            }
+            gaps.push(gap);
+        }
+        Ok(gaps)
+    }
+
+    /// Success criteria are view calls returning a bool. Anything else counts as a failure.
+    fn success_criteria_passed(output: &[u8]) -> bool {
+        matches!(
+            abi::decode(&[ParamType::Bool], output).as_deref(),
+            Ok([Token::Bool(true)])
+        )
+    }
+
+    /// Turns the transaction into a zero-value transfer to the signer, keeping its nonce
+    fn make_self_transfer(&self, tx: &mut TypedTransaction) {
+        let signer = EthersH160::from(self.nonce_manager.signer());
+        tx.set_from(signer);
+        tx.set_to(signer);
+        tx.set_value(0);
+        tx.set_data(Default::default());
+        tx.set_gas(TRANSFER_GAS_LIMIT);
+    }
+
+    async fn send_self_transfer(&self, nonce: u64) -> Result<H256> {
+        let mut transfer = TypedTransaction::Legacy(TransactionRequest::new().nonce(nonce));
+        self.make_self_transfer(&mut transfer);
+        let mut transfer = self
+            .provider
+            .fill_gas_params(
+                transfer,
+                TRANSFER_GAS_LIMIT.into(),
+                self.transaction_overrides(),
+            )
+            .await?;
+        self.provider.fill_transaction(&mut transfer).await?;
+        Ok(self.provider.send(&transfer).await?)
+    }
+
+    /// Every hash the transaction was broadcast under, latest first. Transactions stored before
+    /// all of them were kept only have their latest hash.
+    fn broadcast_hashes(tx: &Transaction) -> Vec<H256> {
+        let mut hashes: Vec<H256> = tx
+            .tx_hashes
+            .iter()
+            .rev()
+            .map(|hash| (*hash).into())
+            .collect();
+        if let Some(hash) = tx.hash {
+            if !tx.tx_hashes.contains(&hash) {
+                hashes.insert(0, hash.into());
+            }
+        }
+        hashes
+    }
+
+    /// The receipt of whichever version of the transaction was included, since gas escalation
+    /// doesn't guarantee that the latest one is
+    async fn receipt(&self, tx: &Transaction) -> Result<Option<TransactionReceipt>> {
+        for hash in Self::broadcast_hashes(tx) {
+            if let Some(receipt) = self.provider.get_transaction_receipt(hash).await? {
+                return Ok(Some(receipt));
+            }
+        }
+        Ok(None)
+    }
+
+    /// Bumps the gas price of a transaction so that it can replace the one already in the
+    /// mempool. The price cap is lifted once the transaction is past its deadline.
+    fn escalate_gas_price(&self, tx: &Transaction, evm_tx: &mut TypedTransaction) {
+        let lift_cap = tx.past_inclusion_soft_deadline();
+        escalate_gas_price(evm_tx, self.transaction_overrides(), lift_cap);
+        if lift_cap {
+            info!(tx_id = %tx.id(), deadline = ?tx.inclusion_soft_deadline, "Transaction is past its deadline, not capping its gas price");
         }
     }
 }
 
 #[async_trait]
 impl AdaptsChain for EthereumTxAdapter {
-    async fn estimate_gas_limit(&self, _payload: &FullPayload) -> eyre::Result<GasLimit> {
-        todo!()
+    async fn estimate_gas_limit(&self, payload: &FullPayload) -> Result<GasLimit> {
+        let tx = Self::payload_to_evm_tx(payload);
+        let gas_limit = self.provider.estimate_gas_limit(&tx).await?;
+        Ok(gas_limit)
+    }
+
+    async fn build_transactions(&self, payloads: Vec<FullPayload>) -> Vec<Transaction> {
+        if payloads.len() <= 1 || self.max_batch_size() <= 1 {
+            return payloads
+                .iter()
+                .map(|payload| {
+                    let tx = Self::payload_to_evm_tx(payload);
+                    Transaction::new(VmSpecificTxData::Evm(tx), &[payload.clone()])
+                })
+                .collect();
+        }
+
+        let mut transactions = Vec::new();
+        for chunk in payloads.chunks(self.max_batch_size()) {
+            if let [payload] = chunk {
+                let tx = Self::payload_to_evm_tx(payload);
+                transactions.push(Transaction::new(VmSpecificTxData::Evm(tx), chunk));
+                continue;
+            }
+            transactions.extend(self.build_batch(chunk).await);
+        }
+        transactions
+    }
+
+    async fn simulate_tx(&self, tx: &Transaction) -> Result<bool> {
+        let evm_tx = Self::evm_tx(tx)?;
+        if tx.payload_details.len() > 1 {
+            let results = self.provider.simulate_batch(evm_tx).await?;
+            return Ok(results.into_iter().all(|success| success));
+        }
+        match self.provider.call(evm_tx).await {
+            Ok(_) => Ok(true),
+            Err(err) => {
+                warn!(tx_id = %tx.id(), ?err, "Transaction simulation failed");
+                Ok(false)
+            }
+        }
+    }
+
+    async fn submit(&self, tx: &mut Transaction) -> Result<()> {
+        let mut evm_tx = Self::evm_tx(tx)?.clone();
+        if evm_tx.nonce().is_none() {
+            // first submission: price the transaction and assign it a nonce
+            let estimated_gas_limit = match evm_tx.gas() {
+                Some(gas_limit) => (*gas_limit).into(),
+                None => self.provider.estimate_gas_limit(&evm_tx).await?,
+            };
+            evm_tx = self
+                .provider
+                .fill_gas_params(evm_tx, estimated_gas_limit, self.transaction_overrides())
+                .await?;
+            let on_chain_nonce = self.on_chain_nonce().await?;
+            let nonce = self
+                .nonce_manager
+                .assign_nonce(tx.id(), on_chain_nonce)
+                .await?;
+            evm_tx.set_nonce(nonce);
+            // the tx only keeps the nonce once it is filled, since a tx with a nonce counts as
+            // sent. If filling fails, the assigned nonce is left as a gap that gets filled
+            self.provider.fill_transaction(&mut evm_tx).await?;
+        } else {
+            // resubmission: reuse the nonce so the new transaction replaces the old one
+            self.escalate_gas_price(tx, &mut evm_tx);
+        }
+        tx.vm_specific_data = VmSpecificTxData::Evm(evm_tx.clone());
+        tx.submission_attempts += 1;
+
+        let hash = self.provider.send(&evm_tx).await?;
+        info!(tx_id = %tx.id(), ?hash, nonce = ?evm_tx.nonce(), "Sent transaction");
+        tx.hash = Some(hash.into());
+        if !tx.tx_hashes.contains(&hash.into()) {
+            tx.tx_hashes.push(hash.into());
+        }
+        Ok(())
+    }
+
+    async fn tx_status(&self, tx: &Transaction) -> Result<TransactionStatus> {
+        let Some(hash) = tx.hash else {
+            return Ok(TransactionStatus::PendingInclusion);
+        };
+        let hash: H256 = hash.into();
+        let Some(receipt) = self.receipt(tx).await? else {
+            let evm_tx = Self::evm_tx(tx)?;
+            let sender = evm_tx.from().copied().unwrap_or_default().into();
+            if self.provider.transaction_exists(hash).await? {
+                return Ok(TransactionStatus::Mempool(sender));
+            }
+            // another transaction used the nonce, so this one can never be included. None of
+            // its hashes has a receipt, so the nonce wasn't used by one of its own versions
+            if let Some(nonce) = evm_tx.nonce() {
+                if nonce.as_u64() < self.on_chain_nonce().await? {
+                    return Ok(TransactionStatus::DroppedByChain(sender));
+                }
+            }
+            return Ok(TransactionStatus::PendingInclusion);
+        };
+        let sender = receipt.from.into();
+        let Some(block_number) = receipt.block_number else {
+            return Ok(TransactionStatus::Mempool(sender));
+        };
+        let finalized_block_number = self
+            .provider
+            .get_finalized_block_number(&self.reorg_period)
+            .await?;
+        if block_number.as_u64() <= finalized_block_number as u64 {
+            Ok(TransactionStatus::Finalized(sender))
+        } else {
+            Ok(TransactionStatus::Included(sender))
+        }
+    }
+
+    async fn reverted_payloads(&self, tx: &Transaction) -> Result<Vec<Uuid>> {
+        let Some(receipt) = self.receipt(tx).await? else {
+            return Ok(vec![]);
+        };
+        // a reverted transaction reverts every payload in it
+        if receipt.status == Some(0u64.into()) {
+            return Ok(tx.payload_details.iter().map(|d| *d.id).collect());
+        }
+        Ok(vec![])
+    }
+
+    /// Only the calls of a batch can fail without reverting the transaction. A single payload
+    /// whose transaction reverted is left to `reverted_payloads`, which drops it instead of
+    /// retrying it forever.
+    async fn payloads_failing_success_criteria(&self, tx: &Transaction) -> Result<Vec<Uuid>> {
+        if tx.payload_details.len() <= 1 {
+            return Ok(vec![]);
+        }
+        let mut failing = vec![];
+        for details in &tx.payload_details {
+            let Some((calldata, contract)) = &details.success_criteria else {
+                continue;
+            };
+            let call = TypedTransaction::Legacy(
+                TransactionRequest::new()
+                    .to(EthersH160::from(*contract))
+                    .data(calldata.clone()),
+            );
+            let output = self.provider.call(&call).await?;
+            if !Self::success_criteria_passed(&output) {
+                warn!(tx_id = %tx.id(), payload = ?details, output = %bytes_to_hex(&output), "Payload failed its success criteria");
+                failing.push(*details.id);
+            }
+        }
+        Ok(failing)
+    }
+
+    fn estimated_block_time(&self) -> Duration {
+        self.estimated_block_time
+    }
+
+    fn blocks_before_resubmission(&self) -> u32 {
+        self.transaction_overrides()
+            .gas_price_escalation_interval_blocks
+            .unwrap_or(DEFAULT_BLOCKS_BEFORE_RESUBMISSION)
+    }
+
+    async fn nonce_gap_exists(&self) -> bool {
+        match self.nonce_gaps().await {
+            Ok(gaps) => !gaps.is_empty(),
+            Err(err) => {
+                error!(?err, "Failed to check for nonce gaps");
+                false
+            }
+        }
+    }
+
+    /// Gaps are filled lowest nonce first, since each one blocks all the later ones
+    async fn fill_nonce_gaps(&self) -> Result<()> {
+        let gaps = self.nonce_gaps().await?;
+        if !gaps.is_empty() {
+            warn!(gaps = gaps.len(), "Nonce gap detected, filling it");
+        }
+        for gap in gaps {
+            match &gap {
+                NonceGap::Unused(nonce) => {
+                    let hash = self.send_self_transfer(*nonce).await?;
+                    info!(nonce, ?hash, "Filled unused nonce with a transfer-to-self");
+                }
+                NonceGap::Pending(tx) => {
+                    // signing is deterministic, so the rebroadcast tx has the same hash and
+                    // can only land once
+                    let hash = self.provider.send(Self::evm_tx(tx)?).await?;
+                    info!(tx_id = %tx.id(), nonce = ?gap.nonce(), ?hash, "Rebroadcast transaction stuck on its nonce");
+                }
+                NonceGap::Abandoned(tx) => self.replace_tx(tx).await?,
+            }
+        }
+        Ok(())
+    }
+
+    async fn replace_tx(&self, tx: &Transaction) -> Result<()> {
+        let mut evm_tx = Self::evm_tx(tx)?.clone();
+        let Some(nonce) = evm_tx.nonce().copied() else {
+            return Err(eyre!("Transaction {} has no nonce to fill", tx.id()));
+        };
+        // outbids the replaced transaction, in case it is still in some mempool
+        self.escalate_gas_price(tx, &mut evm_tx);
+        self.make_self_transfer(&mut evm_tx);
+        let hash = self.provider.send(&evm_tx).await?;
+        info!(tx_id = %tx.id(), ?nonce, ?hash, "Replaced dropped transaction with a transfer-to-self");
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use ethers::{
+        providers::{MockProvider, Provider},
+        types::{Bytes, U64},
+    };
+
+    use aetherium_base::settings::{ChainConnectionConf, CoreContractAddresses};
+    use aetherium_core::{AetheriumDomain, KnownAetheriumDomain, ReorgPeriod, H512};
+    use aetherium_ethereum::{EthereumProvider, RpcConnectionConf};
+
+    use crate::{
+        payload::{DropReason, PayloadDetails},
+        payload_dispatcher::test_utils::tmp_db,
+        transaction::TransactionDb,
+    };
+
+    use super::*;
+
+    const SIGNER: EthersH160 = EthersH160::repeat_byte(0x51);
+
+    fn test_adapter(
+        max_batch_size: u32,
+    ) -> (EthereumTxAdapter, Arc<MockProvider>, tempfile::TempDir) {
+        let (db, db_dir) = tmp_db();
+        let (adapter, mock_provider) = test_adapter_with_db(max_batch_size, db);
+        (adapter, mock_provider, db_dir)
+    }
+
+    fn test_adapter_with_db(
+        max_batch_size: u32,
+        db: Arc<AetheriumRocksDB>,
+    ) -> (EthereumTxAdapter, Arc<MockProvider>) {
+        let domain = AetheriumDomain::Known(KnownAetheriumDomain::Arbitrum);
+        let mock_provider = Arc::new(MockProvider::new());
+        let provider = Arc::new(Provider::new(mock_provider.clone()).with_sender(SIGNER));
+        let connection_conf = ConnectionConf {
+            rpc_connection: RpcConnectionConf::Http {
+                url: "http://127.0.0.1:8545".parse().unwrap(),
+            },
+            transaction_overrides: Default::default(),
+            operation_batch: aetherium_core::config::OperationBatchConfig {
+                batch_contract_address: None,
+                max_batch_size,
+            },
+        };
+        let conf = ChainConf {
+            domain: domain.clone(),
+            signer: None,
+            reorg_period: ReorgPeriod::from_blocks(10),
+            addresses: CoreContractAddresses::default(),
+            connection: ChainConnectionConf::Ethereum(connection_conf),
+            metrics_conf: Default::default(),
+            index: Default::default(),
+        };
+        let adapter = EthereumTxAdapter::from_provider(
+            conf,
+            RawChainConf::default(),
+            Box::new(EthereumProvider::new(provider, domain)),
+            db,
+        )
+        .unwrap();
+        (adapter, mock_provider)
+    }
+
+    /// Stores a submitted transaction that was assigned the nonce
+    async fn stored_tx_with_nonce(
+        adapter: &EthereumTxAdapter,
+        db: &AetheriumRocksDB,
+        nonce: u64,
+        status: TransactionStatus,
+    ) -> Transaction {
+        let mut tx = test_tx(Some(H512::random()));
+        let request = TransactionRequest::new()
+            .from(SIGNER)
+            .nonce(nonce)
+            .gas(100_000)
+            .gas_price(10);
+        tx.vm_specific_data = VmSpecificTxData::Evm(TypedTransaction::Legacy(request));
+        tx.status = status;
+        let assigned = adapter
+            .nonce_manager
+            .assign_nonce(tx.id(), nonce)
+            .await
+            .unwrap();
+        assert_eq!(assigned, nonce);
+        db.store_transaction_by_id(tx.clone()).unwrap();
+        tx
+    }
+
+    fn test_tx(hash: Option<H512>) -> Transaction {
+        let payload = FullPayload::random();
+        let mut tx = Transaction::new(
+            VmSpecificTxData::Evm(EthereumTxAdapter::payload_to_evm_tx(&payload)),
+            &[payload],
+        );
+        tx.hash = hash;
+        tx
+    }
+
+    fn receipt(block_number: Option<u64>, status: u64) -> TransactionReceipt {
+        TransactionReceipt {
+            block_number: block_number.map(U64::from),
+            status: Some(status.into()),
+            ..Default::default()
+        }
     }
 
-    async fn build_transactions(&self, _payloads: Vec<FullPayload>) -> Vec<Transaction> {
-        todo!()
+    #[tokio::test]
+    async fn test_estimate_gas_limit() {
+        let (adapter, mock_provider, _db_dir) = test_adapter(1);
+        // RPC 1: eth_estimateGas
+        mock_provider.push(U256::from(100_000)).unwrap();
+
+        let gas_limit = adapter
+            .estimate_gas_limit(&FullPayload::random())
+            .await
+            .unwrap();
+        assert_eq!(gas_limit, U256::from(100_000));
     }
 
-    async fn simulate_tx(&self, _tx: &Transaction) -> eyre::Result<bool> {
-        todo!()
+    #[tokio::test]
+    async fn test_build_transactions_without_batching() {
+        let (adapter, _, _db_dir) = test_adapter(1);
+        let payloads = vec![FullPayload::random(), FullPayload::random()];
+
+        let txs = adapter.build_transactions(payloads.clone()).await;
+
+        assert_eq!(txs.len(), 2);
+        for (tx, payload) in txs.iter().zip(payloads.iter()) {
+            assert_eq!(tx.payload_details, vec![payload.details.clone()]);
+            let evm_tx = EthereumTxAdapter::evm_tx(tx).unwrap();
+            assert_eq!(evm_tx.data().unwrap().to_vec(), payload.data);
+        }
     }
 
-    async fn submit(&self, _tx: &mut Transaction) -> eyre::Result<()> {
-        todo!()
+    #[tokio::test]
+    async fn test_build_transactions_batches_payloads() {
+        let (adapter, mock_provider, _db_dir) = test_adapter(10);
+        // RPC 1: eth_estimateGas for the batch
+        mock_provider.push(U256::from(300_000)).unwrap();
+        let payloads = vec![FullPayload::random(), FullPayload::random()];
+
+        let txs = adapter.build_transactions(payloads).await;
+
+        assert_eq!(txs.len(), 1);
+        assert_eq!(txs[0].payload_details.len(), 2);
+        let evm_tx = EthereumTxAdapter::evm_tx(&txs[0]).unwrap();
+        assert_eq!(evm_tx.gas(), Some(&EthersU256::from(300_000)));
     }
 
-    async fn tx_status(&self, _tx: &Transaction) -> eyre::Result<TransactionStatus> {
-        todo!()
+    #[tokio::test]
+    async fn test_tx_status_without_hash_is_pending() {
+        let (adapter, _, _db_dir) = test_adapter(1);
+        let status = adapter.tx_status(&test_tx(None)).await.unwrap();
+        assert_eq!(status, TransactionStatus::PendingInclusion);
     }
 
-    async fn reverted_payloads(&self, _tx: &Transaction) -> eyre::Result<Vec<Uuid>> {
-        todo!()
+    #[tokio::test]
+    async fn test_tx_status_included_and_finalized() {
+        let (adapter, mock_provider, _db_dir) = test_adapter(1);
+        let tx = test_tx(Some(H512::random()));
+
+        // The MockProvider responses are processed in LIFO order
+        // RPC 2: eth_blockNumber, so the finalized block is 90
+        mock_provider.push(U64::from(100)).unwrap();
+        // RPC 1: eth_getTransactionReceipt
+        mock_provider.push(receipt(Some(95), 1)).unwrap();
+        let status = adapter.tx_status(&tx).await.unwrap();
+        assert!(matches!(status, TransactionStatus::Included(_)));
+
+        mock_provider.push(U64::from(100)).unwrap();
+        mock_provider.push(receipt(Some(85), 1)).unwrap();
+        let status = adapter.tx_status(&tx).await.unwrap();
+        assert!(matches!(status, TransactionStatus::Finalized(_)));
+    }
+
+    #[tokio::test]
+    async fn test_tx_status_dropped_once_nonce_is_used() {
+        let (db, _db_dir) = tmp_db();
+        let (adapter, mock_provider) = test_adapter_with_db(1, db.clone());
+        let tx = stored_tx_with_nonce(&adapter, &db, 5, TransactionStatus::PendingInclusion).await;
+
+        // RPC 3: eth_getTransactionCount, the nonce is still unused
+        mock_provider.push(U256::from(5)).unwrap();
+        // RPC 2: eth_getTransactionByHash, the tx isn't known to the node
+        mock_provider
+            .push(Option::<ethers::types::Transaction>::None)
+            .unwrap();
+        // RPC 1: eth_getTransactionReceipt
+        mock_provider
+            .push(Option::<TransactionReceipt>::None)
+            .unwrap();
+        let status = adapter.tx_status(&tx).await.unwrap();
+        assert_eq!(status, TransactionStatus::PendingInclusion);
+
+        mock_provider.push(U256::from(6)).unwrap();
+        mock_provider
+            .push(Option::<ethers::types::Transaction>::None)
+            .unwrap();
+        mock_provider
+            .push(Option::<TransactionReceipt>::None)
+            .unwrap();
+        let status = adapter.tx_status(&tx).await.unwrap();
+        assert_eq!(status, TransactionStatus::DroppedByChain(SIGNER.into()));
+    }
+
+    #[tokio::test]
+    async fn test_tx_status_finds_receipt_of_replaced_hash() {
+        let (db, _db_dir) = tmp_db();
+        let (adapter, mock_provider) = test_adapter_with_db(1, db.clone());
+        let mut tx =
+            stored_tx_with_nonce(&adapter, &db, 5, TransactionStatus::Mempool(H256::zero())).await;
+        // the tx was escalated, but the version sent before that is the one that landed
+        tx.tx_hashes = vec![tx.hash.unwrap(), H512::random()];
+        tx.hash = tx.tx_hashes.last().copied();
+
+        // RPC 3: eth_blockNumber, so the finalized block is 90
+        mock_provider.push(U64::from(100)).unwrap();
+        // RPC 2: eth_getTransactionReceipt of the first hash
+        mock_provider.push(receipt(Some(95), 0)).unwrap();
+        // RPC 1: eth_getTransactionReceipt of the latest hash
+        mock_provider
+            .push(Option::<TransactionReceipt>::None)
+            .unwrap();
+        let status = adapter.tx_status(&tx).await.unwrap();
+        assert!(matches!(status, TransactionStatus::Included(_)));
+
+        mock_provider.push(receipt(Some(95), 0)).unwrap();
+        mock_provider
+            .push(Option::<TransactionReceipt>::None)
+            .unwrap();
+        let reverted = adapter.reverted_payloads(&tx).await.unwrap();
+        assert_eq!(reverted, vec![*tx.payload_details[0].id]);
+    }
+
+    #[tokio::test]
+    async fn test_reverted_payloads() {
+        let (adapter, mock_provider, _db_dir) = test_adapter(1);
+        let tx = test_tx(Some(H512::random()));
+
+        mock_provider.push(receipt(Some(95), 0)).unwrap();
+        let reverted = adapter.reverted_payloads(&tx).await.unwrap();
+        assert_eq!(reverted, vec![*tx.payload_details[0].id]);
+
+        mock_provider.push(receipt(Some(95), 1)).unwrap();
+        let reverted = adapter.reverted_payloads(&tx).await.unwrap();
+        assert!(reverted.is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_payloads_failing_success_criteria() {
+        let (adapter, mock_provider, _db_dir) = test_adapter(2);
+        let mut tx = test_tx(Some(H512::random()));
+        let unchecked = tx.payload_details[0].clone();
+        let (delivered, undelivered) = (PayloadDetails::new("a"), PayloadDetails::new("b"));
+        tx.payload_details = vec![unchecked, delivered, undelivered.clone()];
+        for (i, details) in tx.payload_details.iter_mut().skip(1).enumerate() {
+            details.success_criteria = Some((vec![i as u8], H256::random()));
+        }
+
+        // RPC 2: eth_call for the undelivered payload
+        mock_provider
+            .push(Bytes::from(abi::encode(&[Token::Bool(false)])))
+            .unwrap();
+        // RPC 1: eth_call for the delivered payload
+        mock_provider
+            .push(Bytes::from(abi::encode(&[Token::Bool(true)])))
+            .unwrap();
+        let failing = adapter
+            .payloads_failing_success_criteria(&tx)
+            .await
+            .unwrap();
+
+        assert_eq!(failing, vec![*undelivered.id]);
+
+        // a single payload is never retried, even if its transaction reverted
+        tx.payload_details.truncate(1);
+        tx.payload_details[0].success_criteria = Some((vec![0], H256::random()));
+        let failing = adapter
+            .payloads_failing_success_criteria(&tx)
+            .await
+            .unwrap();
+        assert!(failing.is_empty());
+    }
+
+    #[test]
+    fn test_success_criteria_must_return_true() {
+        let encoded = |token| abi::encode(&[token]);
+        assert!(EthereumTxAdapter::success_criteria_passed(&encoded(
+            Token::Bool(true)
+        )));
+        assert!(!EthereumTxAdapter::success_criteria_passed(&encoded(
+            Token::Bool(false)
+        )));
+        assert!(!EthereumTxAdapter::success_criteria_passed(&[]));
+    }
+
+    #[tokio::test]
+    async fn test_no_nonce_gap_while_pending_tx_is_known() {
+        let (db, _db_dir) = tmp_db();
+        let (adapter, mock_provider) = test_adapter_with_db(1, db.clone());
+        stored_tx_with_nonce(&adapter, &db, 5, TransactionStatus::Mempool(H256::zero())).await;
+
+        // RPC 2: eth_getTransactionByHash
+        mock_provider
+            .push(ethers::types::Transaction::default())
+            .unwrap();
+        // RPC 1: eth_getTransactionCount
+        mock_provider.push(U256::from(5)).unwrap();
+        assert!(!adapter.nonce_gap_exists().await);
+
+        // the chain moved past the nonce
+        mock_provider.push(U256::from(6)).unwrap();
+        assert!(!adapter.nonce_gap_exists().await);
+    }
+
+    #[tokio::test]
+    async fn test_fills_nonce_of_dropped_tx_with_self_transfer() {
+        let (db, _db_dir) = tmp_db();
+        let (adapter, mock_provider) = test_adapter_with_db(1, db.clone());
+        let dropped = stored_tx_with_nonce(
+            &adapter,
+            &db,
+            5,
+            TransactionStatus::DroppedByChain(H256::zero()),
+        )
+        .await;
+
+        // RPC 1: eth_getTransactionCount
+        mock_provider.push(U256::from(5)).unwrap();
+        assert!(adapter.nonce_gap_exists().await);
+
+        // RPC 2: eth_sendTransaction
+        mock_provider.push(ethers::types::H256::random()).unwrap();
+        // RPC 1: eth_getTransactionCount
+        mock_provider.push(U256::from(5)).unwrap();
+        adapter.fill_nonce_gaps().await.unwrap();
+
+        let mut transfer = EthereumTxAdapter::evm_tx(&dropped).unwrap().clone();
+        adapter.escalate_gas_price(&dropped, &mut transfer);
+        adapter.make_self_transfer(&mut transfer);
+        assert_eq!(transfer.nonce(), Some(&EthersU256::from(5)));
+        assert_eq!(transfer.gas_price(), Some(EthersU256::from(12)));
+        // unlike the responses, requests are asserted in the order they were made
+        for _ in 0..2 {
+            mock_provider
+                .assert_request("eth_getTransactionCount", (SIGNER, "latest"))
+                .unwrap();
+        }
+        mock_provider
+            .assert_request("eth_sendTransaction", [transfer])
+            .unwrap();
+    }
+
+    #[test]
+    fn test_escalation_follows_overrides() {
+        let (mut adapter, _mock_provider, _db_dir) = test_adapter(1);
+        assert_eq!(
+            adapter.blocks_before_resubmission(),
+            DEFAULT_BLOCKS_BEFORE_RESUBMISSION
+        );
+        let overrides = &mut adapter.connection_conf.transaction_overrides;
+        overrides.gas_price_escalation_interval_blocks = Some(5);
+        overrides.max_gas_price = Some(105.into());
+        assert_eq!(adapter.blocks_before_resubmission(), 5);
+
+        let mut tx = test_tx(None);
+        let mut capped = TypedTransaction::Legacy(TransactionRequest::new().gas_price(100));
+        adapter.escalate_gas_price(&tx, &mut capped);
+        assert_eq!(capped.gas_price(), Some(EthersU256::from(105)));
+
+        // the cap no longer applies once the payload is past its deadline
+        tx.inclusion_soft_deadline = Some(chrono::Utc::now() - chrono::Duration::minutes(1));
+        let mut uncapped = TypedTransaction::Legacy(TransactionRequest::new().gas_price(100));
+        adapter.escalate_gas_price(&tx, &mut uncapped);
+        assert_eq!(uncapped.gas_price(), Some(EthersU256::from(111)));
     }
 }
@@ -0,0 +1,284 @@
+use std::sync::Arc;
+
+use eyre::Result;
+use tokio::sync::Mutex;
+use tracing::info;
+
+use aetherium_base::db::{AetheriumRocksDB, DbResult};
+use aetherium_core::H256;
+
+use crate::transaction::{Transaction, TransactionDb, TransactionId, TransactionStatus};
+
+const NEXT_NONCE_BY_SIGNER_STORAGE_PREFIX: &str = "next_nonce_by_signer_";
+const TRANSACTION_ID_BY_SIGNER_NONCE_STORAGE_PREFIX: &str = "transaction_id_by_signer_nonce_";
+
+pub trait NonceDb: Send + Sync {
+    /// Retrieve the nonce that the signer's next transaction will be assigned
+    fn retrieve_next_nonce_by_signer(&self, signer: &H256) -> DbResult<Option<u64>>;
+
+    /// Store the nonce that the signer's next transaction will be assigned
+    fn store_next_nonce_by_signer(&self, signer: &H256, nonce: u64) -> DbResult<()>;
+
+    /// Retrieve the ID of the transaction that was assigned the signer's nonce
+    fn retrieve_transaction_id_by_signer_nonce(
+        &self,
+        signer: &H256,
+        nonce: u64,
+    ) -> DbResult<Option<TransactionId>>;
+
+    /// Store the ID of the transaction that was assigned the signer's nonce
+    fn store_transaction_id_by_signer_nonce(
+        &self,
+        signer: &H256,
+        nonce: u64,
+        tx_id: &TransactionId,
+    ) -> DbResult<()>;
+}
+
+impl NonceDb for AetheriumRocksDB {
+    fn retrieve_next_nonce_by_signer(&self, signer: &H256) -> DbResult<Option<u64>> {
+        self.retrieve_value_by_key(NEXT_NONCE_BY_SIGNER_STORAGE_PREFIX, signer)
+    }
+
+    fn store_next_nonce_by_signer(&self, signer: &H256, nonce: u64) -> DbResult<()> {
+        self.store_value_by_key(NEXT_NONCE_BY_SIGNER_STORAGE_PREFIX, signer, &nonce)
+    }
+
+    fn retrieve_transaction_id_by_signer_nonce(
+        &self,
+        signer: &H256,
+        nonce: u64,
+    ) -> DbResult<Option<TransactionId>> {
+        self.retrieve_decodable(
+            TRANSACTION_ID_BY_SIGNER_NONCE_STORAGE_PREFIX,
+            signer_nonce_key(signer, nonce),
+        )
+    }
+
+    fn store_transaction_id_by_signer_nonce(
+        &self,
+        signer: &H256,
+        nonce: u64,
+        tx_id: &TransactionId,
+    ) -> DbResult<()> {
+        self.store_encodable(
+            TRANSACTION_ID_BY_SIGNER_NONCE_STORAGE_PREFIX,
+            signer_nonce_key(signer, nonce),
+            tx_id,
+        )
+    }
+}
+
+fn signer_nonce_key(signer: &H256, nonce: u64) -> Vec<u8> {
+    [signer.as_bytes(), &nonce.to_be_bytes()].concat()
+}
+
+/// A nonce between the signer's on-chain nonce and the next locally assigned one, which blocks
+/// every later transaction until something lands with it
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum NonceGap {
+    /// no transaction was stored with the nonce, e.g. because the process crashed right after
+    /// assigning it
+    Unused(u64),
+    /// the transaction holding the nonce is still waiting for inclusion, but may have been
+    /// evicted from the mempool
+    Pending(Transaction),
+    /// the transaction holding the nonce was dropped, so it will never be resubmitted
+    Abandoned(Transaction),
+}
+
+impl NonceGap {
+    pub fn nonce(&self) -> Option<u64> {
+        match self {
+            NonceGap::Unused(nonce) => Some(*nonce),
+            NonceGap::Pending(tx) | NonceGap::Abandoned(tx) => tx.vm_specific_data.nonce(),
+        }
+    }
+}
+
+/// Assigns nonces to the transactions of a signer and finds the gaps they leave behind.
+/// Assignments are persisted, so that restarts don't hand out a nonce twice.
+pub struct NonceManager {
+    signer: H256,
+    db: Arc<dyn NonceDb>,
+    tx_db: Arc<dyn TransactionDb>,
+    /// held while assigning a nonce, so that no nonce is assigned twice
+    assignment_lock: Mutex<()>,
+}
+
+impl NonceManager {
+    pub fn new(signer: H256, db: Arc<dyn NonceDb>, tx_db: Arc<dyn TransactionDb>) -> Self {
+        Self {
+            signer,
+            db,
+            tx_db,
+            assignment_lock: Mutex::new(()),
+        }
+    }
+
+    pub fn signer(&self) -> H256 {
+        self.signer
+    }
+
+    /// Assigns the next nonce to the transaction. The chain's nonce wins if it is ahead, e.g.
+    /// because another process sent transactions with the same signer.
+    pub async fn assign_nonce(&self, tx_id: &TransactionId, on_chain_nonce: u64) -> Result<u64> {
+        let _guard = self.assignment_lock.lock().await;
+        let next_nonce = self.db.retrieve_next_nonce_by_signer(&self.signer)?;
+        let nonce = next_nonce.map_or(on_chain_nonce, |next| next.max(on_chain_nonce));
+        // the assignment is stored before the nonce is used, so a crash can only leave a gap
+        // behind, which gets filled, and never a nonce that is assigned twice
+        self.db
+            .store_transaction_id_by_signer_nonce(&self.signer, nonce, tx_id)?;
+        self.db
+            .store_next_nonce_by_signer(&self.signer, nonce + 1)?;
+        if next_nonce.is_some_and(|next| next < on_chain_nonce) {
+            info!(signer = ?self.signer, ?next_nonce, on_chain_nonce, "Chain nonce is ahead of the local one, skipping ahead");
+        }
+        Ok(nonce)
+    }
+
+    /// The nonces from the chain's nonce up to the next local one that no live transaction
+    /// is going to land. Included transactions are skipped, since the chain will catch up.
+    pub fn gaps(&self, on_chain_nonce: u64) -> Result<Vec<NonceGap>> {
+        let Some(next_nonce) = self.db.retrieve_next_nonce_by_signer(&self.signer)? else {
+            return Ok(vec![]);
+        };
+        let mut gaps = vec![];
+        for nonce in on_chain_nonce..next_nonce {
+            let tx = match self
+                .db
+                .retrieve_transaction_id_by_signer_nonce(&self.signer, nonce)?
+            {
+                Some(tx_id) => self.tx_db.retrieve_transaction_by_id(&tx_id)?,
+                None => None,
+            };
+            // the tx may have been stored before it was assigned the nonce, or not at all
+            let Some(tx) = tx.filter(|tx| tx.vm_specific_data.nonce() == Some(nonce)) else {
+                gaps.push(NonceGap::Unused(nonce));
+                continue;
+            };
+            match tx.status {
+                TransactionStatus::PendingInclusion | TransactionStatus::Mempool(_) => {
+                    gaps.push(NonceGap::Pending(tx))
+                }
+                TransactionStatus::Included(_) | TransactionStatus::Finalized(_) => {}
+                TransactionStatus::DroppedByChain(_) | TransactionStatus::Dropped(_) => {
+                    gaps.push(NonceGap::Abandoned(tx))
+                }
+            }
+        }
+        Ok(gaps)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use ethers::types::{transaction::eip2718::TypedTransaction, TransactionRequest};
+
+    use crate::{
+        payload::{DropReason, FullPayload},
+        payload_dispatcher::test_utils::tmp_db,
+        transaction::VmSpecificTxData,
+    };
+
+    use super::*;
+
+    fn test_manager() -> (NonceManager, Arc<AetheriumRocksDB>, tempfile::TempDir) {
+        let (db, db_dir) = tmp_db();
+        let manager = NonceManager::new(H256::random(), db.clone(), db.clone());
+        (manager, db, db_dir)
+    }
+
+    fn stored_tx(db: &AetheriumRocksDB, nonce: u64, status: TransactionStatus) -> Transaction {
+        let request = TransactionRequest::new().nonce(nonce);
+        let mut tx = Transaction::new(
+            VmSpecificTxData::Evm(TypedTransaction::Legacy(request)),
+            &[FullPayload::random()],
+        );
+        tx.status = status;
+        db.store_transaction_by_id(tx.clone()).unwrap();
+        tx
+    }
+
+    #[tokio::test]
+    async fn test_assigns_consecutive_nonces() {
+        let (manager, db, _db_dir) = test_manager();
+        let (first, second) = (TransactionId::random(), TransactionId::random());
+
+        assert_eq!(manager.assign_nonce(&first, 4).await.unwrap(), 4);
+        // the chain doesn't know about the first tx yet
+        assert_eq!(manager.assign_nonce(&second, 4).await.unwrap(), 5);
+
+        assert_eq!(
+            db.retrieve_transaction_id_by_signer_nonce(&manager.signer(), 5)
+                .unwrap(),
+            Some(second)
+        );
+        // assignments survive restarts
+        let restarted = NonceManager::new(manager.signer(), db.clone(), db);
+        assert_eq!(
+            restarted
+                .assign_nonce(&TransactionId::random(), 4)
+                .await
+                .unwrap(),
+            6
+        );
+    }
+
+    #[tokio::test]
+    async fn test_skips_ahead_to_chain_nonce() {
+        let (manager, _db, _db_dir) = test_manager();
+        manager
+            .assign_nonce(&TransactionId::random(), 1)
+            .await
+            .unwrap();
+
+        let nonce = manager
+            .assign_nonce(&TransactionId::random(), 10)
+            .await
+            .unwrap();
+
+        assert_eq!(nonce, 10);
+        assert!(manager.gaps(10).unwrap().is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_gaps() {
+        let (manager, db, _db_dir) = test_manager();
+        let pending = stored_tx(&db, 3, TransactionStatus::Mempool(H256::zero()));
+        let included = stored_tx(&db, 4, TransactionStatus::Included(H256::zero()));
+        let dropped = stored_tx(
+            &db,
+            5,
+            TransactionStatus::Dropped(DropReason::FailedSimulation),
+        );
+        for tx in [&pending, &included, &dropped] {
+            let nonce = tx.vm_specific_data.nonce().unwrap();
+            assert_eq!(manager.assign_nonce(tx.id(), 3).await.unwrap(), nonce);
+        }
+        // crashed before the tx was stored
+        manager
+            .assign_nonce(&TransactionId::random(), 3)
+            .await
+            .unwrap();
+
+        let gaps = manager.gaps(3).unwrap();
+
+        assert_eq!(
+            gaps,
+            vec![
+                NonceGap::Pending(pending),
+                NonceGap::Abandoned(dropped),
+                NonceGap::Unused(6),
+            ]
+        );
+        assert_eq!(
+            gaps.iter().map(NonceGap::nonce).collect::<Vec<_>>(),
+            vec![Some(3), Some(5), Some(6)]
+        );
+        // nonces the chain already moved past aren't gaps
+        assert_eq!(manager.gaps(6).unwrap(), vec![NonceGap::Unused(6)]);
+        assert!(manager.gaps(7).unwrap().is_empty());
+    }
+}
@@ -1,51 +1,709 @@
+use std::time::Duration;
+
 use async_trait::async_trait;
+use eyre::{eyre, Result};
+use solana_sdk::{
+    instruction::Instruction, signature::Signature, signer::Signer,
+    transaction::Transaction as SealevelTransaction,
+};
+use solana_transaction_status::TransactionConfirmationStatus;
+use tracing::{info, warn};
 use uuid::Uuid;
 
-use aetherium_base::settings::{ChainConf, RawChainConf};
+use aetherium_base::{
+    settings::{ChainConf, ChainConnectionConf, RawChainConf},
+    CoreMetrics,
+};
+use aetherium_core::{H256, H512, U256};
+use aetherium_sealevel::{
+    PriorityFeeOracle, SealevelKeypair, SealevelProviderForSubmitter, SealevelRpcClient,
+    SealevelTxCostEstimate, TransactionSubmitter,
+};
 
 use crate::{
     chain_tx_adapter::{AdaptsChain, GasLimit},
     payload::FullPayload,
-    transaction::{Transaction, TransactionStatus},
+    transaction::{Transaction, TransactionStatus, VmSpecificTxData},
 };
 
+/// Used when the chain metadata doesn't specify `blocks.estimateBlockTime`
+const DEFAULT_ESTIMATED_BLOCK_TIME: Duration = Duration::from_millis(400);
+
+/// Everything needed to (re)sign the transaction of a payload
+#[derive(Debug, Clone, serde::Deserialize, serde::Serialize, PartialEq, Eq)]
+pub struct SealevelTxPrecursor {
+    /// deserialized from the payload data. Compute budget instructions are prepended to it when signing
+    pub instruction: Instruction,
+    /// estimated by simulating the instruction on the first submission
+    pub estimate: Option<SealevelTxCostEstimate>,
+    /// the last transaction sent. Its blockhash may have expired since
+    pub signed_tx: Option<SealevelTransaction>,
+}
+
+impl SealevelTxPrecursor {
+    pub fn new(instruction: Instruction) -> Self {
+        Self {
+            instruction,
+            estimate: None,
+            signed_tx: None,
+        }
+    }
+}
+
 pub struct SealevelTxAdapter {
     _conf: ChainConf,
     _raw_conf: RawChainConf,
+    provider: Box<dyn SealevelProviderForSubmitter>,
+    tx_submitter: Box<dyn TransactionSubmitter>,
+    priority_fee_oracle: Box<dyn PriorityFeeOracle>,
+    payer: SealevelKeypair,
+    estimated_block_time: Duration,
 }
 
 impl SealevelTxAdapter {
-    pub fn new(conf: ChainConf, raw_conf: RawChainConf) -> Self {
+    pub async fn new(
+        conf: ChainConf,
+        raw_conf: RawChainConf,
+        metrics: &CoreMetrics,
+    ) -> Result<Self> {
+        let ChainConnectionConf::Sealevel(connection_conf) = &conf.connection else {
+            return Err(eyre!(
+                "SealevelTxAdapter requires a Sealevel connection config"
+            ));
+        };
+        let priority_fee_oracle = connection_conf.priority_fee_oracle.create_oracle();
+        let (provider, tx_submitter, payer) =
+            conf.build_sealevel_provider_for_submitter(metrics).await?;
+        let payer = payer.ok_or_else(|| eyre!("SealevelTxAdapter requires a signer"))?;
+        Ok(Self::from_parts(
+            conf,
+            raw_conf,
+            provider,
+            tx_submitter,
+            priority_fee_oracle,
+            payer,
+        ))
+    }
+
+    fn from_parts(
+        conf: ChainConf,
+        raw_conf: RawChainConf,
+        provider: Box<dyn SealevelProviderForSubmitter>,
+        tx_submitter: Box<dyn TransactionSubmitter>,
+        priority_fee_oracle: Box<dyn PriorityFeeOracle>,
+        payer: SealevelKeypair,
+    ) -> Self {
+        let estimated_block_time =
+            super::estimated_block_time(&raw_conf, DEFAULT_ESTIMATED_BLOCK_TIME);
         Self {
             _conf: conf,
             _raw_conf: raw_conf,
+            provider,
+            tx_submitter,
+            priority_fee_oracle,
+            payer,
+            estimated_block_time,
         }
     }
+
+    fn payload_to_instruction(payload: &FullPayload) -> Result<Instruction> {
+        serde_json::from_slice(&payload.data).map_err(|err| {
+            eyre!(
+                "Payload {} is not a Sealevel instruction: {err}",
+                payload.id()
+            )
+        })
+    }
+
+    fn precursor(tx: &Transaction) -> Result<&SealevelTxPrecursor> {
+        match &tx.vm_specific_data {
+            VmSpecificTxData::Svm(precursor) => Ok(precursor),
+            _ => Err(eyre!(
+                "Transaction {} is not a Sealevel transaction",
+                tx.id()
+            )),
+        }
+    }
+
+    fn signer_address(&self) -> H256 {
+        H256::from(self.payer.pubkey().to_bytes())
+    }
+
+    async fn estimate(&self, instruction: &Instruction) -> Result<SealevelTxCostEstimate> {
+        let estimate = self
+            .provider
+            .get_estimated_costs_for_instruction(
+                instruction.clone(),
+                &self.payer,
+                &*self.tx_submitter,
+                &*self.priority_fee_oracle,
+            )
+            .await?;
+        Ok(estimate)
+    }
+
+    /// Signs the instruction over the latest finalized blockhash. Its costs are estimated again
+    /// every time, since a re-signed transaction didn't land with the previous priority fee,
+    /// which is kept if the new one is lower.
+    async fn sign(&self, precursor: &mut SealevelTxPrecursor) -> Result<SealevelTransaction> {
+        let mut estimate = self.estimate(&precursor.instruction).await?;
+        if let Some(previous) = precursor.estimate {
+            estimate.compute_unit_price_micro_lamports = estimate
+                .compute_unit_price_micro_lamports
+                .max(previous.compute_unit_price_micro_lamports);
+        }
+        precursor.estimate = Some(estimate);
+        let signed_tx = self
+            .provider
+            .create_transaction_for_instruction(
+                estimate.compute_units,
+                estimate.compute_unit_price_micro_lamports,
+                precursor.instruction.clone(),
+                &self.payer,
+                &*self.tx_submitter,
+                true,
+            )
+            .await?;
+        Ok(signed_tx)
+    }
+
+    /// Whether the last signed transaction can still land, in which case it must not be
+    /// re-signed: both transactions could end up included
+    async fn can_still_land(&self, signed_tx: &SealevelTransaction) -> Result<bool> {
+        if self
+            .provider
+            .is_blockhash_valid(&signed_tx.message.recent_blockhash)
+            .await?
+        {
+            return Ok(true);
+        }
+        // the blockhash may have expired right after the transaction landed
+        let status = self
+            .provider
+            .get_signature_status(&signed_tx.signatures[0])
+            .await?;
+        Ok(status.is_some())
+    }
 }
 
 #[async_trait]
 impl AdaptsChain for SealevelTxAdapter {
-    async fn estimate_gas_limit(&self, _payload: &FullPayload) -> eyre::Result<GasLimit> {
-        todo!()
+    async fn estimate_gas_limit(&self, payload: &FullPayload) -> Result<GasLimit> {
+        let instruction = Self::payload_to_instruction(payload)?;
+        let estimate = self.estimate(&instruction).await?;
+        Ok(U256::from(estimate.compute_units))
+    }
+
+    async fn build_transactions(&self, payloads: Vec<FullPayload>) -> Vec<Transaction> {
+        payloads
+            .iter()
+            .filter_map(|payload| match Self::payload_to_instruction(payload) {
+                Ok(instruction) => {
+                    let precursor = SealevelTxPrecursor::new(instruction);
+                    Some(Transaction::new(
+                        VmSpecificTxData::Svm(precursor),
+                        &[payload.clone()],
+                    ))
+                }
+                Err(err) => {
+                    warn!(?err, payload = ?payload.details, "Failed to build transaction");
+                    None
+                }
+            })
+            .collect()
+    }
+
+    async fn simulate_tx(&self, tx: &Transaction) -> Result<bool> {
+        let precursor = Self::precursor(tx)?;
+        // simulated with the max compute units, since the actual usage isn't estimated yet
+        let simulation_tx = self
+            .provider
+            .create_transaction_for_instruction(
+                SealevelRpcClient::MAX_COMPUTE_UNITS,
+                0,
+                precursor.instruction.clone(),
+                &self.payer,
+                &*self.tx_submitter,
+                false,
+            )
+            .await?;
+        let result = self.provider.simulate_transaction(&simulation_tx).await?;
+        if let Some(err) = result.err {
+            warn!(tx_id = %tx.id(), ?err, logs = ?result.logs, "Transaction simulation failed");
+            return Ok(false);
+        }
+        Ok(true)
+    }
+
+    async fn submit(&self, tx: &mut Transaction) -> Result<()> {
+        let mut precursor = Self::precursor(tx)?.clone();
+        let signed_tx = match precursor.signed_tx.take() {
+            // rebroadcasting the same transaction is idempotent, unlike signing a new one
+            Some(signed_tx) if self.can_still_land(&signed_tx).await? => signed_tx,
+            _ => self.sign(&mut precursor).await?,
+        };
+        let signature = signed_tx.signatures[0];
+        precursor.signed_tx = Some(signed_tx.clone());
+        tx.vm_specific_data = VmSpecificTxData::Svm(precursor);
+        tx.submission_attempts += 1;
+        // set before broadcasting, so that the transaction is tracked even if the RPC errors
+        // after forwarding it
+        tx.hash = Some(H512::from_slice(signature.as_ref()));
+
+        // preflight simulation is skipped since the Inclusion Stage already simulated the tx
+        let signature = self.tx_submitter.send_transaction(&signed_tx, true).await?;
+        info!(tx_id = %tx.id(), ?signature, "Sent transaction");
+        Ok(())
+    }
+
+    async fn tx_status(&self, tx: &Transaction) -> Result<TransactionStatus> {
+        let Some(hash) = tx.hash else {
+            return Ok(TransactionStatus::PendingInclusion);
+        };
+        let signature = Signature::new(hash.as_bytes());
+        let Some(status) = self.provider.get_signature_status(&signature).await? else {
+            // unknown signatures either haven't been processed yet or were dropped, in which
+            // case their blockhash has to expire before the instruction can be re-signed
+            let signed_tx = Self::precursor(tx)?.signed_tx.as_ref();
+            if let Some(signed_tx) = signed_tx {
+                if self
+                    .provider
+                    .is_blockhash_valid(&signed_tx.message.recent_blockhash)
+                    .await?
+                {
+                    return Ok(TransactionStatus::Mempool(self.signer_address()));
+                }
+            }
+            return Ok(TransactionStatus::PendingInclusion);
+        };
+        match status.confirmation_status() {
+            TransactionConfirmationStatus::Finalized => {
+                Ok(TransactionStatus::Finalized(self.signer_address()))
+            }
+            TransactionConfirmationStatus::Processed | TransactionConfirmationStatus::Confirmed => {
+                Ok(TransactionStatus::Included(self.signer_address()))
+            }
+        }
+    }
+
+    async fn reverted_payloads(&self, tx: &Transaction) -> Result<Vec<Uuid>> {
+        let Some(hash) = tx.hash else {
+            return Ok(vec![]);
+        };
+        let signature = Signature::new(hash.as_bytes());
+        let Some(status) = self.provider.get_signature_status(&signature).await? else {
+            return Ok(vec![]);
+        };
+        // a failed transaction reverts every payload in it
+        if let Some(err) = status.err {
+            warn!(tx_id = %tx.id(), ?err, "Transaction failed");
+            return Ok(tx.payload_details.iter().map(|d| *d.id).collect());
+        }
+        Ok(vec![])
+    }
+
+    fn estimated_block_time(&self) -> Duration {
+        self.estimated_block_time
     }
+}
+
+#[cfg(test)]
+mod tests {
+    use solana_client::rpc_response::RpcSimulateTransactionResult;
+    use solana_sdk::{
+        hash::Hash, pubkey::Pubkey, signature::Keypair, transaction::TransactionError,
+    };
+    use solana_transaction_status::TransactionStatus as SignatureStatus;
+
+    use aetherium_base::settings::CoreContractAddresses;
+    use aetherium_core::{AetheriumDomain, ChainResult, KnownAetheriumDomain, ReorgPeriod};
+    use aetherium_sealevel::ConnectionConf;
 
-    async fn build_transactions(&self, _payloads: Vec<FullPayload>) -> Vec<Transaction> {
-        todo!()
+    use super::*;
+
+    mockall::mock! {
+        pub Provider {}
+
+        #[async_trait]
+        impl SealevelProviderForSubmitter for Provider {
+            async fn get_estimated_costs_for_instruction(
+                &self,
+                instruction: Instruction,
+                payer: &SealevelKeypair,
+                tx_submitter: &dyn TransactionSubmitter,
+                priority_fee_oracle: &dyn PriorityFeeOracle,
+            ) -> ChainResult<SealevelTxCostEstimate>;
+            async fn create_transaction_for_instruction(
+                &self,
+                compute_unit_limit: u32,
+                compute_unit_price_micro_lamports: u64,
+                instruction: Instruction,
+                payer: &SealevelKeypair,
+                tx_submitter: &dyn TransactionSubmitter,
+                sign: bool,
+            ) -> ChainResult<SealevelTransaction>;
+            async fn simulate_transaction(
+                &self,
+                transaction: &SealevelTransaction,
+            ) -> ChainResult<RpcSimulateTransactionResult>;
+            async fn get_signature_status(
+                &self,
+                signature: &Signature,
+            ) -> ChainResult<Option<SignatureStatus>>;
+            async fn is_blockhash_valid(&self, hash: &Hash) -> ChainResult<bool>;
+        }
     }
 
-    async fn simulate_tx(&self, _tx: &Transaction) -> eyre::Result<bool> {
-        todo!()
+    mockall::mock! {
+        pub Submitter {}
+
+        #[async_trait]
+        impl TransactionSubmitter for Submitter {
+            fn get_priority_fee_instruction(
+                &self,
+                compute_unit_price_micro_lamports: u64,
+                compute_units: u64,
+                payer: &Pubkey,
+            ) -> Instruction;
+            async fn send_transaction(
+                &self,
+                transaction: &SealevelTransaction,
+                skip_preflight: bool,
+            ) -> ChainResult<Signature>;
+        }
     }
 
-    async fn submit(&self, _tx: &mut Transaction) -> eyre::Result<()> {
-        todo!()
+    mockall::mock! {
+        pub Oracle {}
+
+        #[async_trait]
+        impl PriorityFeeOracle for Oracle {
+            async fn get_priority_fee(&self, transaction: &SealevelTransaction) -> ChainResult<u64>;
+        }
     }
 
-    async fn tx_status(&self, _tx: &Transaction) -> eyre::Result<TransactionStatus> {
-        todo!()
+    const ESTIMATE: SealevelTxCostEstimate = SealevelTxCostEstimate {
+        compute_units: 200_000,
+        compute_unit_price_micro_lamports: 1_000,
+    };
+
+    fn test_adapter(provider: MockProvider, tx_submitter: MockSubmitter) -> SealevelTxAdapter {
+        let connection_conf = ConnectionConf {
+            url: "http://127.0.0.1:8899".parse().unwrap(),
+            operation_batch: Default::default(),
+            native_token: Default::default(),
+            priority_fee_oracle: Default::default(),
+            transaction_submitter: Default::default(),
+        };
+        let conf = ChainConf {
+            domain: AetheriumDomain::Known(KnownAetheriumDomain::SolanaMainnet),
+            signer: None,
+            reorg_period: ReorgPeriod::None,
+            addresses: CoreContractAddresses::default(),
+            connection: ChainConnectionConf::Sealevel(connection_conf),
+            metrics_conf: Default::default(),
+            index: Default::default(),
+        };
+        SealevelTxAdapter::from_parts(
+            conf,
+            RawChainConf::default(),
+            Box::new(provider),
+            Box::new(tx_submitter),
+            Box::new(MockOracle::new()),
+            SealevelKeypair::new(Keypair::new()),
+        )
     }
 
-    async fn reverted_payloads(&self, _tx: &Transaction) -> eyre::Result<Vec<Uuid>> {
-        todo!()
+    fn test_instruction() -> Instruction {
+        Instruction::new_with_bytes(Pubkey::new_unique(), &[1, 2, 3], vec![])
+    }
+
+    fn signed_tx(adapter: &SealevelTxAdapter, instruction: &Instruction) -> SealevelTransaction {
+        SealevelTransaction::new_signed_with_payer(
+            &[instruction.clone()],
+            Some(&adapter.payer.pubkey()),
+            &[adapter.payer.keypair()],
+            Hash::new_unique(),
+        )
+    }
+
+    fn test_tx(precursor: SealevelTxPrecursor) -> Transaction {
+        let mut tx = Transaction::new(VmSpecificTxData::Svm(precursor), &[FullPayload::random()]);
+        if let VmSpecificTxData::Svm(SealevelTxPrecursor {
+            signed_tx: Some(signed_tx),
+            ..
+        }) = &tx.vm_specific_data
+        {
+            tx.hash = Some(H512::from_slice(signed_tx.signatures[0].as_ref()));
+        }
+        tx
+    }
+
+    fn signature_status(
+        confirmation_status: TransactionConfirmationStatus,
+        err: Option<TransactionError>,
+    ) -> SignatureStatus {
+        SignatureStatus {
+            slot: 1,
+            confirmations: None,
+            status: err.clone().map_or(Ok(()), Err),
+            err,
+            confirmation_status: Some(confirmation_status),
+        }
+    }
+
+    #[tokio::test]
+    async fn test_build_transactions_skips_invalid_payloads() {
+        let adapter = test_adapter(MockProvider::new(), MockSubmitter::new());
+        let instruction = test_instruction();
+        let payload = FullPayload::new(
+            "valid",
+            serde_json::to_vec(&instruction).unwrap(),
+            H256::random(),
+        );
+        let invalid_payload = FullPayload::new("invalid", vec![1, 2, 3], H256::random());
+
+        let txs = adapter
+            .build_transactions(vec![payload.clone(), invalid_payload])
+            .await;
+
+        assert_eq!(txs.len(), 1);
+        assert_eq!(txs[0].payload_details, vec![payload.details]);
+        assert_eq!(
+            SealevelTxAdapter::precursor(&txs[0]).unwrap(),
+            &SealevelTxPrecursor::new(instruction)
+        );
+    }
+
+    #[tokio::test]
+    async fn test_simulate_tx_fails_on_error() {
+        let mut provider = MockProvider::new();
+        provider
+            .expect_create_transaction_for_instruction()
+            .returning(|_, _, instruction, payer, _, _| {
+                Ok(SealevelTransaction::new_with_payer(
+                    &[instruction],
+                    Some(&payer.pubkey()),
+                ))
+            });
+        provider.expect_simulate_transaction().returning(|_| {
+            Ok(RpcSimulateTransactionResult {
+                err: Some(TransactionError::AccountNotFound),
+                logs: None,
+                accounts: None,
+                units_consumed: None,
+                return_data: None,
+            })
+        });
+        let adapter = test_adapter(provider, MockSubmitter::new());
+        let tx = test_tx(SealevelTxPrecursor::new(test_instruction()));
+
+        assert!(!adapter.simulate_tx(&tx).await.unwrap());
+    }
+
+    #[tokio::test]
+    async fn test_first_submission_estimates_and_signs() {
+        let instruction = test_instruction();
+        let mut provider = MockProvider::new();
+        provider
+            .expect_get_estimated_costs_for_instruction()
+            .times(1)
+            .returning(|_, _, _, _| Ok(ESTIMATE));
+        provider
+            .expect_create_transaction_for_instruction()
+            .withf(|compute_units, price, _, _, _, sign| {
+                *compute_units == ESTIMATE.compute_units
+                    && *price == ESTIMATE.compute_unit_price_micro_lamports
+                    && *sign
+            })
+            .times(1)
+            .returning(|_, _, instruction, payer, _, _| {
+                Ok(SealevelTransaction::new_signed_with_payer(
+                    &[instruction],
+                    Some(&payer.pubkey()),
+                    &[payer.keypair()],
+                    Hash::new_unique(),
+                ))
+            });
+        let mut tx_submitter = MockSubmitter::new();
+        tx_submitter
+            .expect_send_transaction()
+            .times(1)
+            .returning(|tx, _| Ok(tx.signatures[0]));
+        let adapter = test_adapter(provider, tx_submitter);
+        let mut tx = test_tx(SealevelTxPrecursor::new(instruction));
+
+        adapter.submit(&mut tx).await.unwrap();
+
+        let precursor = SealevelTxAdapter::precursor(&tx).unwrap();
+        assert_eq!(precursor.estimate, Some(ESTIMATE));
+        let signature = precursor.signed_tx.as_ref().unwrap().signatures[0];
+        assert_eq!(tx.hash, Some(H512::from_slice(signature.as_ref())));
+        assert_eq!(tx.submission_attempts, 1);
+    }
+
+    #[tokio::test]
+    async fn test_resubmission_rebroadcasts_while_blockhash_is_valid() {
+        let mut provider = MockProvider::new();
+        provider.expect_is_blockhash_valid().returning(|_| Ok(true));
+        provider.expect_create_transaction_for_instruction().never();
+        let mut tx_submitter = MockSubmitter::new();
+        tx_submitter
+            .expect_send_transaction()
+            .returning(|tx, _| Ok(tx.signatures[0]));
+        let adapter = test_adapter(provider, tx_submitter);
+        let instruction = test_instruction();
+        let mut tx = test_tx(SealevelTxPrecursor {
+            signed_tx: Some(signed_tx(&adapter, &instruction)),
+            estimate: Some(ESTIMATE),
+            instruction,
+        });
+        let hash = tx.hash;
+
+        adapter.submit(&mut tx).await.unwrap();
+
+        assert_eq!(tx.hash, hash);
+    }
+
+    #[tokio::test]
+    async fn test_resubmission_refreshes_expired_blockhash() {
+        let mut provider = MockProvider::new();
+        provider
+            .expect_is_blockhash_valid()
+            .returning(|_| Ok(false));
+        provider
+            .expect_get_signature_status()
+            .returning(|_| Ok(None));
+        // the priority fee went up since the expired transaction was signed
+        provider
+            .expect_get_estimated_costs_for_instruction()
+            .times(1)
+            .returning(|_, _, _, _| {
+                Ok(SealevelTxCostEstimate {
+                    compute_unit_price_micro_lamports: 2_000,
+                    ..ESTIMATE
+                })
+            });
+        provider
+            .expect_create_transaction_for_instruction()
+            .withf(|_, price, _, _, _, _| *price == 2_000)
+            .times(1)
+            .returning(|_, _, instruction, payer, _, _| {
+                Ok(SealevelTransaction::new_signed_with_payer(
+                    &[instruction],
+                    Some(&payer.pubkey()),
+                    &[payer.keypair()],
+                    Hash::new_unique(),
+                ))
+            });
+        let mut tx_submitter = MockSubmitter::new();
+        tx_submitter
+            .expect_send_transaction()
+            .returning(|tx, _| Ok(tx.signatures[0]));
+        let adapter = test_adapter(provider, tx_submitter);
+        let instruction = test_instruction();
+        let expired_tx = signed_tx(&adapter, &instruction);
+        let mut tx = test_tx(SealevelTxPrecursor {
+            signed_tx: Some(expired_tx.clone()),
+            estimate: Some(ESTIMATE),
+            instruction,
+        });
+
+        adapter.submit(&mut tx).await.unwrap();
+
+        let precursor = SealevelTxAdapter::precursor(&tx).unwrap();
+        let refreshed_tx = precursor.signed_tx.as_ref().unwrap();
+        assert_ne!(
+            refreshed_tx.message.recent_blockhash,
+            expired_tx.message.recent_blockhash
+        );
+        assert_eq!(
+            tx.hash,
+            Some(H512::from_slice(refreshed_tx.signatures[0].as_ref()))
+        );
+        assert_eq!(
+            precursor
+                .estimate
+                .unwrap()
+                .compute_unit_price_micro_lamports,
+            2_000
+        );
+        assert_eq!(tx.submission_attempts, 1);
+    }
+
+    #[tokio::test]
+    async fn test_tx_status() {
+        let mut provider = MockProvider::new();
+        provider
+            .expect_get_signature_status()
+            .times(1)
+            .returning(|_| Ok(None));
+        provider
+            .expect_get_signature_status()
+            .times(1)
+            .returning(|_| {
+                Ok(Some(signature_status(
+                    TransactionConfirmationStatus::Confirmed,
+                    None,
+                )))
+            });
+        provider
+            .expect_get_signature_status()
+            .times(1)
+            .returning(|_| {
+                Ok(Some(signature_status(
+                    TransactionConfirmationStatus::Finalized,
+                    None,
+                )))
+            });
+        provider.expect_is_blockhash_valid().returning(|_| Ok(true));
+        let adapter = test_adapter(provider, MockSubmitter::new());
+        let instruction = test_instruction();
+        let payer = adapter.signer_address();
+
+        let unsubmitted = test_tx(SealevelTxPrecursor::new(instruction.clone()));
+        assert_eq!(
+            adapter.tx_status(&unsubmitted).await.unwrap(),
+            TransactionStatus::PendingInclusion
+        );
+
+        let tx = test_tx(SealevelTxPrecursor {
+            signed_tx: Some(signed_tx(&adapter, &instruction)),
+            estimate: Some(ESTIMATE),
+            instruction,
+        });
+        assert_eq!(
+            adapter.tx_status(&tx).await.unwrap(),
+            TransactionStatus::Mempool(payer)
+        );
+        assert_eq!(
+            adapter.tx_status(&tx).await.unwrap(),
+            TransactionStatus::Included(payer)
+        );
+        assert_eq!(
+            adapter.tx_status(&tx).await.unwrap(),
+            TransactionStatus::Finalized(payer)
+        );
+    }
+
+    #[tokio::test]
+    async fn test_reverted_payloads() {
+        let mut provider = MockProvider::new();
+        provider.expect_get_signature_status().returning(|_| {
+            Ok(Some(signature_status(
+                TransactionConfirmationStatus::Finalized,
+                Some(TransactionError::AccountNotFound),
+            )))
+        });
+        let adapter = test_adapter(provider, MockSubmitter::new());
+        let instruction = test_instruction();
+        let tx = test_tx(SealevelTxPrecursor {
+            signed_tx: Some(signed_tx(&adapter, &instruction)),
+            estimate: Some(ESTIMATE),
+            instruction,
+        });
+
+        let reverted = adapter.reverted_payloads(&tx).await.unwrap();
+        assert_eq!(reverted, vec![*tx.payload_details[0].id]);
     }
 }
@@ -6,16 +6,20 @@ use std::io::Write;
 use aetherium_base::db::{DbResult, AetheriumRocksDB};
 use aetherium_core::{identifiers::UniqueIdentifier, Decode, Encode, AetheriumProtocolError};
 
-use super::{FullPayload, PayloadId};
+use super::{FullPayload, PayloadId, PayloadStatus};
 
 const PAYLOAD_BY_ID_STORAGE_PREFIX: &str = "payload_by_id_";
+const PAYLOAD_ID_BY_STATUS_STORAGE_PREFIX: &str = "payload_id_by_status_";
 
-pub trait PayloadDb {
+pub trait PayloadDb: Send + Sync {
     /// Retrieve a payload by its unique ID
     fn retrieve_payload_by_id(&self, id: &UniqueIdentifier) -> DbResult<Option<FullPayload>>;
 
-    /// Store a payload by its unique ID
+    /// Store a payload by its unique ID, keeping the status index up to date
     fn store_payload_by_id(&self, payload: FullPayload) -> DbResult<()>;
+
+    /// Retrieve all payloads whose status is the same variant as `status`, regardless of the inner reason
+    fn retrieve_payloads_by_status(&self, status: &PayloadStatus) -> DbResult<Vec<FullPayload>>;
 }
 
 impl PayloadDb for AetheriumRocksDB {
@@ -24,10 +28,63 @@ impl PayloadDb for AetheriumRocksDB {
     }
 
     fn store_payload_by_id(&self, payload: FullPayload) -> DbResult<()> {
-        self.store_value_by_key(PAYLOAD_BY_ID_STORAGE_PREFIX, payload.id(), &payload)
+        let previous_status = self
+            .retrieve_payload_by_id(payload.id())?
+            .map(|stored| stored.status);
+        // the payload and its index entries are written in a single batch, so that a crash never
+        // leaves the index out of sync with the stored payload
+        let mut batch = self.batch();
+        if let Some(previous) = previous_status {
+            if status_key(&previous) != status_key(&payload.status) {
+                batch.delete_value(
+                    PAYLOAD_ID_BY_STATUS_STORAGE_PREFIX,
+                    status_index_key(&previous, payload.id()),
+                );
+            }
+        }
+        batch.store_encodable(
+            PAYLOAD_ID_BY_STATUS_STORAGE_PREFIX,
+            status_index_key(&payload.status, payload.id()),
+            payload.id(),
+        );
+        batch.store_keyed_encodable(PAYLOAD_BY_ID_STORAGE_PREFIX, payload.id(), &payload);
+        batch.write()
+    }
+
+    fn retrieve_payloads_by_status(&self, status: &PayloadStatus) -> DbResult<Vec<FullPayload>> {
+        let ids: Vec<PayloadId> = self.retrieve_decodables_by_key_prefix(
+            PAYLOAD_ID_BY_STATUS_STORAGE_PREFIX,
+            status_key(status),
+        )?;
+        let mut payloads = Vec::with_capacity(ids.len());
+        for id in ids {
+            // index entries may be stale if the same payload was updated concurrently
+            match self.retrieve_payload_by_id(&id)? {
+                Some(payload) if status_key(&payload.status) == status_key(status) => {
+                    payloads.push(payload)
+                }
+                _ => {}
+            }
+        }
+        Ok(payloads)
+    }
+}
+
+fn status_key(status: &PayloadStatus) -> &'static [u8] {
+    match status {
+        PayloadStatus::ReadyToSubmit => b"ready_to_submit_",
+        PayloadStatus::PendingInclusion => b"pending_inclusion_",
+        PayloadStatus::Included => b"included_",
+        PayloadStatus::Finalized => b"finalized_",
+        PayloadStatus::Dropped(_) => b"dropped_",
+        PayloadStatus::Retry(_) => b"retry_",
     }
 }
 
+fn status_index_key(status: &PayloadStatus, id: &PayloadId) -> Vec<u8> {
+    [status_key(status), id.to_vec().as_slice()].concat()
+}
+
 impl Encode for FullPayload {
     fn write_to<W>(&self, writer: &mut W) -> std::io::Result<usize>
     where
@@ -55,3 +112,64 @@ impl Decode for FullPayload {
         })
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use crate::payload::{DropReason, RetryReason};
+    use crate::payload_dispatcher::test_utils::tmp_db;
+
+    use super::*;
+
+    fn ids(payloads: Vec<FullPayload>) -> Vec<PayloadId> {
+        let mut ids = payloads.iter().map(|p| *p.id()).collect::<Vec<_>>();
+        ids.sort_by_key(|id| **id);
+        ids
+    }
+
+    #[test]
+    fn test_retrieve_payloads_by_status() {
+        let (db, _db_dir) = tmp_db();
+        let ready = FullPayload::random();
+        let mut dropped = FullPayload::random();
+        dropped.status = PayloadStatus::Dropped(DropReason::Reverted);
+        db.store_payload_by_id(ready.clone()).unwrap();
+        db.store_payload_by_id(dropped.clone()).unwrap();
+
+        assert_eq!(
+            ids(db
+                .retrieve_payloads_by_status(&PayloadStatus::ReadyToSubmit)
+                .unwrap()),
+            vec![*ready.id()]
+        );
+        // only the variant is matched, not the reason
+        assert_eq!(
+            ids(db
+                .retrieve_payloads_by_status(&PayloadStatus::Dropped(DropReason::FailedSimulation))
+                .unwrap()),
+            vec![*dropped.id()]
+        );
+        assert!(db
+            .retrieve_payloads_by_status(&PayloadStatus::Retry(RetryReason::Reorged))
+            .unwrap()
+            .is_empty());
+    }
+
+    #[test]
+    fn test_status_index_follows_status_updates() {
+        let (db, _db_dir) = tmp_db();
+        let mut payload = FullPayload::random();
+        db.store_payload_by_id(payload.clone()).unwrap();
+        payload.status = PayloadStatus::PendingInclusion;
+        db.store_payload_by_id(payload.clone()).unwrap();
+
+        assert!(db
+            .retrieve_payloads_by_status(&PayloadStatus::ReadyToSubmit)
+            .unwrap()
+            .is_empty());
+        assert_eq!(
+            db.retrieve_payloads_by_status(&PayloadStatus::PendingInclusion)
+                .unwrap(),
+            vec![payload]
+        );
+    }
+}
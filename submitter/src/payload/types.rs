@@ -14,33 +14,34 @@ type Address = H256;
 #[derive(Debug, Clone, serde::Deserialize, serde::Serialize, PartialEq, Eq)]
 pub struct PayloadDetails {
     /// unique payload identifier
-    id: PayloadId,
+    pub id: PayloadId,
 
     /// to be printed in logs for easier debugging. This may include the Aetherium Message ID
-    metadata: String,
+    pub metadata: String,
 
-    // unused field in MVP
-    /// view calls for checking if batch subcalls reverted. EVM-specific for now.
-    success_criteria: Option<(Vec<u8>, Address)>,
+    /// view call (calldata and contract address) checking whether the payload succeeded, e.g. `Mailbox::delivered(id)`.
+    /// Run once its transaction is included, since batch subcalls can revert without reverting the batch. The call
+    /// must return a bool, and the payload is retried if it returns false. EVM-specific for now.
+    pub success_criteria: Option<(Vec<u8>, Address)>,
 }
 
 /// Full details about a payload. This is instantiated by the caller of PayloadDispatcher
 #[derive(Debug, Clone, serde::Deserialize, serde::Serialize, PartialEq, Eq)]
 pub struct FullPayload {
     /// reference to payload used by other components
-    details: PayloadDetails,
+    pub details: PayloadDetails,
     /// calldata on EVM. On SVM, it is the serialized instructions and account list. On Cosmos, it is the serialized vec of msgs
-    data: Vec<u8>,
+    pub data: Vec<u8>,
     /// defaults to the aetherium mailbox
-    to: Address,
+    pub to: Address,
     /// defaults to `ReadyToSubmit`
-    status: PayloadStatus,
+    pub status: PayloadStatus,
 
     // unused fields in MVP
     // always None initially
-    value: Option<U256>,
+    pub value: Option<U256>,
     /// will be up to the adapter to interpret this. Meant to help enforce the new igp social contract requirement (after 30 mins, stop enforcing any gas price caps)
-    inclusion_soft_deadline: Option<DateTime<Utc>>,
+    pub inclusion_soft_deadline: Option<DateTime<Utc>>,
 }
 
 #[derive(Debug, Clone, serde::Deserialize, serde::Serialize, PartialEq, Eq, Default)]
@@ -63,10 +64,38 @@ pub enum DropReason {
 #[derive(Debug, Clone, serde::Deserialize, serde::Serialize, PartialEq, Eq)]
 pub enum RetryReason {
     Reorged,
+    /// its transaction was included, but its success criteria didn't pass
+    FailedSuccessCriteria,
+}
+
+impl PayloadDetails {
+    pub fn new(metadata: impl Into<String>) -> Self {
+        Self {
+            id: PayloadId::random(),
+            metadata: metadata.into(),
+            success_criteria: None,
+        }
+    }
 }
 
 impl FullPayload {
+    pub fn new(metadata: impl Into<String>, data: Vec<u8>, to: Address) -> Self {
+        Self {
+            details: PayloadDetails::new(metadata),
+            data,
+            to,
+            status: Default::default(),
+            value: None,
+            inclusion_soft_deadline: None,
+        }
+    }
+
     pub fn id(&self) -> &PayloadId {
         &self.details.id
     }
+
+    #[cfg(test)]
+    pub fn random() -> Self {
+        Self::new("random test payload", vec![], Address::random())
+    }
 }
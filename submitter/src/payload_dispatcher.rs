@@ -1,8 +1,12 @@
 // TODO: re-enable clippy warnings
 #![allow(unused_imports)]
 
+mod db_loader;
 mod dispatcher;
 mod entrypoint;
+mod stages;
+#[cfg(test)]
+pub(crate) mod test_utils;
 
 pub use dispatcher::*;
 pub use entrypoint::*;
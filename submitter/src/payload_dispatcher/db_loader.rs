@@ -0,0 +1,225 @@
+use std::collections::HashSet;
+
+use eyre::{eyre, Result};
+use tokio::sync::mpsc;
+use tracing::{info, warn};
+
+use aetherium_core::H256;
+
+use crate::{
+    payload::{PayloadId, PayloadStatus, RetryReason},
+    transaction::{Transaction, TransactionStatus},
+};
+
+use super::PayloadDispatcherState;
+
+/// Rebuilds the in-flight state of the `PayloadDispatcher` from the db when it starts, so that
+/// restarts neither lose track of submitted transactions nor submit their payloads twice.
+pub struct DbLoader {
+    state: PayloadDispatcherState,
+    inclusion_stage_sender: mpsc::UnboundedSender<Transaction>,
+    finality_stage_sender: mpsc::UnboundedSender<Transaction>,
+}
+
+impl DbLoader {
+    pub fn new(
+        state: PayloadDispatcherState,
+        inclusion_stage_sender: mpsc::UnboundedSender<Transaction>,
+        finality_stage_sender: mpsc::UnboundedSender<Transaction>,
+    ) -> Self {
+        Self {
+            state,
+            inclusion_stage_sender,
+            finality_stage_sender,
+        }
+    }
+
+    /// Must run before the stages start, so that payloads aren't queued twice
+    pub async fn load(&self) -> Result<()> {
+        // transactions are loaded first: payloads of in-flight transactions must not be built
+        // into new ones, even if the process crashed before their status was updated
+        let mut in_flight_payloads = HashSet::new();
+        let pending_txs = self.txs_by_status(&[
+            TransactionStatus::PendingInclusion,
+            TransactionStatus::Mempool(H256::zero()),
+        ])?;
+        let included_txs = self.txs_by_status(&[TransactionStatus::Included(H256::zero())])?;
+        let (pending_count, included_count) = (pending_txs.len(), included_txs.len());
+
+        for tx in pending_txs {
+            in_flight_payloads.extend(tx.payload_details.iter().map(|details| details.id));
+            self.inclusion_stage_sender
+                .send(tx)
+                .map_err(|_| eyre!("Inclusion Stage channel closed"))?;
+        }
+        for tx in included_txs {
+            in_flight_payloads.extend(tx.payload_details.iter().map(|details| details.id));
+            self.finality_stage_sender
+                .send(tx)
+                .map_err(|_| eyre!("Finality Stage channel closed"))?;
+        }
+
+        let queued_count = self.load_payloads(&in_flight_payloads).await?;
+        info!(
+            pending_txs = pending_count,
+            included_txs = included_count,
+            queued_payloads = queued_count,
+            "Loaded PayloadDispatcher state from db"
+        );
+        Ok(())
+    }
+
+    fn txs_by_status(&self, statuses: &[TransactionStatus]) -> Result<Vec<Transaction>> {
+        let mut txs = vec![];
+        for status in statuses {
+            txs.extend(self.state.tx_db.retrieve_transactions_by_status(status)?);
+        }
+        Ok(txs)
+    }
+
+    async fn load_payloads(&self, in_flight_payloads: &HashSet<PayloadId>) -> Result<usize> {
+        let mut payloads = self
+            .state
+            .payload_db
+            .retrieve_payloads_by_status(&PayloadStatus::ReadyToSubmit)?;
+        payloads.extend(
+            self.state
+                .payload_db
+                .retrieve_payloads_by_status(&PayloadStatus::Retry(RetryReason::Reorged))?,
+        );
+
+        let mut queue = self.state.building_queue.lock().await;
+        let mut queued_count = 0;
+        for mut payload in payloads {
+            if in_flight_payloads.contains(payload.id()) {
+                warn!(payload = ?payload.details, "Payload is already part of an in-flight transaction, not queueing it again");
+                payload.status = PayloadStatus::PendingInclusion;
+                self.state.payload_db.store_payload_by_id(payload)?;
+                continue;
+            }
+            queue.push_back(payload);
+            queued_count += 1;
+        }
+        Ok(queued_count)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{
+        payload::{DropReason, FullPayload, PayloadDb},
+        payload_dispatcher::test_utils::{dummy_vm_data, tmp_state, MockAdapter},
+        transaction::TransactionDb,
+    };
+
+    use super::*;
+
+    fn store_payload(state: &PayloadDispatcherState, status: PayloadStatus) -> FullPayload {
+        let mut payload = FullPayload::random();
+        payload.status = status;
+        state
+            .payload_db
+            .store_payload_by_id(payload.clone())
+            .unwrap();
+        payload
+    }
+
+    fn store_tx(
+        state: &PayloadDispatcherState,
+        status: TransactionStatus,
+        payload_status: PayloadStatus,
+    ) -> Transaction {
+        let payload = store_payload(state, payload_status);
+        let mut tx = Transaction::new(dummy_vm_data(), &[payload]);
+        tx.status = status;
+        state.tx_db.store_transaction_by_id(tx.clone()).unwrap();
+        tx
+    }
+
+    #[tokio::test]
+    async fn test_load_routes_txs_and_payloads() {
+        let (state, _db_dir) = tmp_state(MockAdapter::new());
+        let pending = store_tx(
+            &state,
+            TransactionStatus::PendingInclusion,
+            PayloadStatus::PendingInclusion,
+        );
+        let mempool = store_tx(
+            &state,
+            TransactionStatus::Mempool(H256::random()),
+            PayloadStatus::PendingInclusion,
+        );
+        let included = store_tx(
+            &state,
+            TransactionStatus::Included(H256::random()),
+            PayloadStatus::Included,
+        );
+        store_tx(
+            &state,
+            TransactionStatus::Finalized(H256::random()),
+            PayloadStatus::Finalized,
+        );
+        store_tx(
+            &state,
+            TransactionStatus::Dropped(DropReason::FailedSimulation),
+            PayloadStatus::Dropped(DropReason::FailedSimulation),
+        );
+        let ready = store_payload(&state, PayloadStatus::ReadyToSubmit);
+        let retry = store_payload(&state, PayloadStatus::Retry(RetryReason::Reorged));
+        store_payload(&state, PayloadStatus::Finalized);
+
+        let (inclusion_sender, mut inclusion_receiver) = mpsc::unbounded_channel();
+        let (finality_sender, mut finality_receiver) = mpsc::unbounded_channel();
+        DbLoader::new(state.clone(), inclusion_sender, finality_sender)
+            .load()
+            .await
+            .unwrap();
+
+        let mut resumed = vec![
+            inclusion_receiver.try_recv().unwrap().id,
+            inclusion_receiver.try_recv().unwrap().id,
+        ];
+        resumed.sort_by_key(|id| **id);
+        let mut expected = vec![pending.id, mempool.id];
+        expected.sort_by_key(|id| **id);
+        assert_eq!(resumed, expected);
+        assert!(inclusion_receiver.try_recv().is_err());
+        assert_eq!(finality_receiver.try_recv().unwrap().id, included.id);
+        assert!(finality_receiver.try_recv().is_err());
+
+        let queue = state.building_queue.lock().await;
+        let mut queued = queue.iter().map(|p| *p.id()).collect::<Vec<_>>();
+        queued.sort_by_key(|id| **id);
+        let mut expected = vec![*ready.id(), *retry.id()];
+        expected.sort_by_key(|id| **id);
+        assert_eq!(queued, expected);
+    }
+
+    #[tokio::test]
+    async fn test_load_does_not_requeue_payloads_of_in_flight_txs() {
+        let (state, _db_dir) = tmp_state(MockAdapter::new());
+        // crash after the Building Stage stored the tx, but before it updated the payload status
+        let tx = store_tx(
+            &state,
+            TransactionStatus::PendingInclusion,
+            PayloadStatus::ReadyToSubmit,
+        );
+        let payload_id = tx.payload_details[0].id;
+
+        let (inclusion_sender, mut inclusion_receiver) = mpsc::unbounded_channel();
+        let (finality_sender, _finality_receiver) = mpsc::unbounded_channel();
+        DbLoader::new(state.clone(), inclusion_sender, finality_sender)
+            .load()
+            .await
+            .unwrap();
+
+        assert_eq!(inclusion_receiver.try_recv().unwrap().id, tx.id);
+        assert!(state.building_queue.lock().await.is_empty());
+        let payload = state
+            .payload_db
+            .retrieve_payload_by_id(&payload_id)
+            .unwrap()
+            .unwrap();
+        assert_eq!(payload.status, PayloadStatus::PendingInclusion);
+    }
+}
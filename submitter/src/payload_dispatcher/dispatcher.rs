@@ -1,14 +1,33 @@
 // TODO: re-enable clippy warnings
 #![allow(dead_code)]
 
-use std::path::PathBuf;
+use std::{collections::VecDeque, path::PathBuf, sync::Arc};
 
-use aetherium_base::settings::{ChainConf, RawChainConf};
+use eyre::Result;
+use tokio::{
+    sync::{mpsc, Mutex},
+    task::JoinHandle,
+};
+use tracing::{error, info_span, instrument::Instrumented, warn, Instrument};
+
+use aetherium_base::{
+    db::{AetheriumRocksDB, DB},
+    settings::{ChainConf, RawChainConf},
+    CoreMetrics,
+};
 use aetherium_core::AetheriumDomain;
-use tokio::task::JoinHandle;
-use tracing::instrument::Instrumented;
 
-use crate::chain_tx_adapter::{AdaptsChain, ChainTxAdapterBuilder};
+use crate::{
+    chain_tx_adapter::{AdaptsChain, ChainTxAdapterBuilder},
+    payload::{PayloadDb, PayloadDetails, PayloadStatus, RetryReason},
+    transaction::{Transaction, TransactionDb},
+};
+
+use super::{
+    db_loader::DbLoader,
+    stages::{BuildingStage, BuildingStageQueue, FinalityStage, InclusionStage},
+    PayloadDispatcherEntrypoint,
+};
 
 /// Settings for `PayloadDispatcher`
 #[derive(Debug)]
@@ -21,36 +40,177 @@ pub struct PayloadDispatcherSettings {
     db_path: PathBuf,
 }
 
+/// State shared by the `PayloadDispatcher` stages and its entrypoint
+#[derive(Clone)]
 pub struct PayloadDispatcherState {
-    // db: DispatcherDb,
-    adapter: Box<dyn AdaptsChain>,
+    pub(crate) payload_db: Arc<dyn PayloadDb>,
+    pub(crate) tx_db: Arc<dyn TransactionDb>,
+    pub(crate) adapter: Arc<dyn AdaptsChain>,
+    /// payloads waiting to be built into transactions
+    pub(crate) building_queue: BuildingStageQueue,
 }
 
 impl PayloadDispatcherState {
-    pub fn new(settings: PayloadDispatcherSettings) -> Self {
-        let adapter = ChainTxAdapterBuilder::build(&settings.chain_conf, &settings.raw_chain_conf);
-        Self { adapter }
+    pub async fn try_from_settings(
+        settings: &PayloadDispatcherSettings,
+        metrics: &CoreMetrics,
+    ) -> Result<Self> {
+        let db = DB::from_path(&settings.db_path)?;
+        let db = Arc::new(AetheriumRocksDB::new(&settings.domain, db));
+        let adapter = ChainTxAdapterBuilder::build(
+            &settings.chain_conf,
+            &settings.raw_chain_conf,
+            db.clone(),
+            metrics,
+        )
+        .await?;
+        Ok(Self::new(db.clone(), db, adapter.into()))
+    }
+
+    pub(crate) fn new(
+        payload_db: Arc<dyn PayloadDb>,
+        tx_db: Arc<dyn TransactionDb>,
+        adapter: Arc<dyn AdaptsChain>,
+    ) -> Self {
+        Self {
+            payload_db,
+            tx_db,
+            adapter,
+            building_queue: Arc::new(Mutex::new(VecDeque::new())),
+        }
     }
 
-    pub(crate) fn from_adapter(adapter: Box<dyn AdaptsChain>) -> Self {
-        Self { adapter }
+    pub(crate) fn store_tx(&self, tx: &Transaction) -> Result<()> {
+        self.tx_db.store_transaction_by_id(tx.clone())?;
+        Ok(())
+    }
+
+    pub(crate) fn update_status_for_payloads(
+        &self,
+        details: &[PayloadDetails],
+        status: PayloadStatus,
+    ) -> Result<()> {
+        for detail in details {
+            let Some(mut payload) = self.payload_db.retrieve_payload_by_id(&detail.id)? else {
+                warn!(payload = ?detail, "Payload not found in db, can't update its status");
+                continue;
+            };
+            payload.status = status.clone();
+            self.payload_db.store_payload_by_id(payload)?;
+        }
+        Ok(())
+    }
+
+    /// Marks the payloads for retry and puts them back in the building queue, so they get included in a new transaction
+    pub(crate) async fn requeue_payloads(
+        &self,
+        details: &[PayloadDetails],
+        reason: RetryReason,
+    ) -> Result<()> {
+        for detail in details {
+            let Some(mut payload) = self.payload_db.retrieve_payload_by_id(&detail.id)? else {
+                warn!(payload = ?detail, "Payload not found in db, can't retry it");
+                continue;
+            };
+            payload.status = PayloadStatus::Retry(reason.clone());
+            self.payload_db.store_payload_by_id(payload.clone())?;
+            self.building_queue.lock().await.push_back(payload);
+        }
+        Ok(())
     }
 }
+
+/// Runs the Building, Inclusion and Finality stages for a single chain.
+///
+/// Payloads sent via the `PayloadDispatcherEntrypoint` are batched into transactions by the
+/// Building Stage. The Inclusion Stage simulates, submits and resubmits them (escalating gas)
+/// until they land in a block, and the Finality Stage waits for them to be older than the
+/// `reorgPeriod` before marking their payloads as finalized.
 pub struct PayloadDispatcher {
     inner: PayloadDispatcherState,
+    domain: String,
 }
 
 impl PayloadDispatcher {
-    pub fn new(settings: PayloadDispatcherSettings) -> Self {
-        Self {
-            inner: PayloadDispatcherState::new(settings),
-        }
+    pub async fn try_from_settings(
+        settings: PayloadDispatcherSettings,
+        metrics: &CoreMetrics,
+    ) -> Result<Self> {
+        Ok(Self {
+            inner: PayloadDispatcherState::try_from_settings(&settings, metrics).await?,
+            domain: settings.domain.to_string(),
+        })
+    }
+
+    /// Returns an entrypoint that shares this dispatcher's state, used for sending payloads
+    /// and querying their status
+    pub fn entrypoint(&self) -> PayloadDispatcherEntrypoint {
+        PayloadDispatcherEntrypoint::from_inner(self.inner.clone())
     }
 
     pub fn spawn(self) -> Instrumented<JoinHandle<()>> {
-        // create the submit queue and channels for the Dispatcher stages
-        // spawn the DbLoader with references to the submit queue and channels
-        // spawn the 3 stages using the adapter, db, queue and channels
-        todo!()
+        let (inclusion_stage_sender, inclusion_stage_receiver) = mpsc::unbounded_channel();
+        let (finality_stage_sender, finality_stage_receiver) = mpsc::unbounded_channel();
+
+        let db_loader = DbLoader::new(
+            self.inner.clone(),
+            inclusion_stage_sender.clone(),
+            finality_stage_sender.clone(),
+        );
+        let building_stage = BuildingStage::new(self.inner.clone(), inclusion_stage_sender.clone());
+        let inclusion_stage = InclusionStage::new(
+            self.inner.clone(),
+            inclusion_stage_receiver,
+            finality_stage_sender,
+        );
+        let finality_stage = FinalityStage::new(
+            self.inner.clone(),
+            finality_stage_receiver,
+            inclusion_stage_sender,
+        );
+        let span = info_span!("PayloadDispatcher", domain = %self.domain);
+        let domain = self.domain;
+
+        tokio::spawn(
+            async move {
+                // the stages only start once the in-flight state is restored, otherwise
+                // payloads could end up in more than one transaction
+                if let Err(err) = db_loader.load().await {
+                    error!(?err, "Failed to load PayloadDispatcher state from db");
+                    return;
+                }
+
+                let mut building_task = tokio::spawn(
+                    building_stage
+                        .run()
+                        .instrument(info_span!("BuildingStage", domain = %domain)),
+                );
+                let mut inclusion_task = tokio::spawn(
+                    inclusion_stage
+                        .run()
+                        .instrument(info_span!("InclusionStage", domain = %domain)),
+                );
+                let mut finality_task = tokio::spawn(
+                    finality_stage
+                        .run()
+                        .instrument(info_span!("FinalityStage", domain = %domain)),
+                );
+
+                let (stage, result) = tokio::select! {
+                    res = &mut building_task => ("BuildingStage", res),
+                    res = &mut inclusion_task => ("InclusionStage", res),
+                    res = &mut finality_task => ("FinalityStage", res),
+                };
+                // the stages only work together, so the others are stopped and the failure is
+                // propagated to whoever awaits the dispatcher
+                for task in [building_task, inclusion_task, finality_task] {
+                    task.abort();
+                }
+                error!(stage, ?result, "PayloadDispatcher stage exited");
+                panic!("PayloadDispatcher stage {stage} exited for domain {domain}: {result:?}");
+            }
+            .instrument(span.clone()),
+        )
+        .instrument(span)
     }
 }
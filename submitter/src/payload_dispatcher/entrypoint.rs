@@ -1,55 +1,82 @@
 // TODO: re-enable clippy warnings
 #![allow(dead_code)]
 
-use super::{PayloadDispatcherSettings, PayloadDispatcherState};
+use eyre::{eyre, Result};
 
+use crate::{
+    chain_tx_adapter::GasLimit,
+    payload::{FullPayload, PayloadId, PayloadStatus},
+};
+
+use super::PayloadDispatcherState;
+
+/// Used by callers to hand payloads over to a running `PayloadDispatcher` and to track them
 pub struct PayloadDispatcherEntrypoint {
     inner: PayloadDispatcherState,
 }
 
 impl PayloadDispatcherEntrypoint {
-    pub fn new(settings: PayloadDispatcherSettings) -> Self {
-        Self {
-            inner: PayloadDispatcherState::new(settings),
-        }
+    pub(crate) fn from_inner(inner: PayloadDispatcherState) -> Self {
+        Self { inner }
     }
 
-    fn from_inner(inner: PayloadDispatcherState) -> Self {
-        Self { inner }
+    /// Stores the payload and queues it for the Building Stage
+    pub async fn send_payload(&self, payload: &FullPayload) -> Result<()> {
+        let mut payload = payload.clone();
+        payload.status = PayloadStatus::ReadyToSubmit;
+        self.inner.payload_db.store_payload_by_id(payload.clone())?;
+        self.inner.building_queue.lock().await.push_back(payload);
+        Ok(())
+    }
+
+    pub async fn payload_status(&self, payload_id: &PayloadId) -> Result<PayloadStatus> {
+        self.inner
+            .payload_db
+            .retrieve_payload_by_id(payload_id)?
+            .map(|payload| payload.status)
+            .ok_or_else(|| eyre!("Payload {payload_id} not found"))
+    }
+
+    pub async fn estimate_gas_limit(&self, payload: &FullPayload) -> Result<GasLimit> {
+        self.inner.adapter.estimate_gas_limit(payload).await
     }
 }
 
 #[cfg(test)]
 mod tests {
-    use async_trait::async_trait;
-    use eyre::Result;
-
     use super::*;
-    use crate::chain_tx_adapter::*;
-    use crate::payload::*;
-    use crate::transaction::*;
-
-    mockall::mock! {
-        pub Adapter {
-        }
-
-        #[async_trait]
-        impl AdaptsChain for Adapter {
-            async fn estimate_gas_limit(&self, payload: &FullPayload) -> Result<GasLimit>;
-            async fn build_transactions(&self, payloads: Vec<FullPayload>) -> Vec<Transaction>;
-            async fn simulate_tx(&self, tx: &Transaction) -> Result<bool>;
-            async fn submit(&self, tx: &mut Transaction) -> Result<()>;
-            async fn tx_status(&self, tx: &Transaction) -> Result<TransactionStatus>;
-            async fn reverted_payloads(&self, tx: &Transaction) -> Result<Vec<uuid::Uuid>>;
-            async fn nonce_gap_exists(&self) -> bool;
-            async fn replace_tx(&self, _tx: &Transaction) -> Result<()>;
-        }
-    }
+    use crate::payload_dispatcher::test_utils::{tmp_state, MockAdapter};
 
     #[test]
     fn test_payload_dispatcher_entrypoint() {
-        let adapter = Box::new(MockAdapter::new()) as Box<dyn AdaptsChain>;
-        let entrypoint_state = PayloadDispatcherState::from_adapter(adapter);
+        let (entrypoint_state, _db_dir) = tmp_state(MockAdapter::new());
         let entrypoint = PayloadDispatcherEntrypoint::from_inner(entrypoint_state);
     }
+
+    #[tokio::test]
+    async fn test_send_payload_queues_it_for_building() {
+        let (state, _db_dir) = tmp_state(MockAdapter::new());
+        let entrypoint = PayloadDispatcherEntrypoint::from_inner(state.clone());
+        let payload = FullPayload::random();
+
+        entrypoint.send_payload(&payload).await.unwrap();
+
+        assert_eq!(
+            entrypoint.payload_status(payload.id()).await.unwrap(),
+            PayloadStatus::ReadyToSubmit
+        );
+        let queue = state.building_queue.lock().await;
+        assert_eq!(queue.iter().collect::<Vec<_>>(), vec![&payload]);
+    }
+
+    #[tokio::test]
+    async fn test_unknown_payload_status() {
+        let (state, _db_dir) = tmp_state(MockAdapter::new());
+        let entrypoint = PayloadDispatcherEntrypoint::from_inner(state);
+
+        assert!(entrypoint
+            .payload_status(&PayloadId::random())
+            .await
+            .is_err());
+    }
 }
@@ -0,0 +1,7 @@
+mod building_stage;
+mod finality_stage;
+mod inclusion_stage;
+
+pub(crate) use building_stage::*;
+pub(crate) use finality_stage::*;
+pub(crate) use inclusion_stage::*;
@@ -0,0 +1,186 @@
+use std::{
+    collections::{HashSet, VecDeque},
+    sync::Arc,
+    time::Duration,
+};
+
+use eyre::{eyre, Result};
+use tokio::{
+    sync::{mpsc, Mutex},
+    time::sleep,
+};
+use tracing::{error, info, warn};
+
+use crate::{
+    payload::{FullPayload, PayloadId, PayloadStatus},
+    transaction::Transaction,
+};
+
+use crate::payload_dispatcher::PayloadDispatcherState;
+
+pub type BuildingStageQueue = Arc<Mutex<VecDeque<FullPayload>>>;
+
+/// Upper bound on the number of payloads handed to the adapter in a single `build_transactions` call
+const MAX_PAYLOADS_PER_BUILD: usize = 100;
+
+/// How long to wait before checking the queue again when it is empty
+const EMPTY_QUEUE_POLL_INTERVAL: Duration = Duration::from_millis(100);
+
+/// Pops payloads from the building queue, batches them into transactions via the adapter and
+/// forwards the transactions to the Inclusion Stage
+pub struct BuildingStage {
+    state: PayloadDispatcherState,
+    inclusion_stage_sender: mpsc::UnboundedSender<Transaction>,
+}
+
+impl BuildingStage {
+    pub fn new(
+        state: PayloadDispatcherState,
+        inclusion_stage_sender: mpsc::UnboundedSender<Transaction>,
+    ) -> Self {
+        Self {
+            state,
+            inclusion_stage_sender,
+        }
+    }
+
+    pub async fn run(self) {
+        loop {
+            let payloads = self.pop_payloads().await;
+            if payloads.is_empty() {
+                sleep(EMPTY_QUEUE_POLL_INTERVAL).await;
+                continue;
+            }
+            if let Err(err) = self.build_and_forward(payloads).await {
+                error!(?err, "Error building transactions");
+                if self.inclusion_stage_sender.is_closed() {
+                    return;
+                }
+            }
+        }
+    }
+
+    async fn pop_payloads(&self) -> Vec<FullPayload> {
+        let mut queue = self.state.building_queue.lock().await;
+        let count = queue.len().min(MAX_PAYLOADS_PER_BUILD);
+        queue.drain(..count).collect()
+    }
+
+    async fn build_and_forward(&self, payloads: Vec<FullPayload>) -> Result<()> {
+        let txs = self
+            .state
+            .adapter
+            .build_transactions(payloads.clone())
+            .await;
+
+        // payloads the adapter couldn't build a transaction for go to the back of the queue
+        let built: HashSet<PayloadId> = txs
+            .iter()
+            .flat_map(|tx| tx.payload_details.iter().map(|details| details.id))
+            .collect();
+        let unbuilt = payloads
+            .into_iter()
+            .filter(|payload| !built.contains(payload.id()))
+            .collect::<Vec<_>>();
+        if !unbuilt.is_empty() {
+            warn!(
+                payloads = ?unbuilt.iter().map(|p| &p.details).collect::<Vec<_>>(),
+                "Failed to build transactions for payloads, requeueing them"
+            );
+            self.state.building_queue.lock().await.extend(unbuilt);
+        }
+
+        for tx in txs {
+            self.state.store_tx(&tx)?;
+            self.state
+                .update_status_for_payloads(&tx.payload_details, PayloadStatus::PendingInclusion)?;
+            info!(tx_id = %tx.id(), payloads = ?tx.payload_details, "Built transaction");
+            self.inclusion_stage_sender
+                .send(tx)
+                .map_err(|_| eyre!("Inclusion Stage channel closed"))?;
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{
+        payload::PayloadDb,
+        payload_dispatcher::test_utils::{dummy_vm_data, tmp_state, MockAdapter},
+        transaction::TransactionDb,
+    };
+
+    use super::*;
+
+    fn store_and_queue(state: &PayloadDispatcherState, payloads: &[FullPayload]) {
+        for payload in payloads {
+            state
+                .payload_db
+                .store_payload_by_id(payload.clone())
+                .unwrap();
+        }
+        state
+            .building_queue
+            .try_lock()
+            .unwrap()
+            .extend(payloads.iter().cloned());
+    }
+
+    #[tokio::test]
+    async fn test_builds_and_forwards_transactions() {
+        let mut adapter = MockAdapter::new();
+        adapter.expect_build_transactions().returning(|payloads| {
+            payloads
+                .iter()
+                .map(|p| Transaction::new(dummy_vm_data(), &[p.clone()]))
+                .collect()
+        });
+        let (state, _db_dir) = tmp_state(adapter);
+        let payloads = vec![FullPayload::random(), FullPayload::random()];
+        store_and_queue(&state, &payloads);
+
+        let (sender, mut receiver) = mpsc::unbounded_channel();
+        let stage = BuildingStage::new(state.clone(), sender);
+        let popped = stage.pop_payloads().await;
+        stage.build_and_forward(popped).await.unwrap();
+
+        for payload in payloads.iter() {
+            let tx = receiver.try_recv().unwrap();
+            assert_eq!(tx.payload_details, vec![payload.details.clone()]);
+            assert_eq!(
+                state.tx_db.retrieve_transaction_by_id(tx.id()).unwrap(),
+                Some(tx)
+            );
+            let stored = state
+                .payload_db
+                .retrieve_payload_by_id(payload.id())
+                .unwrap()
+                .unwrap();
+            assert_eq!(stored.status, PayloadStatus::PendingInclusion);
+        }
+        assert!(state.building_queue.lock().await.is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_requeues_unbuilt_payloads() {
+        let mut adapter = MockAdapter::new();
+        adapter
+            .expect_build_transactions()
+            .returning(|payloads| vec![Transaction::new(dummy_vm_data(), &payloads[..1])]);
+        let (state, _db_dir) = tmp_state(adapter);
+        let payloads = vec![FullPayload::random(), FullPayload::random()];
+        store_and_queue(&state, &payloads);
+
+        let (sender, mut receiver) = mpsc::unbounded_channel();
+        let stage = BuildingStage::new(state.clone(), sender);
+        let popped = stage.pop_payloads().await;
+        stage.build_and_forward(popped).await.unwrap();
+
+        let tx = receiver.try_recv().unwrap();
+        assert_eq!(tx.payload_details, vec![payloads[0].details.clone()]);
+        assert!(receiver.try_recv().is_err());
+        let queue = state.building_queue.lock().await;
+        assert_eq!(queue.iter().collect::<Vec<_>>(), vec![&payloads[1]]);
+    }
+}
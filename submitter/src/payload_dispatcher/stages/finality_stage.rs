@@ -0,0 +1,305 @@
+use std::collections::{HashMap, HashSet};
+
+use eyre::Result;
+use tokio::{
+    sync::mpsc,
+    time::{interval, MissedTickBehavior},
+};
+use tracing::{error, info, warn};
+
+use crate::{
+    payload::{DropReason, PayloadStatus, RetryReason},
+    transaction::{Transaction, TransactionId, TransactionStatus},
+};
+
+use crate::payload_dispatcher::PayloadDispatcherState;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum FinalityOutcome {
+    /// included but not yet older than the reorg period, keep it in the pool
+    Pending,
+    /// finalized, or no longer tracked by this stage
+    Done,
+    /// reorged out of its block, hand it back to the Inclusion Stage
+    Reorged,
+}
+
+/// Polls included transactions until they are older than the chain's `reorgPeriod`, then
+/// marks their payloads as finalized (or dropped, if they reverted)
+pub struct FinalityStage {
+    state: PayloadDispatcherState,
+    tx_receiver: mpsc::UnboundedReceiver<Transaction>,
+    inclusion_stage_sender: mpsc::UnboundedSender<Transaction>,
+    pool: HashMap<TransactionId, Transaction>,
+}
+
+impl FinalityStage {
+    pub fn new(
+        state: PayloadDispatcherState,
+        tx_receiver: mpsc::UnboundedReceiver<Transaction>,
+        inclusion_stage_sender: mpsc::UnboundedSender<Transaction>,
+    ) -> Self {
+        Self {
+            state,
+            tx_receiver,
+            inclusion_stage_sender,
+            pool: HashMap::new(),
+        }
+    }
+
+    pub async fn run(mut self) {
+        let mut poll_interval = interval(self.state.adapter.estimated_block_time());
+        poll_interval.set_missed_tick_behavior(MissedTickBehavior::Delay);
+        loop {
+            tokio::select! {
+                tx = self.tx_receiver.recv() => {
+                    let Some(tx) = tx else {
+                        error!("Finality Stage channel closed");
+                        return;
+                    };
+                    self.pool.insert(tx.id, tx);
+                }
+                _ = poll_interval.tick() => {
+                    let txs = self.pool.drain().map(|(_, tx)| tx).collect::<Vec<_>>();
+                    for tx in txs {
+                        self.process_and_track(tx).await;
+                    }
+                }
+            }
+        }
+    }
+
+    async fn process_and_track(&mut self, mut tx: Transaction) {
+        let outcome = match self.process_tx(&mut tx).await {
+            Ok(outcome) => outcome,
+            Err(err) => {
+                warn!(tx_id = %tx.id(), ?err, "Error processing transaction, will retry");
+                FinalityOutcome::Pending
+            }
+        };
+        match outcome {
+            FinalityOutcome::Pending => {
+                self.pool.insert(tx.id, tx);
+            }
+            FinalityOutcome::Reorged => {
+                if let Err(err) = self.inclusion_stage_sender.send(tx) {
+                    error!(
+                        ?err,
+                        "Failed to send transaction back to the Inclusion Stage"
+                    );
+                }
+            }
+            FinalityOutcome::Done => {}
+        }
+    }
+
+    async fn process_tx(&self, tx: &mut Transaction) -> Result<FinalityOutcome> {
+        let status = self.state.adapter.tx_status(tx).await?;
+        match status {
+            TransactionStatus::Included(_) => {
+                if tx.status != status {
+                    tx.status = status;
+                    self.state.store_tx(tx)?;
+                }
+                Ok(FinalityOutcome::Pending)
+            }
+            TransactionStatus::Finalized(_) => {
+                let reverted = self
+                    .state
+                    .adapter
+                    .reverted_payloads(tx)
+                    .await?
+                    .into_iter()
+                    .collect::<HashSet<_>>();
+                let (reverted, succeeded): (Vec<_>, Vec<_>) = tx
+                    .payload_details
+                    .iter()
+                    .cloned()
+                    .partition(|details| reverted.contains(&*details.id));
+                if !reverted.is_empty() {
+                    warn!(tx_id = %tx.id(), payloads = ?reverted, "Payloads reverted");
+                }
+                self.state.update_status_for_payloads(
+                    &reverted,
+                    PayloadStatus::Dropped(DropReason::Reverted),
+                )?;
+                self.state
+                    .update_status_for_payloads(&succeeded, PayloadStatus::Finalized)?;
+                tx.status = status;
+                self.state.store_tx(tx)?;
+                info!(tx_id = %tx.id(), "Transaction finalized");
+                Ok(FinalityOutcome::Done)
+            }
+            TransactionStatus::PendingInclusion | TransactionStatus::Mempool(_) => {
+                warn!(tx_id = %tx.id(), ?status, "Transaction reorged out of its block, resubmitting it");
+                tx.status = status;
+                self.state.store_tx(tx)?;
+                self.state.update_status_for_payloads(
+                    &tx.payload_details,
+                    PayloadStatus::PendingInclusion,
+                )?;
+                Ok(FinalityOutcome::Reorged)
+            }
+            TransactionStatus::DroppedByChain(_) => {
+                warn!(tx_id = %tx.id(), "Transaction dropped by chain, retrying its payloads");
+                tx.status = status;
+                self.state.store_tx(tx)?;
+                self.state
+                    .requeue_payloads(&tx.payload_details, RetryReason::Reorged)
+                    .await?;
+                Ok(FinalityOutcome::Done)
+            }
+            TransactionStatus::Dropped(_) => {
+                error!(tx_id = %tx.id(), ?status, "Adapter reported a status only the PayloadDispatcher assigns, dropping transaction");
+                Ok(FinalityOutcome::Done)
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use eyre::eyre;
+
+    use aetherium_core::H256;
+
+    use crate::{
+        payload::{FullPayload, PayloadDb},
+        payload_dispatcher::test_utils::{stored_tx, tmp_state, MockAdapter},
+        transaction::TransactionDb,
+    };
+
+    use super::*;
+
+    fn test_stage(
+        adapter: MockAdapter,
+    ) -> (
+        FinalityStage,
+        mpsc::UnboundedReceiver<Transaction>,
+        tempfile::TempDir,
+    ) {
+        let (state, db_dir) = tmp_state(adapter);
+        let (_, tx_receiver) = mpsc::unbounded_channel();
+        let (inclusion_stage_sender, inclusion_stage_receiver) = mpsc::unbounded_channel();
+        let stage = FinalityStage::new(state, tx_receiver, inclusion_stage_sender);
+        (stage, inclusion_stage_receiver, db_dir)
+    }
+
+    fn payload_status(stage: &FinalityStage, payload: &FullPayload) -> PayloadStatus {
+        stage
+            .state
+            .payload_db
+            .retrieve_payload_by_id(payload.id())
+            .unwrap()
+            .unwrap()
+            .status
+    }
+
+    #[tokio::test]
+    async fn test_waits_for_reorg_period() {
+        let mut adapter = MockAdapter::new();
+        adapter
+            .expect_tx_status()
+            .returning(|_| Ok(TransactionStatus::Included(H256::zero())));
+        adapter.expect_reverted_payloads().never();
+        let (mut stage, mut inclusion_receiver, _db_dir) = test_stage(adapter);
+        let tx = stored_tx(&stage.state, &[FullPayload::random()]);
+
+        stage.process_and_track(tx.clone()).await;
+
+        let pending = stage.pool.get(&tx.id).unwrap();
+        assert_eq!(pending.status, TransactionStatus::Included(H256::zero()));
+        assert!(inclusion_receiver.try_recv().is_err());
+    }
+
+    #[tokio::test]
+    async fn test_finalizes_payloads_and_drops_reverted_ones() {
+        let payloads = vec![FullPayload::random(), FullPayload::random()];
+        let reverted_id = *payloads[1].details.id;
+
+        let mut adapter = MockAdapter::new();
+        adapter
+            .expect_tx_status()
+            .returning(|_| Ok(TransactionStatus::Finalized(H256::zero())));
+        adapter
+            .expect_reverted_payloads()
+            .returning(move |_| Ok(vec![reverted_id]));
+        let (mut stage, _inclusion_receiver, _db_dir) = test_stage(adapter);
+        let tx = stored_tx(&stage.state, &payloads);
+
+        stage.process_and_track(tx.clone()).await;
+
+        assert!(stage.pool.is_empty());
+        assert_eq!(
+            payload_status(&stage, &payloads[0]),
+            PayloadStatus::Finalized
+        );
+        assert_eq!(
+            payload_status(&stage, &payloads[1]),
+            PayloadStatus::Dropped(DropReason::Reverted)
+        );
+        let stored_tx = stage
+            .state
+            .tx_db
+            .retrieve_transaction_by_id(&tx.id)
+            .unwrap()
+            .unwrap();
+        assert_eq!(stored_tx.status, TransactionStatus::Finalized(H256::zero()));
+    }
+
+    #[tokio::test]
+    async fn test_sends_reorged_transaction_back_to_inclusion() {
+        let mut adapter = MockAdapter::new();
+        adapter
+            .expect_tx_status()
+            .returning(|_| Ok(TransactionStatus::PendingInclusion));
+        let (mut stage, mut inclusion_receiver, _db_dir) = test_stage(adapter);
+        let payload = FullPayload::random();
+        let tx = stored_tx(&stage.state, &[payload.clone()]);
+
+        stage.process_and_track(tx.clone()).await;
+
+        assert!(stage.pool.is_empty());
+        assert_eq!(inclusion_receiver.try_recv().unwrap().id, tx.id);
+        assert_eq!(
+            payload_status(&stage, &payload),
+            PayloadStatus::PendingInclusion
+        );
+    }
+
+    #[tokio::test]
+    async fn test_requeues_payloads_of_dropped_transaction() {
+        let mut adapter = MockAdapter::new();
+        adapter
+            .expect_tx_status()
+            .returning(|_| Ok(TransactionStatus::DroppedByChain(H256::zero())));
+        let (mut stage, mut inclusion_receiver, _db_dir) = test_stage(adapter);
+        let payload = FullPayload::random();
+        let tx = stored_tx(&stage.state, &[payload.clone()]);
+
+        stage.process_and_track(tx).await;
+
+        assert!(inclusion_receiver.try_recv().is_err());
+        assert_eq!(
+            payload_status(&stage, &payload),
+            PayloadStatus::Retry(RetryReason::Reorged)
+        );
+        let queue = stage.state.building_queue.lock().await;
+        assert_eq!(queue.len(), 1);
+        assert_eq!(queue[0].id(), payload.id());
+    }
+
+    #[tokio::test]
+    async fn test_keeps_transaction_on_status_error() {
+        let mut adapter = MockAdapter::new();
+        adapter
+            .expect_tx_status()
+            .returning(|_| Err(eyre!("rpc unavailable")));
+        let (mut stage, _inclusion_receiver, _db_dir) = test_stage(adapter);
+        let tx = stored_tx(&stage.state, &[FullPayload::random()]);
+
+        stage.process_and_track(tx.clone()).await;
+
+        assert_eq!(stage.pool.get(&tx.id), Some(&tx));
+    }
+}
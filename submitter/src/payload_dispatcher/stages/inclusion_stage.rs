@@ -0,0 +1,420 @@
+use std::collections::{HashMap, HashSet};
+
+use chrono::Utc;
+use eyre::Result;
+use tokio::{
+    sync::mpsc,
+    time::{interval, MissedTickBehavior},
+};
+use tracing::{error, info, warn};
+
+use crate::{
+    payload::{DropReason, PayloadStatus, RetryReason},
+    transaction::{Transaction, TransactionId, TransactionStatus},
+};
+
+use crate::payload_dispatcher::PayloadDispatcherState;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum InclusionOutcome {
+    /// still waiting to be included, keep it in the pool
+    Pending,
+    /// landed in a block, hand it over to the Finality Stage
+    Included,
+    /// no longer tracked by this stage
+    Dropped,
+}
+
+/// Submits transactions and polls them until they are included, resubmitting them with a
+/// higher gas price if they stay pending for too long. Payloads of included transactions that
+/// fail their success criteria are retried.
+pub struct InclusionStage {
+    state: PayloadDispatcherState,
+    tx_receiver: mpsc::UnboundedReceiver<Transaction>,
+    finality_stage_sender: mpsc::UnboundedSender<Transaction>,
+    pool: HashMap<TransactionId, Transaction>,
+}
+
+impl InclusionStage {
+    pub fn new(
+        state: PayloadDispatcherState,
+        tx_receiver: mpsc::UnboundedReceiver<Transaction>,
+        finality_stage_sender: mpsc::UnboundedSender<Transaction>,
+    ) -> Self {
+        Self {
+            state,
+            tx_receiver,
+            finality_stage_sender,
+            pool: HashMap::new(),
+        }
+    }
+
+    pub async fn run(mut self) {
+        let mut poll_interval = interval(self.state.adapter.estimated_block_time());
+        poll_interval.set_missed_tick_behavior(MissedTickBehavior::Delay);
+        loop {
+            tokio::select! {
+                tx = self.tx_receiver.recv() => {
+                    let Some(tx) = tx else {
+                        error!("Inclusion Stage channel closed");
+                        return;
+                    };
+                    // submit new transactions right away instead of waiting for the next tick
+                    self.process_and_track(tx).await;
+                }
+                _ = poll_interval.tick() => {
+                    self.fill_nonce_gaps().await;
+                    let txs = self.pool.drain().map(|(_, tx)| tx).collect::<Vec<_>>();
+                    for tx in txs {
+                        self.process_and_track(tx).await;
+                    }
+                }
+            }
+            if self.finality_stage_sender.is_closed() {
+                error!("Finality Stage channel closed");
+                return;
+            }
+        }
+    }
+
+    async fn process_and_track(&mut self, mut tx: Transaction) {
+        let outcome = match self.process_tx(&mut tx).await {
+            Ok(outcome) => outcome,
+            Err(err) => {
+                warn!(tx_id = %tx.id(), ?err, "Error processing transaction, will retry");
+                InclusionOutcome::Pending
+            }
+        };
+        match outcome {
+            InclusionOutcome::Pending => {
+                self.pool.insert(tx.id, tx);
+            }
+            InclusionOutcome::Included => {
+                if let Err(err) = self.finality_stage_sender.send(tx) {
+                    error!(?err, "Failed to send transaction to the Finality Stage");
+                }
+            }
+            InclusionOutcome::Dropped => {}
+        }
+    }
+
+    async fn process_tx(&self, tx: &mut Transaction) -> Result<InclusionOutcome> {
+        let status = self.state.adapter.tx_status(tx).await?;
+        match status {
+            TransactionStatus::PendingInclusion | TransactionStatus::Mempool(_) => {
+                tx.status = status;
+                if tx.submission_attempts == 0 && !self.state.adapter.simulate_tx(tx).await? {
+                    warn!(tx_id = %tx.id(), payloads = ?tx.payload_details, "Transaction failed simulation, dropping its payloads");
+                    tx.status = TransactionStatus::Dropped(DropReason::FailedSimulation);
+                    self.state.store_tx(tx)?;
+                    self.state.update_status_for_payloads(
+                        &tx.payload_details,
+                        PayloadStatus::Dropped(DropReason::FailedSimulation),
+                    )?;
+                    return Ok(InclusionOutcome::Dropped);
+                }
+                if self.ready_for_submission(tx) {
+                    self.submit(tx).await?;
+                }
+                Ok(InclusionOutcome::Pending)
+            }
+            TransactionStatus::Included(_) | TransactionStatus::Finalized(_) => {
+                info!(tx_id = %tx.id(), ?status, "Transaction included");
+                let failing = self
+                    .state
+                    .adapter
+                    .payloads_failing_success_criteria(tx)
+                    .await?
+                    .into_iter()
+                    .collect::<HashSet<_>>();
+                let (failing, succeeded): (Vec<_>, Vec<_>) = tx
+                    .payload_details
+                    .iter()
+                    .cloned()
+                    .partition(|details| failing.contains(&*details.id));
+                if !failing.is_empty() {
+                    warn!(tx_id = %tx.id(), payloads = ?failing, "Payloads failed their success criteria, retrying them");
+                    // requeued before the tx forgets about them, so a crash in between can't lose them
+                    self.state
+                        .requeue_payloads(&failing, RetryReason::FailedSuccessCriteria)
+                        .await?;
+                }
+                tx.status = status;
+                tx.payload_details = succeeded;
+                self.state.store_tx(tx)?;
+                self.state
+                    .update_status_for_payloads(&tx.payload_details, PayloadStatus::Included)?;
+                Ok(InclusionOutcome::Included)
+            }
+            TransactionStatus::DroppedByChain(_) => {
+                warn!(tx_id = %tx.id(), "Transaction dropped by chain, retrying its payloads");
+                tx.status = status;
+                self.state.store_tx(tx)?;
+                self.state
+                    .requeue_payloads(&tx.payload_details, RetryReason::Reorged)
+                    .await?;
+                Ok(InclusionOutcome::Dropped)
+            }
+            TransactionStatus::Dropped(_) => {
+                error!(tx_id = %tx.id(), ?status, "Adapter reported a status only the PayloadDispatcher assigns, dropping transaction");
+                Ok(InclusionOutcome::Dropped)
+            }
+        }
+    }
+
+    /// A stuck nonce blocks every later transaction of the signer, so gaps are filled before
+    /// the pool is polled. Adapters look for gaps while filling them, so that the chain is only
+    /// queried once per tick.
+    async fn fill_nonce_gaps(&self) {
+        if let Err(err) = self.state.adapter.fill_nonce_gaps().await {
+            error!(?err, "Failed to fill nonce gap");
+        }
+    }
+
+    fn ready_for_submission(&self, tx: &Transaction) -> bool {
+        let Some(last_submission_attempt) = tx.last_submission_attempt else {
+            return true;
+        };
+        let elapsed = (Utc::now() - last_submission_attempt)
+            .to_std()
+            .unwrap_or_default();
+        elapsed
+            >= self.state.adapter.estimated_block_time()
+                * self.state.adapter.blocks_before_resubmission()
+    }
+
+    async fn submit(&self, tx: &mut Transaction) -> Result<()> {
+        // the adapter mutates the tx even if broadcasting fails, so it is stored either way
+        let result = self.state.adapter.submit(tx).await;
+        tx.last_submission_attempt = Some(Utc::now());
+        self.state.store_tx(tx)?;
+        result?;
+        info!(
+            tx_id = %tx.id(),
+            hash = ?tx.hash,
+            attempts = tx.submission_attempts,
+            nonce = ?tx.vm_specific_data.nonce(),
+            max_fee = ?tx.vm_specific_data.max_fee(),
+            "Submitted transaction"
+        );
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::time::Duration;
+
+    use eyre::eyre;
+
+    use aetherium_core::H256;
+
+    use crate::{
+        payload::{FullPayload, PayloadDb},
+        payload_dispatcher::test_utils::{stored_tx, tmp_state, MockAdapter},
+        transaction::TransactionDb,
+    };
+
+    use super::*;
+
+    fn test_stage(
+        adapter: MockAdapter,
+    ) -> (
+        InclusionStage,
+        mpsc::UnboundedReceiver<Transaction>,
+        tempfile::TempDir,
+    ) {
+        let (state, db_dir) = tmp_state(adapter);
+        let (_, tx_receiver) = mpsc::unbounded_channel();
+        let (finality_stage_sender, finality_stage_receiver) = mpsc::unbounded_channel();
+        let stage = InclusionStage::new(state, tx_receiver, finality_stage_sender);
+        (stage, finality_stage_receiver, db_dir)
+    }
+
+    fn payload_status(stage: &InclusionStage, payload: &FullPayload) -> PayloadStatus {
+        stage
+            .state
+            .payload_db
+            .retrieve_payload_by_id(payload.id())
+            .unwrap()
+            .unwrap()
+            .status
+    }
+
+    #[tokio::test]
+    async fn test_submits_new_transaction() {
+        let mut adapter = MockAdapter::new();
+        adapter
+            .expect_tx_status()
+            .returning(|_| Ok(TransactionStatus::PendingInclusion));
+        adapter
+            .expect_simulate_tx()
+            .times(1)
+            .returning(|_| Ok(true));
+        adapter.expect_submit().times(1).returning(|tx| {
+            tx.submission_attempts += 1;
+            Ok(())
+        });
+        adapter
+            .expect_estimated_block_time()
+            .returning(|| Duration::from_secs(12));
+        let (mut stage, mut finality_receiver, _db_dir) = test_stage(adapter);
+        let tx = stored_tx(&stage.state, &[FullPayload::random()]);
+
+        stage.process_and_track(tx.clone()).await;
+        // the second round is too early for a resubmission
+        let pending = stage.pool.remove(&tx.id).unwrap();
+        stage.process_and_track(pending).await;
+
+        let pending = stage.pool.get(&tx.id).unwrap();
+        assert_eq!(pending.submission_attempts, 1);
+        assert!(pending.last_submission_attempt.is_some());
+        assert_eq!(
+            stage
+                .state
+                .tx_db
+                .retrieve_transaction_by_id(&tx.id)
+                .unwrap(),
+            Some(pending.clone())
+        );
+        assert!(finality_receiver.try_recv().is_err());
+    }
+
+    #[tokio::test]
+    async fn test_resubmits_stuck_transaction() {
+        let mut adapter = MockAdapter::new();
+        adapter
+            .expect_tx_status()
+            .returning(|_| Ok(TransactionStatus::Mempool(H256::zero())));
+        adapter.expect_submit().times(1).returning(|tx| {
+            tx.submission_attempts += 1;
+            Ok(())
+        });
+        adapter
+            .expect_estimated_block_time()
+            .returning(|| Duration::from_secs(1));
+        let (mut stage, _finality_receiver, _db_dir) = test_stage(adapter);
+        let mut tx = stored_tx(&stage.state, &[FullPayload::random()]);
+        tx.submission_attempts = 1;
+        tx.last_submission_attempt = Some(Utc::now() - chrono::Duration::seconds(10));
+
+        stage.process_and_track(tx.clone()).await;
+
+        let pending = stage.pool.get(&tx.id).unwrap();
+        assert_eq!(pending.submission_attempts, 2);
+        assert_eq!(pending.status, TransactionStatus::Mempool(H256::zero()));
+    }
+
+    #[tokio::test]
+    async fn test_drops_transaction_failing_simulation() {
+        let mut adapter = MockAdapter::new();
+        adapter
+            .expect_tx_status()
+            .returning(|_| Ok(TransactionStatus::PendingInclusion));
+        adapter.expect_simulate_tx().returning(|_| Ok(false));
+        adapter.expect_submit().never();
+        let (mut stage, mut finality_receiver, _db_dir) = test_stage(adapter);
+        let payload = FullPayload::random();
+        let tx = stored_tx(&stage.state, &[payload.clone()]);
+
+        stage.process_and_track(tx).await;
+
+        assert!(stage.pool.is_empty());
+        assert!(finality_receiver.try_recv().is_err());
+        assert_eq!(
+            payload_status(&stage, &payload),
+            PayloadStatus::Dropped(DropReason::FailedSimulation)
+        );
+    }
+
+    #[tokio::test]
+    async fn test_forwards_included_transaction() {
+        let mut adapter = MockAdapter::new();
+        adapter
+            .expect_tx_status()
+            .returning(|_| Ok(TransactionStatus::Included(H256::zero())));
+        adapter
+            .expect_payloads_failing_success_criteria()
+            .returning(|_| Ok(vec![]));
+        let (mut stage, mut finality_receiver, _db_dir) = test_stage(adapter);
+        let payload = FullPayload::random();
+        let tx = stored_tx(&stage.state, &[payload.clone()]);
+
+        stage.process_and_track(tx.clone()).await;
+
+        assert!(stage.pool.is_empty());
+        let included = finality_receiver.try_recv().unwrap();
+        assert_eq!(included.id, tx.id);
+        assert_eq!(included.status, TransactionStatus::Included(H256::zero()));
+        assert_eq!(payload_status(&stage, &payload), PayloadStatus::Included);
+    }
+
+    #[tokio::test]
+    async fn test_retries_payloads_failing_success_criteria() {
+        let (succeeded, failed) = (FullPayload::random(), FullPayload::random());
+        let failed_id = **failed.id();
+        let mut adapter = MockAdapter::new();
+        adapter
+            .expect_tx_status()
+            .returning(|_| Ok(TransactionStatus::Included(H256::zero())));
+        adapter
+            .expect_payloads_failing_success_criteria()
+            .returning(move |_| Ok(vec![failed_id]));
+        let (mut stage, mut finality_receiver, _db_dir) = test_stage(adapter);
+        let tx = stored_tx(&stage.state, &[succeeded.clone(), failed.clone()]);
+
+        stage.process_and_track(tx.clone()).await;
+
+        // only the payloads that succeeded are left for the Finality Stage to finalize
+        let included = finality_receiver.try_recv().unwrap();
+        assert_eq!(included.payload_details, vec![succeeded.details.clone()]);
+        assert_eq!(
+            stage
+                .state
+                .tx_db
+                .retrieve_transaction_by_id(&tx.id)
+                .unwrap(),
+            Some(included)
+        );
+        assert_eq!(payload_status(&stage, &succeeded), PayloadStatus::Included);
+        assert_eq!(
+            payload_status(&stage, &failed),
+            PayloadStatus::Retry(RetryReason::FailedSuccessCriteria)
+        );
+        let requeued = stage.state.building_queue.lock().await.pop_front().unwrap();
+        assert_eq!(requeued.id(), failed.id());
+    }
+
+    #[tokio::test]
+    async fn test_fills_nonce_gaps_every_tick() {
+        let mut adapter = MockAdapter::new();
+        adapter.expect_nonce_gap_exists().never();
+        adapter
+            .expect_fill_nonce_gaps()
+            .times(1)
+            .returning(|| Ok(()));
+        adapter
+            .expect_fill_nonce_gaps()
+            .times(1)
+            .returning(|| Err(eyre!("rpc unavailable")));
+        let (stage, _finality_receiver, _db_dir) = test_stage(adapter);
+
+        stage.fill_nonce_gaps().await;
+        // errors are logged and the gap is filled on a later tick
+        stage.fill_nonce_gaps().await;
+    }
+
+    #[tokio::test]
+    async fn test_keeps_transaction_on_status_error() {
+        let mut adapter = MockAdapter::new();
+        adapter
+            .expect_tx_status()
+            .returning(|_| Err(eyre!("rpc unavailable")));
+        let (mut stage, _finality_receiver, _db_dir) = test_stage(adapter);
+        let tx = stored_tx(&stage.state, &[FullPayload::random()]);
+
+        stage.process_and_track(tx.clone()).await;
+
+        assert_eq!(stage.pool.get(&tx.id), Some(&tx));
+    }
+}
@@ -0,0 +1,73 @@
+use std::{sync::Arc, time::Duration};
+
+use async_trait::async_trait;
+use eyre::Result;
+use tempfile::TempDir;
+
+use aetherium_base::db::{test_utils::setup_db, AetheriumRocksDB};
+use aetherium_core::{AetheriumDomain, KnownAetheriumDomain};
+
+use crate::{
+    chain_tx_adapter::{AdaptsChain, CosmWasmTxPrecursor, GasLimit},
+    payload::{FullPayload, PayloadDb},
+    transaction::{Transaction, TransactionDb, TransactionStatus, VmSpecificTxData},
+};
+
+use super::PayloadDispatcherState;
+
+mockall::mock! {
+    pub Adapter {
+    }
+
+    #[async_trait]
+    impl AdaptsChain for Adapter {
+        async fn estimate_gas_limit(&self, payload: &FullPayload) -> Result<GasLimit>;
+        async fn build_transactions(&self, payloads: Vec<FullPayload>) -> Vec<Transaction>;
+        async fn simulate_tx(&self, tx: &Transaction) -> Result<bool>;
+        async fn submit(&self, tx: &mut Transaction) -> Result<()>;
+        async fn tx_status(&self, tx: &Transaction) -> Result<TransactionStatus>;
+        async fn reverted_payloads(&self, tx: &Transaction) -> Result<Vec<uuid::Uuid>>;
+        async fn payloads_failing_success_criteria(&self, tx: &Transaction) -> Result<Vec<uuid::Uuid>>;
+        async fn nonce_gap_exists(&self) -> bool;
+        async fn fill_nonce_gaps(&self) -> Result<()>;
+        async fn replace_tx(&self, _tx: &Transaction) -> Result<()>;
+        fn estimated_block_time(&self) -> Duration;
+    }
+}
+
+/// Creates a db in a temporary directory. The returned `TempDir` must be kept alive for as long
+/// as the db is used.
+pub(crate) fn tmp_db() -> (Arc<AetheriumRocksDB>, TempDir) {
+    let db_tmp_dir = TempDir::new().unwrap();
+    let db = setup_db(db_tmp_dir.path().to_str().unwrap().into());
+    let db = Arc::new(AetheriumRocksDB::new(
+        &AetheriumDomain::Known(KnownAetheriumDomain::Arbitrum),
+        db,
+    ));
+    (db, db_tmp_dir)
+}
+
+/// Creates dispatcher state backed by a temporary db
+pub(crate) fn tmp_state(adapter: MockAdapter) -> (PayloadDispatcherState, TempDir) {
+    let (db, db_tmp_dir) = tmp_db();
+    let state = PayloadDispatcherState::new(db.clone(), db, Arc::new(adapter));
+    (state, db_tmp_dir)
+}
+
+/// VM data for transactions the stages only pass around
+pub(crate) fn dummy_vm_data() -> VmSpecificTxData {
+    VmSpecificTxData::CosmWasm(CosmWasmTxPrecursor::new(vec![]))
+}
+
+/// Stores the payloads and returns a transaction that includes all of them
+pub(crate) fn stored_tx(state: &PayloadDispatcherState, payloads: &[FullPayload]) -> Transaction {
+    for payload in payloads {
+        state
+            .payload_db
+            .store_payload_by_id(payload.clone())
+            .unwrap();
+    }
+    let tx = Transaction::new(dummy_vm_data(), payloads);
+    state.tx_db.store_transaction_by_id(tx.clone()).unwrap();
+    tx
+}
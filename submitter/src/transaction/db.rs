@@ -6,16 +6,31 @@ use std::io::Write;
 use aetherium_base::db::{DbResult, AetheriumRocksDB};
 use aetherium_core::{Decode, Encode, AetheriumProtocolError};
 
-use super::{Transaction, TransactionId};
+use super::{Transaction, TransactionId, TransactionStatus};
+use crate::payload::PayloadId;
 
 const TRANSACTION_BY_ID_STORAGE_PREFIX: &str = "transaction_by_id_";
+const TRANSACTION_ID_BY_STATUS_STORAGE_PREFIX: &str = "transaction_id_by_status_";
+const TRANSACTION_ID_BY_PAYLOAD_ID_STORAGE_PREFIX: &str = "transaction_id_by_payload_id_";
 
-pub trait TransactionDb {
+pub trait TransactionDb: Send + Sync {
     /// Retrieve a transaction by its unique ID
     fn retrieve_transaction_by_id(&self, id: &TransactionId) -> DbResult<Option<Transaction>>;
 
-    /// Store a transaction by its unique ID
+    /// Store a transaction by its unique ID, keeping the status and payload indexes up to date
     fn store_transaction_by_id(&self, tx: Transaction) -> DbResult<()>;
+
+    /// Retrieve all transactions whose status is the same variant as `status`, regardless of its inner value
+    fn retrieve_transactions_by_status(
+        &self,
+        status: &TransactionStatus,
+    ) -> DbResult<Vec<Transaction>>;
+
+    /// Retrieve the IDs of all transactions that included the given payload
+    fn retrieve_transaction_ids_by_payload_id(
+        &self,
+        payload_id: &PayloadId,
+    ) -> DbResult<Vec<TransactionId>>;
 }
 
 impl TransactionDb for AetheriumRocksDB {
@@ -24,10 +39,81 @@ impl TransactionDb for AetheriumRocksDB {
     }
 
     fn store_transaction_by_id(&self, tx: Transaction) -> DbResult<()> {
-        self.store_value_by_key(TRANSACTION_BY_ID_STORAGE_PREFIX, tx.id(), &tx)
+        let previous_status = self
+            .retrieve_transaction_by_id(tx.id())?
+            .map(|stored| stored.status);
+        // the tx and its index entries are written in a single batch, so that a crash never
+        // leaves the indexes out of sync with the stored tx
+        let mut batch = self.batch();
+        for details in &tx.payload_details {
+            batch.store_encodable(
+                TRANSACTION_ID_BY_PAYLOAD_ID_STORAGE_PREFIX,
+                [details.id.to_vec(), tx.id().to_vec()].concat(),
+                tx.id(),
+            );
+        }
+        if let Some(previous) = previous_status {
+            if status_key(&previous) != status_key(&tx.status) {
+                batch.delete_value(
+                    TRANSACTION_ID_BY_STATUS_STORAGE_PREFIX,
+                    status_index_key(&previous, tx.id()),
+                );
+            }
+        }
+        batch.store_encodable(
+            TRANSACTION_ID_BY_STATUS_STORAGE_PREFIX,
+            status_index_key(&tx.status, tx.id()),
+            tx.id(),
+        );
+        batch.store_keyed_encodable(TRANSACTION_BY_ID_STORAGE_PREFIX, tx.id(), &tx);
+        batch.write()
+    }
+
+    fn retrieve_transactions_by_status(
+        &self,
+        status: &TransactionStatus,
+    ) -> DbResult<Vec<Transaction>> {
+        let ids: Vec<TransactionId> = self.retrieve_decodables_by_key_prefix(
+            TRANSACTION_ID_BY_STATUS_STORAGE_PREFIX,
+            status_key(status),
+        )?;
+        let mut txs = Vec::with_capacity(ids.len());
+        for id in ids {
+            // index entries may be stale if the same tx was updated concurrently
+            match self.retrieve_transaction_by_id(&id)? {
+                Some(tx) if status_key(&tx.status) == status_key(status) => txs.push(tx),
+                _ => {}
+            }
+        }
+        Ok(txs)
+    }
+
+    fn retrieve_transaction_ids_by_payload_id(
+        &self,
+        payload_id: &PayloadId,
+    ) -> DbResult<Vec<TransactionId>> {
+        self.retrieve_decodables_by_key_prefix(
+            TRANSACTION_ID_BY_PAYLOAD_ID_STORAGE_PREFIX,
+            payload_id.to_vec(),
+        )
     }
 }
 
+fn status_key(status: &TransactionStatus) -> &'static [u8] {
+    match status {
+        TransactionStatus::PendingInclusion => b"pending_inclusion_",
+        TransactionStatus::Mempool(_) => b"mempool_",
+        TransactionStatus::Included(_) => b"included_",
+        TransactionStatus::Finalized(_) => b"finalized_",
+        TransactionStatus::DroppedByChain(_) => b"dropped_by_chain_",
+        TransactionStatus::Dropped(_) => b"dropped_by_dispatcher_",
+    }
+}
+
+fn status_index_key(status: &TransactionStatus, id: &TransactionId) -> Vec<u8> {
+    [status_key(status), id.to_vec().as_slice()].concat()
+}
+
 impl Encode for Transaction {
     fn write_to<W>(&self, writer: &mut W) -> std::io::Result<usize>
     where
@@ -55,3 +141,141 @@ impl Decode for Transaction {
         })
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use ethers::types::{transaction::eip2718::TypedTransaction, TransactionRequest};
+    use solana_sdk::{
+        hash::Hash, instruction::Instruction, pubkey::Pubkey, signature::Keypair, signer::Signer,
+        transaction::Transaction as SealevelTransaction,
+    };
+
+    use aetherium_core::H256;
+    use aetherium_cosmos::{CosmWasmContractCall, CosmosTxFee};
+    use aetherium_sealevel::SealevelTxCostEstimate;
+
+    use crate::chain_tx_adapter::{CosmWasmTxPrecursor, SealevelTxPrecursor};
+    use crate::payload::{DropReason, FullPayload};
+    use crate::payload_dispatcher::test_utils::tmp_db;
+    use crate::transaction::VmSpecificTxData;
+
+    use super::*;
+
+    fn evm_tx(nonce: u64, payloads: &[FullPayload]) -> Transaction {
+        let request = TransactionRequest::new().nonce(nonce);
+        Transaction::new(
+            VmSpecificTxData::Evm(TypedTransaction::Legacy(request)),
+            payloads,
+        )
+    }
+
+    #[test]
+    fn test_status_index_follows_status_updates() {
+        let (db, _db_dir) = tmp_db();
+        let mut tx = evm_tx(1, &[FullPayload::random()]);
+        db.store_transaction_by_id(tx.clone()).unwrap();
+        tx.status = TransactionStatus::Mempool(H256::zero());
+        db.store_transaction_by_id(tx.clone()).unwrap();
+
+        assert!(db
+            .retrieve_transactions_by_status(&TransactionStatus::PendingInclusion)
+            .unwrap()
+            .is_empty());
+        // only the variant is matched, not the signer
+        assert_eq!(
+            db.retrieve_transactions_by_status(&TransactionStatus::Mempool(H256::random()))
+                .unwrap(),
+            vec![tx.clone()]
+        );
+
+        tx.status = TransactionStatus::Dropped(DropReason::FailedSimulation);
+        db.store_transaction_by_id(tx.clone()).unwrap();
+        assert!(db
+            .retrieve_transactions_by_status(&TransactionStatus::DroppedByChain(H256::zero()))
+            .unwrap()
+            .is_empty());
+        assert_eq!(
+            db.retrieve_transactions_by_status(&TransactionStatus::Dropped(
+                DropReason::FailedSimulation
+            ))
+            .unwrap(),
+            vec![tx]
+        );
+    }
+
+    #[test]
+    fn test_decodes_vm_specific_data_of_every_vm() {
+        let (db, _db_dir) = tmp_db();
+        let instruction = Instruction::new_with_bytes(Pubkey::new_unique(), &[1, 2, 3], vec![]);
+        let payer = Keypair::new();
+        let svm_precursor = SealevelTxPrecursor {
+            signed_tx: Some(SealevelTransaction::new_signed_with_payer(
+                &[instruction.clone()],
+                Some(&payer.pubkey()),
+                &[&payer],
+                Hash::new_unique(),
+            )),
+            estimate: Some(SealevelTxCostEstimate {
+                compute_units: 200_000,
+                compute_unit_price_micro_lamports: 1_000,
+            }),
+            instruction,
+        };
+        let cosmwasm_precursor = CosmWasmTxPrecursor {
+            calls: vec![CosmWasmContractCall {
+                contract: H256::random(),
+                msg: br#"{"process":{}}"#.to_vec(),
+            }],
+            gas_limit: Some(150_000),
+            sequence: Some(3),
+            signed_tx: Some(vec![1, 2, 3]),
+            fee: Some(CosmosTxFee {
+                amount: 795,
+                denom: "untrn".to_owned(),
+            }),
+        };
+        let txs = [
+            evm_tx(1, &[FullPayload::random()]),
+            Transaction::new(
+                VmSpecificTxData::Svm(svm_precursor),
+                &[FullPayload::random()],
+            ),
+            Transaction::new(
+                VmSpecificTxData::CosmWasm(cosmwasm_precursor),
+                &[FullPayload::random()],
+            ),
+        ];
+
+        for tx in txs {
+            db.store_transaction_by_id(tx.clone()).unwrap();
+            assert_eq!(db.retrieve_transaction_by_id(tx.id()).unwrap(), Some(tx));
+        }
+    }
+
+    #[test]
+    fn test_retrieve_transaction_ids_by_payload_id() {
+        let (db, _db_dir) = tmp_db();
+        let payloads = vec![FullPayload::random(), FullPayload::random()];
+        let batch = evm_tx(1, &payloads);
+        let retry = evm_tx(2, &payloads[1..]);
+        db.store_transaction_by_id(batch.clone()).unwrap();
+        db.store_transaction_by_id(retry.clone()).unwrap();
+
+        assert_eq!(
+            db.retrieve_transaction_ids_by_payload_id(payloads[0].id())
+                .unwrap(),
+            vec![batch.id]
+        );
+        let mut expected = vec![batch.id, retry.id];
+        expected.sort_by_key(|id| **id);
+        assert_eq!(
+            db.retrieve_transaction_ids_by_payload_id(payloads[1].id())
+                .unwrap(),
+            expected
+        );
+        assert!(db
+            .retrieve_transaction_ids_by_payload_id(&PayloadId::random())
+            .unwrap()
+            .is_empty());
+    }
+}
@@ -2,35 +2,75 @@
 #![allow(dead_code)]
 
 use std::ops::Deref;
+
+use chrono::{DateTime, Utc};
+use ethers::types::transaction::eip2718::TypedTransaction;
 use uuid::Uuid;
 
-use aetherium_core::{identifiers::UniqueIdentifier, H256, H512};
+use aetherium_core::{identifiers::UniqueIdentifier, H256, H512, U256};
 
-use crate::payload::PayloadId;
+use crate::{
+    chain_tx_adapter::{CosmWasmTxPrecursor, SealevelTxPrecursor},
+    payload::{DropReason, FullPayload, PayloadDetails, PayloadId},
+};
 
 pub type TransactionId = UniqueIdentifier;
 type SignerAddress = H256;
 
+const MICRO_LAMPORTS_PER_LAMPORT: u64 = 1_000_000;
+
 /// Full details about a transaction
 #[derive(Debug, Clone, serde::Deserialize, serde::Serialize, PartialEq, Eq)]
 pub struct Transaction {
     /// unique tx identifier. Used as primary key in the db.
-    id: TransactionId,
+    pub id: TransactionId,
     /// tx identifier obtained by hashing its contents. This may change when gas price is escalated
-    hash: Option<H512>,
+    pub hash: Option<H512>,
+    /// every hash the tx was broadcast under, oldest first. Gas escalation replaces the tx at the
+    /// same nonce, so any of them may be the one that gets included
+    #[serde(default)]
+    pub tx_hashes: Vec<H512>,
     /// may include nonce, gas price, etc
-    vm_specific_data: VmSpecificTxData,
+    pub vm_specific_data: VmSpecificTxData,
     /// this is a vec to accommodate batching
-    payload_details: Vec<PayloadId>,
-    status: TransactionStatus,
+    pub payload_details: Vec<PayloadDetails>,
+    pub status: TransactionStatus,
     /// incremented on submission / gas escalation
-    submission_attempts: u32,
+    pub submission_attempts: u32,
+    /// set by the Inclusion Stage every time the tx is (re)submitted
+    pub last_submission_attempt: Option<DateTime<Utc>>,
+    /// the earliest `inclusion_soft_deadline` of its payloads, after which adapters stop
+    /// enforcing gas price caps
+    #[serde(default)]
+    pub inclusion_soft_deadline: Option<DateTime<Utc>>,
 }
 
 impl Transaction {
+    pub fn new(vm_specific_data: VmSpecificTxData, payloads: &[FullPayload]) -> Self {
+        Self {
+            id: TransactionId::random(),
+            hash: None,
+            tx_hashes: vec![],
+            vm_specific_data,
+            payload_details: payloads.iter().map(|p| p.details.clone()).collect(),
+            status: Default::default(),
+            submission_attempts: 0,
+            last_submission_attempt: None,
+            inclusion_soft_deadline: payloads
+                .iter()
+                .filter_map(|p| p.inclusion_soft_deadline)
+                .min(),
+        }
+    }
+
     pub fn id(&self) -> &TransactionId {
         &self.id
     }
+
+    pub fn past_inclusion_soft_deadline(&self) -> bool {
+        self.inclusion_soft_deadline
+            .is_some_and(|deadline| Utc::now() >= deadline)
+    }
 }
 
 #[derive(Debug, Clone, serde::Deserialize, serde::Serialize, PartialEq, Eq, Default)]
@@ -46,12 +86,152 @@ pub enum TransactionStatus {
     Finalized(SignerAddress),
     /// currently only assigned when a reorg is detected
     DroppedByChain(SignerAddress),
+    /// given up on by the PayloadDispatcher, e.g. because it failed simulation
+    Dropped(DropReason),
 }
 
 // add nested enum entries as we add VMs
+/// The VM-specific transaction, with everything needed to (re)sign and resubmit it.
+/// Stored as JSON, so that transactions can be decoded straight from the db for debugging.
 #[derive(Debug, Clone, serde::Deserialize, serde::Serialize, PartialEq, Eq)]
 pub enum VmSpecificTxData {
-    Evm, // likely `TypedTransaction`, imported from ethers-rs
-    Svm, // likely `Transaction` is imported from solana-sdk
-    CosmWasm,
+    /// typed transaction, carrying the nonce and the (EIP-1559) fee fields
+    Evm(TypedTransaction),
+    /// instruction and its compute budget, and the signed message with its blockhash
+    Svm(SealevelTxPrecursor),
+    /// contract calls, and the signed tx with its account sequence and fee
+    CosmWasm(CosmWasmTxPrecursor),
+}
+
+impl VmSpecificTxData {
+    /// The nonce assigned to the tx, for VMs that use account nonces (the sequence on Cosmos)
+    pub fn nonce(&self) -> Option<u64> {
+        match self {
+            VmSpecificTxData::Evm(tx) => tx.nonce().map(|nonce| nonce.low_u64()),
+            VmSpecificTxData::Svm(_) => None,
+            VmSpecificTxData::CosmWasm(precursor) => precursor.sequence,
+        }
+    }
+
+    /// The gas limit of the tx (the compute unit limit on SVM), once it was estimated
+    pub fn gas_limit(&self) -> Option<U256> {
+        match self {
+            VmSpecificTxData::Evm(tx) => tx.gas().map(|gas| U256::from(*gas)),
+            VmSpecificTxData::Svm(precursor) => precursor
+                .estimate
+                .map(|estimate| U256::from(estimate.compute_units)),
+            VmSpecificTxData::CosmWasm(precursor) => precursor.gas_limit.map(U256::from),
+        }
+    }
+
+    /// The most the tx can pay in fees, in the smallest unit of the chain's native token,
+    /// once its fee params are set. Only the priority fee is counted on SVM.
+    pub fn max_fee(&self) -> Option<U256> {
+        match self {
+            VmSpecificTxData::Evm(tx) => {
+                let gas_price = U256::from(tx.gas_price()?);
+                Some(self.gas_limit()?.saturating_mul(gas_price))
+            }
+            VmSpecificTxData::Svm(precursor) => precursor.estimate.map(|estimate| {
+                let micro_lamports = U256::from(estimate.compute_units)
+                    * U256::from(estimate.compute_unit_price_micro_lamports);
+                (micro_lamports + MICRO_LAMPORTS_PER_LAMPORT - 1) / MICRO_LAMPORTS_PER_LAMPORT
+            }),
+            VmSpecificTxData::CosmWasm(precursor) => {
+                precursor.fee.as_ref().map(|fee| U256::from(fee.amount))
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use ethers::types::{Eip1559TransactionRequest, TransactionRequest};
+    use solana_sdk::{instruction::Instruction, pubkey::Pubkey};
+
+    use aetherium_cosmos::CosmosTxFee;
+    use aetherium_sealevel::SealevelTxCostEstimate;
+
+    use super::*;
+
+    #[test]
+    fn test_evm_fee_fields() {
+        let legacy = VmSpecificTxData::Evm(TypedTransaction::Legacy(
+            TransactionRequest::new().nonce(4).gas(21_000).gas_price(10),
+        ));
+        assert_eq!(legacy.nonce(), Some(4));
+        assert_eq!(legacy.gas_limit(), Some(U256::from(21_000)));
+        assert_eq!(legacy.max_fee(), Some(U256::from(210_000)));
+
+        // the max fee per gas bounds what an EIP-1559 transaction pays
+        let eip1559 = VmSpecificTxData::Evm(TypedTransaction::Eip1559(
+            Eip1559TransactionRequest::new()
+                .gas(21_000)
+                .max_fee_per_gas(20)
+                .max_priority_fee_per_gas(2),
+        ));
+        assert_eq!(eip1559.max_fee(), Some(U256::from(420_000)));
+
+        let unestimated = VmSpecificTxData::Evm(TypedTransaction::Legacy(
+            TransactionRequest::new().gas_price(10),
+        ));
+        assert_eq!(unestimated.gas_limit(), None);
+        assert_eq!(unestimated.max_fee(), None);
+    }
+
+    #[test]
+    fn test_svm_fee_fields() {
+        let instruction = Instruction::new_with_bytes(Pubkey::new_unique(), &[], vec![]);
+        let mut precursor = SealevelTxPrecursor::new(instruction);
+        assert_eq!(VmSpecificTxData::Svm(precursor.clone()).max_fee(), None);
+
+        precursor.estimate = Some(SealevelTxCostEstimate {
+            compute_units: 200_001,
+            compute_unit_price_micro_lamports: 5,
+        });
+        let data = VmSpecificTxData::Svm(precursor);
+        assert_eq!(data.nonce(), None);
+        assert_eq!(data.gas_limit(), Some(U256::from(200_001)));
+        // 1_000_005 micro-lamports, rounded up
+        assert_eq!(data.max_fee(), Some(U256::from(2)));
+    }
+
+    #[test]
+    fn test_cosmwasm_fee_fields() {
+        let mut precursor = CosmWasmTxPrecursor::new(vec![]);
+        assert_eq!(VmSpecificTxData::CosmWasm(precursor.clone()).nonce(), None);
+
+        precursor.gas_limit = Some(150_000);
+        precursor.sequence = Some(9);
+        precursor.fee = Some(CosmosTxFee {
+            amount: 795,
+            denom: "untrn".to_owned(),
+        });
+        let data = VmSpecificTxData::CosmWasm(precursor);
+        assert_eq!(data.nonce(), Some(9));
+        assert_eq!(data.gas_limit(), Some(U256::from(150_000)));
+        assert_eq!(data.max_fee(), Some(U256::from(795)));
+    }
+
+    #[test]
+    fn test_inclusion_soft_deadline_is_earliest_of_payloads() {
+        let now = Utc::now();
+        let deadlines = [None, Some(now + chrono::Duration::minutes(5)), Some(now)];
+        let payloads = deadlines
+            .into_iter()
+            .map(|deadline| FullPayload {
+                inclusion_soft_deadline: deadline,
+                ..FullPayload::random()
+            })
+            .collect::<Vec<_>>();
+        let tx = Transaction::new(
+            VmSpecificTxData::Evm(TypedTransaction::Legacy(TransactionRequest::new())),
+            &payloads,
+        );
+        assert_eq!(tx.inclusion_soft_deadline, Some(now));
+        assert!(tx.past_inclusion_soft_deadline());
+
+        let tx = Transaction::new(tx.vm_specific_data, &payloads[..2]);
+        assert!(!tx.past_inclusion_soft_deadline());
+    }
 }